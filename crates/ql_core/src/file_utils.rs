@@ -296,14 +296,46 @@ pub async fn download_file_to_path(
             }
         }
 
-        let mut file = tokio::fs::File::create(&path).await.path(path)?;
-        tokio::io::copy(&mut stream, &mut file).await.path(path)?;
+        // Downloaded next to the file and then moved into place, so an
+        // interrupted download doesn't leave a broken file behind,
+        // and hardlinks to the old file (in cloned instances) keep it
+        let part_path = part_path(path);
+        let mut file = tokio::fs::File::create(&part_path).await.path(&part_path)?;
+        tokio::io::copy(&mut stream, &mut file)
+            .await
+            .path(&part_path)?;
+        drop(file);
+        tokio::fs::rename(&part_path, path).await.path(path)?;
         Ok(())
     }
 
     retry(|| async { inner(url, user_agent, path).await }).await
 }
 
+/// Writes `contents` to a new file next to `path` and then
+/// moves it into place, replacing any existing file.
+///
+/// Unlike [`tokio::fs::write`], a failed write never leaves
+/// a half-written file, and other hardlinks to the old file
+/// (in cloned instances) aren't affected.
+///
+/// # Errors
+/// If the file couldn't be written or moved.
+pub async fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> Result<(), IoError> {
+    let part_path = part_path(path);
+    tokio::fs::write(&part_path, contents)
+        .await
+        .path(&part_path)?;
+    tokio::fs::rename(&part_path, path).await.path(path)?;
+    Ok(())
+}
+
+fn part_path(path: &Path) -> PathBuf {
+    let mut part_path = path.as_os_str().to_owned();
+    part_path.push(".part");
+    PathBuf::from(part_path)
+}
+
 /// Downloads a file from the given URL into a `Vec<u8>`,
 /// with a custom user agent.
 ///
@@ -0,0 +1,74 @@
+//! Custom instance icons, stored as `icon.png`
+//! in the instance directory (so they're included
+//! when exporting/importing instances).
+//!
+//! Any PNG, JPEG or WebP image can be used; it's
+//! shrunk down and converted to PNG when set.
+
+use std::{io::Cursor, path::PathBuf};
+
+use thiserror::Error;
+
+use crate::{file_utils, InstanceSelection, IntoIoError, IoError, RequestError};
+
+pub const ICON_FILE: &str = "icon.png";
+/// Icons are shrunk down to fit within this size (in pixels).
+pub const ICON_SIZE: u32 = 64;
+
+#[derive(Debug, Error)]
+pub enum IconError {
+    #[error("invalid icon image: {0}")]
+    Image(#[from] image::ImageError),
+    #[error("couldn't save icon: {0}")]
+    Io(#[from] IoError),
+    #[error("couldn't download icon: {0}")]
+    Request(#[from] RequestError),
+}
+
+#[must_use]
+pub fn path(instance: &InstanceSelection) -> PathBuf {
+    instance.get_instance_path().join(ICON_FILE)
+}
+
+/// Reads the icon of an instance, if it has one.
+pub async fn read(instance: &InstanceSelection) -> Option<Vec<u8>> {
+    tokio::fs::read(path(instance)).await.ok()
+}
+
+/// Sets the icon of an instance from the bytes
+/// of an image file (PNG, JPEG, WebP, ...).
+///
+/// # Errors
+/// - The image couldn't be decoded
+/// - The icon couldn't be written
+pub async fn set(instance: &InstanceSelection, image: &[u8]) -> Result<(), IconError> {
+    let img = image::load_from_memory(image)?.thumbnail(ICON_SIZE, ICON_SIZE);
+    let mut buffer = Vec::new();
+    img.write_to(&mut Cursor::new(&mut buffer), image::ImageFormat::Png)?;
+
+    let path = path(instance);
+    tokio::fs::write(&path, buffer).await.path(path)?;
+    Ok(())
+}
+
+/// Downloads an image and sets it as the icon of an instance.
+///
+/// # Errors
+/// - The image couldn't be downloaded or decoded
+/// - The icon couldn't be written
+pub async fn set_from_url(instance: &InstanceSelection, url: &str) -> Result<(), IconError> {
+    let image = file_utils::download_file_to_bytes(url, true).await?;
+    set(instance, &image).await
+}
+
+/// Removes the icon of an instance, if it has one.
+///
+/// # Errors
+/// If the icon exists but couldn't be deleted.
+pub async fn remove(instance: &InstanceSelection) -> Result<(), IoError> {
+    let path = path(instance);
+    if path.exists() {
+        tokio::fs::remove_file(&path).await.path(path)?;
+    }
+    Ok(())
+}
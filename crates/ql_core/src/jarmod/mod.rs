@@ -133,6 +133,18 @@ pub async fn build(instance: &InstanceSelection) -> Result<PathBuf, JarModError>
     Ok(out_jar)
 }
 
+/// The jar [`build`] would return, without building
+/// it (or creating the `jarmods` directory).
+pub async fn build_path(instance: &InstanceSelection) -> Result<PathBuf, JarModError> {
+    let instance_dir = instance.get_instance_path();
+    let jarmods_dir = instance_dir.join("jarmods");
+    if !jarmods_dir.is_dir() || is_dir_empty(&jarmods_dir).await {
+        get_original_jar(instance, &instance_dir).await
+    } else {
+        Ok(instance_dir.join("build.jar"))
+    }
+}
+
 async fn get_original_jar(
     instance: &InstanceSelection,
     instance_dir: &Path,
@@ -0,0 +1,181 @@
+//! The play history of an instance, stored in
+//! `instances/<name>/history.json`.
+//!
+//! Every time the game exits, a [`PlaySession`]
+//! is added to the history.
+
+use std::{path::Path, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{err, file_utils, InstanceSelection, IntoIoError, IntoJsonError, JsonFileError};
+
+const HISTORY_FILE: &str = "history.json";
+
+/// A single time the game was played.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PlaySession {
+    /// Unix timestamp (seconds) of when the game was launched
+    pub start: i64,
+    /// Unix timestamp (seconds) of when the game exited
+    pub end: i64,
+    /// `None` if the game was killed by a signal
+    pub exit_code: Option<i32>,
+    /// `Offline`, `Microsoft`, `ElyBy`, `LittleSkin`, ...
+    pub account_type: String,
+    /// The Minecraft version, like `1.21.1`
+    pub version: String,
+    /// The mod loader, like `Fabric` or `Vanilla`
+    pub loader: String,
+}
+
+impl PlaySession {
+    #[must_use]
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs(u64::try_from(self.end - self.start).unwrap_or_default())
+    }
+}
+
+/// All the sessions an instance has been played for,
+/// oldest first.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct InstanceHistory {
+    pub sessions: Vec<PlaySession>,
+}
+
+impl InstanceHistory {
+    /// Loads the history of an instance from its directory.
+    /// Instances that were never played have an empty history.
+    ///
+    /// # Errors
+    /// If `history.json` exists but couldn't be read or parsed.
+    pub async fn read_from_dir(dir: &Path) -> Result<Self, JsonFileError> {
+        let path = dir.join(HISTORY_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let json = tokio::fs::read_to_string(&path).await.path(path)?;
+        Ok(serde_json::from_str(&json).json(json)?)
+    }
+
+    /// Loads the history of an instance.
+    /// Instances that were never played have an empty history.
+    ///
+    /// # Errors
+    /// If `history.json` exists but couldn't be read or parsed.
+    pub async fn read(instance: &InstanceSelection) -> Result<Self, JsonFileError> {
+        Self::read_from_dir(&instance.get_instance_path()).await
+    }
+
+    /// Adds a session to the history of an instance, saving it to disk.
+    ///
+    /// If `history.json` is corrupted, it's backed up
+    /// (as `history.json.bak`) and a new one is started,
+    /// so playtime keeps being recorded.
+    ///
+    /// # Errors
+    /// If `history.json` couldn't be read or written.
+    pub async fn add_session(
+        instance: &InstanceSelection,
+        session: PlaySession,
+    ) -> Result<(), JsonFileError> {
+        Self::add_session_to_dir(&instance.get_instance_path(), session).await
+    }
+
+    async fn add_session_to_dir(dir: &Path, session: PlaySession) -> Result<(), JsonFileError> {
+        let path = dir.join(HISTORY_FILE);
+        let mut history = match Self::read_from_dir(dir).await {
+            Ok(history) => history,
+            Err(JsonFileError::SerdeError(error)) => {
+                err!("Play history of {dir:?} is corrupted, starting a new one\n{error}");
+                let backup = path.with_extension("json.bak");
+                tokio::fs::rename(&path, &backup).await.path(&backup)?;
+                Self::default()
+            }
+            Err(error) => return Err(error),
+        };
+        history.sessions.push(session);
+
+        let json = serde_json::to_string_pretty(&history).json_to()?;
+        file_utils::write_atomic(&path, json).await?;
+        Ok(())
+    }
+
+    /// The total time this instance has been played for.
+    #[must_use]
+    pub fn total_playtime(&self) -> Duration {
+        self.sessions.iter().map(PlaySession::duration).sum()
+    }
+
+    /// Unix timestamp (seconds) of when
+    /// this instance was last played.
+    #[must_use]
+    pub fn last_played(&self) -> Option<i64> {
+        self.sessions.iter().map(|n| n.end).max()
+    }
+}
+
+/// Formats a playtime like `3h 25m`, or `12m` if under an hour.
+#[must_use]
+pub fn format_playtime(playtime: Duration) -> String {
+    let minutes = playtime.as_secs() / 60;
+    if minutes >= 60 {
+        format!("{}h {}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{minutes}m")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(start: i64, end: i64) -> PlaySession {
+        PlaySession {
+            start,
+            end,
+            exit_code: Some(0),
+            account_type: "Offline".to_owned(),
+            version: "1.21.1".to_owned(),
+            loader: "Vanilla".to_owned(),
+        }
+    }
+
+    #[test]
+    fn playtime() {
+        let history = InstanceHistory {
+            sessions: vec![
+                session(1000, 4600),
+                session(5000, 5600),
+                session(9000, 8000),
+            ],
+        };
+        assert_eq!(history.total_playtime(), Duration::from_secs(4200));
+        assert_eq!(history.last_played(), Some(8000));
+        assert_eq!(format_playtime(history.total_playtime()), "1h 10m");
+        assert_eq!(format_playtime(Duration::from_secs(59)), "0m");
+
+        assert_eq!(InstanceHistory::default().last_played(), None);
+    }
+
+    #[tokio::test]
+    async fn corrupted_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(HISTORY_FILE);
+        std::fs::write(&path, r#"{ "sessions": [{ "start": 10"#).unwrap();
+
+        InstanceHistory::add_session_to_dir(dir.path(), session(0, 60))
+            .await
+            .unwrap();
+        InstanceHistory::add_session_to_dir(dir.path(), session(100, 160))
+            .await
+            .unwrap();
+
+        let history = InstanceHistory::read_from_dir(dir.path()).await.unwrap();
+        assert_eq!(history.sessions, [session(0, 60), session(100, 160)]);
+        assert_eq!(
+            std::fs::read_to_string(path.with_extension("json.bak")).unwrap(),
+            r#"{ "sessions": [{ "start": 10"#
+        );
+    }
+}
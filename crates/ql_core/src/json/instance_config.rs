@@ -1,8 +1,8 @@
-use std::path::Path;
+use std::{collections::BTreeMap, path::Path};
 
 use serde::{Deserialize, Serialize};
 
-use crate::{InstanceSelection, IntoIoError, IntoJsonError, JsonFileError};
+use crate::{err, InstanceSelection, IntoIoError, IntoJsonError, JsonFileError};
 
 /// Configuration for using a custom Minecraft JAR file
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
@@ -11,6 +11,17 @@ pub struct CustomJarConfig {
     pub autoset_main_class: bool,
 }
 
+/// Which account an instance is launched with.
+/// See [`InstanceConfigJson::preferred_account`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum PreferredAccount {
+    /// A logged-in account, by its name in the
+    /// launcher's account list (like `Steve (elyby)`)
+    Account(String),
+    /// Offline mode with this username
+    Offline(String),
+}
+
 /// Defines how instance Java arguments should interact with global Java arguments
 #[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
 pub enum JavaArgsMode {
@@ -220,6 +231,14 @@ pub struct InstanceConfigJson {
     ///
     /// Ultimately if you want one less icon in your taskbar then go ahead.
     pub close_on_start: Option<bool>,
+    /// **Client Only**
+    ///
+    /// The account to launch this instance with
+    /// (from the play button, or the CLI when no username
+    /// is given) instead of the one selected in the launcher.
+    ///
+    /// **Default: `None`** (use the selected account)
+    pub preferred_account: Option<PreferredAccount>,
 
     pub global_settings: Option<GlobalSettings>,
 
@@ -234,6 +253,13 @@ pub struct InstanceConfigJson {
     ///
     /// **Default: `PreLaunchPrefixMode::CombineGlobalLocal`**
     pub pre_launch_prefix_mode: Option<PreLaunchPrefixMode>,
+    /// Controls how this instance's environment variables interact with
+    /// global environment variables. Works like [`Self::pre_launch_prefix_mode`]:
+    /// when combined, variables from whichever comes last take priority.
+    ///
+    /// **Default: `PreLaunchPrefixMode::CombineGlobalLocal`**
+    /// (instance variables override global ones)
+    pub env_vars_mode: Option<PreLaunchPrefixMode>,
     /// **Client and Server**
     ///
     /// Custom jar configuration for using alternative client/server jars.
@@ -368,6 +394,87 @@ impl InstanceConfigJson {
         }
     }
 
+    pub fn get_env_var_list(&mut self) -> &mut Vec<String> {
+        self.global_settings
+            .get_or_insert_with(GlobalSettings::default)
+            .env_vars
+            .get_or_insert_with(Vec::new)
+    }
+
+    /// Gets the environment variables to launch the game with,
+    /// with global fallback/combination support.
+    ///
+    /// The behavior depends on the instance's `env_vars_mode`
+    /// (see [`PreLaunchPrefixMode`]). When combining, if both define
+    /// the same variable then the one that comes last wins.
+    ///
+    /// Variables with invalid names (see [`is_valid_env_var_name`])
+    /// are skipped.
+    #[must_use]
+    pub fn get_env_vars(&self, global: Option<&GlobalSettings>) -> BTreeMap<String, String> {
+        fn meaningful(vars: Option<&GlobalSettings>) -> BTreeMap<String, String> {
+            vars.and_then(|n| n.env_vars.as_ref())
+                .into_iter()
+                .flatten()
+                .filter_map(|n| n.split_once('='))
+                .map(|(k, v)| (k.trim(), v))
+                .filter(|(k, _)| {
+                    if k.is_empty() {
+                        return false;
+                    }
+                    let is_valid = is_valid_env_var_name(k);
+                    if !is_valid {
+                        err!("Skipping environment variable with invalid name: {k:?}");
+                    }
+                    is_valid
+                })
+                .map(|(k, v)| (k.to_owned(), v.to_owned()))
+                .collect()
+        }
+
+        let instance_vars = meaningful(self.global_settings.as_ref());
+        let global_vars = meaningful(global);
+
+        match self.env_vars_mode.unwrap_or_default() {
+            PreLaunchPrefixMode::Fallback => {
+                if instance_vars.is_empty() {
+                    global_vars
+                } else {
+                    instance_vars
+                }
+            }
+            PreLaunchPrefixMode::Disable => instance_vars,
+            PreLaunchPrefixMode::CombineGlobalLocal => {
+                let mut vars = global_vars;
+                vars.extend(instance_vars);
+                vars
+            }
+            PreLaunchPrefixMode::CombineLocalGlobal => {
+                let mut vars = instance_vars;
+                vars.extend(global_vars);
+                vars
+            }
+        }
+    }
+
+    /// Gets the pre-launch and post-exit hook commands,
+    /// falling back to the global ones if the instance doesn't have any.
+    #[must_use]
+    pub fn get_hooks(&self, global: Option<&GlobalSettings>) -> (Option<String>, Option<String>) {
+        let get = |f: fn(&GlobalSettings) -> Option<&String>| {
+            self.global_settings
+                .as_ref()
+                .and_then(f)
+                .or(global.and_then(f))
+                .filter(|n| !n.trim().is_empty())
+                .cloned()
+        };
+        (
+            get(|n| n.pre_launch_hook.as_ref()),
+            get(|n| n.post_exit_hook.as_ref()),
+        )
+    }
+
     pub fn get_launch_prefix(&mut self) -> &mut Vec<String> {
         self.global_settings
             .get_or_insert_with(GlobalSettings::default)
@@ -428,6 +535,18 @@ impl InstanceConfigJson {
     }
 }
 
+/// Whether `name` can be used as an environment variable
+/// (`[A-Za-z_][A-Za-z0-9_]*`), so that it can be safely
+/// written into launch scripts.
+#[must_use]
+pub fn is_valid_env_var_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
 /// Settings that can both be set on a per-instance basis
 /// and also have a global default.
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
@@ -449,4 +568,137 @@ pub struct GlobalSettings {
     /// This is an optional list of commands to prepend
     /// to the launch command (e.g., "prime-run" for NVIDIA GPU usage on Linux).
     pub pre_launch_prefix: Option<Vec<String>>,
+    /// Environment variables to launch the game with,
+    /// in the form `KEY=value`.
+    pub env_vars: Option<Vec<String>>,
+    /// A shell command to run before launching the game.
+    /// If it fails, the game isn't launched.
+    ///
+    /// It receives these environment variables:
+    /// - `QL_INSTANCE_NAME`
+    /// - `QL_INSTANCE_DIR` (`instances/NAME/`)
+    /// - `QL_MINECRAFT_DIR` (`instances/NAME/.minecraft/`)
+    /// - `QL_LAUNCHER_DIR`
+    pub pre_launch_hook: Option<String>,
+    /// A shell command to run after the game exits.
+    ///
+    /// Receives the same environment variables as
+    /// [`Self::pre_launch_hook`], as well as `QL_EXIT_CODE`
+    /// (empty if the game was killed by a signal).
+    pub post_exit_hook: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(env_vars: &[&str]) -> GlobalSettings {
+        GlobalSettings {
+            env_vars: Some(env_vars.iter().map(|n| (*n).to_owned()).collect()),
+            ..Default::default()
+        }
+    }
+
+    fn env_vars(
+        mode: PreLaunchPrefixMode,
+        instance: &[&str],
+        global: &[&str],
+    ) -> Vec<(String, String)> {
+        let mut config: InstanceConfigJson =
+            serde_json::from_str(r#"{ "mod_type": "Vanilla", "ram_in_mb": 2048 }"#).unwrap();
+        config.env_vars_mode = Some(mode);
+        config.global_settings = Some(settings(instance));
+        config
+            .get_env_vars(Some(&settings(global)))
+            .into_iter()
+            .collect()
+    }
+
+    fn pairs(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter()
+            .map(|(k, v)| ((*k).to_owned(), (*v).to_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn env_vars_fallback() {
+        let global = ["A=global", "G=1"];
+        assert_eq!(
+            env_vars(PreLaunchPrefixMode::Fallback, &["A=local"], &global),
+            pairs(&[("A", "local")])
+        );
+        // Blank entries don't count as having variables
+        assert_eq!(
+            env_vars(
+                PreLaunchPrefixMode::Fallback,
+                &["", " =x", "novalue"],
+                &global
+            ),
+            pairs(&[("A", "global"), ("G", "1")])
+        );
+    }
+
+    #[test]
+    fn env_vars_disable() {
+        assert_eq!(
+            env_vars(PreLaunchPrefixMode::Disable, &[], &["A=global"]),
+            pairs(&[])
+        );
+        assert_eq!(
+            env_vars(
+                PreLaunchPrefixMode::Disable,
+                &["A=local"],
+                &["A=global", "G=1"]
+            ),
+            pairs(&[("A", "local")])
+        );
+    }
+
+    #[test]
+    fn env_vars_combine() {
+        let (instance, global) = (["A=local", "L=1"], ["A=global", "G=1"]);
+        assert_eq!(
+            env_vars(PreLaunchPrefixMode::CombineGlobalLocal, &instance, &global),
+            pairs(&[("A", "local"), ("G", "1"), ("L", "1")])
+        );
+        assert_eq!(
+            env_vars(PreLaunchPrefixMode::CombineLocalGlobal, &instance, &global),
+            pairs(&[("A", "global"), ("G", "1"), ("L", "1")])
+        );
+    }
+
+    #[test]
+    fn env_vars_duplicates() {
+        // Within one list, the last one wins too.
+        // Keys are trimmed, values kept as-is (including `=`)
+        assert_eq!(
+            env_vars(
+                PreLaunchPrefixMode::Disable,
+                &["A=1", " A =2", "B=x=y", "C= spaced "],
+                &[]
+            ),
+            pairs(&[("A", "2"), ("B", "x=y"), ("C", " spaced ")])
+        );
+    }
+
+    #[test]
+    fn env_var_names() {
+        assert!(is_valid_env_var_name("JAVA_HOME"));
+        assert!(is_valid_env_var_name("_x1"));
+        assert!(!is_valid_env_var_name(""));
+        assert!(!is_valid_env_var_name("1ABC"));
+        assert!(!is_valid_env_var_name("A B"));
+        assert!(!is_valid_env_var_name("A;rm -rf ~"));
+        assert!(!is_valid_env_var_name("$(id)"));
+        assert!(!is_valid_env_var_name("Ä"));
+
+        assert_eq!(
+            env_vars(
+                PreLaunchPrefixMode::Disable,
+                &["OK=1", "A B=2", "X;id=3", "$(id)=4"],
+                &[]
+            ),
+            pairs(&[("OK", "1")])
+        );
+    }
 }
@@ -0,0 +1,314 @@
+//! Launcher-side information about instances
+//! (groups, tags, favourites, creation date), stored in
+//! `QuantumLauncher/instance_meta.json`.
+//!
+//! Unlike [`super::InstanceConfigJson`] this isn't part of the
+//! instance itself, so it isn't exported with it.
+
+use std::{cmp::Ordering, collections::BTreeMap, fmt::Display};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    json::{InstanceConfigJson, InstanceHistory, VersionDetails},
+    InstanceSelection, IntoIoError, IntoJsonError, JsonFileError, LAUNCHER_DIR,
+};
+
+const META_FILE: &str = "instance_meta.json";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct InstanceMeta {
+    /// The group (collapsible folder in the sidebar)
+    /// this instance is in, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Favourites are shown at the top of the list
+    #[serde(default)]
+    pub favourite: bool,
+    /// Unix timestamp (seconds) of when the instance was created.
+    ///
+    /// `None` for instances created before this was tracked,
+    /// in which case the directory's creation time is used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<i64>,
+}
+
+impl InstanceMeta {
+    /// Case-insensitive check for a tag.
+    #[must_use]
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|n| n.eq_ignore_ascii_case(tag))
+    }
+
+    /// Whether an instance with this metadata (and `name`) should be
+    /// shown for a search `filter`, matching the name, group or tags.
+    #[must_use]
+    pub fn matches_filter(&self, name: &str, filter: &str) -> bool {
+        let filter = filter.trim().to_lowercase();
+        if filter.is_empty() {
+            return true;
+        }
+        name.to_lowercase().contains(&filter)
+            || self
+                .group
+                .as_ref()
+                .is_some_and(|n| n.to_lowercase().contains(&filter))
+            || self.tags.iter().any(|n| n.to_lowercase().contains(&filter))
+    }
+}
+
+/// The metadata of all instances and servers, by name.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct InstanceMetaFile {
+    #[serde(default)]
+    pub instances: BTreeMap<String, InstanceMeta>,
+    #[serde(default)]
+    pub servers: BTreeMap<String, InstanceMeta>,
+}
+
+impl InstanceMetaFile {
+    /// Loads the metadata of all instances.
+    /// If it doesn't exist yet, it's empty.
+    ///
+    /// # Errors
+    /// If the file exists but couldn't be read or parsed.
+    pub async fn load() -> Result<Self, JsonFileError> {
+        let path = LAUNCHER_DIR.join(META_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let json = tokio::fs::read_to_string(&path).await.path(path)?;
+        Ok(serde_json::from_str(&json).json(json)?)
+    }
+
+    /// Written to a temporary file first and then moved
+    /// into place, so an interrupted (or overlapping) save
+    /// never leaves a half-written file behind.
+    ///
+    /// # Errors
+    /// If the file couldn't be written.
+    pub async fn save(&self) -> Result<(), JsonFileError> {
+        let path = LAUNCHER_DIR.join(META_FILE);
+        let tmp_path = path.with_extension("json.tmp");
+        let json = serde_json::to_string_pretty(self).json_to()?;
+        tokio::fs::write(&tmp_path, json).await.path(&tmp_path)?;
+        tokio::fs::rename(&tmp_path, &path).await.path(path)?;
+        Ok(())
+    }
+
+    fn map(&self, is_server: bool) -> &BTreeMap<String, InstanceMeta> {
+        if is_server {
+            &self.servers
+        } else {
+            &self.instances
+        }
+    }
+
+    #[must_use]
+    pub fn get(&self, instance: &InstanceSelection) -> Option<&InstanceMeta> {
+        self.map(instance.is_server()).get(instance.get_name())
+    }
+
+    pub fn get_mut(&mut self, instance: &InstanceSelection) -> &mut InstanceMeta {
+        let map = if instance.is_server() {
+            &mut self.servers
+        } else {
+            &mut self.instances
+        };
+        map.entry(instance.get_name().to_owned()).or_default()
+    }
+
+    /// Forgets the metadata of a deleted instance.
+    /// Returns whether it had any.
+    pub fn remove(&mut self, instance: &InstanceSelection) -> bool {
+        let map = if instance.is_server() {
+            &mut self.servers
+        } else {
+            &mut self.instances
+        };
+        map.remove(instance.get_name()).is_some()
+    }
+
+    /// Moves the metadata of a renamed instance to its new name.
+    /// Returns whether it had any.
+    pub fn rename(&mut self, instance: &InstanceSelection, new_name: &str) -> bool {
+        let map = if instance.is_server() {
+            &mut self.servers
+        } else {
+            &mut self.instances
+        };
+        if let Some(meta) = map.remove(instance.get_name()) {
+            map.insert(new_name.to_owned(), meta);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Records the current time as the creation date
+    /// of a freshly created instance.
+    ///
+    /// # Errors
+    /// If the metadata file couldn't be read or written.
+    pub async fn record_created(instance: &InstanceSelection) -> Result<(), JsonFileError> {
+        let mut meta = Self::load().await?;
+        meta.get_mut(instance).created = Some(chrono::Utc::now().timestamp());
+        meta.save().await
+    }
+}
+
+/// The order of the instance list.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InstanceSort {
+    /// Alphabetical
+    #[default]
+    Name,
+    /// Newest Minecraft version first
+    Version,
+    /// Grouped by mod loader
+    Loader,
+    /// Most recently played first
+    LastPlayed,
+    /// Most recently created first
+    Created,
+}
+
+impl InstanceSort {
+    pub const ALL: &'static [Self] = &[
+        Self::Name,
+        Self::Version,
+        Self::Loader,
+        Self::LastPlayed,
+        Self::Created,
+    ];
+}
+
+impl Display for InstanceSort {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                InstanceSort::Name => "Name",
+                InstanceSort::Version => "Version",
+                InstanceSort::Loader => "Loader",
+                InstanceSort::LastPlayed => "Last played",
+                InstanceSort::Created => "Date created",
+            }
+        )
+    }
+}
+
+/// What instances are compared by, for [`sort_instances`].
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum SortKey {
+    /// Sorted descending
+    Newest(std::cmp::Reverse<Option<i64>>),
+    /// Sorted descending
+    ReleaseTime(std::cmp::Reverse<String>),
+    Text(String),
+}
+
+/// Sorts instances (or servers) by `sort`, with
+/// favourites first and the name as a tiebreaker.
+///
+/// Instances whose details couldn't be read go last.
+pub async fn sort_instances(
+    instances: &mut [String],
+    is_server: bool,
+    sort: InstanceSort,
+    meta: &InstanceMetaFile,
+) {
+    let mut keys = std::collections::HashMap::new();
+    for name in instances.iter() {
+        let instance = InstanceSelection::new(name, is_server);
+        let key = match sort {
+            InstanceSort::Name => None,
+            InstanceSort::Version => Some(SortKey::ReleaseTime(std::cmp::Reverse(
+                VersionDetails::load(&instance)
+                    .await
+                    .map(|n| n.releaseTime)
+                    .unwrap_or_default(),
+            ))),
+            InstanceSort::Loader => Some(SortKey::Text(
+                InstanceConfigJson::read(&instance)
+                    .await
+                    .map(|n| n.mod_type.to_lowercase())
+                    // Sorts after everything else
+                    .unwrap_or_else(|_| "~".to_owned()),
+            )),
+            InstanceSort::LastPlayed => Some(SortKey::Newest(std::cmp::Reverse(
+                InstanceHistory::read(&instance)
+                    .await
+                    .ok()
+                    .and_then(|n| n.last_played()),
+            ))),
+            InstanceSort::Created => Some(SortKey::Newest(std::cmp::Reverse(
+                created_time(&instance, meta).await,
+            ))),
+        };
+        keys.insert(name.clone(), key);
+    }
+
+    let is_favourite = |name: &String| meta.map(is_server).get(name).is_some_and(|n| n.favourite);
+    instances.sort_by(|a, b| {
+        is_favourite(b)
+            .cmp(&is_favourite(a))
+            .then_with(|| keys.get(a).cmp(&keys.get(b)))
+            .then_with(|| compare_names(a, b))
+    });
+}
+
+fn compare_names(a: &str, b: &str) -> Ordering {
+    a.to_lowercase()
+        .cmp(&b.to_lowercase())
+        .then_with(|| a.cmp(b))
+}
+
+/// When the instance was created: from the metadata,
+/// else the creation (or modification) time of its directory.
+async fn created_time(instance: &InstanceSelection, meta: &InstanceMetaFile) -> Option<i64> {
+    if let Some(created) = meta.get(instance).and_then(|n| n.created) {
+        return Some(created);
+    }
+    let metadata = tokio::fs::metadata(instance.get_instance_path())
+        .await
+        .ok()?;
+    let time = metadata.created().or_else(|_| metadata.modified()).ok()?;
+    let time = time.duration_since(std::time::UNIX_EPOCH).ok()?;
+    i64::try_from(time.as_secs()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter() {
+        let meta = InstanceMeta {
+            group: Some("Modded".to_owned()),
+            tags: vec!["Survival".to_owned(), "1.20".to_owned()],
+            favourite: false,
+            created: None,
+        };
+        assert!(meta.matches_filter("Anything", ""));
+        assert!(meta.matches_filter("Create Mod", "create"));
+        assert!(meta.matches_filter("Something", "modd"));
+        assert!(meta.matches_filter("Something", "SURVIVAL"));
+        assert!(!meta.matches_filter("Something", "creative"));
+        assert!(meta.has_tag("survival"));
+        assert!(!meta.has_tag("surv"));
+    }
+
+    #[tokio::test]
+    async fn sort_favourites_first() {
+        let mut meta = InstanceMetaFile::default();
+        meta.get_mut(&InstanceSelection::Instance("zeta".to_owned()))
+            .favourite = true;
+        let mut instances = vec!["beta".to_owned(), "Alpha".to_owned(), "zeta".to_owned()];
+        sort_instances(&mut instances, false, InstanceSort::Name, &meta).await;
+        assert_eq!(instances, ["zeta", "Alpha", "beta"]);
+    }
+}
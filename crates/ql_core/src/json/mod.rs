@@ -3,7 +3,9 @@ pub mod forge;
 pub mod optifine;
 
 pub mod asset_index;
+pub mod history;
 pub mod instance_config;
+pub mod instance_meta;
 pub mod manifest;
 pub mod version;
 
@@ -11,7 +13,9 @@ pub use fabric::FabricJSON;
 pub use optifine::{JsonOptifine, OptifineArguments, OptifineLibrary};
 
 pub use asset_index::AssetIndex;
+pub use history::{InstanceHistory, PlaySession};
 pub use instance_config::{GlobalSettings, InstanceConfigJson};
+pub use instance_meta::{InstanceMeta, InstanceMetaFile, InstanceSort};
 pub use manifest::Manifest;
 pub use version::{
     VersionDetails, V_1_12_2, V_1_5_2, V_OFFICIAL_FABRIC_SUPPORT, V_PRECLASSIC_LAST,
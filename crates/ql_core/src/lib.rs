@@ -19,6 +19,8 @@ pub mod clean;
 mod error;
 /// Common utilities for working with files.
 pub mod file_utils;
+/// Custom instance icons.
+pub mod icon;
 pub mod jarmod;
 /// JSON structs for version, instance config, Fabric, Forge, Optifine, Quilt, Neoforge, etc.
 pub mod json;
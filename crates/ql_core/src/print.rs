@@ -2,7 +2,10 @@ use std::{
     fmt::Display,
     fs::{File, OpenOptions},
     io::{BufWriter, Write},
-    sync::{LazyLock, Mutex},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        LazyLock, Mutex,
+    },
 };
 
 use chrono::{Datelike, Timelike};
@@ -173,13 +176,31 @@ pub fn print_to_storage(msg: &str, t: LogType) {
     }
 }
 
+static LOGS_TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+/// Prints [`info!`] and [`pt!`] messages to stderr instead
+/// of stdout, for when stdout is the actual output of a
+/// command (eg: `launch --dry-run`) and may be piped.
+pub fn set_logs_to_stderr(to_stderr: bool) {
+    LOGS_TO_STDERR.store(to_stderr, Ordering::Relaxed);
+}
+
+#[doc(hidden)]
+pub fn print_log_line(line: std::fmt::Arguments) {
+    if LOGS_TO_STDERR.load(Ordering::Relaxed) {
+        eprintln!("{line}");
+    } else {
+        println!("{line}");
+    }
+}
+
 /// Print an informational message.
 /// Saved to a log file.
 #[macro_export]
 macro_rules! info {
     ($($arg:tt)*) => {{
         let plain_text = $crate::print::strip_ansi_codes(&format!("{}", format_args!($($arg)*)));
-        println!("{} {}", owo_colors::OwoColorize::yellow(&"[info]"), format_args!($($arg)*));
+        $crate::print::print_log_line(format_args!("{} {}", owo_colors::OwoColorize::yellow(&"[info]"), format_args!($($arg)*)));
         $crate::print::print_to_file(&plain_text, $crate::print::LogType::Info);
     }};
 }
@@ -190,7 +211,7 @@ macro_rules! info {
 macro_rules! info_no_log {
     ($($arg:tt)*) => {{
         let plain_text = $crate::print::strip_ansi_codes(&format!("{}", format_args!($($arg)*)));
-        println!("{} {}", owo_colors::OwoColorize::yellow(&"[info]"), format_args!($($arg)*));
+        $crate::print::print_log_line(format_args!("{} {}", owo_colors::OwoColorize::yellow(&"[info]"), format_args!($($arg)*)));
         $crate::print::print_to_storage(&plain_text, $crate::print::LogType::Info);
     }};
 }
@@ -223,7 +244,7 @@ macro_rules! err {
 macro_rules! pt {
     ($($arg:tt)*) => {{
         let plain_text = $crate::print::strip_ansi_codes(&format!("{}", format_args!($($arg)*)));
-        println!("{} {}", owo_colors::OwoColorize::bold(&"-"), format_args!($($arg)*));
+        $crate::print::print_log_line(format_args!("{} {}", owo_colors::OwoColorize::bold(&"-"), format_args!($($arg)*)));
         $crate::print::print_to_file(&plain_text, $crate::print::LogType::Point);
     }};
 }
@@ -19,7 +19,7 @@ impl std::fmt::Display for AccountResponseError {
     }
 }
 
-const AUTH_ERR_PREFIX: &str = "while logging into ely.by/littleskin account:\n";
+const AUTH_ERR_PREFIX: &str = "while logging into ely.by/littleskin/custom server account:\n";
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error("{AUTH_ERR_PREFIX}{0}")]
@@ -32,6 +32,8 @@ pub enum Error {
     KeyringError(#[from] KeyringError),
     #[error("{AUTH_ERR_PREFIX}Littleskin response:\n{0}")]
     LittleSkin(String),
+    #[error("{AUTH_ERR_PREFIX}not a Yggdrasil (authlib-injector) auth server: {0}")]
+    InvalidAuthServer(String),
 
     #[error("{AUTH_ERR_PREFIX}while logging in through oauth:\n{0}")]
     Oauth(#[from] OauthError),
@@ -41,12 +43,14 @@ pub enum Error {
 pub enum OauthError {
     #[error("device code expired")]
     DeviceCodeExpired,
-    #[error("unexpected response from littleskin:\n\n{0}")]
+    #[error("unexpected response from auth server:\n\n{0}")]
     UnexpectedResponse(String),
     #[error("no access token in response")]
     NoAccessToken,
     #[error("no minecraft profile found for account")]
     NoMinecraftProfile,
+    #[error("this account type doesn't support oauth login")]
+    NotSupported,
 }
 
 impl From<reqwest::Error> for Error {
@@ -57,11 +61,12 @@ impl From<reqwest::Error> for Error {
 
 impl From<keyring::Error> for Error {
     fn from(err: keyring::Error) -> Self {
-        Self::KeyringError(KeyringError(err))
+        Self::KeyringError(KeyringError::Keyring(err))
     }
 }
 
 #[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)]
 pub enum Account {
     Account(AccountData),
     NeedsOTP,
@@ -0,0 +1,437 @@
+//! Where account refresh tokens are saved.
+//!
+//! By default they go in the OS keyring (Secret Service, Windows
+//! Credential Manager, macOS Keychain) through the `keyring` crate.
+//! On systems without one (headless Linux boxes, minimal window managers)
+//! they're saved in an encrypted file, `QuantumLauncher/credentials.json`,
+//! instead. This happens automatically when the keyring fails,
+//! or always if [`CredentialStore::EncryptedFile`] is selected.
+//!
+//! The file is encrypted (XChaCha20-Poly1305) with a key derived (Argon2)
+//! from the `QL_CREDENTIAL_PASSPHRASE` environment variable if set,
+//! or otherwise from a machine-specific secret (`/etc/machine-id` on Linux).
+//! The latter only prevents reading the tokens if the file is copied
+//! to another computer; use a passphrase for real protection.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    path::PathBuf,
+    sync::{Mutex, RwLock},
+};
+
+use argon2::Argon2;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::{
+    aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng},
+    XChaCha20Poly1305, XNonce,
+};
+use ql_core::{err, info, IntoIoError, IntoJsonError, IoError, JsonError, LAUNCHER_DIR};
+use serde::{Deserialize, Serialize};
+
+use super::{AccountType, KeyringError};
+
+const SERVICE: &str = "QuantumLauncher";
+const FILE_NAME: &str = "credentials.json";
+/// Used in place of `/etc/machine-id` on systems without one
+const SECRET_FILE_NAME: &str = ".credentials_secret";
+pub const PASSPHRASE_ENV_VAR: &str = "QL_CREDENTIAL_PASSPHRASE";
+
+static STORE: RwLock<CredentialStore> = RwLock::new(CredentialStore::Keyring);
+/// Prevents concurrent logins/refreshes from
+/// overwriting each other's changes to the file.
+static FILE_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CredentialStore {
+    /// The OS keyring, falling back to the
+    /// encrypted file if it's unavailable.
+    #[default]
+    Keyring,
+    EncryptedFile,
+}
+
+impl CredentialStore {
+    pub const ALL: &'static [Self] = &[Self::Keyring, Self::EncryptedFile];
+
+    #[must_use]
+    fn other(self) -> Self {
+        match self {
+            CredentialStore::Keyring => CredentialStore::EncryptedFile,
+            CredentialStore::EncryptedFile => CredentialStore::Keyring,
+        }
+    }
+}
+
+impl Display for CredentialStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CredentialStore::Keyring => write!(f, "System keyring"),
+            CredentialStore::EncryptedFile => write!(f, "Encrypted file"),
+        }
+    }
+}
+
+/// Sets where tokens are saved from now on
+/// (usually from the launcher config, at startup).
+///
+/// This doesn't move existing tokens, see [`migrate`] for that.
+pub fn set_store(store: CredentialStore) {
+    if let Ok(mut n) = STORE.write() {
+        *n = store;
+    }
+}
+
+#[must_use]
+pub fn get_store() -> CredentialStore {
+    STORE.read().map_or(CredentialStore::default(), |n| *n)
+}
+
+const FILE_ERR_PREFIX: &str = "Encrypted credential file error:\n";
+#[derive(Debug, thiserror::Error)]
+pub enum CredentialFileError {
+    #[error("{FILE_ERR_PREFIX}{0}")]
+    Io(#[from] IoError),
+    #[error("{FILE_ERR_PREFIX}{0}")]
+    Json(#[from] JsonError),
+    #[error("{FILE_ERR_PREFIX}invalid base64: {0}")]
+    Base64(#[from] base64::DecodeError),
+    #[error("{FILE_ERR_PREFIX}couldn't derive key: {0}")]
+    Kdf(String),
+    #[error("{FILE_ERR_PREFIX}couldn't decrypt {FILE_NAME}\n\nWas it created with a different passphrase ({PASSPHRASE_ENV_VAR}),\nor copied from another computer?")]
+    Decrypt,
+    #[error("{FILE_ERR_PREFIX}couldn't encrypt credentials")]
+    Encrypt,
+    // Deliberately doesn't include the contents (tokens)
+    #[error("{FILE_ERR_PREFIX}decrypted contents are corrupted: {0}")]
+    Corrupted(serde_json::Error),
+    #[error("{FILE_ERR_PREFIX}no saved login for {0:?}")]
+    NoEntry(String),
+}
+
+/// A saved refresh token of an account, in whichever
+/// [`CredentialStore`] is selected.
+///
+/// Has the same interface as [`keyring::Entry`].
+pub(crate) struct Credential {
+    user: String,
+}
+
+impl Credential {
+    pub(crate) fn new(user: String) -> Self {
+        Self { user }
+    }
+
+    pub(crate) fn get_password(&self) -> Result<String, KeyringError> {
+        if get_store() == CredentialStore::EncryptedFile {
+            return Ok(file_get(&self.user)?);
+        }
+        match keyring_get(&self.user) {
+            Ok(n) => Ok(n),
+            // Might have been saved there while the keyring was down
+            Err(err) if is_unavailable(&err) || matches!(err, keyring::Error::NoEntry) => {
+                match file_get(&self.user) {
+                    Ok(n) => Ok(n),
+                    Err(_) => Err(err.into()),
+                }
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub(crate) fn set_password(&self, password: &str) -> Result<(), KeyringError> {
+        if get_store() == CredentialStore::EncryptedFile {
+            return Ok(file_set(&self.user, Some(password))?);
+        }
+        match keyring_set(&self.user, password) {
+            Ok(()) => Ok(()),
+            Err(err) if is_unavailable(&err) => {
+                err!("Keyring unavailable, saving login to encrypted file instead:\n{err}");
+                Ok(file_set(&self.user, Some(password))?)
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Removes the token from both stores.
+    pub(crate) fn delete_credential(&self) -> Result<(), KeyringError> {
+        if file_path().exists() {
+            file_set(&self.user, None)?;
+        }
+        if get_store() == CredentialStore::EncryptedFile {
+            return Ok(());
+        }
+        match keyring_entry(&self.user).and_then(|n| n.delete_credential()) {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+/// Moves the saved tokens of `accounts` (keyring identifier, type)
+/// to the `to` store, returning how many were moved.
+///
+/// Accounts without a token in the other store are skipped.
+///
+/// # Errors
+/// If either store couldn't be accessed. Tokens moved
+/// before the error stay in the new store.
+pub fn migrate(
+    accounts: &[(String, AccountType)],
+    to: CredentialStore,
+) -> Result<usize, KeyringError> {
+    let from = to.other();
+    let mut moved = 0;
+    for (username, account_type) in accounts {
+        let user = account_type.get_keyring_user(username);
+        let token = match from {
+            CredentialStore::Keyring => match keyring_get(&user) {
+                Ok(n) => n,
+                // If the keyring is down, the token (if any)
+                // was already saved to the file
+                Err(err) if is_unavailable(&err) => continue,
+                Err(keyring::Error::NoEntry) => continue,
+                Err(err) => return Err(err.into()),
+            },
+            CredentialStore::EncryptedFile => match file_get(&user) {
+                Ok(n) => n,
+                Err(CredentialFileError::NoEntry(_)) => continue,
+                Err(err) => return Err(err.into()),
+            },
+        };
+        match to {
+            CredentialStore::Keyring => {
+                keyring_set(&user, &token)?;
+                file_set(&user, None)?;
+            }
+            CredentialStore::EncryptedFile => {
+                file_set(&user, Some(&token))?;
+                match keyring_entry(&user).and_then(|n| n.delete_credential()) {
+                    Ok(()) | Err(keyring::Error::NoEntry) => {}
+                    Err(err) => return Err(err.into()),
+                }
+            }
+        }
+        moved += 1;
+    }
+    info!("Moved {moved} account login(s) to {to}");
+    Ok(moved)
+}
+
+fn is_unavailable(err: &keyring::Error) -> bool {
+    matches!(
+        err,
+        keyring::Error::PlatformFailure(_) | keyring::Error::NoStorageAccess(_)
+    )
+}
+
+fn keyring_entry(user: &str) -> Result<keyring::Entry, keyring::Error> {
+    keyring::Entry::new(SERVICE, user)
+}
+
+fn keyring_get(user: &str) -> Result<String, keyring::Error> {
+    keyring_entry(user)?.get_password()
+}
+
+fn keyring_set(user: &str, password: &str) -> Result<(), keyring::Error> {
+    keyring_entry(user)?.set_password(password)
+}
+
+fn file_path() -> PathBuf {
+    LAUNCHER_DIR.join(FILE_NAME)
+}
+
+fn file_get(user: &str) -> Result<String, CredentialFileError> {
+    let _lock = FILE_LOCK.lock();
+    let path = file_path();
+    if !path.exists() {
+        return Err(CredentialFileError::NoEntry(user.to_owned()));
+    }
+    let mut entries = read_file(&path)?;
+    entries
+        .remove(user)
+        .ok_or_else(|| CredentialFileError::NoEntry(user.to_owned()))
+}
+
+/// Saves (or with `None`, removes) a token in the file.
+fn file_set(user: &str, password: Option<&str>) -> Result<(), CredentialFileError> {
+    let _lock = FILE_LOCK.lock();
+    let path = file_path();
+    let mut entries = if path.exists() {
+        read_file(&path)?
+    } else {
+        BTreeMap::new()
+    };
+    if let Some(password) = password {
+        entries.insert(user.to_owned(), password.to_owned());
+    } else if entries.remove(user).is_none() {
+        return Ok(());
+    }
+    write_file(&path, &entries)
+}
+
+#[derive(Serialize, Deserialize)]
+struct EncryptedFile {
+    /// Whether the key is derived from [`PASSPHRASE_ENV_VAR`]
+    /// (for better error messages)
+    passphrase: bool,
+    salt: String,
+    nonce: String,
+    data: String,
+}
+
+fn read_file(path: &std::path::Path) -> Result<BTreeMap<String, String>, CredentialFileError> {
+    let json = std::fs::read_to_string(path).path(path)?;
+    let file: EncryptedFile = serde_json::from_str(&json).json(json)?;
+    if file.passphrase && std::env::var_os(PASSPHRASE_ENV_VAR).is_none() {
+        err!("{FILE_NAME} is encrypted with a passphrase, but {PASSPHRASE_ENV_VAR} isn't set");
+    }
+
+    let salt = STANDARD.decode(&file.salt)?;
+    let nonce = STANDARD.decode(&file.nonce)?;
+    let data = STANDARD.decode(&file.data)?;
+    if nonce.len() != 24 {
+        return Err(CredentialFileError::Decrypt);
+    }
+    let data = decrypt(&get_secret()?, &salt, &nonce, &data)?;
+    serde_json::from_slice(&data).map_err(CredentialFileError::Corrupted)
+}
+
+fn write_file(
+    path: &std::path::Path,
+    entries: &BTreeMap<String, String>,
+) -> Result<(), CredentialFileError> {
+    let data = serde_json::to_vec(entries).json_to()?;
+    let mut salt = [0; 16];
+    OsRng.fill_bytes(&mut salt);
+    let (nonce, data) = encrypt(&get_secret()?, &salt, &data)?;
+
+    let file = EncryptedFile {
+        passphrase: std::env::var_os(PASSPHRASE_ENV_VAR).is_some(),
+        salt: STANDARD.encode(salt),
+        nonce: STANDARD.encode(nonce),
+        data: STANDARD.encode(data),
+    };
+    let json = serde_json::to_string_pretty(&file).json_to()?;
+    write_private(path, json.as_bytes()).path(path)?;
+    Ok(())
+}
+
+fn get_cipher(secret: &[u8], salt: &[u8]) -> Result<XChaCha20Poly1305, CredentialFileError> {
+    let mut key = [0; 32];
+    Argon2::default()
+        .hash_password_into(secret, salt, &mut key)
+        .map_err(|n| CredentialFileError::Kdf(n.to_string()))?;
+    Ok(XChaCha20Poly1305::new(&key.into()))
+}
+
+fn encrypt(
+    secret: &[u8],
+    salt: &[u8],
+    data: &[u8],
+) -> Result<(Vec<u8>, Vec<u8>), CredentialFileError> {
+    let cipher = get_cipher(secret, salt)?;
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let data = cipher
+        .encrypt(&nonce, data)
+        .map_err(|_| CredentialFileError::Encrypt)?;
+    Ok((nonce.to_vec(), data))
+}
+
+fn decrypt(
+    secret: &[u8],
+    salt: &[u8],
+    nonce: &[u8],
+    data: &[u8],
+) -> Result<Vec<u8>, CredentialFileError> {
+    get_cipher(secret, salt)?
+        .decrypt(XNonce::from_slice(nonce), data)
+        .map_err(|_| CredentialFileError::Decrypt)
+}
+
+/// The passphrase if set, otherwise a machine-specific secret.
+fn get_secret() -> Result<Vec<u8>, CredentialFileError> {
+    if let Some(passphrase) = std::env::var_os(PASSPHRASE_ENV_VAR) {
+        return Ok(passphrase.into_encoded_bytes());
+    }
+    for path in ["/etc/machine-id", "/var/lib/dbus/machine-id"] {
+        if let Ok(id) = std::fs::read_to_string(path) {
+            let id = id.trim();
+            if !id.is_empty() {
+                return Ok(id.as_bytes().to_vec());
+            }
+        }
+    }
+
+    // No machine id (Windows, macOS, some containers),
+    // so generate one and keep it in the launcher dir
+    let path = LAUNCHER_DIR.join(SECRET_FILE_NAME);
+    if path.exists() {
+        return Ok(std::fs::read(&path).path(path)?);
+    }
+    let mut secret = [0; 32];
+    OsRng.fill_bytes(&mut secret);
+    let secret = STANDARD.encode(secret).into_bytes();
+    write_private(&path, &secret).path(&path)?;
+    Ok(secret)
+}
+
+/// Writes a file only readable by the current user.
+///
+/// The contents go to a temporary file that's created
+/// with those permissions (so they're never readable by
+/// others, even briefly), which then replaces `path`.
+fn write_private(path: &std::path::Path, contents: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let tmp_path = path.with_extension("tmp");
+    // Left over from a crash
+    _ = std::fs::remove_file(&tmp_path);
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&tmp_path)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    drop(file);
+    std::fs::rename(&tmp_path, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encrypt_roundtrip() {
+        let (secret, salt) = (b"machine-id", [7; 16]);
+        let (nonce, data) = encrypt(secret, &salt, b"token").unwrap();
+        assert_ne!(data, b"token");
+        assert_eq!(decrypt(secret, &salt, &nonce, &data).unwrap(), b"token");
+
+        assert!(matches!(
+            decrypt(b"other-machine", &salt, &nonce, &data),
+            Err(CredentialFileError::Decrypt)
+        ));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn private_file() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = std::env::temp_dir().join(format!("ql_private_{}.json", std::process::id()));
+        std::fs::write(&path, "old").unwrap();
+        write_private(&path, b"secret").unwrap();
+
+        assert_eq!(std::fs::read(&path).unwrap(), b"secret");
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert!(!path.with_extension("tmp").exists());
+        std::fs::remove_file(path).unwrap();
+    }
+}
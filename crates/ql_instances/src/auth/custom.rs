@@ -0,0 +1,195 @@
+//! User-defined Yggdrasil (authlib-injector compatible)
+//! authentication servers, for self-hosted account
+//! systems like [Blessing Skin](https://github.com/bs-community/blessing-skin-server).
+
+use ql_core::{info, pt, CLIENT};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+use super::alt::Error;
+
+/// The header that points a website to its Yggdrasil API root
+/// (see the authlib-injector "API Location Indication" spec).
+const API_LOCATION_HEADER: &str = "X-Authlib-Injector-API-Location";
+
+/// A custom Yggdrasil authentication server.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CustomAuthServer {
+    /// Shown in the UI and appended to usernames,
+    /// like `Steve (My Server)`. Must be unique.
+    pub name: String,
+    /// The root of the Yggdrasil API, which is passed
+    /// to authlib-injector.
+    ///
+    /// Example: `https://example.com/api/yggdrasil`
+    pub api_root: String,
+    /// Login through an OAuth device code flow
+    /// (like littleskin's), in addition to password login.
+    pub oauth: Option<CustomOauth>,
+}
+
+/// OAuth device code flow settings of a [`CustomAuthServer`].
+///
+/// After logging in, a Minecraft token is obtained from
+/// `{api_root}/authserver/oauth` (as Blessing Skin does).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CustomOauth {
+    pub client_id: String,
+    /// Example: `https://example.com/oauth/device_code`
+    pub device_code_url: String,
+    /// Example: `https://example.com/oauth/token`
+    pub token_url: String,
+    /// If `None`, the Yggdrasil scopes used for littleskin are requested.
+    pub scope: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ApiMetadata {
+    meta: ApiMetadataMeta,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiMetadataMeta {
+    server_name: Option<String>,
+}
+
+impl CustomAuthServer {
+    /// Finds the Yggdrasil API of a website and creates
+    /// a server for it.
+    ///
+    /// `url` can be the website's homepage (which points to the
+    /// API through the `X-Authlib-Injector-API-Location` header)
+    /// or the API root itself. `https://` is assumed if no scheme is given.
+    ///
+    /// If `name` is `None`, the server's advertised name is used.
+    ///
+    /// # Errors
+    /// - If the URL is invalid or couldn't be reached
+    /// - If it isn't a Yggdrasil API
+    pub async fn discover(url: &str, name: Option<String>) -> Result<Self, Error> {
+        info!("Discovering auth server at {url}");
+        let url = parse_url(url)?;
+
+        let response = CLIENT.get(url.clone()).send().await?;
+        let api_root = match response.headers().get(API_LOCATION_HEADER) {
+            Some(location) => {
+                let location = location
+                    .to_str()
+                    .map_err(|_| Error::InvalidAuthServer(url.to_string()))?;
+                // The location may be relative to the requested page
+                url.join(location)
+                    .map_err(|_| Error::InvalidAuthServer(location.to_owned()))?
+            }
+            None => url,
+        };
+        let api_root = api_root.as_str().trim_end_matches('/').to_owned();
+        pt!("API root: {api_root}");
+
+        let response = CLIENT.get(&api_root).send().await?;
+        let text = response.text().await?;
+        let metadata: ApiMetadata =
+            serde_json::from_str(&text).map_err(|_| Error::InvalidAuthServer(api_root.clone()))?;
+
+        let name = name
+            .filter(|n| !n.trim().is_empty())
+            .or(metadata.meta.server_name)
+            .unwrap_or_else(|| api_root.clone());
+        Ok(Self {
+            name: name.trim().to_owned(),
+            api_root,
+            oauth: None,
+        })
+    }
+}
+
+fn parse_url(url: &str) -> Result<Url, Error> {
+    let url = url.trim();
+    let url = if url.contains("://") {
+        url.to_owned()
+    } else {
+        format!("https://{url}")
+    };
+    Url::parse(&url).map_err(|_| Error::InvalidAuthServer(url))
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    use super::*;
+
+    /// Serves a homepage pointing to `/api/yggdrasil`,
+    /// and the API metadata there.
+    async fn mock_server(location: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            loop {
+                let Ok((mut stream, _)) = listener.accept().await else {
+                    return;
+                };
+                let mut buf = [0; 4096];
+                let len = stream.read(&mut buf).await.unwrap();
+                let request = String::from_utf8_lossy(&buf[..len]);
+                let path = request.split_whitespace().nth(1).unwrap_or("/");
+
+                let (headers, body) = if path == "/api/yggdrasil" {
+                    (
+                        String::new(),
+                        r#"{"meta":{"serverName":"Mock Server"},"skinDomains":[]}"#,
+                    )
+                } else {
+                    (
+                        format!("{API_LOCATION_HEADER}: {location}\r\n"),
+                        "<html></html>",
+                    )
+                };
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\n{headers}Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        format!("http://{addr}")
+    }
+
+    #[tokio::test]
+    async fn discover_api_root() {
+        let url = mock_server("/api/yggdrasil").await;
+
+        let server = CustomAuthServer::discover(&url, None).await.unwrap();
+        assert_eq!(server.name, "Mock Server");
+        assert_eq!(server.api_root, format!("{url}/api/yggdrasil"));
+
+        // Pointing directly to the API root works too
+        let server =
+            CustomAuthServer::discover(&format!("{url}/api/yggdrasil/"), Some("Mine".to_owned()))
+                .await
+                .unwrap();
+        assert_eq!(server.name, "Mine");
+        assert_eq!(server.api_root, format!("{url}/api/yggdrasil"));
+    }
+
+    #[tokio::test]
+    async fn discover_not_yggdrasil() {
+        let url = mock_server("/somewhere-else").await;
+        assert!(CustomAuthServer::discover(&url, None).await.is_err());
+    }
+
+    #[test]
+    fn url_scheme() {
+        assert_eq!(
+            parse_url(" example.com/api ").unwrap().as_str(),
+            "https://example.com/api"
+        );
+        assert_eq!(
+            parse_url("http://localhost:8080").unwrap().as_str(),
+            "http://localhost:8080/"
+        );
+    }
+}
@@ -0,0 +1,226 @@
+//! Checking whether logged-in accounts still work,
+//! by refreshing their tokens ahead of time
+//! (instead of finding out when pressing play),
+//! or by looking at the stored tokens ([`check_stored`]).
+
+use std::fmt::Display;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use ql_core::{err, RequestError};
+use reqwest::StatusCode;
+use serde::Deserialize;
+
+use super::{alt, ms, yggdrasil, AccountData, AccountType};
+
+/// Access tokens expiring within this many seconds
+/// count as [`AccountStatus::is_expiring`].
+pub const EXPIRY_MARGIN: i64 = 10 * 60;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccountStatus {
+    /// Being refreshed right now
+    Checking,
+    /// Logged in, with the access token valid
+    /// until the given unix timestamp (if known)
+    Valid { expires_at: Option<i64> },
+    /// The access token expired, but the login can
+    /// still be refreshed (which happens at launch)
+    Expired,
+    /// Saved, but not checked without refreshing
+    /// (Microsoft refresh tokens can't be validated)
+    Unchecked,
+    /// The login expired or was revoked (password changed,
+    /// logged out from elsewhere), so it has to be redone.
+    Revoked,
+    /// Microsoft account that doesn't own Minecraft
+    NoOwnership,
+    /// Couldn't be checked (no internet, auth server down)
+    Error(String),
+}
+
+impl AccountStatus {
+    /// Whether the access token expires within
+    /// [`EXPIRY_MARGIN`] of `now` (unix timestamp).
+    #[must_use]
+    pub fn is_expiring(&self, now: i64) -> bool {
+        matches!(self, AccountStatus::Valid { expires_at: Some(n) } if *n - now <= EXPIRY_MARGIN)
+    }
+
+    /// Whether the account can be used to play.
+    #[must_use]
+    pub fn is_usable(&self) -> bool {
+        matches!(
+            self,
+            AccountStatus::Checking
+                | AccountStatus::Valid { .. }
+                | AccountStatus::Expired
+                | AccountStatus::Unchecked
+                | AccountStatus::Error(_)
+        )
+    }
+
+    /// A short description, like `Valid` or `Expiring`.
+    #[must_use]
+    pub fn summary(&self, now: i64) -> &'static str {
+        match self {
+            AccountStatus::Checking => "Checking",
+            AccountStatus::Valid { .. } if self.is_expiring(now) => "Expiring",
+            AccountStatus::Valid { .. } => "Valid",
+            AccountStatus::Expired => "Expired",
+            AccountStatus::Unchecked => "Saved",
+            AccountStatus::Revoked => "Logged out",
+            AccountStatus::NoOwnership => "Doesn't own Minecraft",
+            AccountStatus::Error(_) => "Couldn't check",
+        }
+    }
+}
+
+impl Display for AccountStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AccountStatus::Checking => write!(f, "Checking..."),
+            AccountStatus::Valid {
+                expires_at: Some(expires_at),
+            } => {
+                let time = chrono::DateTime::from_timestamp(*expires_at, 0)
+                    .map(|n| n.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M"));
+                match time {
+                    Some(time) => write!(f, "Valid (until {time})"),
+                    None => write!(f, "Valid"),
+                }
+            }
+            AccountStatus::Valid { expires_at: None } => write!(f, "Valid"),
+            AccountStatus::Expired => write!(f, "Session expired, will be refreshed at launch"),
+            AccountStatus::Unchecked => write!(f, "Not checked, will be refreshed at launch"),
+            AccountStatus::Revoked => write!(f, "Login expired or revoked, log in again"),
+            AccountStatus::NoOwnership => write!(f, "Account doesn't own Minecraft"),
+            AccountStatus::Error(err) => write!(f, "Couldn't check: {err}"),
+        }
+    }
+}
+
+/// Refreshes the account's tokens and checks its status.
+///
+/// Returns the refreshed account (if it could be refreshed)
+/// to use from now on, instead of refreshing again at launch.
+pub async fn check(account: &AccountData) -> (Option<AccountData>, AccountStatus) {
+    let refreshed = match &account.account_type {
+        AccountType::Microsoft => ms::login_refresh(
+            account.username.clone(),
+            account.refresh_token.clone(),
+            None,
+        )
+        .await
+        .map_err(|err| ms_error_status(&err)),
+        AccountType::ElyBy | AccountType::LittleSkin | AccountType::Custom(_) => {
+            yggdrasil::login_refresh(
+                account.username.clone(),
+                account.refresh_token.clone(),
+                account.account_type.clone(),
+            )
+            .await
+            .map_err(|err| yggdrasil_error_status(&err))
+        }
+    };
+    let refreshed = match refreshed {
+        Ok(n) => n,
+        Err(status) => {
+            if let AccountStatus::Error(err) = &status {
+                err!("Couldn't check account {}: {err}", account.nice_username);
+            }
+            return (None, status);
+        }
+    };
+
+    let Some(access_token) = refreshed.access_token.clone() else {
+        return (Some(refreshed), AccountStatus::Valid { expires_at: None });
+    };
+    if refreshed.is_microsoft() {
+        match ms::check_minecraft_ownership(&access_token).await {
+            Ok(true) => {}
+            Ok(false) => return (Some(refreshed), AccountStatus::NoOwnership),
+            Err(err) => err!("Couldn't check game ownership: {err}"),
+        }
+    }
+    let expires_at = token_expiry(&access_token);
+    (Some(refreshed), AccountStatus::Valid { expires_at })
+}
+
+/// Checks the account's stored token without refreshing it,
+/// so nothing gets invalidated or rotated.
+///
+/// For Yggdrasil accounts the stored token is an access token,
+/// so it's validated with the auth server. Microsoft accounts
+/// only store a refresh token, which can't be checked without
+/// using it, so they're [`AccountStatus::Unchecked`].
+pub async fn check_stored(account: &AccountData) -> AccountStatus {
+    if account.is_microsoft() {
+        return AccountStatus::Unchecked;
+    }
+    let access_token = &account.refresh_token;
+    match yggdrasil::validate(access_token, &account.account_type).await {
+        Ok(true) => AccountStatus::Valid {
+            expires_at: token_expiry(access_token),
+        },
+        Ok(false) => AccountStatus::Expired,
+        Err(err) => {
+            err!("Couldn't check account {}: {err}", account.nice_username);
+            AccountStatus::Error(err.to_string())
+        }
+    }
+}
+
+fn ms_error_status(err: &ms::Error) -> AccountStatus {
+    match err {
+        ms::Error::TokenRevoked(_) | ms::Error::InvalidAccessToken => AccountStatus::Revoked,
+        ms::Error::DoesntOwnGame => AccountStatus::NoOwnership,
+        err => AccountStatus::Error(err.to_string()),
+    }
+}
+
+fn yggdrasil_error_status(err: &alt::Error) -> AccountStatus {
+    match err {
+        alt::Error::Request(RequestError::DownloadError { code, .. })
+            if *code == StatusCode::UNAUTHORIZED || *code == StatusCode::FORBIDDEN =>
+        {
+            AccountStatus::Revoked
+        }
+        err => AccountStatus::Error(err.to_string()),
+    }
+}
+
+/// Reads the expiry time (unix timestamp) of an access token,
+/// if it's a JWT (Minecraft, ely.by and most Yggdrasil servers).
+#[must_use]
+pub fn token_expiry(access_token: &str) -> Option<i64> {
+    #[derive(Deserialize)]
+    struct Claims {
+        exp: i64,
+    }
+
+    let payload = access_token.split('.').nth(1)?;
+    let payload = URL_SAFE_NO_PAD.decode(payload.trim_end_matches('=')).ok()?;
+    let claims: Claims = serde_json::from_slice(&payload).ok()?;
+    Some(claims.exp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expiry() {
+        let payload = URL_SAFE_NO_PAD.encode(r#"{"sub":"steve","exp":1700000000}"#);
+        let token = format!("eyJhbGciOiJIUzI1NiJ9.{payload}.signature");
+        assert_eq!(token_expiry(&token), Some(1_700_000_000));
+        assert_eq!(token_expiry("not-a-jwt"), None);
+
+        let status = AccountStatus::Valid {
+            expires_at: Some(1_700_000_000),
+        };
+        assert!(!status.is_expiring(1_700_000_000 - EXPIRY_MARGIN - 1));
+        assert!(status.is_expiring(1_700_000_000 - 60));
+        assert_eq!(status.summary(1_700_000_000 - 60), "Expiring");
+        assert_eq!(status.summary(0), "Valid");
+    }
+}
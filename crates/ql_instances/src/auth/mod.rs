@@ -1,11 +1,16 @@
-use ql_core::{err, IntoStringError};
+use ql_core::err;
 use std::fmt::Display;
 
 mod alt;
 pub mod authlib;
+pub mod credentials;
+pub mod custom;
+pub mod health;
 pub mod ms;
+pub mod skins;
 pub mod yggdrasil;
 pub use authlib::get_authlib_injector;
+pub use custom::{CustomAuthServer, CustomOauth};
 
 #[derive(Debug, Clone)]
 pub struct AccountData {
@@ -23,29 +28,31 @@ pub struct AccountData {
 impl AccountData {
     #[must_use]
     pub fn get_username_modified(&self) -> String {
-        let suffix = match self.account_type {
-            AccountType::Microsoft => "",
-            AccountType::ElyBy => " (elyby)",
-            AccountType::LittleSkin => " (littleskin)",
-        };
-        format!("{}{suffix}", self.nice_username)
+        format!(
+            "{}{}",
+            self.nice_username,
+            self.account_type.username_suffix()
+        )
     }
 
     #[must_use]
-    pub fn get_authlib_url(&self) -> Option<&'static str> {
-        match self.account_type {
-            AccountType::Microsoft => None,
+    pub fn get_authlib_url(&self) -> Option<&str> {
+        match &self.account_type {
             AccountType::ElyBy => Some("ely.by"),
-            AccountType::LittleSkin => Some("https://littleskin.cn/api/yggdrasil"),
+            account_type => account_type.yggdrasil_api_root(),
         }
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+const LITTLESKIN_API_ROOT: &str = "https://littleskin.cn/api/yggdrasil";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AccountType {
     Microsoft,
     ElyBy,
     LittleSkin,
+    /// A user-defined Yggdrasil server
+    Custom(CustomAuthServer),
 }
 
 impl Display for AccountType {
@@ -57,71 +64,119 @@ impl Display for AccountType {
                 AccountType::Microsoft => "Microsoft",
                 AccountType::ElyBy => "ElyBy",
                 AccountType::LittleSkin => "LittleSkin",
+                AccountType::Custom(server) => &server.name,
             }
         )
     }
 }
 
 impl AccountType {
+    /// The root of the Yggdrasil API (as passed to authlib-injector),
+    /// for servers that follow the authlib-injector layout.
     #[must_use]
-    pub fn yggdrasil_authenticate(self) -> &'static str {
+    pub fn yggdrasil_api_root(&self) -> Option<&str> {
+        match self {
+            AccountType::Microsoft | AccountType::ElyBy => None,
+            AccountType::LittleSkin => Some(LITTLESKIN_API_ROOT),
+            AccountType::Custom(server) => Some(&server.api_root),
+        }
+    }
+
+    #[must_use]
+    pub fn yggdrasil_authenticate(&self) -> String {
         match self {
             AccountType::Microsoft => unreachable!(),
-            AccountType::ElyBy => "https://authserver.ely.by/auth/authenticate",
-            AccountType::LittleSkin => {
-                "https://littleskin.cn/api/yggdrasil/authserver/authenticate"
-            }
+            AccountType::ElyBy => "https://authserver.ely.by/auth/authenticate".to_owned(),
+            AccountType::LittleSkin | AccountType::Custom(_) => format!(
+                "{}/authserver/authenticate",
+                self.yggdrasil_api_root().unwrap_or_default()
+            ),
         }
     }
 
     #[must_use]
-    pub fn yggdrasil_refresh(self) -> &'static str {
+    pub fn yggdrasil_refresh(&self) -> String {
         match self {
             AccountType::Microsoft => unreachable!(),
-            AccountType::ElyBy => "https://authserver.ely.by/auth/refresh",
-            AccountType::LittleSkin => "https://littleskin.cn/api/yggdrasil/authserver/refresh",
+            AccountType::ElyBy => "https://authserver.ely.by/auth/refresh".to_owned(),
+            AccountType::LittleSkin | AccountType::Custom(_) => format!(
+                "{}/authserver/refresh",
+                self.yggdrasil_api_root().unwrap_or_default()
+            ),
         }
     }
 
     #[must_use]
-    pub fn yggdrasil_needs_agent_field(self) -> bool {
+    pub fn yggdrasil_validate(&self) -> String {
+        match self {
+            AccountType::Microsoft => unreachable!(),
+            AccountType::ElyBy => "https://authserver.ely.by/auth/validate".to_owned(),
+            AccountType::LittleSkin | AccountType::Custom(_) => format!(
+                "{}/authserver/validate",
+                self.yggdrasil_api_root().unwrap_or_default()
+            ),
+        }
+    }
+
+    #[must_use]
+    pub fn yggdrasil_needs_agent_field(&self) -> bool {
         match self {
             AccountType::Microsoft | AccountType::ElyBy => false,
+            AccountType::LittleSkin | AccountType::Custom(_) => true,
+        }
+    }
+
+    /// Whether accounts can be logged into through
+    /// [`yggdrasil::oauth`] (instead of a password).
+    #[must_use]
+    pub fn supports_oauth(&self) -> bool {
+        match self {
             AccountType::LittleSkin => true,
+            AccountType::Microsoft | AccountType::ElyBy => false,
+            AccountType::Custom(server) => server.oauth.is_some(),
         }
     }
 
-    fn get_keyring_entry(self, username: &str) -> Result<keyring::Entry, KeyringError> {
-        Ok(keyring::Entry::new(
-            "QuantumLauncher",
-            &format!(
-                "{username}{}",
-                match self {
-                    AccountType::Microsoft => "",
-                    AccountType::ElyBy => "#elyby",
-                    AccountType::LittleSkin => "#littleskin",
-                }
-            ),
-        )?)
+    fn get_keyring_user(&self, username: &str) -> String {
+        let suffix = match self {
+            AccountType::Microsoft => String::new(),
+            AccountType::ElyBy => "#elyby".to_owned(),
+            AccountType::LittleSkin => "#littleskin".to_owned(),
+            AccountType::Custom(server) => format!("#custom#{}", server.name),
+        };
+        format!("{username}{suffix}")
+    }
+
+    fn get_keyring_entry(&self, username: &str) -> credentials::Credential {
+        credentials::Credential::new(self.get_keyring_user(username))
     }
 
     #[must_use]
-    pub(crate) fn get_client_id(self) -> &'static str {
+    pub(crate) fn get_client_id(&self) -> &str {
         match self {
             AccountType::Microsoft => ms::CLIENT_ID,
             AccountType::ElyBy => "quantumlauncher1",
             AccountType::LittleSkin => "1160",
+            AccountType::Custom(_) => "quantumlauncher",
         }
     }
 
+    /// What's appended to the usernames of these
+    /// accounts in the UI, like ` (elyby)`.
     #[must_use]
-    pub fn strip_name(self, name: &str) -> &str {
+    pub fn username_suffix(&self) -> String {
         match self {
-            AccountType::Microsoft => name,
-            AccountType::ElyBy => name.strip_suffix(" (elyby)").unwrap_or(name),
-            AccountType::LittleSkin => name.strip_suffix(" (littleskin)").unwrap_or(name),
+            AccountType::Microsoft => String::new(),
+            AccountType::ElyBy => " (elyby)".to_owned(),
+            AccountType::LittleSkin => " (littleskin)".to_owned(),
+            AccountType::Custom(server) => format!(" ({})", server.name),
         }
     }
+
+    #[must_use]
+    pub fn strip_name<'a>(&self, name: &'a str) -> &'a str {
+        name.strip_suffix(&self.username_suffix()).unwrap_or(name)
+    }
 }
 
 impl AccountData {
@@ -140,12 +195,19 @@ impl AccountData {
 }
 
 #[derive(Debug, thiserror::Error)]
-pub struct KeyringError(#[from] pub keyring::Error);
+pub enum KeyringError {
+    Keyring(#[from] keyring::Error),
+    File(#[from] credentials::CredentialFileError),
+}
 
 impl Display for KeyringError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let err = match self {
+            KeyringError::Keyring(err) => err,
+            KeyringError::File(err) => return write!(f, "{err}"),
+        };
         writeln!(f, "Account keyring error:")?;
-        match &self.0 {
+        match err {
             #[cfg(target_os = "linux")]
             keyring::Error::PlatformFailure(error)
                 if error.to_string().contains("The name is not activatable") =>
@@ -171,22 +233,22 @@ Now after this, in the sidebar, right click it and click "Set as Default""#
                 )
             }
 
-            _ => write!(f, "{}", self.0),
+            _ => write!(f, "{err}"),
         }
     }
 }
 
 pub fn read_refresh_token(
     username: &str,
-    account_type: AccountType,
+    account_type: &AccountType,
 ) -> Result<String, KeyringError> {
-    let entry = account_type.get_keyring_entry(username)?;
+    let entry = account_type.get_keyring_entry(username);
     let refresh_token = entry.get_password()?;
     Ok(refresh_token)
 }
 
-pub fn logout(username: &str, account_type: AccountType) -> Result<(), String> {
-    let entry = account_type.get_keyring_entry(username).strerr()?;
+pub fn logout(username: &str, account_type: &AccountType) -> Result<(), String> {
+    let entry = account_type.get_keyring_entry(username);
     if let Err(err) = entry.delete_credential() {
         err!("Couldn't remove {account_type} account credential (Username: {username}):\n{err}");
     }
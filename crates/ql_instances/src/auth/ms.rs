@@ -138,6 +138,14 @@ struct AuthServiceErrorMessage {
     error: String,
 }
 
+/// Returned by the Microsoft token endpoint on failure.
+#[derive(Debug, Clone, Deserialize)]
+struct OauthErrorResponse {
+    error: String,
+    #[serde(default)]
+    error_description: String,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[allow(non_snake_case)]
 pub struct MsaResponseError {
@@ -183,6 +191,9 @@ pub enum Error {
     #[error("{AUTH_ERR_PREFIX}{0}")]
     Response(MsaResponseError),
 
+    #[error("{AUTH_ERR_PREFIX}login expired or was revoked, please log in again:\n{0}")]
+    TokenRevoked(String),
+
     #[error("Your Microsoft account doesn't own Minecraft!\nJust enter the username in the text box instead of logging in.")]
     DoesntOwnGame,
 }
@@ -195,7 +206,7 @@ impl From<reqwest::Error> for Error {
 
 impl From<keyring::Error> for Error {
     fn from(err: keyring::Error) -> Self {
-        Self::KeyringError(KeyringError(err))
+        Self::KeyringError(KeyringError::Keyring(err))
     }
 }
 
@@ -233,9 +244,14 @@ pub async fn login_refresh(
     })
     .await?;
 
+    if let Ok(err) = serde_json::from_str::<OauthErrorResponse>(&response) {
+        if err.error == "invalid_grant" {
+            return Err(Error::TokenRevoked(err.error_description));
+        }
+    }
     let data: RefreshResponse = serde_json::from_str(&response).json(response)?;
 
-    let entry = keyring::Entry::new("QuantumLauncher", &username)?;
+    let entry = AccountType::Microsoft.get_keyring_entry(&username);
     entry.set_password(&data.refresh_token)?;
 
     let data = login_3_xbox(
@@ -300,7 +316,7 @@ pub async fn login_3_xbox(
         }
     }
 
-    let entry = keyring::Entry::new("QuantumLauncher", &final_details.name)?;
+    let entry = AccountType::Microsoft.get_keyring_entry(&final_details.name);
     entry.set_password(&data.refresh_token)?;
 
     let data = AccountData {
@@ -488,7 +504,7 @@ async fn get_final_details(
     Ok(info)
 }
 
-async fn check_minecraft_ownership(access_token: &str) -> Result<bool, Error> {
+pub(crate) async fn check_minecraft_ownership(access_token: &str) -> Result<bool, Error> {
     #[derive(Deserialize)]
     struct Ownership {
         items: Vec<serde_json::Value>,
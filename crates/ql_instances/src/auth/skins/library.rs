@@ -0,0 +1,125 @@
+use std::path::PathBuf;
+
+use ql_core::{IntoIoError, IntoJsonError, LAUNCHER_DIR};
+use serde::{Deserialize, Serialize};
+
+use super::{validate_skin, SkinError, SkinModel};
+
+const LIBRARY_FILE: &str = "library.json";
+
+/// A skin saved in the [`SkinLibrary`].
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LibrarySkin {
+    pub name: String,
+    /// File name of the PNG, in `QuantumLauncher/skins/`
+    pub file: String,
+    pub model: SkinModel,
+}
+
+/// Skins saved locally (in `QuantumLauncher/skins/`),
+/// to quickly switch between them.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SkinLibrary {
+    pub skins: Vec<LibrarySkin>,
+}
+
+impl SkinLibrary {
+    fn dir() -> PathBuf {
+        LAUNCHER_DIR.join("skins")
+    }
+
+    /// Loads the library, or an empty one if
+    /// no skins have been saved yet.
+    ///
+    /// # Errors
+    /// If `skins/library.json` exists but couldn't be read or parsed.
+    pub async fn load() -> Result<Self, SkinError> {
+        let path = Self::dir().join(LIBRARY_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let json = tokio::fs::read_to_string(&path).await.path(path)?;
+        Ok(serde_json::from_str(&json).json(json)?)
+    }
+
+    async fn save(&self) -> Result<(), SkinError> {
+        let path = Self::dir().join(LIBRARY_FILE);
+        let json = serde_json::to_string_pretty(self).json_to()?;
+        tokio::fs::write(&path, json).await.path(path)?;
+        Ok(())
+    }
+
+    /// Saves a skin to the library.
+    ///
+    /// # Errors
+    /// - If the image isn't a valid skin
+    /// - If a skin with the same name already exists
+    /// - If the files couldn't be written
+    pub async fn add(
+        mut self,
+        name: String,
+        png: Vec<u8>,
+        model: SkinModel,
+    ) -> Result<Self, SkinError> {
+        validate_skin(&png)?;
+        if self.skins.iter().any(|n| n.name == name) {
+            return Err(SkinError::AlreadyExists(name));
+        }
+
+        let dir = Self::dir();
+        tokio::fs::create_dir_all(&dir).await.path(&dir)?;
+        let file = format!("{}.png", sanitize_file_name(&name));
+        let path = dir.join(&file);
+        tokio::fs::write(&path, png).await.path(path)?;
+
+        self.skins.push(LibrarySkin { name, file, model });
+        self.save().await?;
+        Ok(self)
+    }
+
+    /// Deletes a skin from the library.
+    ///
+    /// # Errors
+    /// If the files couldn't be deleted or written.
+    pub async fn remove(mut self, name: &str) -> Result<Self, SkinError> {
+        let Some(idx) = self.skins.iter().position(|n| n.name == name) else {
+            return Ok(self);
+        };
+        let skin = self.skins.remove(idx);
+        let path = Self::dir().join(&skin.file);
+        if path.exists() {
+            tokio::fs::remove_file(&path).await.path(path)?;
+        }
+        self.save().await?;
+        Ok(self)
+    }
+
+    /// Reads the PNG image of a skin in the library.
+    ///
+    /// # Errors
+    /// If the file couldn't be read.
+    pub async fn read_png(skin: &LibrarySkin) -> Result<Vec<u8>, SkinError> {
+        let path = Self::dir().join(&skin.file);
+        Ok(tokio::fs::read(&path).await.path(path)?)
+    }
+}
+
+/// Makes a file name out of a skin name, keeping it unique
+/// by appending a timestamp if anything had to be replaced.
+fn sanitize_file_name(name: &str) -> String {
+    let sanitized: String = name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if sanitized == name {
+        sanitized
+    } else {
+        format!("{sanitized}_{}", chrono::Utc::now().timestamp_millis())
+    }
+}
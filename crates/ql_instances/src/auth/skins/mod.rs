@@ -0,0 +1,441 @@
+//! Viewing and changing the skin and cape of an account.
+//!
+//! - Microsoft accounts use the Minecraft profile API
+//! - Yggdrasil accounts (littleskin, custom servers) use
+//!   the authlib-injector texture API
+//! - ely.by skins can only be viewed here, they are
+//!   changed through the website
+
+use std::fmt::Display;
+
+use base64::Engine;
+use ql_core::{
+    file_utils::{self, check_for_success},
+    info, IntoJsonError, IoError, JsonError, RequestError, CLIENT,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{AccountData, AccountType};
+
+mod library;
+mod preview;
+pub use library::{LibrarySkin, SkinLibrary};
+pub use preview::{render_preview, SkinPreview};
+
+const MS_PROFILE_URL: &str = "https://api.minecraftservices.com/minecraft/profile";
+const ELYBY_API_ROOT: &str = "https://authserver.ely.by/api/authlib-injector";
+const MULTIPART_BOUNDARY: &str = "----QuantumLauncherSkinUpload";
+
+/// The arm width of a skin.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SkinModel {
+    /// Steve, 4 pixel wide arms
+    #[default]
+    Classic,
+    /// Alex, 3 pixel wide arms
+    Slim,
+}
+
+impl SkinModel {
+    pub const ALL: &[Self] = &[Self::Classic, Self::Slim];
+
+    fn arm_width(self) -> u32 {
+        match self {
+            SkinModel::Classic => 4,
+            SkinModel::Slim => 3,
+        }
+    }
+}
+
+impl Display for SkinModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                SkinModel::Classic => "Classic (Steve)",
+                SkinModel::Slim => "Slim (Alex)",
+            }
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Skin {
+    pub url: String,
+    pub model: SkinModel,
+}
+
+#[derive(Debug, Clone)]
+pub struct Cape {
+    /// Only Microsoft capes have an ID
+    /// (needed to switch between them).
+    pub id: Option<String>,
+    pub url: String,
+    /// Like `Migrator` or `Pan`
+    pub alias: Option<String>,
+    pub active: bool,
+}
+
+/// The current skin and capes of an account.
+#[derive(Debug, Clone, Default)]
+pub struct AccountTextures {
+    pub skin: Option<Skin>,
+    /// All the capes the account owns (Microsoft),
+    /// or the current cape (Yggdrasil).
+    pub capes: Vec<Cape>,
+}
+
+impl AccountTextures {
+    #[must_use]
+    pub fn active_cape(&self) -> Option<&Cape> {
+        self.capes.iter().find(|n| n.active)
+    }
+}
+
+const SKIN_ERR_PREFIX: &str = "while managing skins:\n";
+#[derive(Debug, thiserror::Error)]
+pub enum SkinError {
+    #[error("{SKIN_ERR_PREFIX}{0}")]
+    Request(#[from] RequestError),
+    #[error("{SKIN_ERR_PREFIX}{0}")]
+    Json(#[from] JsonError),
+    #[error("{SKIN_ERR_PREFIX}{0}")]
+    Io(#[from] IoError),
+    #[error("{SKIN_ERR_PREFIX}invalid texture data: {0}")]
+    Base64(#[from] base64::DecodeError),
+    #[error("{SKIN_ERR_PREFIX}couldn't read image: {0}")]
+    Image(#[from] image::ImageError),
+    #[error("{SKIN_ERR_PREFIX}skin must be a 64x64 or 64x32 PNG (got {0}x{1})")]
+    InvalidSize(u32, u32),
+    #[error("{SKIN_ERR_PREFIX}account isn't logged in (no access token)")]
+    NotLoggedIn,
+    #[error("{SKIN_ERR_PREFIX}{0} accounts don't support {1}")]
+    Unsupported(String, &'static str),
+    #[error("{SKIN_ERR_PREFIX}a skin called {0:?} already exists in the library")]
+    AlreadyExists(String),
+}
+
+impl From<reqwest::Error> for SkinError {
+    fn from(value: reqwest::Error) -> Self {
+        Self::Request(RequestError::ReqwestError(value))
+    }
+}
+
+/// Gets the current skin and capes of an account.
+///
+/// # Errors
+/// - If the account isn't logged in (Microsoft only)
+/// - If the profile couldn't be fetched or parsed
+pub async fn get_textures(account: &AccountData) -> Result<AccountTextures, SkinError> {
+    info!("Fetching skin of {}", account.nice_username);
+    match &account.account_type {
+        AccountType::Microsoft => get_textures_ms(account).await,
+        account_type => {
+            let api_root = get_api_root(account_type).unwrap_or_default();
+            get_textures_yggdrasil(api_root, &account.uuid).await
+        }
+    }
+}
+
+/// Downloads a skin or cape image.
+///
+/// # Errors
+/// If the image couldn't be downloaded.
+pub async fn download_texture(url: &str) -> Result<Vec<u8>, SkinError> {
+    Ok(file_utils::download_file_to_bytes(url, false).await?)
+}
+
+/// Changes the skin of an account to a PNG image.
+///
+/// # Errors
+/// - If the image isn't a valid skin
+/// - If the account doesn't support uploading skins (ely.by)
+/// - If the upload failed
+pub async fn upload_skin(
+    account: &AccountData,
+    png: Vec<u8>,
+    model: SkinModel,
+) -> Result<(), SkinError> {
+    info!("Uploading skin of {} ({model})", account.nice_username);
+    validate_skin(&png)?;
+    let access_token = account
+        .access_token
+        .as_deref()
+        .ok_or(SkinError::NotLoggedIn)?;
+
+    let request = match &account.account_type {
+        AccountType::Microsoft => {
+            let variant = match model {
+                SkinModel::Classic => "classic",
+                SkinModel::Slim => "slim",
+            };
+            CLIENT
+                .post(format!("{MS_PROFILE_URL}/skins"))
+                .body(multipart_body("variant", variant, &png))
+        }
+        AccountType::ElyBy => {
+            return Err(SkinError::Unsupported(
+                account.account_type.to_string(),
+                "uploading skins",
+            ))
+        }
+        account_type => {
+            // Texture upload API from the authlib-injector Yggdrasil server spec
+            let api_root = get_api_root(account_type).unwrap_or_default();
+            let model = match model {
+                SkinModel::Classic => "",
+                SkinModel::Slim => "slim",
+            };
+            CLIENT
+                .put(format!(
+                    "{api_root}/api/user/profile/{}/skin",
+                    account.uuid.replace('-', "")
+                ))
+                .body(multipart_body("model", model, &png))
+        }
+    };
+
+    let response = request
+        .bearer_auth(access_token)
+        .header(
+            "Content-Type",
+            format!("multipart/form-data; boundary={MULTIPART_BOUNDARY}"),
+        )
+        .send()
+        .await?;
+    check_for_success(&response)?;
+    Ok(())
+}
+
+/// Wears a cape owned by a Microsoft account
+/// (or hides it, if `cape_id` is `None`).
+///
+/// # Errors
+/// - If the account isn't a Microsoft account
+/// - If the request failed
+pub async fn set_cape(account: &AccountData, cape_id: Option<String>) -> Result<(), SkinError> {
+    if !account.is_microsoft() {
+        return Err(SkinError::Unsupported(
+            account.account_type.to_string(),
+            "switching capes",
+        ));
+    }
+    let access_token = account
+        .access_token
+        .as_deref()
+        .ok_or(SkinError::NotLoggedIn)?;
+
+    let url = format!("{MS_PROFILE_URL}/capes/active");
+    let request = if let Some(cape_id) = cape_id {
+        info!("Switching cape of {} to {cape_id}", account.nice_username);
+        CLIENT
+            .put(url)
+            .json(&serde_json::json!({ "capeId": cape_id }))
+    } else {
+        info!("Hiding cape of {}", account.nice_username);
+        CLIENT.delete(url)
+    };
+    let response = request.bearer_auth(access_token).send().await?;
+    check_for_success(&response)?;
+    Ok(())
+}
+
+/// Checks that an image is a valid skin.
+fn validate_skin(png: &[u8]) -> Result<(), SkinError> {
+    let image = image::load_from_memory_with_format(png, image::ImageFormat::Png)?;
+    match (image.width(), image.height()) {
+        (64, 64 | 32) => Ok(()),
+        (w, h) => Err(SkinError::InvalidSize(w, h)),
+    }
+}
+
+fn get_api_root(account_type: &AccountType) -> Option<&str> {
+    match account_type {
+        AccountType::ElyBy => Some(ELYBY_API_ROOT),
+        _ => account_type.yggdrasil_api_root(),
+    }
+}
+
+fn multipart_body(field: &str, value: &str, png: &[u8]) -> Vec<u8> {
+    let mut body = format!(
+        "--{MULTIPART_BOUNDARY}\r\nContent-Disposition: form-data; name=\"{field}\"\r\n\r\n{value}\r\n\
+        --{MULTIPART_BOUNDARY}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"skin.png\"\r\nContent-Type: image/png\r\n\r\n"
+    )
+    .into_bytes();
+    body.extend_from_slice(png);
+    body.extend_from_slice(format!("\r\n--{MULTIPART_BOUNDARY}--\r\n").as_bytes());
+    body
+}
+
+#[derive(Deserialize)]
+struct MsProfile {
+    skins: Vec<MsSkin>,
+    capes: Vec<MsCape>,
+}
+
+#[derive(Deserialize)]
+struct MsSkin {
+    state: String,
+    url: String,
+    variant: String,
+}
+
+#[derive(Deserialize)]
+struct MsCape {
+    id: String,
+    state: String,
+    url: String,
+    alias: Option<String>,
+}
+
+async fn get_textures_ms(account: &AccountData) -> Result<AccountTextures, SkinError> {
+    let access_token = account
+        .access_token
+        .as_deref()
+        .ok_or(SkinError::NotLoggedIn)?;
+    let response = CLIENT
+        .get(MS_PROFILE_URL)
+        .bearer_auth(access_token)
+        .send()
+        .await?;
+    check_for_success(&response)?;
+    let text = response.text().await?;
+    let profile: MsProfile = serde_json::from_str(&text).json(text)?;
+
+    Ok(AccountTextures {
+        skin: profile
+            .skins
+            .into_iter()
+            .find(|n| n.state == "ACTIVE")
+            .map(|n| Skin {
+                url: n.url,
+                model: if n.variant.eq_ignore_ascii_case("slim") {
+                    SkinModel::Slim
+                } else {
+                    SkinModel::Classic
+                },
+            }),
+        capes: profile
+            .capes
+            .into_iter()
+            .map(|n| Cape {
+                id: Some(n.id),
+                url: n.url,
+                alias: n.alias,
+                active: n.state == "ACTIVE",
+            })
+            .collect(),
+    })
+}
+
+#[derive(Deserialize)]
+struct YggdrasilProfile {
+    #[serde(default)]
+    properties: Vec<YggdrasilProperty>,
+}
+
+#[derive(Deserialize)]
+struct YggdrasilProperty {
+    name: String,
+    value: String,
+}
+
+#[derive(Deserialize)]
+struct TexturesProperty {
+    textures: TexturesPropertyInner,
+}
+
+#[derive(Deserialize)]
+struct TexturesPropertyInner {
+    #[serde(rename = "SKIN")]
+    skin: Option<TextureEntry>,
+    #[serde(rename = "CAPE")]
+    cape: Option<TextureEntry>,
+}
+
+#[derive(Deserialize)]
+struct TextureEntry {
+    url: String,
+    metadata: Option<TextureMetadata>,
+}
+
+#[derive(Deserialize)]
+struct TextureMetadata {
+    model: Option<String>,
+}
+
+async fn get_textures_yggdrasil(api_root: &str, uuid: &str) -> Result<AccountTextures, SkinError> {
+    let url = format!(
+        "{api_root}/sessionserver/session/minecraft/profile/{}?unsigned=true",
+        uuid.replace('-', "")
+    );
+    let response = CLIENT.get(&url).send().await?;
+    check_for_success(&response)?;
+    let text = response.text().await?;
+    let profile: YggdrasilProfile = serde_json::from_str(&text).json(text)?;
+
+    let Some(textures) = profile.properties.iter().find(|n| n.name == "textures") else {
+        return Ok(AccountTextures::default());
+    };
+    parse_textures_property(&textures.value)
+}
+
+/// Parses the base64 encoded `textures`
+/// property of a Yggdrasil profile.
+fn parse_textures_property(value: &str) -> Result<AccountTextures, SkinError> {
+    let decoded = base64::engine::general_purpose::STANDARD.decode(value)?;
+    let decoded = String::from_utf8_lossy(&decoded).to_string();
+    let textures: TexturesProperty = serde_json::from_str(&decoded).json(decoded)?;
+    let textures = textures.textures;
+
+    Ok(AccountTextures {
+        skin: textures.skin.map(|n| Skin {
+            model: if n
+                .metadata
+                .and_then(|n| n.model)
+                .is_some_and(|n| n == "slim")
+            {
+                SkinModel::Slim
+            } else {
+                SkinModel::Classic
+            },
+            url: n.url,
+        }),
+        capes: textures
+            .cape
+            .map(|n| Cape {
+                id: None,
+                url: n.url,
+                alias: None,
+                active: true,
+            })
+            .into_iter()
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn textures_property() {
+        let json = r#"{"timestamp":0,"profileId":"abc","profileName":"Steve","textures":{"SKIN":{"url":"https://example.com/skin.png","metadata":{"model":"slim"}},"CAPE":{"url":"https://example.com/cape.png"}}}"#;
+        let value = base64::engine::general_purpose::STANDARD.encode(json);
+
+        let textures = parse_textures_property(&value).unwrap();
+        let skin = textures.skin.as_ref().unwrap();
+        assert_eq!(skin.url, "https://example.com/skin.png");
+        assert_eq!(skin.model, SkinModel::Slim);
+        assert_eq!(
+            textures.active_cape().map(|n| n.url.as_str()),
+            Some("https://example.com/cape.png")
+        );
+
+        let json = r#"{"textures":{}}"#;
+        let value = base64::engine::general_purpose::STANDARD.encode(json);
+        let textures = parse_textures_property(&value).unwrap();
+        assert!(textures.skin.is_none() && textures.capes.is_empty());
+    }
+}
@@ -0,0 +1,176 @@
+use image::RgbaImage;
+
+use super::{SkinError, SkinModel};
+
+/// A flat 2D render of a skin, 16x32 pixels.
+#[derive(Debug, Clone)]
+pub struct SkinPreview {
+    pub width: u32,
+    pub height: u32,
+    /// RGBA pixels, row by row
+    pub pixels: Vec<u8>,
+}
+
+/// A rectangle of the skin texture, and where it's
+/// drawn on the preview.
+struct Part {
+    src: (u32, u32),
+    size: (u32, u32),
+    dst: (u32, u32),
+    /// For the left limbs of old 64x32 skins,
+    /// which reuse the right ones
+    mirror: bool,
+}
+
+const fn part(src: (u32, u32), size: (u32, u32), dst: (u32, u32)) -> Part {
+    Part {
+        src,
+        size,
+        dst,
+        mirror: false,
+    }
+}
+
+/// Renders the front (or back) of a skin PNG,
+/// like it's shown in the launcher's accounts screen.
+///
+/// # Errors
+/// If the image isn't a valid 64x64 or 64x32 skin.
+pub fn render_preview(png: &[u8], model: SkinModel, back: bool) -> Result<SkinPreview, SkinError> {
+    let skin = image::load_from_memory_with_format(png, image::ImageFormat::Png)?.to_rgba8();
+    let preview = render_preview_image(&skin, model, back)?;
+    Ok(SkinPreview {
+        width: preview.width(),
+        height: preview.height(),
+        pixels: preview.into_raw(),
+    })
+}
+
+fn render_preview_image(
+    skin: &RgbaImage,
+    model: SkinModel,
+    back: bool,
+) -> Result<RgbaImage, SkinError> {
+    let is_legacy = match (skin.width(), skin.height()) {
+        (64, 64) => false,
+        (64, 32) => true,
+        (w, h) => return Err(SkinError::InvalidSize(w, h)),
+    };
+    let arm = model.arm_width();
+
+    // Layout of a limb's texture (starting at x): right side (4 wide),
+    // front (w), left side (4), back (w), all starting 4 pixels down.
+    let (front, limb_back) = (4, 8 + arm);
+    let (right_arm_x, left_arm_x) = if back { (12, 4 - arm) } else { (4 - arm, 12) };
+    let (right_leg_x, left_leg_x) = if back { (8, 4) } else { (4, 8) };
+    let face = |x: u32| if back { x + limb_back } else { x + front };
+    let leg_face = |x: u32| if back { x + 12 } else { x + 4 };
+
+    let mut parts = vec![
+        // Head and hat
+        part((if back { 24 } else { 8 }, 8), (8, 8), (4, 0)),
+        part((if back { 56 } else { 40 }, 8), (8, 8), (4, 0)),
+        // Body
+        part((if back { 32 } else { 20 }, 20), (8, 12), (4, 8)),
+        // Right arm and leg
+        part((face(40), 20), (arm, 12), (right_arm_x, 8)),
+        part((leg_face(0), 20), (4, 12), (right_leg_x, 20)),
+    ];
+    if is_legacy {
+        parts.extend([
+            Part {
+                mirror: true,
+                ..part((face(40), 20), (arm, 12), (left_arm_x, 8))
+            },
+            Part {
+                mirror: true,
+                ..part((leg_face(0), 20), (4, 12), (left_leg_x, 20))
+            },
+        ]);
+    } else {
+        parts.extend([
+            // Left arm and leg
+            part((face(32), 52), (arm, 12), (left_arm_x, 8)),
+            part((leg_face(16), 52), (4, 12), (left_leg_x, 20)),
+            // Overlays: jacket, sleeves, pants
+            part((if back { 32 } else { 20 }, 36), (8, 12), (4, 8)),
+            part((face(40), 36), (arm, 12), (right_arm_x, 8)),
+            part((face(48), 52), (arm, 12), (left_arm_x, 8)),
+            part((leg_face(0), 36), (4, 12), (right_leg_x, 20)),
+            part((leg_face(0), 52), (4, 12), (left_leg_x, 20)),
+        ]);
+    }
+
+    let mut preview = RgbaImage::new(16, 32);
+    for part in parts {
+        for y in 0..part.size.1 {
+            for x in 0..part.size.0 {
+                let src_x = if part.mirror {
+                    part.src.0 + part.size.0 - 1 - x
+                } else {
+                    part.src.0 + x
+                };
+                let pixel = *skin.get_pixel(src_x, part.src.1 + y);
+                // Overlays only cover the base where they aren't transparent
+                if pixel.0[3] > 0 {
+                    preview.put_pixel(part.dst.0 + x, part.dst.1 + y, pixel);
+                }
+            }
+        }
+    }
+    Ok(preview)
+}
+
+#[cfg(test)]
+mod tests {
+    use image::Rgba;
+
+    use super::*;
+
+    const RED: Rgba<u8> = Rgba([255, 0, 0, 255]);
+    const BLUE: Rgba<u8> = Rgba([0, 0, 255, 255]);
+
+    fn fill(skin: &mut RgbaImage, (x, y): (u32, u32), (w, h): (u32, u32), color: Rgba<u8>) {
+        for dy in 0..h {
+            for dx in 0..w {
+                skin.put_pixel(x + dx, y + dy, color);
+            }
+        }
+    }
+
+    #[test]
+    fn front_and_back() {
+        let mut skin = RgbaImage::new(64, 64);
+        fill(&mut skin, (8, 8), (8, 8), RED); // Head front
+        fill(&mut skin, (24, 8), (8, 8), BLUE); // Head back
+        fill(&mut skin, (44, 20), (4, 12), RED); // Right arm front (classic)
+        fill(&mut skin, (51, 20), (3, 12), BLUE); // Right arm back (slim)
+
+        let front = render_preview_image(&skin, SkinModel::Classic, false).unwrap();
+        assert_eq!(*front.get_pixel(4, 0), RED);
+        assert_eq!(*front.get_pixel(0, 8), RED);
+        // Nothing drawn where the slim arm would leave a gap
+        let front = render_preview_image(&skin, SkinModel::Slim, false).unwrap();
+        assert_eq!(front.get_pixel(0, 8).0[3], 0);
+
+        let back = render_preview_image(&skin, SkinModel::Slim, true).unwrap();
+        assert_eq!(*back.get_pixel(11, 7), BLUE);
+        assert_eq!(*back.get_pixel(12, 8), BLUE);
+        assert_eq!(*back.get_pixel(14, 19), BLUE);
+    }
+
+    #[test]
+    fn legacy_mirrors_limbs() {
+        let mut skin = RgbaImage::new(64, 32);
+        // Right leg front: left column red, others blue
+        fill(&mut skin, (4, 20), (4, 12), BLUE);
+        fill(&mut skin, (4, 20), (1, 12), RED);
+
+        let front = render_preview_image(&skin, SkinModel::Classic, false).unwrap();
+        assert_eq!(*front.get_pixel(4, 20), RED);
+        assert_eq!(*front.get_pixel(11, 20), RED);
+        assert_eq!(*front.get_pixel(8, 20), BLUE);
+
+        assert!(render_preview_image(&RgbaImage::new(32, 32), SkinModel::Classic, false).is_err());
+    }
+}
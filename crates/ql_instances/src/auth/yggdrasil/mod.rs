@@ -5,6 +5,7 @@ use ql_core::{info, pt, IntoJsonError, CLIENT};
 
 pub use super::alt::{Account, AccountResponseError, Error};
 use ql_core::file_utils::check_for_success;
+use reqwest::StatusCode;
 use serde::Serialize;
 
 pub mod oauth;
@@ -34,7 +35,7 @@ pub async fn login_new(
         "password": &password,
         "clientToken": account_type.get_client_id()
     });
-    insert_agent_field(account_type, &mut value);
+    insert_agent_field(&account_type, &mut value);
 
     let response = CLIENT
         .post(account_type.yggdrasil_authenticate())
@@ -58,7 +59,7 @@ pub async fn login_new(
         }
     };
 
-    let entry = account_type.get_keyring_entry(&email)?;
+    let entry = account_type.get_keyring_entry(&email);
     entry.set_password(&account_response.accessToken)?;
 
     Ok(Account::Account(AccountData {
@@ -74,7 +75,7 @@ pub async fn login_new(
     }))
 }
 
-fn insert_agent_field(account_type: AccountType, value: &mut serde_json::Value) {
+fn insert_agent_field(account_type: &AccountType, value: &mut serde_json::Value) {
     if account_type.yggdrasil_needs_agent_field() {
         if let (Some(value), Ok(insert)) = (value.as_object_mut(), serde_json::to_value(AGENT)) {
             value.insert("agent".to_owned(), insert);
@@ -90,13 +91,13 @@ pub async fn login_refresh(
     // NOTE: It says email, but both username and email are accepted
 
     pt!("Refreshing {account_type} account...");
-    let entry = account_type.get_keyring_entry(&email)?;
+    let entry = account_type.get_keyring_entry(&email);
 
     let mut value = serde_json::json!({
         "accessToken": refresh_token,
         "clientToken": account_type.get_client_id()
     });
-    insert_agent_field(account_type, &mut value);
+    insert_agent_field(&account_type, &mut value);
     let response = CLIENT
         .post(account_type.yggdrasil_refresh())
         .json(&value)
@@ -120,3 +121,22 @@ pub async fn login_refresh(
         account_type,
     })
 }
+
+/// Checks whether an access token still works, without refreshing
+/// it (so the stored token stays valid). Returns `false` if the
+/// token was rejected, in which case it needs to be refreshed.
+pub async fn validate(access_token: &str, account_type: &AccountType) -> Result<bool, Error> {
+    let response = CLIENT
+        .post(account_type.yggdrasil_validate())
+        .json(&serde_json::json!({
+            "accessToken": access_token,
+            "clientToken": account_type.get_client_id()
+        }))
+        .send()
+        .await?;
+    if response.status() == StatusCode::UNAUTHORIZED || response.status() == StatusCode::FORBIDDEN {
+        return Ok(false);
+    }
+    check_for_success(&response)?;
+    Ok(true)
+}
@@ -1,5 +1,4 @@
-use crate::auth::alt::OauthError;
-use keyring;
+use crate::auth::{alt::OauthError, AccountType, CustomAuthServer};
 use ql_core::file_utils::check_for_success;
 use ql_core::{IntoJsonError, CLIENT};
 use serde::{Deserialize, Serialize};
@@ -9,6 +8,44 @@ use super::{Error, CLIENT_ID};
 pub const SCOPE: &str =
     "Yggdrasil.PlayerProfiles.Read Yggdrasil.Server.Join Yggdrasil.MinecraftToken.Create User.Read";
 
+/// Where the device code flow of an account type happens.
+struct OauthEndpoints<'a> {
+    client_id: &'a str,
+    scope: &'a str,
+    device_code_url: &'a str,
+    token_url: &'a str,
+    /// Gives the name used to store the account (littleskin only),
+    /// otherwise the Minecraft profile name is used.
+    user_info_url: Option<&'a str>,
+    api_root: &'a str,
+}
+
+fn get_endpoints(account_type: &AccountType) -> Result<OauthEndpoints<'_>, OauthError> {
+    match account_type {
+        AccountType::LittleSkin => Ok(OauthEndpoints {
+            client_id: CLIENT_ID,
+            scope: SCOPE,
+            device_code_url: "https://open.littleskin.cn/oauth/device_code",
+            token_url: "https://open.littleskin.cn/oauth/token",
+            user_info_url: Some("https://littleskin.cn/api/user"),
+            api_root: account_type.yggdrasil_api_root().unwrap_or_default(),
+        }),
+        AccountType::Custom(CustomAuthServer {
+            api_root,
+            oauth: Some(oauth),
+            ..
+        }) => Ok(OauthEndpoints {
+            client_id: &oauth.client_id,
+            scope: oauth.scope.as_deref().unwrap_or(SCOPE),
+            device_code_url: &oauth.device_code_url,
+            token_url: &oauth.token_url,
+            user_info_url: None,
+            api_root,
+        }),
+        _ => Err(OauthError::NotSupported),
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 struct TokenResponse {
     token_type: String,
@@ -82,9 +119,9 @@ async fn refresh_token(client: &Client, refresh_token: &str) -> Result<TokenResp
 }*/
 
 /// Step 4: Get user info using the access token
-async fn get_user_info(access_token: &str) -> Result<UserInfo, Error> {
+async fn get_user_info(url: &str, access_token: &str) -> Result<UserInfo, Error> {
     let resp = CLIENT
-        .get("https://littleskin.cn/api/user")
+        .get(url)
         .header("Accept", "application/json")
         .bearer_auth(access_token)
         .send()
@@ -96,6 +133,7 @@ async fn get_user_info(access_token: &str) -> Result<UserInfo, Error> {
 }
 
 /// Device Code Flow structs and functions for littleskin
+/// (and custom servers with [`crate::auth::CustomOauth`])
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct DeviceCodeResponse {
     pub device_code: String,
@@ -118,11 +156,12 @@ struct DeviceTokenResponse {
 }
 
 /// Step 1: Request device code
-pub async fn request_device_code() -> Result<DeviceCodeResponse, Error> {
-    let encoded_scope = urlencoding::encode(SCOPE);
-    let body = format!("client_id={CLIENT_ID}&scope={encoded_scope}");
+pub async fn request_device_code(account_type: AccountType) -> Result<DeviceCodeResponse, Error> {
+    let endpoints = get_endpoints(&account_type)?;
+    let encoded_scope = urlencoding::encode(endpoints.scope);
+    let body = format!("client_id={}&scope={encoded_scope}", endpoints.client_id);
     let resp = CLIENT
-        .post("https://open.littleskin.cn/oauth/device_code")
+        .post(endpoints.device_code_url)
         .header("Accept", "application/json")
         .header("Content-Type", "application/x-www-form-urlencoded")
         .body(body)
@@ -140,32 +179,39 @@ pub async fn poll_device_token(
     device_code: String,
     interval: u64,
     expires_in: u64,
+    account_type: AccountType,
 ) -> Result<super::Account, Error> {
+    let endpoints = get_endpoints(&account_type)?;
+
     // Step A: exchange device_code for OAuth access_token
-    let token_resp = get_device_token(&device_code, interval, expires_in).await?;
+    let token_resp = get_device_token(&endpoints, &device_code, interval, expires_in).await?;
     let oauth_access_token = token_resp.access_token.ok_or(OauthError::NoAccessToken)?;
 
-    // Step B: fetch user info (we need uuid & username)
-    let user_info = get_user_info(&oauth_access_token).await?;
-
-    // Step C: exchange OAuth token for a Yggdrasil/Minecraft token (needed for actual game login)
+    // Step B: exchange OAuth token for a Yggdrasil/Minecraft token (needed for actual game login)
     // Sub step get UUID
-    let profile = get_minecraft_profile(&oauth_access_token).await?;
+    let profile = get_minecraft_profile(endpoints.api_root, &oauth_access_token).await?;
     let uuid = profile.id;
-    let mut mc_token_resp = create_minecraft_token(&oauth_access_token, &uuid).await?;
+    let profile_name = profile.name;
+    let mut mc_token_resp =
+        create_minecraft_token(endpoints.api_root, &oauth_access_token, &uuid).await?;
     // If server didn't include selectedProfile, fetch via sessionserver
     if mc_token_resp.selected_profile.is_none() {
-        if let Ok(profile) = get_minecraft_profile(&oauth_access_token).await {
+        if let Ok(profile) = get_minecraft_profile(endpoints.api_root, &oauth_access_token).await {
             mc_token_resp.selected_profile = Some(profile);
         }
     }
 
+    // Step C: fetch user info (we need the username)
+    let username = if let Some(url) = endpoints.user_info_url {
+        get_user_info(url, &oauth_access_token).await?.username
+    } else {
+        profile_name
+    };
+
     // Store Minecraft token in keyring (same convention as password flow)
-    keyring::Entry::new(
-        "QuantumLauncher",
-        &format!("{}#littleskin", user_info.username),
-    )
-    .and_then(|e| e.set_password(&mc_token_resp.access_token))?;
+    account_type
+        .get_keyring_entry(&username)
+        .set_password(&mc_token_resp.access_token)?;
 
     // Build account data compatible with existing flows
     Ok(super::Account::Account(super::AccountData {
@@ -175,14 +221,14 @@ pub async fn poll_device_token(
             .as_ref()
             .map(|p| p.id.clone())
             .unwrap_or_default(),
-        username: user_info.username.clone(),
         nice_username: mc_token_resp
             .selected_profile
             .as_ref()
-            .map_or_else(|| user_info.username.clone(), |p| p.name.clone()),
+            .map_or_else(|| username.clone(), |p| p.name.clone()),
+        username,
         refresh_token: mc_token_resp.access_token,
         needs_refresh: false,
-        account_type: crate::auth::AccountType::LittleSkin,
+        account_type,
     }))
 }
 
@@ -202,9 +248,14 @@ struct MinecraftProfile {
     _properties: Option<Vec<serde_json::Value>>, // ignored
 }
 
-async fn get_minecraft_profile(oauth_access_token: &str) -> Result<MinecraftProfile, Error> {
+async fn get_minecraft_profile(
+    api_root: &str,
+    oauth_access_token: &str,
+) -> Result<MinecraftProfile, Error> {
     let resp = CLIENT
-        .get("https://littleskin.cn/api/yggdrasil/sessionserver/session/minecraft/profile")
+        .get(format!(
+            "{api_root}/sessionserver/session/minecraft/profile"
+        ))
         .header("Accept", "application/json")
         .bearer_auth(oauth_access_token)
         .send()
@@ -218,11 +269,12 @@ async fn get_minecraft_profile(oauth_access_token: &str) -> Result<MinecraftProf
 }
 
 async fn create_minecraft_token(
+    api_root: &str,
     oauth_access_token: &str,
     uuid: &str,
 ) -> Result<MinecraftTokenResponse, Error> {
     let resp = CLIENT
-        .post("https://littleskin.cn/api/yggdrasil/authserver/oauth")
+        .post(format!("{api_root}/authserver/oauth"))
         .bearer_auth(oauth_access_token)
         .header("Accept", "application/json")
         .json(&serde_json::json!({ "uuid": uuid.to_string() }))
@@ -236,6 +288,7 @@ async fn create_minecraft_token(
 }
 
 async fn get_device_token(
+    endpoints: &OauthEndpoints<'_>,
     device_code: &str,
     interval: u64,
     expires_in: u64,
@@ -249,10 +302,10 @@ async fn get_device_token(
         let params = [
             ("grant_type", "urn:ietf:params:oauth:grant-type:device_code"),
             ("device_code", device_code),
-            ("client_id", CLIENT_ID),
+            ("client_id", endpoints.client_id),
         ];
         let resp = CLIENT
-            .post("https://open.littleskin.cn/oauth/token")
+            .post(endpoints.token_url)
             .form(&params)
             .header("Accept", "application/json")
             .send()
@@ -321,11 +321,13 @@ impl GameDownloader {
             is_classic_server: None,
             do_gc_tuning: None,
             close_on_start: None,
+            preferred_account: None,
             is_server: Some(false),
             omniarchive: None,
             global_settings: None,
             java_args_mode: None,
             pre_launch_prefix_mode: None,
+            env_vars_mode: None,
             custom_jar: None,
             main_class_override: None,
         };
@@ -205,9 +205,7 @@ impl GameDownloader {
             .path(lib_dir_path)?;
         let library_downloaded = file_utils::download_file_to_bytes(&artifact.url, false).await?;
 
-        tokio::fs::write(&lib_file_path, &library_downloaded)
-            .await
-            .path(lib_file_path)?;
+        file_utils::write_atomic(&lib_file_path, &library_downloaded).await?;
 
         Ok(library_downloaded)
     }
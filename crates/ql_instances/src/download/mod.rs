@@ -1,7 +1,8 @@
 use std::sync::mpsc::Sender;
 
 use ql_core::{
-    info, DownloadProgress, IntoIoError, ListEntry, LAUNCHER_DIR, LAUNCHER_VERSION_NAME,
+    err, info, json::InstanceMetaFile, DownloadProgress, InstanceSelection, IntoIoError, ListEntry,
+    LAUNCHER_DIR, LAUNCHER_VERSION_NAME,
 };
 
 pub mod constants;
@@ -75,6 +76,11 @@ pub async fn create_instance(
         .join(".minecraft/mods");
     tokio::fs::create_dir_all(&mods_dir).await.path(mods_dir)?;
 
+    let instance = InstanceSelection::Instance(instance_name.clone());
+    if let Err(err) = InstanceMetaFile::record_created(&instance).await {
+        err!("Couldn't save creation date of instance: {err}");
+    }
+
     info!("Finished creating instance: {instance_name}");
 
     Ok(instance_name)
@@ -0,0 +1,230 @@
+use std::path::{Path, PathBuf};
+
+use ql_core::{
+    err, file_utils, info,
+    json::{InstanceMeta, InstanceMetaFile},
+    pt, InstanceSelection, IntoIoError, IoError, JsonError, JsonFileError,
+};
+use thiserror::Error;
+
+use super::rename::{rewrite_config_paths, validate_name};
+
+const CLONE_ERR_PREFIX: &str = "while cloning instance:\n";
+
+#[derive(Debug, Error)]
+pub enum CloneError {
+    #[error("{CLONE_ERR_PREFIX}{0}")]
+    Io(#[from] IoError),
+    #[error("{CLONE_ERR_PREFIX}{0}")]
+    Json(#[from] JsonError),
+    #[error("an instance with that name already exists: {0}")]
+    AlreadyExists(String),
+    #[error("{CLONE_ERR_PREFIX}instance not found: {0}")]
+    NotFound(String),
+    #[error("invalid instance name {0:?}: {1}")]
+    InvalidName(String, &'static str),
+}
+
+impl From<JsonFileError> for CloneError {
+    fn from(value: JsonFileError) -> Self {
+        match value {
+            JsonFileError::SerdeError(err) => Self::Json(err),
+            JsonFileError::Io(err) => Self::Io(err),
+        }
+    }
+}
+
+/// What to bring over when cloning an instance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CloneOptions {
+    /// Singleplayer worlds (`saves/`, or `world*/` for servers)
+    pub copy_saves: bool,
+    /// `logs/` and `crash-reports/`
+    pub copy_logs: bool,
+    pub copy_screenshots: bool,
+    /// Hardlink the libraries and game jar instead of copying
+    /// them, so the clone takes (almost) no extra space.
+    /// They're never modified in place, so this is safe.
+    pub share_libraries: bool,
+}
+
+impl Default for CloneOptions {
+    fn default() -> Self {
+        Self {
+            copy_saves: true,
+            copy_logs: false,
+            copy_screenshots: false,
+            share_libraries: true,
+        }
+    }
+}
+
+/// Copies an instance (or server) to a new name.
+///
+/// Absolute paths to the old instance in its `config.json`
+/// (in arguments, hooks, etc.) are changed to the new one.
+/// The group and tags are kept, but the play history isn't.
+///
+/// Java installations are shared by all instances anyway.
+///
+/// # Errors
+/// - `new_name` is invalid (see [`validate_name`]) or already used
+/// - The instance couldn't be read or copied
+///   (in which case the partial copy is removed)
+pub async fn clone_instance(
+    instance: &InstanceSelection,
+    new_name: &str,
+    options: CloneOptions,
+) -> Result<InstanceSelection, CloneError> {
+    let new_name = new_name.trim();
+    validate_name(new_name).map_err(|n| CloneError::InvalidName(new_name.to_owned(), n))?;
+
+    let new_instance = InstanceSelection::new(new_name, instance.is_server());
+    let src = instance.get_instance_path();
+    let dst = new_instance.get_instance_path();
+    if !src.is_dir() {
+        return Err(CloneError::NotFound(instance.get_name().to_owned()));
+    }
+    if dst.exists() {
+        return Err(CloneError::AlreadyExists(new_name.to_owned()));
+    }
+
+    info!("Cloning {} to {new_name}", instance.get_name());
+    let skipped = get_skipped_paths(instance, options).await;
+    let shared = if options.share_libraries {
+        Shared {
+            dirs: SHARED_DIRS.iter().map(|n| src.join(n)).collect(),
+            // Re-extracted at launch, so each instance needs its own
+            except: src.join("libraries/natives"),
+        }
+    } else {
+        Shared::default()
+    };
+
+    if let Err(err) = copy_dir(&src, &dst, &skipped, &shared, false).await {
+        if let Err(err) = tokio::fs::remove_dir_all(&dst).await {
+            err!("Couldn't clean up partial clone: {err}");
+        }
+        return Err(err.into());
+    }
+
+    pt!("Updating paths in config.json");
+    rewrite_config_paths(&dst, &src, &dst).await?;
+    copy_meta(instance, &new_instance).await;
+
+    pt!("Done!");
+    Ok(new_instance)
+}
+
+async fn get_skipped_paths(instance: &InstanceSelection, options: CloneOptions) -> Vec<PathBuf> {
+    let src = instance.get_instance_path();
+    let mc_dir = instance.get_dot_minecraft_path();
+
+    // Playtime of the original instance
+    let mut skipped = vec![src.join("history.json")];
+    if !options.copy_logs {
+        skipped.push(mc_dir.join("logs"));
+        skipped.push(mc_dir.join("crash-reports"));
+    }
+    if !options.copy_screenshots {
+        skipped.push(mc_dir.join("screenshots"));
+    }
+    if !options.copy_saves {
+        if instance.is_server() {
+            // `world`, `world_nether`, `world_the_end` by default
+            if let Ok(mut dir) = tokio::fs::read_dir(&src).await {
+                while let Ok(Some(entry)) = dir.next_entry().await {
+                    let path = entry.path();
+                    if path.is_dir() && path.join("level.dat").exists() {
+                        skipped.push(path);
+                    }
+                }
+            }
+        } else {
+            skipped.push(mc_dir.join("saves"));
+        }
+    }
+    skipped
+}
+
+/// Dirs that can be hardlinked. Files in them are only ever
+/// added, or replaced by writing a new file and renaming it
+/// (see [`ql_core::file_utils::write_atomic`]), so a change
+/// in one instance never shows up in the other.
+///
+/// `.minecraft/versions` isn't shared, as loader installers
+/// (like OptiFine's) overwrite the version JSON in place.
+const SHARED_DIRS: &[&str] = &["libraries"];
+
+#[derive(Default)]
+struct Shared {
+    dirs: Vec<PathBuf>,
+    except: PathBuf,
+}
+
+/// Like [`ql_core::file_utils::copy_dir_recursive_ext`],
+/// but hardlinks files in `shared` dirs (falling back to copying).
+///
+/// Broken symlinks are copied as links (or skipped if
+/// that fails) instead of failing the whole copy.
+async fn copy_dir(
+    src: &Path,
+    dst: &Path,
+    skipped: &[PathBuf],
+    shared: &Shared,
+    is_shared: bool,
+) -> Result<(), IoError> {
+    if !src.exists() {
+        if let Ok(target) = tokio::fs::read_link(src).await {
+            if let Err(err) = file_utils::create_symlink(&target, dst) {
+                err!("Couldn't copy broken link {src:?}: {err}");
+            }
+            return Ok(());
+        }
+    }
+    if src.is_file() {
+        if is_shared && tokio::fs::hard_link(src, dst).await.is_ok() {
+            return Ok(());
+        }
+        tokio::fs::copy(src, dst).await.path(src)?;
+        return Ok(());
+    }
+    tokio::fs::create_dir_all(dst).await.path(dst)?;
+
+    let mut dir = tokio::fs::read_dir(src).await.dir(src)?;
+    while let Some(entry) = dir.next_entry().await.dir(src)? {
+        let path = entry.path();
+        if skipped.contains(&path) {
+            continue;
+        }
+        let is_shared = (is_shared || shared.dirs.contains(&path)) && path != shared.except;
+        Box::pin(copy_dir(
+            &path,
+            &dst.join(entry.file_name()),
+            skipped,
+            shared,
+            is_shared,
+        ))
+        .await?;
+    }
+    Ok(())
+}
+
+/// Gives the clone the same group and tags.
+async fn copy_meta(instance: &InstanceSelection, new_instance: &InstanceSelection) {
+    let result = async {
+        let mut meta = InstanceMetaFile::load().await?;
+        let old = meta.get(instance).cloned().unwrap_or_default();
+        *meta.get_mut(new_instance) = InstanceMeta {
+            group: old.group,
+            tags: old.tags,
+            favourite: false,
+            created: Some(chrono::Utc::now().timestamp()),
+        };
+        meta.save().await
+    }
+    .await;
+    if let Err(err) = result {
+        err!("Couldn't copy group/tags of instance: {err}");
+    }
+}
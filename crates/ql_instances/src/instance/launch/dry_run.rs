@@ -0,0 +1,290 @@
+//! The resolved command of a launch, as produced by
+//! [`super::launch_dry_run`], for inspecting or
+//! exporting it without starting the game.
+
+use std::{collections::BTreeMap, fmt::Write, path::PathBuf};
+
+use ql_core::{err, json::instance_config::is_valid_env_var_name};
+use serde::Serialize;
+
+/// The environment variable that exported launch scripts
+/// read the account's access token from.
+pub(super) const ACCESS_TOKEN_VAR: &str = "QL_ACCESS_TOKEN";
+/// Stands in for the access token in the arguments
+/// of exported launch scripts, see [`ACCESS_TOKEN_VAR`].
+pub(super) const ACCESS_TOKEN_PLACEHOLDER: &str = "${QL_ACCESS_TOKEN}";
+
+/// The fully resolved command that would be run to launch the game.
+#[derive(Serialize, Debug, Clone)]
+pub struct LaunchCommand {
+    /// The executable, usually `java`
+    /// (or the first part of the launch prefix).
+    pub program: String,
+    /// Java arguments, followed by game arguments.
+    pub args: Vec<String>,
+    /// Environment variables set by the launcher
+    /// (not including the inherited ones).
+    pub env: BTreeMap<String, String>,
+    /// The `.minecraft` directory of the instance.
+    pub working_dir: Option<PathBuf>,
+}
+
+impl LaunchCommand {
+    pub(super) fn from_command(command: &std::process::Command) -> Self {
+        Self {
+            program: command.get_program().to_string_lossy().to_string(),
+            args: command
+                .get_args()
+                .map(|n| n.to_string_lossy().to_string())
+                .collect(),
+            env: command
+                .get_envs()
+                .filter_map(|(k, v)| {
+                    Some((
+                        k.to_string_lossy().to_string(),
+                        v?.to_string_lossy().to_string(),
+                    ))
+                })
+                .collect(),
+            working_dir: command.get_current_dir().map(ToOwned::to_owned),
+        }
+    }
+
+    /// Turns the command into a script that launches the game:
+    /// a `sh` script on Unix, or a batch file on Windows.
+    #[must_use]
+    pub fn to_script(&self) -> String {
+        if cfg!(target_os = "windows") {
+            self.to_batch_script()
+        } else {
+            self.to_shell_script()
+        }
+    }
+
+    fn needs_access_token(&self) -> bool {
+        self.args
+            .iter()
+            .any(|n| n.contains(ACCESS_TOKEN_PLACEHOLDER))
+    }
+
+    /// Environment variables that can be written into a script
+    /// (names with anything other than letters, digits and
+    /// `_` could inject commands).
+    fn valid_env(&self) -> impl Iterator<Item = (&String, &String)> {
+        self.env.iter().filter(|(key, _)| {
+            let is_valid = is_valid_env_var_name(key);
+            if !is_valid {
+                err!("Not exporting environment variable with invalid name: {key:?}");
+            }
+            is_valid
+        })
+    }
+
+    fn to_shell_script(&self) -> String {
+        let mut out = "#!/bin/sh\n".to_owned();
+        if self.needs_access_token() {
+            _ = writeln!(
+                out,
+                ": \"${{{ACCESS_TOKEN_VAR}:?Set {ACCESS_TOKEN_VAR} to the account's access token}}\""
+            );
+        }
+        if let Some(dir) = &self.working_dir {
+            _ = writeln!(out, "cd {} || exit 1", quote_sh(&dir.to_string_lossy()));
+        }
+        for (key, value) in self.valid_env() {
+            _ = writeln!(out, "export {key}={}", quote_sh(value));
+        }
+        out.push_str("exec ");
+        out.push_str(&quote_sh(&self.program));
+        for arg in &self.args {
+            out.push_str(" \\\n    ");
+            out.push_str(&quote_sh(arg));
+        }
+        out.push('\n');
+        out
+    }
+
+    fn to_batch_script(&self) -> String {
+        let mut out = "@echo off\r\n".to_owned();
+        if self.needs_access_token() {
+            _ = write!(
+                out,
+                "if not defined {ACCESS_TOKEN_VAR} (\r\n    echo Set {ACCESS_TOKEN_VAR} to the account's access token\r\n    exit /b 1\r\n)\r\n"
+            );
+        }
+        if let Some(dir) = &self.working_dir {
+            _ = write!(out, "cd /d {}\r\n", quote_bat(&dir.to_string_lossy()));
+        }
+        for (key, value) in self.valid_env() {
+            _ = write!(out, "set \"{key}={}\"\r\n", value.replace('%', "%%"));
+        }
+        out.push_str(&quote_bat(&self.program));
+        for arg in &self.args {
+            out.push_str(" ^\r\n    ");
+            out.push_str(&quote_bat(arg));
+        }
+        out.push_str("\r\n");
+        out
+    }
+}
+
+/// Quotes an argument for `sh`, only if needed.
+///
+/// [`ACCESS_TOKEN_PLACEHOLDER`] is turned into a
+/// reference to the environment variable.
+fn quote_sh(arg: &str) -> String {
+    if arg.contains(ACCESS_TOKEN_PLACEHOLDER) {
+        return arg
+            .split(ACCESS_TOKEN_PLACEHOLDER)
+            .map(|n| {
+                if n.is_empty() {
+                    String::new()
+                } else {
+                    quote_sh(n)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(&format!("\"${ACCESS_TOKEN_VAR}\""));
+    }
+    if !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=,+@%".contains(c))
+    {
+        arg.to_owned()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+/// Quotes an argument for `cmd`, only if needed.
+///
+/// [`ACCESS_TOKEN_PLACEHOLDER`] is turned into a
+/// reference to the environment variable.
+fn quote_bat(arg: &str) -> String {
+    let arg = arg
+        .split(ACCESS_TOKEN_PLACEHOLDER)
+        .map(|n| n.replace('%', "%%"))
+        .collect::<Vec<_>>()
+        .join(&format!("%{ACCESS_TOKEN_VAR}%"));
+    if !arg.is_empty() && !arg.contains([' ', '&', '|', '<', '>', '^', '(', ')', ';', ',', '=']) {
+        arg
+    } else {
+        format!("\"{}\"", arg.replace('"', "\"\""))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shell_quoting() {
+        assert_eq!(quote_sh("-Xmx2048M"), "-Xmx2048M");
+        assert_eq!(quote_sh("/opt/java/bin/java"), "/opt/java/bin/java");
+        assert_eq!(quote_sh(""), "''");
+        assert_eq!(quote_sh("My World"), "'My World'");
+        assert_eq!(quote_sh("it's"), r"'it'\''s'");
+        assert_eq!(quote_sh("$HOME"), "'$HOME'");
+        assert_eq!(quote_sh(ACCESS_TOKEN_PLACEHOLDER), "\"$QL_ACCESS_TOKEN\"");
+        assert_eq!(
+            quote_sh(&format!("token:{ACCESS_TOKEN_PLACEHOLDER}:a b")),
+            "token:\"$QL_ACCESS_TOKEN\"':a b'"
+        );
+    }
+
+    #[test]
+    fn shell_script() {
+        let command = LaunchCommand {
+            program: "java".to_owned(),
+            args: vec!["-cp".to_owned(), "a.jar:b.jar".to_owned()],
+            env: BTreeMap::from([("FOO".to_owned(), "bar baz".to_owned())]),
+            working_dir: Some(PathBuf::from("/tmp/My Instance")),
+        };
+        assert_eq!(
+            command.to_shell_script(),
+            "#!/bin/sh\ncd '/tmp/My Instance' || exit 1\nexport FOO='bar baz'\nexec java \\\n    -cp \\\n    a.jar:b.jar\n"
+        );
+    }
+
+    #[test]
+    fn invalid_env_names() {
+        let command = LaunchCommand {
+            program: "java".to_owned(),
+            args: Vec::new(),
+            env: BTreeMap::from([
+                ("OK".to_owned(), "1".to_owned()),
+                ("A;id".to_owned(), "2".to_owned()),
+                ("$(id)".to_owned(), "3".to_owned()),
+                ("A B".to_owned(), "4".to_owned()),
+            ]),
+            working_dir: None,
+        };
+        assert_eq!(
+            command.to_shell_script(),
+            "#!/bin/sh\nexport OK=1\nexec java\n"
+        );
+        assert_eq!(
+            command.to_batch_script(),
+            "@echo off\r\nset \"OK=1\"\r\njava\r\n"
+        );
+    }
+
+    /// Removes the test instance, even if the test fails.
+    struct TempInstance(PathBuf);
+
+    impl Drop for TempInstance {
+        fn drop(&mut self) {
+            _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[tokio::test]
+    async fn dry_run_jarmods() {
+        let name = format!("ql_test_dry_run_{}", std::process::id());
+        let dir = ql_core::LAUNCHER_DIR.join("instances").join(&name);
+        let _guard = TempInstance(dir.clone());
+        std::fs::create_dir_all(dir.join("jarmods")).unwrap();
+        std::fs::write(dir.join("jarmods/mod.zip"), b"").unwrap();
+        std::fs::write(
+            dir.join("config.json"),
+            r#"{ "mod_type": "Vanilla", "ram_in_mb": 2048 }"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("details.json"),
+            serde_json::json!({
+                "assetIndex": { "id": "1.12", "sha1": "", "size": 0, "totalSize": 0, "url": "" },
+                "assets": "1.12",
+                "downloads": { "client": { "sha1": "", "size": 0, "url": "" } },
+                "id": "1.12.2",
+                "libraries": [{
+                    "name": "com.example:missing:1.0",
+                    "downloads": { "artifact": {
+                        "path": "com/example/missing/1.0/missing-1.0.jar",
+                        "sha1": "", "size": 0, "url": ""
+                    } }
+                }],
+                "mainClass": "net.minecraft.client.main.Main",
+                "minecraftArguments": "--username ${auth_player_name} --gameDir ${game_directory}",
+                "releaseTime": "2017-09-18T08:39:46+00:00",
+                "time": "2017-09-18T08:39:46+00:00",
+                "type": "release",
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        let command =
+            super::super::launch_dry_run(name, "Player".to_owned(), None, None, Vec::new(), None)
+                .await
+                .unwrap();
+
+        // Not built (or downloaded) in a dry run, but still in the command
+        let classpath = &command.args[command.args.iter().position(|n| n == "-cp").unwrap() + 1];
+        assert!(classpath.contains("build.jar"), "{classpath}");
+        assert!(classpath.contains("missing-1.0.jar"), "{classpath}");
+        assert!(!dir.join("build.jar").exists());
+        assert!(command.args.contains(&"Player".to_owned()));
+    }
+}
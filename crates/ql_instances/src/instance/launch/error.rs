@@ -31,6 +31,10 @@ pub enum GameLaunchError {
     PathBufToString(PathBuf),
     #[error("{GAME_ERR_PREFIX}couldn't run java command: {0}")]
     CommandError(std::io::Error, PathBuf),
+    #[error("{GAME_ERR_PREFIX}custom Java executable isn't a working Java runtime: {0:?}\nChange it in Edit -> Custom Java executable")]
+    InvalidJavaOverride(PathBuf),
+    #[error("{GAME_ERR_PREFIX}hook command failed ({0})\nCheck the logs for its output")]
+    HookFailed(std::process::ExitStatus),
 
     #[error("{GAME_ERR_PREFIX}{0}")]
     Download(#[from] DownloadError),
@@ -0,0 +1,201 @@
+//! Exports an instance's resolved launch command as a
+//! standalone script, so the game can be started
+//! (from systemd, a kiosk session, etc.) without
+//! the launcher running.
+
+use std::path::{Path, PathBuf};
+
+use ql_core::{info, json::GlobalSettings, IntoIoError, IoError};
+
+use super::{
+    dry_run::{LaunchCommand, ACCESS_TOKEN_PLACEHOLDER, ACCESS_TOKEN_VAR},
+    error::GameLaunchError,
+    prepare,
+};
+use crate::auth::AccountData;
+
+/// Writes a launch script for an instance into `out_dir`
+/// (`<instance>.sh`, or `<instance>.bat` on Windows),
+/// along with a `<instance>.desktop` entry on Linux/BSD.
+///
+/// If `account` is `None`, the game is started in offline
+/// mode with `username`. Otherwise, the script reads the
+/// access token from the `QL_ACCESS_TOKEN` environment
+/// variable instead of storing it (tokens expire anyway).
+/// No `.desktop` entry is written in that case, as nothing
+/// would set the variable when launched from a desktop.
+///
+/// Pre-launch/post-exit hooks aren't included.
+///
+/// Returns the paths of the written files.
+///
+/// # Errors
+/// Same as [`super::launch`], as well as errors
+/// while writing the files.
+pub async fn export_launch_script(
+    instance_name: String,
+    username: String,
+    account: Option<AccountData>,
+    global_settings: Option<GlobalSettings>,
+    extra_java_args: Vec<String>,
+    out_dir: PathBuf,
+) -> Result<Vec<PathBuf>, GameLaunchError> {
+    info!("Exporting launch script for {instance_name}");
+    let account = account.map(|n| AccountData {
+        access_token: Some(ACCESS_TOKEN_PLACEHOLDER.to_owned()),
+        ..n
+    });
+
+    let (mut game_launcher, game_arguments, java_arguments) = prepare(
+        instance_name.clone(),
+        username,
+        None,
+        account.as_ref(),
+        global_settings,
+        extra_java_args,
+        None,
+        false,
+    )
+    .await?;
+    let (command, _) = game_launcher
+        .get_command(game_arguments, java_arguments)
+        .await?;
+    let command = LaunchCommand::from_command(command.as_std());
+
+    let extension = if cfg!(target_os = "windows") {
+        "bat"
+    } else {
+        "sh"
+    };
+    let script_path = out_dir.join(format!("{instance_name}.{extension}"));
+    write_file(&script_path, &command.to_script()).await?;
+    let mut written = vec![script_path.clone()];
+
+    if account.is_none() && cfg!(not(any(target_os = "windows", target_os = "macos"))) {
+        let desktop_path = out_dir.join(format!("{instance_name}.desktop"));
+        let entry = get_desktop_entry(&instance_name, &script_path, &command);
+        write_file(&desktop_path, &entry).await?;
+        written.push(desktop_path);
+    }
+
+    if account.is_some() {
+        info!("The script needs the {ACCESS_TOKEN_VAR} environment variable to be set");
+    }
+    Ok(written)
+}
+
+/// Writes an executable file (desktop environments
+/// only trust `.desktop` entries marked executable).
+async fn write_file(path: &Path, contents: &str) -> Result<(), IoError> {
+    tokio::fs::write(path, contents).await.path(path)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        tokio::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))
+            .await
+            .path(path)?;
+    }
+    Ok(())
+}
+
+/// A freedesktop.org `.desktop` entry running the script.
+fn get_desktop_entry(instance_name: &str, script_path: &Path, command: &LaunchCommand) -> String {
+    let script_path = script_path
+        .canonicalize()
+        .unwrap_or_else(|_| script_path.to_owned());
+    let name = escape_desktop_value(instance_name);
+    let mut entry = format!(
+        "[Desktop Entry]\nType=Application\nName={name}\nComment=Minecraft ({name})\nExec={}\nTerminal=false\nCategories=Game;\n",
+        quote_desktop_exec(&script_path.to_string_lossy())
+    );
+    if let Some(dir) = &command.working_dir {
+        entry.push_str(&format!(
+            "Path={}\n",
+            escape_desktop_value(&dir.to_string_lossy())
+        ));
+    }
+    entry
+}
+
+/// Escapes a string value of a `.desktop` entry
+/// (`\\`, `\n`, `\t`, `\r`, and `\s` for a leading space).
+fn escape_desktop_value(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace('\t', "\\t")
+        .replace('\r', "\\r");
+    match escaped.strip_prefix(' ') {
+        Some(rest) => format!("\\s{rest}"),
+        None => escaped,
+    }
+}
+
+/// Quotes a path for the `Exec` key of a `.desktop` entry.
+fn quote_desktop_exec(path: &str) -> String {
+    let escaped = path
+        .replace('\\', "\\\\\\\\")
+        .replace('"', "\\\\\"")
+        .replace('`', "\\\\`")
+        .replace('$', "\\\\$")
+        .replace('%', "%%");
+    format!("\"{escaped}\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn desktop_values() {
+        assert_eq!(escape_desktop_value("My Pack"), "My Pack");
+        assert_eq!(escape_desktop_value(" leading"), r"\sleading");
+        assert_eq!(escape_desktop_value("100% done"), "100% done");
+        assert_eq!(escape_desktop_value(r#"say "hi""#), r#"say "hi""#);
+        assert_eq!(escape_desktop_value(r"C:\Games"), r"C:\\Games");
+        assert_eq!(escape_desktop_value("a\nb\tc\rd"), r"a\nb\tc\rd");
+    }
+
+    #[test]
+    fn desktop_exec() {
+        assert_eq!(
+            quote_desktop_exec("/home/me/My Pack.sh"),
+            r#""/home/me/My Pack.sh""#
+        );
+        assert_eq!(quote_desktop_exec("/tmp/100%.sh"), r#""/tmp/100%%.sh""#);
+        // Escaped once for the quoted argument, and
+        // once more as a string value
+        assert_eq!(quote_desktop_exec(r#"/tmp/a"b.sh"#), r#""/tmp/a\\"b.sh""#);
+        assert_eq!(quote_desktop_exec(r"/tmp/a\b.sh"), r#""/tmp/a\\\\b.sh""#);
+        assert_eq!(
+            quote_desktop_exec("/tmp/$HOME`id`.sh"),
+            r#""/tmp/\\$HOME\\`id\\`.sh""#
+        );
+    }
+
+    #[test]
+    fn desktop_entry() {
+        let command = LaunchCommand {
+            program: "java".to_owned(),
+            args: Vec::new(),
+            env: std::collections::BTreeMap::new(),
+            working_dir: Some(PathBuf::from(r"/tmp/ql\ test/.minecraft")),
+        };
+        let entry = get_desktop_entry(
+            " 50% \"Pack\"",
+            Path::new("/nonexistent/ 50% \"Pack\".sh"),
+            &command,
+        );
+        assert_eq!(
+            entry,
+            concat!(
+                "[Desktop Entry]\nType=Application\n",
+                "Name=\\s50% \"Pack\"\n",
+                "Comment=Minecraft (\\s50% \"Pack\")\n",
+                "Exec=\"/nonexistent/ 50%% \\\\\"Pack\\\\\".sh\"\n",
+                "Terminal=false\nCategories=Game;\n",
+                "Path=/tmp/ql\\\\ test/.minecraft\n",
+            )
+        );
+    }
+}
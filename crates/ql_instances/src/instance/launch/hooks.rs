@@ -0,0 +1,105 @@
+//! Shell commands run before launching the game and after it exits
+//! (see [`GlobalSettings::pre_launch_hook`] and
+//! [`GlobalSettings::post_exit_hook`]).
+
+use std::{collections::BTreeMap, path::Path, process::Stdio};
+
+use ql_core::{
+    err, info,
+    json::{GlobalSettings, InstanceConfigJson},
+    no_window, pt, InstanceSelection, LAUNCHER_DIR,
+};
+use tokio::process::Command;
+
+use super::error::GameLaunchError;
+
+pub(super) struct HookContext<'a> {
+    pub instance_name: &'a str,
+    pub instance_dir: &'a Path,
+    pub minecraft_dir: &'a Path,
+    pub env_vars: &'a BTreeMap<String, String>,
+}
+
+/// Runs a hook command through the system shell,
+/// logging its output.
+///
+/// `exit_code` is only passed to post-exit hooks:
+/// `Some(None)` means the game was killed by a signal.
+pub(super) async fn run_hook(
+    hook: &str,
+    context: &HookContext<'_>,
+    exit_code: Option<Option<i32>>,
+) -> Result<(), GameLaunchError> {
+    info!("Running hook: {hook}");
+
+    let (shell, flag) = if cfg!(target_os = "windows") {
+        ("cmd", "/C")
+    } else {
+        ("sh", "-c")
+    };
+    let mut command = Command::new(shell);
+    command
+        .args([flag, hook])
+        .current_dir(context.minecraft_dir)
+        .envs(context.env_vars)
+        .env("QL_INSTANCE_NAME", context.instance_name)
+        .env("QL_INSTANCE_DIR", context.instance_dir)
+        .env("QL_MINECRAFT_DIR", context.minecraft_dir)
+        .env("QL_LAUNCHER_DIR", &*LAUNCHER_DIR)
+        .stdin(Stdio::null());
+    if let Some(code) = exit_code {
+        command.env(
+            "QL_EXIT_CODE",
+            code.map(|n| n.to_string()).unwrap_or_default(),
+        );
+    }
+    no_window!(command);
+
+    let output = command
+        .output()
+        .await
+        .map_err(|err| GameLaunchError::CommandError(err, shell.into()))?;
+    for line in String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .chain(String::from_utf8_lossy(&output.stderr).lines())
+    {
+        pt!("{line}");
+    }
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(GameLaunchError::HookFailed(output.status))
+    }
+}
+
+/// Runs the post-exit hook of an instance (if any),
+/// after the game has exited with `exit_code`
+/// (`None` if it was killed by a signal).
+///
+/// `global_settings` are the launcher-wide settings,
+/// used if the instance doesn't have its own hook.
+pub async fn run_post_exit_hook(
+    instance_name: String,
+    exit_code: Option<i32>,
+    global_settings: Option<GlobalSettings>,
+) -> Result<(), GameLaunchError> {
+    let instance = InstanceSelection::Instance(instance_name);
+    let config = InstanceConfigJson::read(&instance).await?;
+    let (_, Some(hook)) = config.get_hooks(global_settings.as_ref()) else {
+        return Ok(());
+    };
+
+    let instance_dir = instance.get_instance_path();
+    let context = HookContext {
+        instance_name: instance.get_name(),
+        minecraft_dir: &instance.get_dot_minecraft_path(),
+        instance_dir: &instance_dir,
+        env_vars: &config.get_env_vars(global_settings.as_ref()),
+    };
+    let result = run_hook(&hook, &context, Some(exit_code)).await;
+    if let Err(error) = &result {
+        err!("Post-exit hook failed: {error}");
+    }
+    result
+}
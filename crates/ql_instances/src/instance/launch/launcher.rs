@@ -14,7 +14,7 @@ use ql_core::{
     pt, GenericProgress, InstanceSelection, IntoIoError, IntoJsonError, IoError, JsonFileError,
     CLASSPATH_SEPARATOR, LAUNCHER_DIR,
 };
-use ql_java_handler::{get_java_binary, JavaVersion};
+use ql_java_handler::{get_java_binary, get_java_binary_path, JavaVersion};
 use std::{
     collections::HashSet,
     path::{Path, PathBuf},
@@ -23,7 +23,13 @@ use std::{
 };
 use tokio::process::Command;
 
-use super::{error::GameLaunchError, replace_var};
+use super::{
+    error::GameLaunchError,
+    hooks::{run_hook, HookContext},
+    replace_var,
+    rules::{evaluate_arguments, load_os_version, ArgumentFeatures},
+    QuickPlay,
+};
 
 pub struct GameLauncher {
     username: String,
@@ -47,6 +53,11 @@ pub struct GameLauncher {
     /// can be overridden by `config_json.global_settings`.
     global_settings: Option<GlobalSettings>,
     extra_java_args: Vec<String>,
+    quick_play: Option<QuickPlay>,
+    /// Only resolve the launch command, without changing
+    /// anything on disk (installing Java, downloading missing
+    /// libraries, building jar mods, migrating old files).
+    pub(super) dry_run: bool,
 }
 
 impl GameLauncher {
@@ -56,6 +67,7 @@ impl GameLauncher {
         java_install_progress_sender: Option<Sender<GenericProgress>>,
         global_settings: Option<GlobalSettings>,
         extra_java_args: Vec<String>,
+        quick_play: Option<QuickPlay>,
     ) -> Result<Self, GameLaunchError> {
         let instance_dir = get_instance_dir(&instance_name).await?;
 
@@ -65,6 +77,7 @@ impl GameLauncher {
             .path(&minecraft_dir)?;
 
         let config_json = InstanceConfigJson::read_from_dir(&instance_dir).await?;
+        load_os_version().await;
 
         let instance = InstanceSelection::Instance(instance_name.clone());
         let mut version_json = VersionDetails::load(&instance).await?;
@@ -80,6 +93,8 @@ impl GameLauncher {
             version_json,
             global_settings,
             extra_java_args,
+            quick_play,
+            dry_run: false,
         })
     }
 
@@ -91,21 +106,18 @@ impl GameLauncher {
             if let Some(arguments) = &self.version_json.minecraftArguments {
                 arguments.split(' ').map(ToOwned::to_owned).collect()
             } else if let Some(arguments) = &self.version_json.arguments {
-                arguments
-                    .game
-                    .iter()
-                    .filter_map(|arg| arg.as_str())
-                    .map(ToOwned::to_owned)
-                    .collect()
+                evaluate_arguments(&arguments.game, &self.argument_features())
             } else {
                 return Err(GameLaunchError::VersionJsonNoArgumentsField(Box::new(
                     self.version_json.clone(),
                 )));
             };
 
-        if let Some(account_type) = account_details.map(|n| n.account_type) {
-            if matches!(account_type, AccountType::ElyBy | AccountType::LittleSkin)
-                && !self.version_json.is_legacy_version()
+        if let Some(account_type) = account_details.map(|n| &n.account_type) {
+            if matches!(
+                account_type,
+                AccountType::ElyBy | AccountType::LittleSkin | AccountType::Custom(_)
+            ) && !self.version_json.is_legacy_version()
                 && !game_arguments.iter().any(|n| n.contains("uuid"))
             {
                 game_arguments.push("--uuid".to_owned());
@@ -133,20 +145,60 @@ impl GameLauncher {
             .config_json
             .get_window_size(self.global_settings.as_ref());
 
-        if let Some(width) = width_to_use {
+        // Modern versions add these through `has_custom_resolution`
+        let has_resolution_args = game_arguments.iter().any(|n| n == "--width");
+        if let (Some(width), false) = (width_to_use, has_resolution_args) {
             game_arguments.push("--width".to_owned());
             game_arguments.push(width.to_string());
         }
-        if let Some(height) = height_to_use {
+        if let (Some(height), false) = (height_to_use, has_resolution_args) {
             game_arguments.push("--height".to_owned());
             game_arguments.push(height.to_string());
         }
 
+        self.add_quick_play_arguments(&mut game_arguments);
+
         game_arguments.extend(self.config_json.game_args.iter().flatten().cloned());
 
         Ok(game_arguments)
     }
 
+    /// Adds the arguments to join a world/server directly,
+    /// if the version JSON didn't already add them
+    /// (through the `is_quick_play_*` feature rules).
+    ///
+    /// 23w14a and above use `--quickPlay*` arguments,
+    /// older versions can only join servers (`--server`, `--port`).
+    fn add_quick_play_arguments(&self, game_arguments: &mut Vec<String>) {
+        let Some(quick_play) = &self.quick_play else {
+            return;
+        };
+        let (flag, value) = match quick_play {
+            QuickPlay::Singleplayer(n) => ("--quickPlaySingleplayer", n),
+            QuickPlay::Multiplayer(n) => ("--quickPlayMultiplayer", n),
+            QuickPlay::Realms(n) => ("--quickPlayRealms", n),
+        };
+        if game_arguments.iter().any(|n| n == flag) {
+            return;
+        }
+
+        if supports_quick_play(&self.version_json.releaseTime) {
+            game_arguments.push(flag.to_owned());
+            game_arguments.push(value.clone());
+        } else if let QuickPlay::Multiplayer(address) = quick_play {
+            let (host, port) = split_server_address(address);
+            game_arguments.push("--server".to_owned());
+            game_arguments.push(host.to_owned());
+            game_arguments.push("--port".to_owned());
+            game_arguments.push(port.to_owned());
+        } else {
+            err!(
+                "Minecraft {} doesn't support directly opening worlds/realms, ignoring",
+                self.version_json.get_id()
+            );
+        }
+    }
+
     pub async fn fill_game_arguments(
         &self,
         game_arguments: &mut [String],
@@ -196,10 +248,41 @@ impl GameLauncher {
             replace_var(arg, "version_type", "release");
             replace_var(arg, "assets_index_name", &self.version_json.assetIndex.id);
             replace_var(arg, "user_properties", "{}");
+
+            let (width, height) = self
+                .config_json
+                .get_window_size(self.global_settings.as_ref());
+            replace_var(arg, "resolution_width", &width.unwrap_or(854).to_string());
+            replace_var(arg, "resolution_height", &height.unwrap_or(480).to_string());
+
+            if let Some(quick_play) = &self.quick_play {
+                let (var, value) = match quick_play {
+                    QuickPlay::Singleplayer(n) => ("quickPlaySingleplayer", n),
+                    QuickPlay::Multiplayer(n) => ("quickPlayMultiplayer", n),
+                    QuickPlay::Realms(n) => ("quickPlayRealms", n),
+                };
+                replace_var(arg, var, value);
+            }
         }
         Ok(())
     }
 
+    /// Launcher features that the version JSON's
+    /// `arguments` rules can check for.
+    fn argument_features(&self) -> ArgumentFeatures {
+        let (width, height) = self
+            .config_json
+            .get_window_size(self.global_settings.as_ref());
+        let quick_play = self.quick_play.as_ref();
+        ArgumentFeatures {
+            has_custom_resolution: width.is_some() || height.is_some(),
+            is_quick_play_singleplayer: matches!(quick_play, Some(QuickPlay::Singleplayer(_))),
+            is_quick_play_multiplayer: matches!(quick_play, Some(QuickPlay::Multiplayer(_))),
+            is_quick_play_realms: matches!(quick_play, Some(QuickPlay::Realms(_))),
+            ..Default::default()
+        }
+    }
+
     async fn set_assets_argument(&self, argument: &mut String) -> Result<(), GameLaunchError> {
         let launcher_dir = &*LAUNCHER_DIR;
 
@@ -210,19 +293,23 @@ impl GameLauncher {
         let old_assets_path_v1 = self.instance_dir.join("assets");
         let assets_path = launcher_dir.join("assets/dir");
 
-        if old_assets_path_v2.exists() {
-            info!("Migrating old assets to new path...");
-            file_utils::copy_dir_recursive(&old_assets_path_v2, &assets_path).await?;
-            tokio::fs::remove_dir_all(&old_assets_path_v2)
-                .await
-                .path(old_assets_path_v2)?;
-        }
+        let needs_migration = old_assets_path_v2.exists() || old_assets_path_v1.exists();
+        if !self.dry_run {
+            if old_assets_path_v2.exists() {
+                info!("Migrating old assets to new path...");
+                file_utils::copy_dir_recursive(&old_assets_path_v2, &assets_path).await?;
+                tokio::fs::remove_dir_all(&old_assets_path_v2)
+                    .await
+                    .path(old_assets_path_v2)?;
+            }
 
-        if old_assets_path_v1.exists() {
-            migrate_to_new_assets_path(&old_assets_path_v1, &assets_path).await?;
+            if old_assets_path_v1.exists() {
+                migrate_to_new_assets_path(&old_assets_path_v1, &assets_path).await?;
+            }
         }
 
-        let assets_path_fixed = if assets_path.exists() {
+        // In a dry run, the old assets would have been migrated by now
+        let assets_path_fixed = if assets_path.exists() || (self.dry_run && needs_migration) {
             assets_path
         } else {
             launcher_dir.join("assets/null")
@@ -250,23 +337,55 @@ impl GameLauncher {
             .to_str()
             .ok_or(GameLaunchError::PathBufToString(natives_path.clone()))?;
 
-        // TODO: deal with self.version_json.arguments.jvm (currently ignored)
         let mut args: Vec<String> = self
             .config_json
             .get_java_args(&self.extra_java_args)
             .into_iter()
             .filter(|arg| !arg.trim().is_empty())
-            .chain([
-                "-Dminecraft.launcher.brand=minecraft-launcher".to_owned(),
-                "-Dminecraft.launcher.version=2.1.1349".to_owned(),
-                format!("-Djava.library.path={natives_path}"),
-                format!("-Djna.tmpdir={natives_path}"),
-                format!("-Dorg.lwjgl.system.SharedLibraryExtractPath={natives_path}"),
-                format!("-Dio.netty.native.workdir={natives_path}"),
-                self.config_json.get_ram_argument(),
-            ])
             .collect();
 
+        if let Some(arguments) = &self.version_json.arguments {
+            let mut jvm = evaluate_arguments(&arguments.jvm, &self.argument_features());
+            // The classpath is added later on, by `get_class_path`
+            if let Some(i) = jvm.iter().position(|n| n == "-cp") {
+                if jvm.get(i + 1).is_some_and(|n| n == "${classpath}") {
+                    jvm.drain(i..i + 2);
+                }
+            }
+            args.extend(jvm);
+        } else {
+            // Defaults from the official launcher, for old versions
+            // that don't specify any JVM arguments
+            args.push("-Dminecraft.launcher.brand=${launcher_name}".to_owned());
+            args.push("-Dminecraft.launcher.version=${launcher_version}".to_owned());
+            args.push("-Djava.library.path=${natives_directory}".to_owned());
+            if cfg!(target_pointer_width = "32") {
+                args.push("-Xss1M".to_owned());
+            }
+            if cfg!(target_os = "macos") {
+                args.push("-XstartOnFirstThread".to_owned());
+            }
+        }
+
+        // Older versions don't have these, but they
+        // keep extracted natives out of the temp dir
+        for property in [
+            "-Djna.tmpdir",
+            "-Dorg.lwjgl.system.SharedLibraryExtractPath",
+            "-Dio.netty.native.workdir",
+        ] {
+            if !args.iter().any(|n| n.starts_with(property)) {
+                args.push(format!("{property}=${{natives_directory}}"));
+            }
+        }
+
+        for arg in &mut args {
+            replace_var(arg, "natives_directory", natives_path);
+            replace_var(arg, "launcher_name", "minecraft-launcher");
+            replace_var(arg, "launcher_version", "2.1.1349");
+        }
+        args.push(self.config_json.get_ram_argument());
+
         // I've disabled these for now because they make the
         // FPS slightly worse (!) from my testing?
         //
@@ -299,14 +418,6 @@ impl GameLauncher {
             args.push(crate::auth::get_authlib_injector(authlib).await?);
         }
 
-        if cfg!(target_pointer_width = "32") {
-            args.push("-Xss1M".to_owned());
-        }
-
-        if cfg!(target_os = "macos") {
-            args.push("-XstartOnFirstThread".to_owned());
-        }
-
         self.java_arguments_betacraft(&mut args);
 
         Ok(args)
@@ -531,9 +642,14 @@ impl GameLauncher {
         // version of a library has already been loaded.
 
         let instance = InstanceSelection::Instance(self.instance_name.clone());
-        let jar_path = jarmod::build(&instance).await?;
+        let jar_path = if self.dry_run {
+            jarmod::build_path(&instance).await?
+        } else {
+            jarmod::build(&instance).await?
+        };
+        // A dry run doesn't build jarmods or download anything
         debug_assert!(
-            jar_path.is_file(),
+            self.dry_run || jar_path.is_file(),
             "Minecraft JAR file should exist\nPath: {jar_path:?}"
         );
         let jar_path = jar_path
@@ -587,7 +703,7 @@ impl GameLauncher {
                 }
 
                 let library_path = self.instance_dir.join("libraries").join(library.get_path());
-                debug_assert!(library_path.is_file());
+                debug_assert!(self.dry_run || library_path.is_file());
                 class_path.push_str(
                     library_path
                         .to_str()
@@ -764,7 +880,9 @@ impl GameLauncher {
             .join("libraries")
             .join(artifact.get_path());
 
-        if !library_path.exists() {
+        if !library_path.exists() && self.dry_run {
+            pt!("library {library_path:?} not found! It will be downloaded on launch");
+        } else if !library_path.exists() {
             pt!("library {library_path:?} not found! Downloading...");
             if let Err(err) = downloader.download_library(library, Some(artifact)).await {
                 err!("Couldn't download library! Skipping...\n{err}");
@@ -778,7 +896,7 @@ impl GameLauncher {
         };
         if main_class != "org.mcphackers.launchwrapper.Launch" && library_path.contains("20230311")
         {
-            println!("  (skipping json-20230311.jar)");
+            pt!("(skipping json-20230311.jar)");
             return Ok(());
         }
 
@@ -793,27 +911,35 @@ impl GameLauncher {
     }
 
     pub async fn get_java_command(&mut self) -> Result<(Command, PathBuf), GameLaunchError> {
+        let version = if let Some(version) = self.version_json.javaVersion.clone() {
+            version.into()
+        } else {
+            JavaVersion::JAVA_8
+        };
         if let Some(java_override) = &self.config_json.java_override {
             if !java_override.is_empty() {
-                return Ok((Command::new(java_override), PathBuf::from(java_override)));
+                let path = PathBuf::from(java_override);
+                check_java_override(&path, &version).await?;
+                return Ok((Command::new(java_override), path));
             }
         }
-        let version = if let Some(version) = self.version_json.javaVersion.clone() {
-            version.into()
+
+        let name = if cfg!(target_os = "windows") && self.config_json.enable_logger.unwrap_or(true)
+        {
+            "javaw"
         } else {
-            JavaVersion::Java8
+            "java"
+        };
+        let program = if self.dry_run {
+            get_java_binary_path(version, name).await
+        } else {
+            get_java_binary(
+                version,
+                name,
+                self.java_install_progress_sender.take().as_ref(),
+            )
+            .await?
         };
-
-        let program = get_java_binary(
-            version,
-            if cfg!(target_os = "windows") && self.config_json.enable_logger.unwrap_or(true) {
-                "javaw"
-            } else {
-                "java"
-            },
-            self.java_install_progress_sender.take().as_ref(),
-        )
-        .await?;
         info!("Java: {program:?}");
         Ok((Command::new(&program), program))
     }
@@ -837,6 +963,21 @@ impl GameLauncher {
         Ok(())
     }
 
+    /// Runs the pre-launch hook command, if any
+    /// (see [`GlobalSettings::pre_launch_hook`]).
+    pub async fn run_pre_launch_hook(&self) -> Result<(), GameLaunchError> {
+        let (Some(hook), _) = self.config_json.get_hooks(self.global_settings.as_ref()) else {
+            return Ok(());
+        };
+        let context = HookContext {
+            instance_name: &self.instance_name,
+            instance_dir: &self.instance_dir,
+            minecraft_dir: &self.minecraft_dir,
+            env_vars: &self.config_json.get_env_vars(self.global_settings.as_ref()),
+        };
+        run_hook(&hook, &context, None).await
+    }
+
     pub async fn get_command(
         &mut self,
         game_arguments: Vec<String>,
@@ -881,6 +1022,11 @@ impl GameLauncher {
         }
 
         command.current_dir(&self.minecraft_dir);
+        let env_vars = self.config_json.get_env_vars(self.global_settings.as_ref());
+        if !env_vars.is_empty() {
+            info!("Environment variables: {:?}", env_vars.keys());
+            command.envs(env_vars);
+        }
         if self.config_json.enable_logger.unwrap_or(true) {
             command.stdout(Stdio::piped()).stderr(Stdio::piped());
         }
@@ -909,6 +1055,39 @@ impl GameLauncher {
     }
 }
 
+/// Whether a version (by its release time) supports
+/// `--quickPlay*` arguments, added in 23w14a (1.20 snapshot).
+fn supports_quick_play(release_time: &str) -> bool {
+    // Minecraft 23w14a release date
+    const MC_23W14A: &str = "2023-04-05T00:00:00+00:00";
+
+    if let (Ok(dt), Ok(quick_play)) = (
+        chrono::DateTime::parse_from_rfc3339(release_time),
+        chrono::DateTime::parse_from_rfc3339(MC_23W14A),
+    ) {
+        dt >= quick_play
+    } else {
+        false
+    }
+}
+
+/// Splits `host:port`, defaulting to port 25565.
+///
+/// IPv6 addresses need brackets to have a port
+/// (`[::1]:25565`), the brackets are removed.
+fn split_server_address(address: &str) -> (&str, &str) {
+    if let Some((host, rest)) = address.strip_prefix('[').and_then(|n| n.split_once(']')) {
+        return match rest.strip_prefix(':') {
+            Some(port) if port.parse::<u16>().is_ok() => (host, port),
+            _ => (host, "25565"),
+        };
+    }
+    match address.rsplit_once(':') {
+        Some((host, port)) if !host.contains(':') && port.parse::<u16>().is_ok() => (host, port),
+        _ => (address, "25565"),
+    }
+}
+
 async fn get_instance_dir(instance_name: &str) -> Result<PathBuf, GameLaunchError> {
     if instance_name.is_empty() {
         return Err(GameLaunchError::InstanceNotFound);
@@ -931,6 +1110,25 @@ async fn get_instance_dir(instance_name: &str) -> Result<PathBuf, GameLaunchErro
     Ok(instance_dir)
 }
 
+/// Makes sure a custom Java executable actually runs.
+///
+/// A different major version than the one the game asks
+/// for is only warned about, as it often works anyway
+/// (eg: old versions on a newer Java).
+async fn check_java_override(path: &Path, required: &JavaVersion) -> Result<(), GameLaunchError> {
+    let Some(install) = ql_java_handler::probe_java(path).await else {
+        return Err(GameLaunchError::InvalidJavaOverride(path.to_owned()));
+    };
+    if !install.matches(required.major) {
+        err!(
+            "This instance needs Java {}, but the custom Java executable is Java {}",
+            required.major,
+            install.major
+        );
+    }
+    Ok(())
+}
+
 async fn delete_junk_file(forge_dir: &Path, path: &str) -> Result<(), GameLaunchError> {
     let path = forge_dir.join(path);
     if path.exists() {
@@ -1066,3 +1264,40 @@ fn deduplicate_game_args(arr1: &[String], arr2: &[String]) -> Vec<String> {
     // HashMap -> Vec<String> (key, value, key, value, ...)
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn server_addresses() {
+        assert_eq!(
+            split_server_address("example.com"),
+            ("example.com", "25565")
+        );
+        assert_eq!(
+            split_server_address("example.com:25570"),
+            ("example.com", "25570")
+        );
+        assert_eq!(
+            split_server_address("example.com:notaport"),
+            ("example.com:notaport", "25565")
+        );
+        assert_eq!(split_server_address("[::1]:25570"), ("::1", "25570"));
+        assert_eq!(split_server_address("[::1]"), ("::1", "25565"));
+        assert_eq!(split_server_address("::1"), ("::1", "25565"));
+        assert_eq!(
+            split_server_address("2001:db8::1:25570"),
+            ("2001:db8::1:25570", "25565")
+        );
+    }
+
+    #[test]
+    fn quick_play_support() {
+        assert!(supports_quick_play("2023-06-07T09:35:22+00:00")); // 1.20
+        assert!(supports_quick_play("2023-04-05T00:00:00+00:00"));
+        assert!(!supports_quick_play("2023-03-14T12:56:18+00:00")); // 1.19.4
+        assert!(!supports_quick_play("2011-11-17T22:00:00+00:00"));
+        assert!(!supports_quick_play("not a date"));
+    }
+}
@@ -4,11 +4,31 @@ use ql_core::{err, info, GenericProgress};
 use std::sync::{mpsc::Sender, Arc, Mutex};
 use tokio::process::Child;
 
+mod dry_run;
 pub(super) mod error;
+mod export;
+mod hooks;
 mod launcher;
+mod rules;
+pub use dry_run::LaunchCommand;
+use dry_run::ACCESS_TOKEN_PLACEHOLDER;
+pub use export::export_launch_script;
+pub use hooks::run_post_exit_hook;
 pub use launcher::GameLauncher;
 use ql_core::json::GlobalSettings;
 
+/// Joins a world, server or Realm directly after
+/// launching the game, skipping the title screen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QuickPlay {
+    /// The name of the world's folder in `saves/`.
+    Singleplayer(String),
+    /// A server address, optionally with a port (`host:port`).
+    Multiplayer(String),
+    /// The ID of a Realm.
+    Realms(String),
+}
+
 /// Launches a Minecraft instance.
 ///
 /// # Arguments
@@ -20,6 +40,7 @@ use ql_core::json::GlobalSettings;
 /// - `global_settings` - (Optional) Global launcher-level settings that apply to instance
 ///   like window width/height, etc.
 /// - `pre_launch_prefix` - Commands to prepend to the launch command (e.g., "prime-run")
+/// - `quick_play` - (Optional) A world, server or Realm to join directly. See [`QuickPlay`].
 pub async fn launch(
     instance_name: String,
     username: String,
@@ -27,7 +48,111 @@ pub async fn launch(
     auth: Option<AccountData>,
     global_settings: Option<GlobalSettings>,
     extra_java_args: Vec<String>,
+    quick_play: Option<QuickPlay>,
 ) -> Result<Arc<Mutex<Child>>, GameLaunchError> {
+    let (mut game_launcher, mut game_arguments, java_arguments) = prepare(
+        instance_name,
+        username,
+        java_install_progress_sender,
+        auth.as_ref(),
+        global_settings,
+        extra_java_args,
+        quick_play,
+        false,
+    )
+    .await?;
+
+    info!("Java args: {java_arguments:?}\n");
+
+    print_censored_args(auth.as_ref(), &game_arguments);
+
+    game_launcher.run_pre_launch_hook().await?;
+
+    let (mut command, path) = game_launcher
+        .get_command(std::mem::take(&mut game_arguments), java_arguments)
+        .await?;
+    let child = command
+        .spawn()
+        .map_err(|err| GameLaunchError::CommandError(err, path))?;
+    if let Some(id) = child.id() {
+        info!("Launched! PID: {id}");
+    } else {
+        err!("No ID found!");
+    }
+
+    if game_launcher.config_json.close_on_start.unwrap_or(false) {
+        ql_core::logger_finish();
+        std::process::exit(0);
+    }
+
+    Ok(Arc::new(Mutex::new(child)))
+}
+
+/// Runs the whole launch process, without actually
+/// starting the game (or running the pre-launch hook),
+/// and returns the command that would be run.
+///
+/// Nothing is changed on disk: Java isn't installed, missing
+/// libraries aren't downloaded, etc. The command shows where
+/// they would be. The account isn't refreshed either, so
+/// its access token is replaced with `${QL_ACCESS_TOKEN}`
+/// (as in [`export_launch_script`]).
+///
+/// Useful for debugging classpath/argument issues.
+/// Sensitive information (like the UUID)
+/// is redacted if [`crate::ARG_REDACT_SECTIONS`] is set.
+///
+/// Takes the same arguments as [`launch`]
+/// (other than the Java install progress sender).
+pub async fn launch_dry_run(
+    instance_name: String,
+    username: String,
+    auth: Option<AccountData>,
+    global_settings: Option<GlobalSettings>,
+    extra_java_args: Vec<String>,
+    quick_play: Option<QuickPlay>,
+) -> Result<LaunchCommand, GameLaunchError> {
+    let auth = auth.map(|n| AccountData {
+        access_token: Some(ACCESS_TOKEN_PLACEHOLDER.to_owned()),
+        ..n
+    });
+    let (mut game_launcher, game_arguments, java_arguments) = prepare(
+        instance_name,
+        username,
+        None,
+        auth.as_ref(),
+        global_settings,
+        extra_java_args,
+        quick_play,
+        true,
+    )
+    .await?;
+
+    let (command, _) = game_launcher
+        .get_command(game_arguments, java_arguments)
+        .await?;
+    let mut command = LaunchCommand::from_command(command.as_std());
+    if *ARG_REDACT_SECTIONS.lock().unwrap() {
+        redact_arguments(auth.as_ref(), &mut command.args);
+    }
+    Ok(command)
+}
+
+/// Resolves everything needed to launch the game,
+/// returning the game and Java arguments.
+///
+/// See [`GameLauncher::dry_run`] for `dry_run`.
+#[allow(clippy::too_many_arguments)]
+async fn prepare(
+    instance_name: String,
+    username: String,
+    java_install_progress_sender: Option<Sender<GenericProgress>>,
+    auth: Option<&AccountData>,
+    global_settings: Option<GlobalSettings>,
+    extra_java_args: Vec<String>,
+    quick_play: Option<QuickPlay>,
+    dry_run: bool,
+) -> Result<(GameLauncher, Vec<String>, Vec<String>), GameLaunchError> {
     if username.is_empty() {
         return Err(GameLaunchError::UsernameIsEmpty);
     }
@@ -41,14 +166,18 @@ pub async fn launch(
         java_install_progress_sender,
         global_settings,
         extra_java_args,
+        quick_play,
     )
     .await?;
+    game_launcher.dry_run = dry_run;
 
-    game_launcher.migrate_old_instances().await?;
-    game_launcher.create_mods_dir().await?;
+    if !dry_run {
+        game_launcher.migrate_old_instances().await?;
+        game_launcher.create_mods_dir().await?;
+    }
 
-    let mut game_arguments = game_launcher.init_game_arguments(auth.as_ref())?;
-    let mut java_arguments = game_launcher.init_java_arguments(auth.as_ref()).await?;
+    let mut game_arguments = game_launcher.init_game_arguments(auth)?;
+    let mut java_arguments = game_launcher.init_java_arguments(auth).await?;
 
     let fabric_json = game_launcher
         .setup_fabric(&mut java_arguments, &mut game_arguments)
@@ -61,7 +190,7 @@ pub async fn launch(
     game_launcher.fill_java_arguments(&mut java_arguments);
 
     game_launcher
-        .fill_game_arguments(&mut game_arguments, auth.as_ref())
+        .fill_game_arguments(&mut game_arguments, auth)
         .await?;
 
     game_launcher.setup_logging(&mut java_arguments)?;
@@ -84,88 +213,34 @@ pub async fn launch(
     );
     java_arguments.push(main_class);
 
-    info!("Java args: {java_arguments:?}\n");
-
-    print_censored_args(auth.as_ref(), &mut game_arguments);
-
-    let (mut command, path) = game_launcher
-        .get_command(game_arguments, java_arguments)
-        .await?;
-    let child = command
-        .spawn()
-        .map_err(|err| GameLaunchError::CommandError(err, path))?;
-    if let Some(id) = child.id() {
-        info!("Launched! PID: {id}");
-    } else {
-        err!("No ID found!");
-    }
-
-    if game_launcher.config_json.close_on_start.unwrap_or(false) {
-        ql_core::logger_finish();
-        std::process::exit(0);
-    }
-
-    Ok(Arc::new(Mutex::new(child)))
+    Ok((game_launcher, game_arguments, java_arguments))
 }
 
-fn print_censored_args(auth: Option<&AccountData>, game_arguments: &mut Vec<String>) {
-    let redact = *ARG_REDACT_SECTIONS.lock().unwrap();
-    if redact {
-        censor(game_arguments, "--clientId", |args| {
-            censor(args, "--session", |args| {
-                censor(args, "--accessToken", |args| {
-                    censor(args, "--uuid", |args| {
-                        censor_string(
-                            args,
-                            &auth
-                                .as_ref()
-                                .and_then(|n| n.access_token.clone())
-                                .unwrap_or_default(),
-                            |args| {
-                                info!("Game args: {args:?}\n");
-                            },
-                        );
-                    });
-                });
-            });
-        });
+fn print_censored_args(auth: Option<&AccountData>, game_arguments: &[String]) {
+    if *ARG_REDACT_SECTIONS.lock().unwrap() {
+        let mut game_arguments = game_arguments.to_owned();
+        redact_arguments(auth, &mut game_arguments);
+        info!("Game args: {game_arguments:?}\n");
     } else {
         info!("Game args: {game_arguments:?}\n");
     }
 }
 
-fn censor<F: FnOnce(&mut Vec<String>)>(vec: &mut Vec<String>, argument: &str, code: F) {
-    if let Some(index) = vec
-        .iter_mut()
-        .enumerate()
-        .find_map(|(i, n)| (n == argument).then_some(i))
-    {
-        let old_id = vec.get(index + 1).cloned();
-        if let Some(n) = vec.get_mut(index + 1) {
-            "[REDACTED]".clone_into(n);
-        }
-
-        code(vec);
+/// Replaces sensitive information (access token, UUID, etc)
+/// in the arguments with `[REDACTED]`.
+fn redact_arguments(auth: Option<&AccountData>, args: &mut [String]) {
+    const SENSITIVE_ARGS: &[&str] = &["--clientId", "--session", "--accessToken", "--uuid"];
 
-        if let (Some(n), Some(old_id)) = (vec.get_mut(index + 1), old_id) {
-            *n = old_id;
+    let token = auth.and_then(|n| n.access_token.as_deref());
+    let mut redact_next = false;
+    for arg in args {
+        if redact_next || token.is_some_and(|token| !token.is_empty() && arg.contains(token)) {
+            "[REDACTED]".clone_into(arg);
         }
-    } else {
-        code(vec);
+        redact_next = SENSITIVE_ARGS.contains(&arg.as_str());
     }
 }
 
-fn censor_string<F: FnOnce(&mut Vec<String>)>(vec: &[String], argument: &str, code: F) {
-    let mut new = vec.to_owned();
-    for s in &mut new {
-        if s == argument {
-            "[REDACTED]".clone_into(s);
-        }
-    }
-
-    code(&mut new);
-}
-
 fn replace_var(string: &mut String, var: &str, value: &str) {
     *string = string.replace(&format!("${{{var}}}"), value);
 }
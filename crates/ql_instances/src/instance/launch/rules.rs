@@ -0,0 +1,247 @@
+//! Evaluates the argument `rules` found in
+//! the `arguments` field of modern (1.13+) version JSONs.
+//!
+//! An argument may either be a plain string, or an object like:
+//!
+//! ```json
+//! {
+//!     "rules": [{ "action": "allow", "os": { "name": "osx" } }],
+//!     "value": ["-XstartOnFirstThread"]
+//! }
+//! ```
+//!
+//! Rules may check the OS name, architecture and version (regex),
+//! as well as launcher "features" (see [`ArgumentFeatures`]).
+
+use std::sync::OnceLock;
+
+use ql_core::no_window;
+use serde_json::Value;
+
+use crate::download::constants::OS_NAMES;
+
+/// Launcher features that version JSON
+/// arguments may be conditional on.
+#[derive(Debug, Clone, Default)]
+pub struct ArgumentFeatures {
+    pub is_demo_user: bool,
+    pub has_custom_resolution: bool,
+    pub has_quick_plays_support: bool,
+    pub is_quick_play_singleplayer: bool,
+    pub is_quick_play_multiplayer: bool,
+    pub is_quick_play_realms: bool,
+}
+
+impl ArgumentFeatures {
+    fn get(&self, name: &str) -> bool {
+        match name {
+            "is_demo_user" => self.is_demo_user,
+            "has_custom_resolution" => self.has_custom_resolution,
+            "has_quick_plays_support" => self.has_quick_plays_support,
+            "is_quick_play_singleplayer" => self.is_quick_play_singleplayer,
+            "is_quick_play_multiplayer" => self.is_quick_play_multiplayer,
+            "is_quick_play_realms" => self.is_quick_play_realms,
+            // Unknown features are treated as unsupported
+            _ => false,
+        }
+    }
+}
+
+/// Evaluates a list of version JSON arguments,
+/// returning the ones that apply to this system.
+pub fn evaluate_arguments(arguments: &[Value], features: &ArgumentFeatures) -> Vec<String> {
+    let mut out = Vec::new();
+    for argument in arguments {
+        match argument {
+            Value::String(n) => out.push(n.clone()),
+            Value::Object(obj) => {
+                let rules = obj.get("rules").and_then(Value::as_array);
+                if !rules.is_none_or(|rules| rules_allow(rules, features)) {
+                    continue;
+                }
+                match obj.get("value") {
+                    Some(Value::String(n)) => out.push(n.clone()),
+                    Some(Value::Array(values)) => {
+                        out.extend(values.iter().filter_map(Value::as_str).map(str::to_owned));
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Checks whether a list of rules allows something.
+///
+/// Like the official launcher, everything is disallowed
+/// by default, and the last matching rule wins.
+pub fn rules_allow(rules: &[Value], features: &ArgumentFeatures) -> bool {
+    let mut allowed = false;
+    for rule in rules {
+        if rule_matches(rule, features) {
+            allowed = rule.get("action").and_then(Value::as_str) == Some("allow");
+        }
+    }
+    allowed
+}
+
+fn rule_matches(rule: &Value, features: &ArgumentFeatures) -> bool {
+    if let Some(os) = rule.get("os") {
+        if let Some(name) = os.get("name").and_then(Value::as_str) {
+            if !OS_NAMES.contains(&name) {
+                return false;
+            }
+        }
+        if let Some(arch) = os.get("arch").and_then(Value::as_str) {
+            if arch != OS_ARCH {
+                return false;
+            }
+        }
+        if let Some(version) = os.get("version").and_then(Value::as_str) {
+            let Ok(regex) = regex::Regex::new(version) else {
+                return false;
+            };
+            let os_version = OS_VERSION.get().and_then(Option::as_deref);
+            if !os_version.is_some_and(|n| regex.is_match(n)) {
+                return false;
+            }
+        }
+    }
+
+    if let Some(required) = rule.get("features").and_then(Value::as_object) {
+        if !required
+            .iter()
+            .all(|(name, value)| value.as_bool() == Some(features.get(name)))
+        {
+            return false;
+        }
+    }
+    true
+}
+
+/// The architecture, as Java's `os.arch` would report it.
+const OS_ARCH: &str = if cfg!(target_arch = "x86") {
+    "x86"
+} else if cfg!(target_arch = "x86_64") {
+    "amd64"
+} else if cfg!(target_arch = "aarch64") {
+    "aarch64"
+} else if cfg!(target_arch = "arm") {
+    "arm"
+} else {
+    "unknown"
+};
+
+/// The OS version, as Java's `os.version` would report it
+/// (`10.0` on Windows 10/11, kernel release on Linux,
+/// product version on macOS).
+///
+/// Filled in by [`load_os_version`] before launching.
+static OS_VERSION: OnceLock<Option<String>> = OnceLock::new();
+
+/// Finds out the OS version (once) for
+/// rules that check it.
+pub async fn load_os_version() {
+    if OS_VERSION.get().is_none() {
+        _ = OS_VERSION.set(get_os_version().await);
+    }
+}
+
+async fn get_os_version() -> Option<String> {
+    if cfg!(target_os = "windows") {
+        // Output looks like: "Microsoft Windows [Version 10.0.19045.4529]"
+        let mut command = tokio::process::Command::new("cmd");
+        command.args(["/C", "ver"]);
+        no_window!(command);
+        let output = command.output().await.ok()?;
+        let output = String::from_utf8_lossy(&output.stdout);
+        let version = output.split("Version ").nth(1)?;
+        let mut parts = version.trim_end_matches([']', '\r', '\n']).split('.');
+        Some(format!("{}.{}", parts.next()?, parts.next()?))
+    } else if cfg!(target_os = "macos") {
+        let output = tokio::process::Command::new("sw_vers")
+            .arg("-productVersion")
+            .output()
+            .await
+            .ok()?;
+        Some(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+    } else {
+        tokio::fs::read_to_string("/proc/sys/kernel/osrelease")
+            .await
+            .ok()
+            .map(|n| n.trim().to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn plain_and_feature_arguments() {
+        let args = json!([
+            "--username",
+            "${auth_player_name}",
+            {
+                "rules": [{ "action": "allow", "features": { "is_demo_user": true } }],
+                "value": "--demo"
+            },
+            {
+                "rules": [{ "action": "allow", "features": { "has_custom_resolution": true } }],
+                "value": ["--width", "${resolution_width}"]
+            }
+        ]);
+        let args = args.as_array().unwrap();
+
+        let features = ArgumentFeatures::default();
+        assert_eq!(
+            evaluate_arguments(args, &features),
+            ["--username", "${auth_player_name}"]
+        );
+
+        let features = ArgumentFeatures {
+            has_custom_resolution: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            evaluate_arguments(args, &features),
+            [
+                "--username",
+                "${auth_player_name}",
+                "--width",
+                "${resolution_width}"
+            ]
+        );
+    }
+
+    #[test]
+    fn os_rules() {
+        let this_os = OS_NAMES[0];
+        let other_os = if this_os == "windows" {
+            "linux"
+        } else {
+            "windows"
+        };
+
+        let args = json!([
+            { "rules": [{ "action": "allow", "os": { "name": this_os } }], "value": "-Dthis" },
+            { "rules": [{ "action": "allow", "os": { "name": other_os } }], "value": "-Dother" },
+            {
+                "rules": [
+                    { "action": "allow" },
+                    { "action": "disallow", "os": { "name": this_os } }
+                ],
+                "value": "-Dnot_this"
+            },
+            { "rules": [{ "action": "allow", "os": { "arch": OS_ARCH } }], "value": "-Darch" }
+        ]);
+        assert_eq!(
+            evaluate_arguments(args.as_array().unwrap(), &ArgumentFeatures::default()),
+            ["-Dthis", "-Darch"]
+        );
+    }
+}
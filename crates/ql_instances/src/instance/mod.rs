@@ -1,4 +1,8 @@
+pub mod clone;
 pub mod launch;
 pub mod list_versions;
 mod migrate;
+pub mod monitor;
+pub mod play_history;
 pub mod read_log;
+pub mod rename;
@@ -0,0 +1,254 @@
+//! Samples the resource usage (CPU, memory, threads)
+//! of a running game process, for the whole session.
+//!
+//! Only supported on Linux (reads `/proc/<pid>/`),
+//! no samples are collected on other platforms.
+
+use std::{
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+/// Clock ticks per second of the CPU times in `/proc/<pid>/stat`.
+/// Always 100 on Linux, regardless of the kernel's real tick rate.
+const USER_HZ: f32 = 100.0;
+/// Fraction of the max memory (`-Xmx`) above which a warning is shown.
+const MEMORY_WARNING_RATIO: f32 = 0.9;
+
+/// A single measurement of the game's resource usage.
+#[derive(Debug, Clone, Copy)]
+pub struct ProcessSample {
+    /// Time since the monitor was started
+    pub time: Duration,
+    /// Can be above 100% if multiple cores are used
+    pub cpu_percent: f32,
+    /// Resident memory (RSS) in bytes
+    pub memory_bytes: u64,
+    pub threads: usize,
+}
+
+/// Keeps track of the resource usage of a game process.
+///
+/// Call [`ProcessMonitor::tick`] regularly
+/// (it only samples once every second).
+#[derive(Debug)]
+pub struct ProcessMonitor {
+    pid: Option<u32>,
+    max_memory_mb: Option<usize>,
+    started: Instant,
+    last_sampled: Option<Instant>,
+    /// Total CPU time (in clock ticks) at the last sample
+    last_cpu_ticks: Option<u64>,
+    samples: Vec<ProcessSample>,
+    is_memory_high: bool,
+}
+
+impl ProcessMonitor {
+    /// `max_memory_mb` is the memory given to the game
+    /// (the `ram_in_mb` of its config).
+    #[must_use]
+    pub fn new(pid: Option<u32>, max_memory_mb: Option<usize>) -> Self {
+        Self {
+            pid,
+            max_memory_mb,
+            started: Instant::now(),
+            last_sampled: None,
+            last_cpu_ticks: None,
+            samples: Vec::new(),
+            is_memory_high: false,
+        }
+    }
+
+    /// Takes a new sample if enough time has passed since the last one.
+    ///
+    /// Returns `true` if the memory usage has just gone above
+    /// 90% of the max memory (so the user can be warned once,
+    /// instead of every second).
+    pub fn tick(&mut self) -> bool {
+        let now = Instant::now();
+        if self
+            .last_sampled
+            .is_some_and(|n| now.duration_since(n) < SAMPLE_INTERVAL)
+        {
+            return false;
+        }
+        let Some((cpu_ticks, memory_bytes, threads)) = self.pid.and_then(read_proc) else {
+            return false;
+        };
+
+        let cpu_percent = match (self.last_cpu_ticks, self.last_sampled) {
+            (Some(last_ticks), Some(last_sampled)) => {
+                let elapsed = now.duration_since(last_sampled).as_secs_f32();
+                #[allow(clippy::cast_precision_loss)]
+                let used = cpu_ticks.saturating_sub(last_ticks) as f32 / USER_HZ;
+                used / elapsed * 100.0
+            }
+            _ => 0.0,
+        };
+        self.last_cpu_ticks = Some(cpu_ticks);
+        self.last_sampled = Some(now);
+
+        self.samples.push(ProcessSample {
+            time: now.duration_since(self.started),
+            cpu_percent,
+            memory_bytes,
+            threads,
+        });
+
+        let is_memory_high = self.max_memory_mb.is_some_and(|max| {
+            #[allow(clippy::cast_precision_loss)]
+            let ratio = memory_bytes as f32 / (max as f32 * 1024.0 * 1024.0);
+            ratio >= MEMORY_WARNING_RATIO
+        });
+        let should_warn = is_memory_high && !self.is_memory_high;
+        self.is_memory_high = is_memory_high;
+        should_warn
+    }
+
+    #[must_use]
+    pub fn samples(&self) -> &[ProcessSample] {
+        &self.samples
+    }
+
+    #[must_use]
+    pub fn latest(&self) -> Option<&ProcessSample> {
+        self.samples.last()
+    }
+
+    #[must_use]
+    pub fn max_memory_mb(&self) -> Option<usize> {
+        self.max_memory_mb
+    }
+
+    /// Whether memory usage is currently above
+    /// 90% of the max memory.
+    #[must_use]
+    pub fn is_memory_high(&self) -> bool {
+        self.is_memory_high
+    }
+
+    /// Summarizes the session, or `None` if
+    /// no samples were collected.
+    #[must_use]
+    pub fn summary(&self) -> Option<MonitorSummary> {
+        let last = self.samples.last()?;
+        #[allow(clippy::cast_precision_loss)]
+        let average_cpu_percent =
+            self.samples.iter().map(|n| n.cpu_percent).sum::<f32>() / self.samples.len() as f32;
+        Some(MonitorSummary {
+            duration: last.time,
+            peak_memory_bytes: self.samples.iter().map(|n| n.memory_bytes).max()?,
+            max_memory_mb: self.max_memory_mb,
+            average_cpu_percent,
+            peak_threads: self.samples.iter().map(|n| n.threads).max()?,
+        })
+    }
+}
+
+/// Resource usage over a whole game session.
+#[derive(Debug, Clone, Copy)]
+pub struct MonitorSummary {
+    pub duration: Duration,
+    pub peak_memory_bytes: u64,
+    pub max_memory_mb: Option<usize>,
+    pub average_cpu_percent: f32,
+    pub peak_threads: usize,
+}
+
+impl Display for MonitorSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let secs = self.duration.as_secs();
+        write!(
+            f,
+            "Session: {}h {}m {}s, peak memory: {} MB",
+            secs / 3600,
+            (secs / 60) % 60,
+            secs % 60,
+            self.peak_memory_bytes / (1024 * 1024)
+        )?;
+        if let Some(max) = self.max_memory_mb {
+            write!(f, " (of {max} MB)")?;
+        }
+        write!(
+            f,
+            ", average CPU: {:.1}%, peak threads: {}",
+            self.average_cpu_percent, self.peak_threads
+        )
+    }
+}
+
+/// Returns the total CPU time (in clock ticks),
+/// resident memory (in bytes) and thread count of a process.
+#[cfg(target_os = "linux")]
+fn read_proc(pid: u32) -> Option<(u64, u64, usize)> {
+    let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    let status = std::fs::read_to_string(format!("/proc/{pid}/status")).ok()?;
+    let cpu_ticks = parse_stat(&stat)?;
+    let (memory_bytes, threads) = parse_status(&status)?;
+    Some((cpu_ticks, memory_bytes, threads))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_proc(_pid: u32) -> Option<(u64, u64, usize)> {
+    None
+}
+
+/// Reads `utime + stime` from `/proc/<pid>/stat`.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_stat(stat: &str) -> Option<u64> {
+    // The process name (2nd field) is in brackets and may
+    // contain spaces, so only look at what comes after it.
+    let (_, fields) = stat.rsplit_once(')')?;
+    let mut fields = fields.split_whitespace();
+    // Fields 14 and 15 (counting from 1), after skipping the first two
+    let utime: u64 = fields.nth(11)?.parse().ok()?;
+    let stime: u64 = fields.next()?.parse().ok()?;
+    Some(utime + stime)
+}
+
+/// Reads `VmRSS` (in bytes) and `Threads` from `/proc/<pid>/status`.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_status(status: &str) -> Option<(u64, usize)> {
+    let get = |key: &str| {
+        status.lines().find_map(|line| {
+            let value = line.strip_prefix(key)?.strip_prefix(':')?;
+            value.split_whitespace().next()?.parse::<u64>().ok()
+        })
+    };
+    let rss_kb = get("VmRSS")?;
+    let threads = get("Threads")?;
+    Some((rss_kb * 1024, usize::try_from(threads).ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_proc_files() {
+        let stat = "1234 (java (main)) S 1 1234 1234 0 -1 4194560 50000 0 10 0 \
+                    700 300 0 0 20 0 42 0 123456 5000000000 250000";
+        assert_eq!(parse_stat(stat), Some(1000));
+        assert_eq!(parse_stat("garbage"), None);
+
+        let status = "Name:\tjava\nVmPeak:\t 9000000 kB\nVmRSS:\t  2048 kB\nThreads:\t42\n";
+        assert_eq!(parse_status(status), Some((2048 * 1024, 42)));
+        assert_eq!(parse_status("Name:\tjava\n"), None);
+    }
+
+    #[test]
+    fn summary_format() {
+        let summary = MonitorSummary {
+            duration: Duration::from_secs(3725),
+            peak_memory_bytes: 1536 * 1024 * 1024,
+            max_memory_mb: Some(2048),
+            average_cpu_percent: 85.34,
+            peak_threads: 56,
+        };
+        assert_eq!(
+            summary.to_string(),
+            "Session: 1h 2m 5s, peak memory: 1536 MB (of 2048 MB), average CPU: 85.3%, peak threads: 56"
+        );
+    }
+}
@@ -0,0 +1,48 @@
+use ql_core::{
+    err,
+    json::{InstanceConfigJson, InstanceHistory, PlaySession, VersionDetails},
+    InstanceSelection, JsonFileError,
+};
+
+use crate::auth::AccountType;
+
+/// Records a finished play session in the instance's
+/// history (see [`InstanceHistory`]).
+///
+/// - `start`: Unix timestamp (seconds) of when the game was launched
+/// - `exit_code`: `None` if the game was killed by a signal
+/// - `account_type`: `None` for offline mode
+///
+/// # Errors
+/// If the instance's config couldn't be read,
+/// or the history couldn't be saved.
+pub async fn record_play_session(
+    instance_name: String,
+    start: i64,
+    exit_code: Option<i32>,
+    account_type: Option<AccountType>,
+) -> Result<(), JsonFileError> {
+    let instance = InstanceSelection::Instance(instance_name);
+    let config = InstanceConfigJson::read(&instance).await?;
+    let version = match VersionDetails::load(&instance).await {
+        Ok(n) => n.get_id().to_owned(),
+        Err(error) => {
+            err!("Couldn't read version of instance: {error}");
+            String::new()
+        }
+    };
+
+    let session = PlaySession {
+        start,
+        end: chrono::Utc::now().timestamp(),
+        exit_code,
+        account_type: account_type.map_or_else(|| "Offline".to_owned(), |n| n.to_string()),
+        version,
+        loader: config.mod_type,
+    };
+    let result = InstanceHistory::add_session(&instance, session).await;
+    if let Err(error) = &result {
+        err!("Couldn't save play history: {error}");
+    }
+    result
+}
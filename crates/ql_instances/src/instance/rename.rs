@@ -0,0 +1,289 @@
+use std::path::Path;
+
+use ql_core::{
+    err, info,
+    json::{InstanceConfigJson, InstanceMetaFile},
+    pt, InstanceSelection, IntoIoError, IoError, JsonError, JsonFileError,
+};
+use thiserror::Error;
+
+const RENAME_ERR_PREFIX: &str = "while renaming instance:\n";
+
+#[derive(Debug, Error)]
+pub enum RenameError {
+    #[error("{RENAME_ERR_PREFIX}{0}")]
+    Io(#[from] IoError),
+    #[error("{RENAME_ERR_PREFIX}{0}")]
+    Json(#[from] JsonError),
+    #[error("an instance with that name already exists: {0}")]
+    AlreadyExists(String),
+    #[error("{RENAME_ERR_PREFIX}instance not found: {0}")]
+    NotFound(String),
+    #[error("invalid instance name {0:?}: {1}")]
+    InvalidName(String, &'static str),
+}
+
+impl From<JsonFileError> for RenameError {
+    fn from(value: JsonFileError) -> Self {
+        match value {
+            JsonFileError::SerdeError(err) => Self::Json(err),
+            JsonFileError::Io(err) => Self::Io(err),
+        }
+    }
+}
+
+/// Names that Windows doesn't allow for files,
+/// even with an extension (`CON.txt`).
+const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Characters not allowed in file names on at least one OS
+/// (`'` is allowed everywhere, but breaks some launch scripts).
+const DISALLOWED_CHARS: &[char] = &['/', '\\', ':', '*', '?', '"', '<', '>', '|', '\'', '\u{7F}'];
+
+/// Longer names start running into path
+/// length limits on Windows.
+const MAX_NAME_LEN: usize = 64;
+
+/// Checks that `name` can be used as an instance
+/// (directory) name on every OS, so instances can
+/// be moved between computers.
+///
+/// # Errors
+/// The reason the name isn't allowed.
+pub fn validate_name(name: &str) -> Result<(), &'static str> {
+    if name.trim().is_empty() {
+        return Err("name is empty");
+    }
+    if name.chars().count() > MAX_NAME_LEN {
+        return Err("name is too long (max 64 characters)");
+    }
+    if name.starts_with(' ') || name.ends_with(' ') || name.ends_with('.') {
+        return Err("name can't start with a space or end with a space or dot");
+    }
+    if name
+        .chars()
+        .any(|c| c.is_control() || DISALLOWED_CHARS.contains(&c))
+    {
+        return Err(r#"name can't contain any of / \ : * ? " < > | ' or control characters"#);
+    }
+    let stem = name.split('.').next().unwrap_or(name).trim_end();
+    if RESERVED_NAMES.iter().any(|n| n.eq_ignore_ascii_case(stem)) {
+        return Err("name is reserved by Windows");
+    }
+    Ok(())
+}
+
+/// Renames an instance (or server), moving its directory
+/// and updating everything that refers to it by name or path:
+/// - absolute paths in its `config.json` (arguments, hooks, etc.)
+/// - its group, tags and favourite status
+///
+/// If any step fails, the earlier ones are undone.
+///
+/// The instance must not be running while renamed.
+///
+/// # Errors
+/// - `new_name` is invalid (see [`validate_name`]) or
+///   already used (case-insensitively, as some OSes ignore case)
+/// - The instance couldn't be moved or its files updated
+pub async fn rename_instance(
+    instance: &InstanceSelection,
+    new_name: &str,
+) -> Result<InstanceSelection, RenameError> {
+    let new_name = new_name.trim();
+    validate_name(new_name).map_err(|n| RenameError::InvalidName(new_name.to_owned(), n))?;
+
+    let new_instance = InstanceSelection::new(new_name, instance.is_server());
+    let src = instance.get_instance_path();
+    let dst = new_instance.get_instance_path();
+    if !src.is_dir() {
+        return Err(RenameError::NotFound(instance.get_name().to_owned()));
+    }
+    if instance.get_name() == new_name {
+        return Ok(new_instance);
+    }
+    if is_name_taken(instance, new_name).await? {
+        return Err(RenameError::AlreadyExists(new_name.to_owned()));
+    }
+
+    info!("Renaming {} to {new_name}", instance.get_name());
+    tokio::fs::rename(&src, &dst).await.path(&src)?;
+
+    if let Err(err) = update_references(instance, &new_instance).await {
+        pt!("Failed, undoing rename");
+        if let Err(err) = rewrite_config_paths(&dst, &dst, &src).await {
+            err!("Couldn't restore config.json: {err}");
+        }
+        if let Err(err) = tokio::fs::rename(&dst, &src).await {
+            err!("Couldn't move instance back: {err}");
+        }
+        return Err(err);
+    }
+
+    pt!("Done!");
+    Ok(new_instance)
+}
+
+async fn update_references(
+    instance: &InstanceSelection,
+    new_instance: &InstanceSelection,
+) -> Result<(), RenameError> {
+    let (src, dst) = (
+        instance.get_instance_path(),
+        new_instance.get_instance_path(),
+    );
+    rewrite_config_paths(&dst, &src, &dst).await?;
+
+    let mut meta = InstanceMetaFile::load().await?;
+    if meta.rename(instance, new_instance.get_name()) {
+        meta.save().await?;
+    }
+    Ok(())
+}
+
+/// Whether another instance (or server) is already called `name`,
+/// ignoring case. The instance itself doesn't count, so its
+/// name's capitalization can be changed.
+async fn is_name_taken(instance: &InstanceSelection, name: &str) -> Result<bool, IoError> {
+    let Some(dir) = instance.get_instance_path().parent().map(Path::to_owned) else {
+        return Ok(false);
+    };
+    let mut entries = tokio::fs::read_dir(&dir).await.dir(&dir)?;
+    while let Some(entry) = entries.next_entry().await.dir(&dir)? {
+        let entry_name = entry.file_name();
+        let entry_name = entry_name.to_string_lossy();
+        if entry_name != instance.get_name() && entry_name.to_lowercase() == name.to_lowercase() {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Replaces the path `old` with `new` in the
+/// path-holding fields of the `config.json` in `instance_dir`.
+pub(super) async fn rewrite_config_paths(
+    instance_dir: &Path,
+    old: &Path,
+    new: &Path,
+) -> Result<(), JsonFileError> {
+    let mut config = InstanceConfigJson::read_from_dir(instance_dir).await?;
+    replace_in_config(&mut config, &old.to_string_lossy(), &new.to_string_lossy());
+    config.save_to_dir(instance_dir).await
+}
+
+fn replace_in_config(config: &mut InstanceConfigJson, old: &str, new: &str) {
+    let replace = |s: &mut String| {
+        if s.contains(old) {
+            *s = replace_path(s, old, new);
+        }
+    };
+
+    config.java_override.iter_mut().for_each(replace);
+    config.java_args.iter_mut().flatten().for_each(replace);
+    config.game_args.iter_mut().flatten().for_each(replace);
+    if let Some(settings) = &mut config.global_settings {
+        settings
+            .pre_launch_prefix
+            .iter_mut()
+            .flatten()
+            .for_each(replace);
+        settings.env_vars.iter_mut().flatten().for_each(replace);
+        settings.pre_launch_hook.iter_mut().for_each(replace);
+        settings.post_exit_hook.iter_mut().for_each(replace);
+    }
+}
+
+/// Replaces `old` with `new` where it's a whole path
+/// (or the start of one), so `/instances/Main` doesn't
+/// also match the start of `/instances/Main2`.
+fn replace_path(s: &str, old: &str, new: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(i) = rest.find(old) {
+        let after = &rest[i + old.len()..];
+        out.push_str(&rest[..i]);
+        if after.is_empty() || after.starts_with(['/', '\\']) {
+            out.push_str(new);
+        } else {
+            out.push_str(old);
+        }
+        rest = after;
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names() {
+        assert!(validate_name("My Pack 1.20.1").is_ok());
+        assert!(validate_name("Création (test)").is_ok());
+        assert!(validate_name("").is_err());
+        assert!(validate_name("   ").is_err());
+        assert!(validate_name(" leading").is_err());
+        assert!(validate_name("trailing.").is_err());
+        assert!(validate_name("a/b").is_err());
+        assert!(validate_name("what?").is_err());
+        assert!(validate_name("tab\there").is_err());
+        assert!(validate_name("con").is_err());
+        assert!(validate_name("LPT1.txt").is_err());
+        assert!(validate_name("Console").is_ok());
+        assert!(validate_name(&"a".repeat(65)).is_err());
+    }
+
+    fn config() -> InstanceConfigJson {
+        serde_json::from_value(serde_json::json!({
+            "mod_type": "Vanilla",
+            "ram_in_mb": 2048,
+            "java_args": ["-Dfoo=/ql/instances/Main/.minecraft/foo.txt", "-Xss2M"],
+            "global_settings": {
+                "pre_launch_hook": "/ql/instances/Main/start.sh",
+                "post_exit_hook": "cp /ql/instances/Main2/a /ql/instances/Main",
+            },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn replace_paths() {
+        let mut config = config();
+        replace_in_config(&mut config, "/ql/instances/Main", "/ql/instances/Test");
+        assert_eq!(
+            config.java_args.unwrap(),
+            ["-Dfoo=/ql/instances/Test/.minecraft/foo.txt", "-Xss2M"]
+        );
+        let settings = config.global_settings.unwrap();
+        assert_eq!(
+            settings.pre_launch_hook.unwrap(),
+            "/ql/instances/Test/start.sh"
+        );
+        assert_eq!(
+            settings.post_exit_hook.unwrap(),
+            "cp /ql/instances/Main2/a /ql/instances/Test"
+        );
+        assert_eq!(config.ram_in_mb, 2048);
+    }
+
+    #[tokio::test]
+    async fn rewrite_keeps_layout() {
+        let dir = tempfile::tempdir().unwrap();
+        config().save_to_dir(dir.path()).await.unwrap();
+        let before = std::fs::read_to_string(dir.path().join("config.json")).unwrap();
+
+        let (old, new) = (
+            Path::new("/ql/instances/Main"),
+            Path::new("/ql/instances/Test"),
+        );
+        rewrite_config_paths(dir.path(), old, new).await.unwrap();
+        rewrite_config_paths(dir.path(), new, old).await.unwrap();
+
+        let after = std::fs::read_to_string(dir.path().join("config.json")).unwrap();
+        assert_eq!(before, after);
+    }
+}
@@ -59,14 +59,24 @@ mod launcher_update_detector;
 use std::sync::{LazyLock, Mutex};
 
 pub use download::{constants::OS_NAME, create_instance, DownloadError};
-pub use instance::launch::launch;
+pub use instance::clone::{clone_instance, CloneError, CloneOptions};
+pub use instance::launch::{
+    export_launch_script, launch, launch_dry_run, run_post_exit_hook, LaunchCommand, QuickPlay,
+};
 pub use instance::list_versions::list_versions;
+pub use instance::monitor::{MonitorSummary, ProcessMonitor, ProcessSample};
+pub use instance::play_history::record_play_session;
 pub use instance::read_log::{read_logs, LogEvent, LogLine, ReadError};
+pub use instance::rename::{rename_instance, validate_name, RenameError};
 pub use launcher_update_detector::{
     check_for_launcher_updates, install_launcher_update, UpdateCheckInfo, UpdateError,
 };
 pub use ql_core::jarmod;
-pub use ql_java_handler::delete_java_installs;
+pub use ql_java_handler::{
+    delete_java_install, delete_java_installs, find_java_installs, list_java_installs, probe_java,
+    repair_java_install, update_java_install, verify_java_install, JavaInstall, JavaSource,
+    ManagedJava,
+};
 
 use semver::{BuildMetadata, Prerelease};
 
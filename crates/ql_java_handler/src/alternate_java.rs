@@ -10,27 +10,31 @@
 //!   <https://github.com/Mrmayman/get-jdk>
 //! - 🟢²: Uses later version of Java (with backwards compatibility)
 //!
-//! | Platforms   | 8  | 16 | 17 | 21 |
-//! |-------------|----|----|----|----|
-//! | Linux   x86_64  | ✅ | ✅ | ✅ | ✅ |
-//! | Linux   i686¹   | ✅ |    |    |   |
-//! | Linux   aarch64 | 🟢 | 🟢 | 🟢 | 🟢 |
-//! | Linux   arm32¹  | 🟢³|    |    |    |
-//! | Linux   sparc64 |    |    |    |    |
-//! | | | | |
-//! | FreeBSD x86_64¹ | 🟢³|    |    |    |
-//! | FreeBSD aarch64 |    |    |    |    |
-//! | FreeBSD i686    |    |    |    |    |
-//! | | | | |
-//! | Solaris x86_64¹ | 🟢³|    |    |    |
-//! | Solaris sparc64¹| 🟢³|    |    |    |
-//! | | | | |
-//! | macOS   x86_64  | 🟢 | ✅  | ✅ | ✅ |
-//! | macOS   aarch64 | 🟢 | 🟢  | ✅ | ✅ |
-//! | | | | |
-//! | Windows x86_64  | 🟢 | ✅ | ✅ | ✅  |
-//! | Windows i686    | 🟢 | ✅ | ✅ | 🟢³|
-//! | Windows aarch64²| 🟢²|🟢²| ✅ | ✅ |
+//! | Platforms   | 8  | 16 | 17 | 21 | 25 |
+//! |-------------|----|----|----|----|----|
+//! | Linux   x86_64  | ✅ | ✅ | ✅ | ✅ | 🟢 |
+//! | Linux   i686¹   | ✅ |    |    |    |    |
+//! | Linux   aarch64 | 🟢 | 🟢 | 🟢 | 🟢 | 🟢 |
+//! | Linux   arm32¹  | 🟢³|    |    |    |    |
+//! | Linux   sparc64 |    |    |    |    |    |
+//! | | | | | |
+//! | FreeBSD x86_64¹ | 🟢³|    |    |    |    |
+//! | FreeBSD aarch64 |    |    |    |    |    |
+//! | FreeBSD i686    |    |    |    |    |    |
+//! | | | | | |
+//! | Solaris x86_64¹ | 🟢³|    |    |    |    |
+//! | Solaris sparc64¹| 🟢³|    |    |    |    |
+//! | | | | | |
+//! | macOS   x86_64  | 🟢 | ✅  | ✅ | ✅ | 🟢 |
+//! | macOS   aarch64 | 🟢 | 🟢  | ✅ | ✅ | 🟢 |
+//! | | | | | |
+//! | Windows x86_64  | 🟢 | ✅ | ✅ | ✅  | 🟢 |
+//! | Windows i686    | 🟢 | ✅ | ✅ | 🟢³|    |
+//! | Windows aarch64²| 🟢²|🟢²| ✅ | ✅ | 🟢 |
+//!
+//! Java versions not listed here are installed from Mojang if available
+//! (see [`crate::JavaVersion`]), or otherwise from the closest
+//! newer LTS release of Amazon Corretto (eg: 22 -> 25).
 //!
 //! ¹ Only Java 8 is supported on these platforms,
 //!   you can only play Minecraft 1.16.5 and below.
@@ -62,11 +66,12 @@ use ql_core::{file_utils, GenericProgress};
 
 use crate::{extract_tar_gz, send_progress, JavaInstallError, JavaVersion};
 
+/// Installs Java from an archive, returning its URL.
 pub(crate) async fn install(
-    version: JavaVersion,
+    version: &JavaVersion,
     java_install_progress_sender: Option<&Sender<GenericProgress>>,
     install_dir: &Path,
-) -> Result<(), JavaInstallError> {
+) -> Result<String, JavaInstallError> {
     let url = version.get_alternate_url();
 
     let Some(url) = url else {
@@ -82,7 +87,7 @@ pub(crate) async fn install(
             has_finished: false,
         },
     );
-    let file_bytes = file_utils::download_file_to_bytes(url, false).await?;
+    let file_bytes = file_utils::download_file_to_bytes(&url, false).await?;
     send_progress(
         java_install_progress_sender,
         GenericProgress {
@@ -97,26 +102,41 @@ pub(crate) async fn install(
     } else if url.ends_with("zip") {
         file_utils::extract_zip_archive(Cursor::new(&file_bytes), install_dir, true)?;
     } else {
-        return Err(JavaInstallError::UnknownExtension(url.to_owned()));
+        return Err(JavaInstallError::UnknownExtension(url));
     }
-    Ok(())
+    Ok(url)
 }
 
-fn error_unsupported(version: JavaVersion) -> JavaInstallError {
-    if let JavaVersion::Java16 | JavaVersion::Java17 | JavaVersion::Java21 = version {
-        if JavaVersion::Java8.get_alternate_url().is_some() {
-            JavaInstallError::UnsupportedOnlyJava8
-        } else {
-            JavaInstallError::UnsupportedPlatform
-        }
+fn error_unsupported(version: &JavaVersion) -> JavaInstallError {
+    let java8 = JavaVersion::JAVA_8.get_alternate_url().is_some();
+    let java17 = JavaVersion::JAVA_17.get_alternate_url().is_some();
+
+    if version.major > 8 && java8 && !java17 {
+        JavaInstallError::UnsupportedOnlyJava8
+    } else if java8 || java17 {
+        JavaInstallError::UnsupportedVersion(version.major)
     } else {
         JavaInstallError::UnsupportedPlatform
     }
 }
 
+/// Amazon Corretto only provides LTS releases, so this picks
+/// the closest one that's at least `major` (but not above `max`).
+/// Newer Java is mostly backwards compatible.
+fn corretto_url(major: usize, platform: &str, extension: &str, max: usize) -> Option<String> {
+    const CORRETTO_VERSIONS: &[usize] = &[8, 17, 21, 25];
+
+    let version = CORRETTO_VERSIONS
+        .iter()
+        .find(|n| **n >= major && **n <= max)?;
+    Some(format!(
+        "https://corretto.aws/downloads/latest/amazon-corretto-{version}-{platform}-jdk.{extension}"
+    ))
+}
+
 impl JavaVersion {
     #[must_use]
-    pub(crate) fn get_alternate_url(self) -> Option<&'static str> {
+    pub(crate) fn get_alternate_url(&self) -> Option<String> {
         // Sources:
         // https://aws.amazon.com/corretto/
         // https://github.com/Mrmayman/get-jdk/
@@ -139,24 +159,18 @@ impl JavaVersion {
             //
             // For licensing/source code, consult the other files here,
             // and FreeBSD's repositories too, as this was taken from there.
-            if let JavaVersion::Java8 = self {
-                if cfg!(target_arch = "x86_64") {
-                    Some("https://github.com/Mrmayman/get-jdk/releases/download/java8-1/jdk-8u452-freebsd-x64.tar.gz")
-                } else {
-                    None
-                }
+            if self.major == 8 && cfg!(target_arch = "x86_64") {
+                Some("https://github.com/Mrmayman/get-jdk/releases/download/java8-1/jdk-8u452-freebsd-x64.tar.gz".to_owned())
             } else {
                 None
             }
         } else if cfg!(target_os = "solaris") {
-            if let JavaVersion::Java8 = self {
-                if cfg!(target_arch = "x86_64") {
-                    Some("https://github.com/Mrmayman/get-jdk/releases/download/java8-1/jdk-8u231-solaris-x64.tar.gz")
-                } else if cfg!(target_arch = "sparc64") {
-                    Some("https://github.com/Mrmayman/get-jdk/releases/download/java8-1/jdk-8u231-solaris-sparcv9.tar.gz")
-                } else {
-                    None
-                }
+            if self.major != 8 {
+                None
+            } else if cfg!(target_arch = "x86_64") {
+                Some("https://github.com/Mrmayman/get-jdk/releases/download/java8-1/jdk-8u231-solaris-x64.tar.gz".to_owned())
+            } else if cfg!(target_arch = "sparc64") {
+                Some("https://github.com/Mrmayman/get-jdk/releases/download/java8-1/jdk-8u231-solaris-sparcv9.tar.gz".to_owned())
             } else {
                 None
             }
@@ -165,97 +179,37 @@ impl JavaVersion {
         }
     }
 
-    fn get_url_linux(self) -> Option<&'static str> {
+    fn get_url_linux(&self) -> Option<String> {
         if cfg!(target_arch = "x86_64") {
-            Some(match self {
-                JavaVersion::Java16 | JavaVersion::Java17 => {
-                    "https://corretto.aws/downloads/latest/amazon-corretto-17-x64-linux-jdk.zip"
-                }
-                JavaVersion::Java21 => {
-                    "https://corretto.aws/downloads/latest/amazon-corretto-21-x64-linux-jdk.zip"
-                }
-                JavaVersion::Java8 => {
-                    "https://corretto.aws/downloads/latest/amazon-corretto-8-x64-linux-jdk.tar.gz"
-                }
-            })
+            let extension = if self.major == 8 { "tar.gz" } else { "zip" };
+            corretto_url(self.major, "x64-linux", extension, usize::MAX)
         } else if cfg!(target_arch = "aarch64") {
-            Some(match self {
-                JavaVersion::Java16 | JavaVersion::Java17 => {
-                    "https://corretto.aws/downloads/latest/amazon-corretto-17-aarch64-linux-jdk.tar.gz"
-                }
-                JavaVersion::Java21 => {
-                    "https://corretto.aws/downloads/latest/amazon-corretto-21-aarch64-linux-jdk.tar.gz"
-                }
-                JavaVersion::Java8 => {
-                    "https://corretto.aws/downloads/latest/amazon-corretto-8-aarch64-linux-jdk.tar.gz"
-                }
-            })
+            corretto_url(self.major, "aarch64-linux", "tar.gz", usize::MAX)
         } else if cfg!(target_arch = "arm") {
-            if let JavaVersion::Java8 = self {
-                Some("https://github.com/Mrmayman/get-jdk/releases/download/java8-1/jdk-8u231-linux-arm32-vfp-hflt.tar.gz")
-            } else {
-                None
-            }
+            (self.major == 8).then(|| "https://github.com/Mrmayman/get-jdk/releases/download/java8-1/jdk-8u231-linux-arm32-vfp-hflt.tar.gz".to_owned())
         } else {
             None
         }
     }
 
-    fn get_url_macos(self) -> Option<&'static str> {
+    fn get_url_macos(&self) -> Option<String> {
         if cfg!(target_arch = "x86_64") {
-            Some(match self {
-                JavaVersion::Java16 | JavaVersion::Java17 => {
-                    "https://corretto.aws/downloads/latest/amazon-corretto-17-x64-macos-jdk.tar.gz"
-                }
-                JavaVersion::Java21 => {
-                    "https://corretto.aws/downloads/latest/amazon-corretto-21-x64-macos-jdk.tar.gz"
-                }
-                JavaVersion::Java8 => {
-                    "https://corretto.aws/downloads/latest/amazon-corretto-8-x64-macos-jdk.tar.gz"
-                }
-            })
+            corretto_url(self.major, "x64-macos", "tar.gz", usize::MAX)
         } else if cfg!(target_arch = "aarch64") {
-            Some(match self {
-                JavaVersion::Java16 | JavaVersion::Java17 => {
-                    "https://corretto.aws/downloads/latest/amazon-corretto-17-aarch64-macos-jdk.tar.gz"
-                }
-                JavaVersion::Java21 => {
-                    "https://corretto.aws/downloads/latest/amazon-corretto-21-aarch64-macos-jdk.tar.gz"
-                }
-                JavaVersion::Java8 => {
-                    "https://corretto.aws/downloads/latest/amazon-corretto-8-aarch64-macos-jdk.tar.gz"
-                }
-            })
+            corretto_url(self.major, "aarch64-macos", "tar.gz", usize::MAX)
         } else {
             None
         }
     }
 
-    fn get_url_windows(self) -> Option<&'static str> {
+    fn get_url_windows(&self) -> Option<String> {
         if cfg!(target_arch = "x86_64") {
-            Some(match self {
-                JavaVersion::Java16 | JavaVersion::Java17 => {
-                    "https://corretto.aws/downloads/latest/amazon-corretto-17-x64-windows-jdk.zip"
-                }
-                JavaVersion::Java21 => {
-                    "https://corretto.aws/downloads/latest/amazon-corretto-21-x64-windows-jdk.zip"
-                }
-                JavaVersion::Java8 => {
-                    "https://corretto.aws/downloads/latest/amazon-corretto-8-x64-windows-jdk.zip"
-                }
-            })
+            corretto_url(self.major, "x64-windows", "zip", usize::MAX)
         } else if cfg!(target_arch = "x86") {
-            Some(match self {
-                JavaVersion::Java16 | JavaVersion::Java17 => {
-                    "https://corretto.aws/downloads/latest/amazon-corretto-17-x86-windows-jdk.zip"
-                }
-                JavaVersion::Java21 => {
-                    "https://corretto.aws/downloads/latest/amazon-corretto-21-x86-windows-jdk.zip"
-                }
-                JavaVersion::Java8 => {
-                    "https://corretto.aws/downloads/latest/amazon-corretto-8-x86-windows-jdk.zip"
-                }
-            })
+            // Corretto stopped making 32-bit builds after 21
+            corretto_url(self.major, "x86-windows", "zip", 21)
+        } else if cfg!(target_arch = "aarch64") {
+            corretto_url(self.major.max(17), "aarch64-windows", "zip", usize::MAX)
         } else {
             None
         }
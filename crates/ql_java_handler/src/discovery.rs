@@ -0,0 +1,320 @@
+//! Finds Java runtimes installed on the system
+//! (outside of the launcher's own `java_installs`),
+//! so that instances can use them instead of
+//! the auto-installed ones.
+//!
+//! Looks in `JAVA_HOME`, `PATH` and common install
+//! locations (`/usr/lib/jvm`, SDKMAN, `~/.jdks`,
+//! `Program Files`, ...), then runs each candidate with
+//! `-XshowSettings:properties -version` to find out
+//! its vendor, version and architecture.
+//!
+//! Results are cached in `QuantumLauncher/java_detected.json`,
+//! and a runtime is only probed again if its executable changes.
+
+use std::{
+    collections::HashSet,
+    fmt::Display,
+    path::{Path, PathBuf},
+    process::Stdio,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use ql_core::{err, info, no_window, pt, IntoJsonError, LAUNCHER_DIR};
+use serde::{Deserialize, Serialize};
+
+const CACHE_FILE: &str = "java_detected.json";
+
+/// A working runtime answers in well under a second,
+/// this is just so a broken one can't hang discovery.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[cfg(target_os = "windows")]
+const JAVA_EXE: &str = "java.exe";
+#[cfg(not(target_os = "windows"))]
+const JAVA_EXE: &str = "java";
+
+/// A Java runtime found on the system.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct JavaInstall {
+    /// Path to the `java` executable.
+    pub path: PathBuf,
+    /// `java.vendor`, like `Eclipse Adoptium`
+    pub vendor: String,
+    /// `java.version`, like `17.0.8` or `1.8.0_382`
+    pub version: String,
+    /// The major version, like `17` or `8`
+    pub major: usize,
+    /// `os.arch`, like `amd64` or `aarch64`
+    pub arch: String,
+}
+
+impl JavaInstall {
+    /// Checks whether this runtime has the major version
+    /// required by an instance (the `javaVersion.majorVersion`
+    /// field of its version JSON).
+    #[must_use]
+    pub fn matches(&self, required_major: usize) -> bool {
+        self.major == required_major
+    }
+}
+
+impl Display for JavaInstall {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Java {} ({}, {}) - {}",
+            self.version,
+            self.vendor,
+            self.arch,
+            self.path.to_string_lossy()
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedInstall {
+    #[serde(flatten)]
+    install: JavaInstall,
+    /// Modification time of the executable when it was probed
+    modified: u64,
+}
+
+/// Finds all Java runtimes installed on the system.
+///
+/// Previously probed runtimes are read from the cache,
+/// unless `rescan` is `true`.
+pub async fn find_java_installs(rescan: bool) -> Vec<JavaInstall> {
+    info!("Looking for installed Java runtimes");
+    let cache_path = LAUNCHER_DIR.join(CACHE_FILE);
+    let cached: Vec<CachedInstall> = if rescan {
+        Vec::new()
+    } else {
+        read_cache(&cache_path).await
+    };
+
+    let mut installs = Vec::new();
+    for path in get_candidates().await {
+        let modified = get_modified(&path).await;
+        let install = if let Some(cached) = cached
+            .iter()
+            .find(|n| n.install.path == path && n.modified == modified)
+        {
+            Some(cached.install.clone())
+        } else {
+            probe_java(&path).await
+        };
+        if let Some(install) = install {
+            installs.push(CachedInstall { install, modified });
+        }
+    }
+    installs.sort_by(|a, b| {
+        (b.install.major, &a.install.path).cmp(&(a.install.major, &b.install.path))
+    });
+
+    match serde_json::to_string(&installs).json_to() {
+        Ok(json) => {
+            if let Err(error) = tokio::fs::write(&cache_path, json).await {
+                err!("Couldn't save detected Java runtimes: {error}");
+            }
+        }
+        Err(error) => err!("Couldn't save detected Java runtimes: {error}"),
+    }
+
+    pt!("Found {} runtime(s)", installs.len());
+    installs.into_iter().map(|n| n.install).collect()
+}
+
+async fn read_cache(path: &Path) -> Vec<CachedInstall> {
+    let Ok(json) = tokio::fs::read_to_string(path).await else {
+        return Vec::new();
+    };
+    serde_json::from_str(&json).unwrap_or_default()
+}
+
+async fn get_modified(path: &Path) -> u64 {
+    tokio::fs::metadata(path)
+        .await
+        .ok()
+        .and_then(|n| n.modified().ok())
+        .and_then(|n| n.duration_since(UNIX_EPOCH).ok())
+        .map_or(0, |n| n.as_secs())
+}
+
+/// Runs `java -XshowSettings:properties -version`
+/// and reads the runtime's properties from the output.
+///
+/// Returns `None` if it isn't a working Java runtime.
+pub async fn probe_java(path: &Path) -> Option<JavaInstall> {
+    let mut command = tokio::process::Command::new(path);
+    command
+        .args(["-XshowSettings:properties", "-version"])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    no_window!(command);
+
+    let output = match tokio::time::timeout(PROBE_TIMEOUT, command.output()).await {
+        Ok(Ok(n)) => n,
+        Ok(Err(error)) => {
+            err!("Couldn't run {path:?}: {error}");
+            return None;
+        }
+        Err(_) => {
+            err!("{path:?} didn't respond in time, skipping");
+            return None;
+        }
+    };
+    // The settings are printed to stderr
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let install = parse_properties(path, &stderr);
+    if install.is_none() {
+        err!("Couldn't read Java properties from {path:?}");
+    }
+    install
+}
+
+fn parse_properties(path: &Path, output: &str) -> Option<JavaInstall> {
+    let get = |key: &str| {
+        output.lines().find_map(|line| {
+            let (k, v) = line.split_once('=')?;
+            (k.trim() == key).then(|| v.trim().to_owned())
+        })
+    };
+
+    let version = get("java.version")?;
+    let spec = get("java.specification.version").unwrap_or_else(|| version.clone());
+    // "1.8" for Java 8, "17" for Java 17
+    let spec = spec.strip_prefix("1.").unwrap_or(&spec);
+    let major = spec.split('.').next()?.parse().ok()?;
+
+    Some(JavaInstall {
+        path: path.to_owned(),
+        vendor: get("java.vendor").unwrap_or_else(|| "Unknown".to_owned()),
+        version,
+        major,
+        arch: get("os.arch").unwrap_or_else(|| "unknown".to_owned()),
+    })
+}
+
+/// Returns the (canonicalized, deduplicated) paths of
+/// all `java` executables in the usual places.
+async fn get_candidates() -> Vec<PathBuf> {
+    let mut java_homes: Vec<PathBuf> = Vec::new();
+
+    if let Some(home) = std::env::var_os("JAVA_HOME") {
+        java_homes.push(PathBuf::from(home));
+    }
+
+    let mut dirs_to_scan: Vec<PathBuf> = Vec::new();
+    if cfg!(target_os = "windows") {
+        for program_files in ["ProgramFiles", "ProgramFiles(x86)", "ProgramW6432"] {
+            let Some(program_files) = std::env::var_os(program_files) else {
+                continue;
+            };
+            let program_files = PathBuf::from(program_files);
+            for vendor in [
+                "Java",
+                "Eclipse Adoptium",
+                "Eclipse Foundation",
+                "AdoptOpenJDK",
+                "Zulu",
+                "Microsoft",
+                "Amazon Corretto",
+                "BellSoft",
+            ] {
+                dirs_to_scan.push(program_files.join(vendor));
+            }
+        }
+    } else if cfg!(target_os = "macos") {
+        dirs_to_scan.push(PathBuf::from("/Library/Java/JavaVirtualMachines"));
+    } else {
+        dirs_to_scan.extend(
+            [
+                "/usr/lib/jvm",
+                "/usr/lib64/jvm",
+                "/usr/java",
+                "/opt/java",
+                "/opt/jdk",
+            ]
+            .into_iter()
+            .map(PathBuf::from),
+        );
+    }
+    if let Some(home) = dirs::home_dir() {
+        dirs_to_scan.extend([
+            home.join(".sdkman/candidates/java"),
+            home.join(".jdks"),
+            home.join(".asdf/installs/java"),
+            home.join(".local/share/mise/installs/java"),
+        ]);
+        if cfg!(target_os = "macos") {
+            dirs_to_scan.push(home.join("Library/Java/JavaVirtualMachines"));
+        }
+    }
+
+    for dir in dirs_to_scan {
+        let Ok(mut entries) = tokio::fs::read_dir(&dir).await else {
+            continue;
+        };
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let path = entry.path();
+            java_homes.push(path.join("Contents/Home"));
+            java_homes.push(path);
+        }
+    }
+
+    let mut binaries: Vec<PathBuf> = java_homes
+        .into_iter()
+        .map(|n| n.join("bin").join(JAVA_EXE))
+        .collect();
+    if let Some(path) = std::env::var_os("PATH") {
+        binaries.extend(std::env::split_paths(&path).map(|n| n.join(JAVA_EXE)));
+    }
+
+    let launcher_installs = LAUNCHER_DIR.join("java_installs");
+    let launcher_installs = launcher_installs
+        .canonicalize()
+        .unwrap_or(launcher_installs);
+
+    let mut seen = HashSet::new();
+    binaries
+        .into_iter()
+        .filter(|n| n.is_file())
+        // Symlinks like `/usr/bin/java` point to one of the other runtimes
+        .filter_map(|n| n.canonicalize().ok())
+        .filter(|n| !n.starts_with(&launcher_installs))
+        .filter(|n| seen.insert(n.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_java_properties() {
+        let java_8 = r"Property settings:
+    java.specification.version = 1.8
+    java.vendor = Temurin
+    java.version = 1.8.0_382
+    os.arch = amd64
+
+openjdk version 1.8.0_382";
+        let install = parse_properties(Path::new("java"), java_8).unwrap();
+        assert_eq!(install.major, 8);
+        assert_eq!(install.vendor, "Temurin");
+        assert_eq!(install.version, "1.8.0_382");
+        assert_eq!(install.arch, "amd64");
+        assert!(install.matches(8));
+        assert!(!install.matches(17));
+
+        let java_21 = "    java.specification.version = 21\n    java.version = 21.0.4\n";
+        let install = parse_properties(Path::new("java"), java_21).unwrap();
+        assert_eq!(install.major, 21);
+        assert_eq!(install.vendor, "Unknown");
+
+        assert!(parse_properties(Path::new("java"), "Error: not java").is_none());
+    }
+}
@@ -39,7 +39,7 @@ pub struct JavaFileDownload {
 
 #[derive(Deserialize)]
 pub struct JavaFileDownloadDetails {
-    // sha1: String,
+    pub sha1: String,
     // size: usize,
     pub url: String,
 }
@@ -1,4 +1,4 @@
-use std::fmt::Display;
+use std::{borrow::Cow, collections::HashMap, fmt::Display};
 
 use crate::file_utils;
 use ql_core::json::version::JavaVersionJson;
@@ -6,52 +6,62 @@ use serde::Deserialize;
 
 use crate::JsonDownloadError;
 
-#[derive(Clone, Copy, Debug, PartialEq)]
-pub enum JavaVersion {
-    Java16,
-    Java17,
-    Java21,
-    Java8,
+/// A Java runtime, identified by its major version
+/// and the name of its `component` in Mojang's runtime manifest
+/// (like `java-runtime-delta`).
+///
+/// Version JSONs specify this in their `javaVersion` field,
+/// so new Java versions don't need any code changes.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct JavaVersion {
+    pub major: usize,
+    pub component: Cow<'static, str>,
+}
+
+impl JavaVersion {
+    /// Java 8u202 (Minecraft 1.16.5 and below)
+    pub const JAVA_8: Self = Self::new(8, "jre-legacy");
+    /// Java 16.0.1 (Minecraft 1.17)
+    pub const JAVA_16: Self = Self::new(16, "java-runtime-alpha");
+    /// Java 17 (Minecraft 1.18 to 1.20.4)
+    pub const JAVA_17: Self = Self::new(17, "java-runtime-gamma");
+    /// Java 21 (Minecraft 1.20.5 and above)
+    pub const JAVA_21: Self = Self::new(21, "java-runtime-delta");
+    /// Java 25 (newer snapshots)
+    pub const JAVA_25: Self = Self::new(25, "java-runtime-epsilon");
+
+    #[must_use]
+    pub const fn new(major: usize, component: &'static str) -> Self {
+        Self {
+            major,
+            component: Cow::Borrowed(component),
+        }
+    }
 }
 
 impl Display for JavaVersion {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                JavaVersion::Java16 => "java_16",
-                JavaVersion::Java17 => "java_17",
-                JavaVersion::Java21 => "java_21",
-                JavaVersion::Java8 => "java_8",
-            }
-        )
+        // Also used as the name of the install directory
+        write!(f, "java_{}", self.major)
     }
 }
 
 impl From<JavaVersionJson> for JavaVersion {
     fn from(version: JavaVersionJson) -> Self {
-        match version.majorVersion {
-            8 => JavaVersion::Java8,
-            16 => JavaVersion::Java16,
-            17 => JavaVersion::Java17,
-            _ => JavaVersion::Java21,
+        Self {
+            major: version.majorVersion,
+            component: Cow::Owned(version.component),
         }
     }
 }
 
+/// Mojang's list of Java runtimes.
+///
+/// Maps platform (like `linux` or `mac-os-arm64`)
+/// to runtime component (like `java-runtime-delta`)
+/// to the available builds.
 #[derive(Deserialize, Debug)]
-#[serde(rename_all = "kebab-case")]
-pub struct JavaListJson {
-    // gamecore: JavaList,
-    linux: JavaList,
-    linux_i386: JavaList,
-    mac_os: JavaList,
-    mac_os_arm64: JavaList,
-    windows_arm64: JavaList,
-    windows_x86: JavaList,
-    windows_x64: JavaList,
-}
+pub struct JavaListJson(HashMap<String, HashMap<String, Vec<JavaInstallListing>>>);
 
 impl JavaListJson {
     pub async fn download() -> Result<Self, JsonDownloadError> {
@@ -59,90 +69,102 @@ impl JavaListJson {
         file_utils::download_file_to_json(JAVA_LIST_URL, false).await
     }
 
-    pub fn get_url(&self, version: JavaVersion) -> Option<String> {
+    /// Returns the latest build of a Java version
+    /// for this platform, if Mojang provides one.
+    pub fn get_listing(&self, version: &JavaVersion) -> Option<&JavaInstallListing> {
         if (cfg!(target_os = "windows")
             || (cfg!(target_os = "macos") && cfg!(target_arch = "x86_64")))
-            && version == JavaVersion::Java8
+            && version.major == 8
         {
             return None;
         }
 
-        let java_list = if cfg!(target_os = "linux") {
+        let platform = if cfg!(target_os = "linux") {
             if cfg!(target_arch = "x86_64") {
-                &self.linux
+                "linux"
             } else if cfg!(target_arch = "x86") {
-                &self.linux_i386
+                "linux-i386"
             } else {
                 return None;
             }
         } else if cfg!(target_os = "macos") {
-            // aarch64
             if cfg!(target_arch = "aarch64") {
-                &self.mac_os_arm64
+                "mac-os-arm64"
             } else if cfg!(target_arch = "x86_64") {
-                &self.mac_os
+                "mac-os"
             } else {
                 return None;
             }
         } else if cfg!(target_os = "windows") {
             if cfg!(target_arch = "x86_64") {
-                &self.windows_x64
+                "windows-x64"
             } else if cfg!(target_arch = "x86") {
-                &self.windows_x86
+                "windows-x86"
             } else if cfg!(target_arch = "aarch64") {
-                &self.windows_arm64
+                "windows-arm64"
             } else {
                 return None;
             }
         } else {
             return None;
         };
-
-        let version_listing = match version {
-            JavaVersion::Java16 => &java_list.java_runtime_alpha,
-            JavaVersion::Java17 => {
-                if !java_list.java_runtime_gamma.is_empty() {
-                    &java_list.java_runtime_gamma
-                } else if !java_list.java_runtime_gamma_snapshot.is_empty() {
-                    &java_list.java_runtime_gamma_snapshot
-                } else {
-                    &java_list.java_runtime_beta
-                }
-            }
-            JavaVersion::Java21 => &java_list.java_runtime_delta,
-            JavaVersion::Java8 => &java_list.jre_legacy,
-        };
-
-        let first_version = version_listing.first()?;
-        Some(first_version.manifest.url.clone())
+        find_listing(self.0.get(platform)?, version)
     }
 }
 
-#[derive(Deserialize, Debug)]
-#[serde(rename_all = "kebab-case")]
-pub struct JavaList {
-    /// Java 16.0.1.9.1
-    java_runtime_alpha: Vec<JavaInstallListing>,
-    /// Java 17.0.1.12.1
-    java_runtime_beta: Vec<JavaInstallListing>,
-    /// Java 21.0.3
-    java_runtime_delta: Vec<JavaInstallListing>,
-    /// Java 17.0.8
-    java_runtime_gamma: Vec<JavaInstallListing>,
-    /// Java 17.0.8
-    java_runtime_gamma_snapshot: Vec<JavaInstallListing>,
-    // FIXME: Some platforms need exotic versions of Java 8?
-    /// Java 8u202
-    jre_legacy: Vec<JavaInstallListing>,
-    // Ugly windows specific thing that doesn't seem to be required?
-    // minecraft_java_exe: Vec<JavaInstallListing>,
+/// The requested component, or else any other
+/// component with the same major version
+/// (eg: `java-runtime-beta` instead of `java-runtime-gamma`).
+///
+/// This way, versions asking for a component that doesn't
+/// exist on this platform (or doesn't exist yet) still work.
+fn find_listing<'a>(
+    java_list: &'a HashMap<String, Vec<JavaInstallListing>>,
+    version: &JavaVersion,
+) -> Option<&'a JavaInstallListing> {
+    java_list
+        .get(&*version.component)
+        .and_then(|n| n.first())
+        .or_else(|| {
+            let mut others: Vec<_> = java_list
+                .iter()
+                .filter_map(|(name, listings)| Some((name, listings.first()?)))
+                .filter(|(_, listing)| listing.major_version() == Some(version.major))
+                .collect();
+            // Prefer stable builds over `*-snapshot` ones
+            others.sort_by_key(|(name, _)| (name.ends_with("-snapshot"), *name));
+            others.first().map(|(_, listing)| *listing)
+        })
 }
 
 #[derive(Deserialize, Debug)]
 pub struct JavaInstallListing {
     // availability: JavaInstallListingAvailability,
     manifest: JavaInstallListingManifest,
-    // version: JavaInstallListingVersion,
+    version: JavaInstallListingVersion,
+}
+
+impl JavaInstallListing {
+    /// The URL of the file manifest ([`super::files::JavaFilesJson`]).
+    pub fn manifest_url(&self) -> &str {
+        &self.manifest.url
+    }
+
+    /// The name of this build, like `17.0.8`.
+    pub fn build_name(&self) -> &str {
+        &self.version.name
+    }
+
+    /// Parses the major version from names like
+    /// `8u51`, `1.8.0_51` or `17.0.8`.
+    fn major_version(&self) -> Option<usize> {
+        let name = &self.version.name;
+        let name = name.strip_prefix("1.").unwrap_or(name);
+        let end = name
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(name.len());
+        name[..end].parse().ok()
+    }
 }
 
 // WTF: Yes this is approaching Java levels of name length.
@@ -159,8 +181,102 @@ pub struct JavaInstallListingManifest {
     url: String,
 }
 
-// #[derive(Deserialize, Debug)]
-// pub struct JavaInstallListingVersion {
-// name: String,
-// released: String,
-// }
+#[derive(Deserialize, Debug)]
+pub struct JavaInstallListingVersion {
+    name: String,
+    // released: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn listing(name: &str) -> Vec<JavaInstallListing> {
+        vec![JavaInstallListing {
+            manifest: JavaInstallListingManifest {
+                url: format!("https://example.com/{name}.json"),
+            },
+            version: JavaInstallListingVersion {
+                name: name.to_owned(),
+            },
+        }]
+    }
+
+    fn find(
+        list: &HashMap<String, Vec<JavaInstallListing>>,
+        major: usize,
+        component: &str,
+    ) -> Option<String> {
+        let version = JavaVersion {
+            major,
+            component: Cow::Owned(component.to_owned()),
+        };
+        find_listing(list, &version).map(|n| n.build_name().to_owned())
+    }
+
+    #[test]
+    fn major_versions() {
+        for (name, major) in [
+            ("8u51", Some(8)),
+            ("1.8.0_51", Some(8)),
+            ("16.0.1", Some(16)),
+            ("17.0.8", Some(17)),
+            ("25", Some(25)),
+            ("21-ea+35", Some(21)),
+            ("unknown", None),
+        ] {
+            assert_eq!(listing(name)[0].major_version(), major, "{name}");
+        }
+    }
+
+    #[test]
+    fn component_fallback() {
+        let list: HashMap<String, Vec<JavaInstallListing>> = [
+            ("jre-legacy", listing("8u51")),
+            ("java-runtime-gamma", listing("17.0.8")),
+            ("java-runtime-delta", listing("21.0.7")),
+            ("java-runtime-epsilon-snapshot", listing("25-ea")),
+            ("java-runtime-epsilon", listing("25.0.1")),
+            ("java-runtime-empty", Vec::new()),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_owned(), v))
+        .collect();
+
+        assert_eq!(
+            find(&list, 17, "java-runtime-gamma").as_deref(),
+            Some("17.0.8")
+        );
+        // Known component with a wrong major version: the component wins
+        assert_eq!(
+            find(&list, 16, "java-runtime-gamma").as_deref(),
+            Some("17.0.8")
+        );
+        // Unknown/new component names fall back to the major version,
+        // preferring stable builds
+        assert_eq!(
+            find(&list, 17, "java-runtime-beta").as_deref(),
+            Some("17.0.8")
+        );
+        assert_eq!(
+            find(&list, 25, "java-runtime-zeta").as_deref(),
+            Some("25.0.1")
+        );
+        assert_eq!(find(&list, 21, "").as_deref(), Some("21.0.7"));
+        assert_eq!(find(&list, 8, "").as_deref(), Some("8u51"));
+        // A component without builds
+        assert_eq!(find(&list, 99, "java-runtime-empty"), None);
+        assert_eq!(find(&list, 99, "java-runtime-omega"), None);
+    }
+
+    #[test]
+    fn from_version_json() {
+        let version = JavaVersion::from(JavaVersionJson {
+            component: "java-runtime-zeta".to_owned(),
+            majorVersion: 29,
+        });
+        assert_eq!(version.major, 29);
+        assert_eq!(version.component, "java-runtime-zeta");
+        assert_eq!(version.to_string(), "java_29");
+    }
+}
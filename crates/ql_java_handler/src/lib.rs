@@ -19,9 +19,16 @@ mod compression;
 pub use compression::extract_tar_gz;
 
 pub mod alternate_java;
+pub mod discovery;
 mod json;
+mod manage;
 
+pub use discovery::{find_java_installs, probe_java, JavaInstall};
 pub use json::list::JavaVersion;
+pub use manage::{
+    delete_java_install, list_java_installs, repair_java_install, update_java_install,
+    verify_java_install, JavaSource, ManagedJava,
+};
 
 #[cfg(target_os = "windows")]
 pub const JAVA: &str = "javaw";
@@ -53,11 +60,11 @@ pub const JAVA: &str = "java";
 /// use ql_java_handler::{get_java_binary, JavaVersion};
 /// use std::path::PathBuf;
 ///
-/// let java_binary: PathBuf = get_java_binary(JavaVersion::Java16, "java", None).await?;
+/// let java_binary: PathBuf = get_java_binary(JavaVersion::JAVA_16, "java", None).await?;
 ///
 /// let command = std::process::Command::new(java_binary).arg("-version").output()?;
 ///
-/// let java_compiler_binary: PathBuf = get_java_binary(JavaVersion::Java16, "javac", None).await?;
+/// let java_compiler_binary: PathBuf = get_java_binary(JavaVersion::JAVA_16, "javac", None).await?;
 ///
 /// let command = std::process::Command::new(java_compiler_binary)
 ///     .args(&["MyApp.java", "-d", "."])
@@ -74,30 +81,44 @@ pub async fn get_java_binary(
     name: &str,
     java_install_progress_sender: Option<&Sender<GenericProgress>>,
 ) -> Result<PathBuf, JavaInstallError> {
-    let java_dir = LAUNCHER_DIR.join("java_installs").join(version.to_string());
-    let is_incomplete_install = java_dir.join("install.lock").exists();
+    if cfg!(target_os = "windows") && cfg!(target_arch = "aarch64") && version.major < 17 {
+        // Java 8 and 16 are unsupported on Windows aarch64.
 
-    if cfg!(target_os = "windows") && cfg!(target_arch = "aarch64") {
-        version = match version {
-            // Java 8 and 16 are unsupported on Windows aarch64.
-
-            // 17 should be backwards compatible with 8 and 16
-            // for the most part, but some things like Beta ModLoader
-            // might break?
-            JavaVersion::Java8 | JavaVersion::Java16 | JavaVersion::Java17 => JavaVersion::Java17,
-            JavaVersion::Java21 => JavaVersion::Java21,
-        }
+        // 17 should be backwards compatible with 8 and 16
+        // for the most part, but some things like Beta ModLoader
+        // might break?
+        version = JavaVersion::JAVA_17;
     }
 
+    let java_dir = LAUNCHER_DIR.join("java_installs").join(version.to_string());
+    let is_incomplete_install = java_dir.join("install.lock").exists();
+
     if !java_dir.exists() || is_incomplete_install {
         info!("Installing Java: {version}");
-        install_java(version, java_install_progress_sender).await?;
+        install_java(&version, java_install_progress_sender).await?;
     }
 
     let bin_path = find_java_bin(name, &java_dir).await?;
     Ok(bin_path.canonicalize().path(bin_path)?)
 }
 
+/// Like [`get_java_binary`], but never installs Java.
+///
+/// If the runtime isn't installed (yet), returns
+/// where its binary will most likely be once it is.
+pub async fn get_java_binary_path(mut version: JavaVersion, name: &str) -> PathBuf {
+    if cfg!(target_os = "windows") && cfg!(target_arch = "aarch64") && version.major < 17 {
+        // See `get_java_binary`
+        version = JavaVersion::JAVA_17;
+    }
+
+    let java_dir = LAUNCHER_DIR.join("java_installs").join(version.to_string());
+    match find_java_bin(name, &java_dir).await {
+        Ok(path) if !java_dir.join("install.lock").exists() => path.canonicalize().unwrap_or(path),
+        _ => java_dir.join("bin").join(name),
+    }
+}
+
 async fn find_java_bin(name: &str, java_dir: &Path) -> Result<PathBuf, JavaInstallError> {
     let names = [
         format!("bin/{name}"),
@@ -125,7 +146,7 @@ async fn find_java_bin(name: &str, java_dir: &Path) -> Result<PathBuf, JavaInsta
 }
 
 async fn install_java(
-    version: JavaVersion,
+    version: &JavaVersion,
     java_install_progress_sender: Option<&Sender<GenericProgress>>,
 ) -> Result<(), JavaInstallError> {
     #[cfg(target_os = "macos")]
@@ -139,13 +160,19 @@ async fn install_java(
     send_progress(java_install_progress_sender, GenericProgress::default());
 
     let java_list_json = JavaListJson::download().await?;
-    let Some(java_files_url) = java_list_json.get_url(version) else {
+    let Some(listing) = java_list_json.get_listing(version) else {
         // Mojang doesn't officially provide java for som platforms.
         // In that case, fetch from alternate sources.
-        return alternate_java::install(version, java_install_progress_sender, &install_dir).await;
+        let url =
+            alternate_java::install(version, java_install_progress_sender, &install_dir).await?;
+        lock_finish(&lock_file).await?;
+        manage::write_source(&install_dir, &JavaSource::Archive { url }).await?;
+        info!("Finished installing {version}");
+        return Ok(());
     };
+    let java_files_url = listing.manifest_url();
 
-    let json: JavaFilesJson = file_utils::download_file_to_json(&java_files_url, false).await?;
+    let json: JavaFilesJson = file_utils::download_file_to_json(java_files_url, false).await?;
 
     let num_files = json.files.len();
     let file_num = Mutex::new(0);
@@ -166,8 +193,17 @@ async fn install_java(
     .await?;
 
     lock_finish(&lock_file).await?;
+    manage::write_source(
+        &install_dir,
+        &JavaSource::Mojang {
+            component: version.component.to_string(),
+            build: listing.build_name().to_owned(),
+            manifest_url: java_files_url.to_owned(),
+        },
+    )
+    .await?;
     send_progress(java_install_progress_sender, GenericProgress::finished());
-    info!("Finished installing {}", version.to_string());
+    info!("Finished installing {version}");
 
     Ok(())
 }
@@ -188,7 +224,7 @@ async fn lock_init(install_dir: &Path) -> Result<PathBuf, IoError> {
     Ok(lock_file)
 }
 
-async fn get_install_dir(version: JavaVersion) -> Result<PathBuf, JavaInstallError> {
+async fn get_install_dir(version: &JavaVersion) -> Result<PathBuf, JavaInstallError> {
     let java_installs_dir = LAUNCHER_DIR.join("java_installs");
     tokio::fs::create_dir_all(&java_installs_dir)
         .await
@@ -313,6 +349,8 @@ pub enum JavaInstallError {
 
     #[error("on your platform, only Java 8 (Minecraft 1.16.5 and below) is supported!\n")]
     UnsupportedOnlyJava8,
+    #[error("no Java {0} runtime is available for your platform!\nPlease manually install Java {0} (or newer),\nand add the executable path in instance Edit tab")]
+    UnsupportedVersion(usize),
     #[error("Java auto-installation is not supported on your platform!\nPlease manually install Java,\nand add the executable path in instance Edit tab")]
     UnsupportedPlatform,
 
@@ -322,6 +360,13 @@ pub enum JavaInstallError {
     TarGzExtract(std::io::Error),
     #[error("{JAVA_INSTALL_ERR_PREFIX}unknown extension for java: {0}\n\nTHIS IS A BUG, PLEASE REPORT ON DISCORD")]
     UnknownExtension(String),
+
+    #[error("Java {0} is not installed")]
+    NotInstalled(usize),
+    #[error("Java {0} can't be verified, as it wasn't installed from Mojang\n(or was installed by an older launcher version).\nUpdate it to reinstall it instead")]
+    CantVerify(usize),
+    #[error("Java {0} is used as the Java override of: {1}\nChange their Java override first")]
+    InUse(usize, String),
 }
 
 pub async fn delete_java_installs() {
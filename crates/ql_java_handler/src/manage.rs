@@ -0,0 +1,370 @@
+//! Maintenance of the Java runtimes installed by the launcher
+//! (in `QuantumLauncher/java_installs/`).
+//!
+//! Each runtime remembers where it came from in an `install.json`
+//! file, so it can later be verified against Mojang's
+//! file manifest, repaired or updated to the latest build.
+//! Runtimes installed by older launcher versions don't have
+//! this file, and can only be updated (reinstalled) or deleted.
+
+use std::{
+    borrow::Cow,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use ql_core::{
+    do_jobs_with_limit, err, file_utils, info, json::InstanceConfigJson, pt, IntoIoError,
+    IntoJsonError, IoError, LAUNCHER_DIR,
+};
+use serde::{Deserialize, Serialize};
+use sha1::Digest;
+
+use crate::{
+    install_java, java_install_fn,
+    json::{
+        files::{JavaFile, JavaFilesJson},
+        list::JavaListJson,
+    },
+    lock_finish, JavaInstallError, JavaVersion,
+};
+
+const SOURCE_FILE: &str = "install.json";
+
+/// Where a managed Java runtime was installed from.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JavaSource {
+    /// Mojang's Java runtime manifest
+    Mojang {
+        /// Like `java-runtime-delta`
+        component: String,
+        /// Like `21.0.3`
+        build: String,
+        manifest_url: String,
+    },
+    /// An archive from a third party (like Amazon Corretto)
+    Archive { url: String },
+}
+
+impl std::fmt::Display for JavaSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JavaSource::Mojang {
+                component, build, ..
+            } => write!(f, "Mojang ({component}, {build})"),
+            JavaSource::Archive { url } => {
+                write!(f, "{}", url.rsplit('/').next().unwrap_or(url))
+            }
+        }
+    }
+}
+
+/// A Java runtime installed by the launcher.
+#[derive(Debug, Clone)]
+pub struct ManagedJava {
+    pub major: usize,
+    pub path: PathBuf,
+    /// Total size in bytes
+    pub size: u64,
+    /// `None` for runtimes installed by older
+    /// launcher versions.
+    pub source: Option<JavaSource>,
+    /// Whether the installation was interrupted
+    /// (it will be reinstalled on next use).
+    pub is_incomplete: bool,
+}
+
+fn java_installs_dir() -> PathBuf {
+    LAUNCHER_DIR.join("java_installs")
+}
+
+fn install_dir(major: usize) -> PathBuf {
+    java_installs_dir().join(format!("java_{major}"))
+}
+
+pub(crate) async fn write_source(install_dir: &Path, source: &JavaSource) -> Result<(), IoError> {
+    let path = install_dir.join(SOURCE_FILE);
+    let json = serde_json::to_string(source).expect("JavaSource should serialize");
+    tokio::fs::write(&path, json).await.path(path)
+}
+
+async fn read_source(install_dir: &Path) -> Option<JavaSource> {
+    let json = tokio::fs::read_to_string(install_dir.join(SOURCE_FILE))
+        .await
+        .ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+/// Lists the Java runtimes installed by the launcher,
+/// sorted by major version.
+///
+/// # Errors
+/// If the `java_installs` directory couldn't be read.
+pub async fn list_java_installs() -> Result<Vec<ManagedJava>, IoError> {
+    let dir = java_installs_dir();
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut installs = Vec::new();
+    let mut entries = tokio::fs::read_dir(&dir).await.path(&dir)?;
+    while let Some(entry) = entries.next_entry().await.path(&dir)? {
+        let path = entry.path();
+        let Some(major) = entry
+            .file_name()
+            .to_str()
+            .and_then(|n| n.strip_prefix("java_"))
+            .and_then(|n| n.parse().ok())
+        else {
+            continue;
+        };
+        if !path.is_dir() {
+            continue;
+        }
+        installs.push(ManagedJava {
+            major,
+            size: dir_size(&path).await?,
+            source: read_source(&path).await,
+            is_incomplete: path.join("install.lock").exists(),
+            path,
+        });
+    }
+    installs.sort_by_key(|n| n.major);
+    Ok(installs)
+}
+
+async fn dir_size(dir: &Path) -> Result<u64, IoError> {
+    let mut size = 0;
+    let mut stack = vec![dir.to_owned()];
+    while let Some(dir) = stack.pop() {
+        let mut entries = tokio::fs::read_dir(&dir).await.path(&dir)?;
+        while let Some(entry) = entries.next_entry().await.path(&dir)? {
+            // Doesn't follow symlinks
+            let metadata = entry.metadata().await.path(entry.path())?;
+            if metadata.is_dir() {
+                stack.push(entry.path());
+            } else {
+                size += metadata.len();
+            }
+        }
+    }
+    Ok(size)
+}
+
+async fn get_manifest(major: usize) -> Result<(PathBuf, JavaFilesJson), JavaInstallError> {
+    let dir = install_dir(major);
+    if !dir.is_dir() {
+        return Err(JavaInstallError::NotInstalled(major));
+    }
+    let Some(JavaSource::Mojang { manifest_url, .. }) = read_source(&dir).await else {
+        return Err(JavaInstallError::CantVerify(major));
+    };
+    let json = file_utils::download_file_to_json(&manifest_url, false).await?;
+    Ok((dir, json))
+}
+
+/// Checks the files of a managed Java runtime against
+/// the hashes in Mojang's file manifest.
+///
+/// Returns the (relative) paths of missing or corrupted files.
+///
+/// # Errors
+/// - Runtime isn't installed
+/// - Runtime wasn't installed from Mojang ([`JavaInstallError::CantVerify`])
+/// - Manifest couldn't be downloaded
+pub async fn verify_java_install(major: usize) -> Result<Vec<String>, JavaInstallError> {
+    info!("Verifying Java {major}");
+    let (dir, manifest) = get_manifest(major).await?;
+    let broken = get_broken_files(&dir, &manifest).await;
+    pt!("{} broken file(s)", broken.len());
+    Ok(broken)
+}
+
+async fn get_broken_files(dir: &Path, manifest: &JavaFilesJson) -> Vec<String> {
+    let mut broken = Vec::new();
+    for (name, file) in &manifest.files {
+        let path = dir.join(name);
+        let is_ok = match file {
+            JavaFile::file { downloads, .. } => tokio::fs::read(&path).await.is_ok_and(|bytes| {
+                format!("{:x}", sha1::Sha1::digest(&bytes))
+                    .eq_ignore_ascii_case(&downloads.raw.sha1)
+            }),
+            JavaFile::directory {} => path.is_dir(),
+            JavaFile::link { .. } => true,
+        };
+        if !is_ok {
+            broken.push(name.clone());
+        }
+    }
+    broken
+}
+
+/// Redownloads missing or corrupted files of a
+/// managed Java runtime (see [`verify_java_install`]).
+///
+/// Returns the number of files that were repaired.
+///
+/// # Errors
+/// Same as [`verify_java_install`], as well as
+/// errors while downloading/writing files.
+pub async fn repair_java_install(major: usize) -> Result<usize, JavaInstallError> {
+    info!("Repairing Java {major}");
+    let (dir, manifest) = get_manifest(major).await?;
+    let broken = get_broken_files(&dir, &manifest).await;
+
+    let num_files = broken.len();
+    let file_num = Mutex::new(0);
+    do_jobs_with_limit(
+        broken.iter().filter_map(|name| {
+            let file = manifest.files.get(name)?;
+            Some(java_install_fn(
+                None, &file_num, num_files, name, &dir, file,
+            ))
+        }),
+        16,
+    )
+    .await?;
+
+    let lock_file = dir.join("install.lock");
+    if lock_file.exists() {
+        lock_finish(&lock_file).await?;
+    }
+    pt!("Repaired {num_files} file(s)");
+    Ok(num_files)
+}
+
+/// Updates a managed Java runtime to the latest
+/// build of the same major version, if there is one.
+///
+/// Runtimes from third-party archives (or older launcher
+/// versions) are always reinstalled, as their build
+/// can't be compared.
+///
+/// Returns whether the runtime was reinstalled.
+///
+/// # Errors
+/// If the Java list couldn't be downloaded,
+/// or the runtime couldn't be reinstalled.
+pub async fn update_java_install(major: usize) -> Result<bool, JavaInstallError> {
+    let dir = install_dir(major);
+    if !dir.is_dir() {
+        return Err(JavaInstallError::NotInstalled(major));
+    }
+    let source = read_source(&dir).await;
+
+    let component = if let Some(JavaSource::Mojang {
+        component,
+        manifest_url,
+        ..
+    }) = &source
+    {
+        let list = JavaListJson::download().await?;
+        let version = JavaVersion {
+            major,
+            component: Cow::Owned(component.clone()),
+        };
+        if list
+            .get_listing(&version)
+            .is_some_and(|n| n.manifest_url() == manifest_url)
+        {
+            info!("Java {major} is up to date");
+            return Ok(false);
+        }
+        component.clone()
+    } else {
+        // Unknown component, the latest build
+        // of the same major version will be picked.
+        String::new()
+    };
+
+    info!("Updating Java {major}");
+    // The old runtime is kept until the new one is
+    // installed, so a failed download doesn't leave
+    // the user without Java.
+    let backup = java_installs_dir().join(format!("java_{major}.old"));
+    if backup.exists() {
+        tokio::fs::remove_dir_all(&backup).await.path(&backup)?;
+    }
+    tokio::fs::rename(&dir, &backup).await.path(&dir)?;
+
+    let result = install_java(
+        &JavaVersion {
+            major,
+            component: Cow::Owned(component),
+        },
+        None,
+    )
+    .await;
+    if let Err(error) = result {
+        pt!("Failed, restoring the old runtime");
+        if dir.exists() {
+            tokio::fs::remove_dir_all(&dir).await.path(&dir)?;
+        }
+        tokio::fs::rename(&backup, &dir).await.path(&backup)?;
+        return Err(error);
+    }
+
+    tokio::fs::remove_dir_all(&backup).await.path(backup)?;
+    Ok(true)
+}
+
+/// Deletes a single managed Java runtime.
+///
+/// # Errors
+/// - [`JavaInstallError::InUse`] if an instance or server
+///   uses this runtime as its Java override
+/// - Runtime couldn't be deleted
+pub async fn delete_java_install(major: usize) -> Result<(), JavaInstallError> {
+    let dir = install_dir(major);
+    if !dir.is_dir() {
+        return Err(JavaInstallError::NotInstalled(major));
+    }
+
+    let dependents = get_dependents(&dir).await;
+    if !dependents.is_empty() {
+        return Err(JavaInstallError::InUse(major, dependents.join(", ")));
+    }
+
+    info!("Deleting Java {major}");
+    tokio::fs::remove_dir_all(&dir).await.path(dir)?;
+    Ok(())
+}
+
+/// Finds the instances and servers whose
+/// Java override points inside `java_dir`.
+async fn get_dependents(java_dir: &Path) -> Vec<String> {
+    let java_dir = java_dir
+        .canonicalize()
+        .unwrap_or_else(|_| java_dir.to_owned());
+    let mut dependents = Vec::new();
+
+    for kind in ["instances", "servers"] {
+        let dir = LAUNCHER_DIR.join(kind);
+        let Ok(mut entries) = tokio::fs::read_dir(&dir).await else {
+            continue;
+        };
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let config_path = entry.path().join("config.json");
+            let Ok(config) = tokio::fs::read_to_string(&config_path).await else {
+                continue;
+            };
+            let config: InstanceConfigJson = match serde_json::from_str(&config).json(config) {
+                Ok(n) => n,
+                Err(error) => {
+                    err!("Couldn't read {config_path:?}: {error}");
+                    continue;
+                }
+            };
+            let Some(java_override) = config.java_override.filter(|n| !n.trim().is_empty()) else {
+                continue;
+            };
+            let java_override = PathBuf::from(java_override);
+            let java_override = java_override.canonicalize().unwrap_or(java_override);
+            if java_override.starts_with(&java_dir) {
+                dependents.push(entry.file_name().to_string_lossy().to_string());
+            }
+        }
+    }
+    dependents
+}
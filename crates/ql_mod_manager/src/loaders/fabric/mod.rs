@@ -16,7 +16,7 @@ pub use error::FabricInstallError;
 mod make_launch_jar;
 mod uninstall;
 pub use uninstall::{uninstall, uninstall_client, uninstall_server};
-mod version_compare;
+pub(crate) mod version_compare;
 
 const FABRIC_URL: &str = "https://meta.fabricmc.net/v2";
 const QUILT_URL: &str = "https://meta.quiltmc.org/v3";
@@ -211,7 +211,7 @@ impl ForgeInstaller {
         j_progress: Option<&Sender<GenericProgress>>,
         installer_name: &str,
     ) -> Result<(), ForgeInstallError> {
-        let javac_path = get_java_binary(JavaVersion::Java21, "javac", j_progress).await?;
+        let javac_path = get_java_binary(JavaVersion::JAVA_21, "javac", j_progress).await?;
         let java_source_file = include_str!("../../../../../assets/installers/ForgeInstaller.java")
             .replace("CLIENT", if self.is_server { "SERVER" } else { "CLIENT" });
         let source_path = self.forge_dir.join("ForgeInstaller.java");
@@ -248,7 +248,7 @@ impl ForgeInstaller {
             ));
         }
 
-        let java_path = get_java_binary(JavaVersion::Java21, JAVA, None).await?;
+        let java_path = get_java_binary(JavaVersion::JAVA_21, JAVA, None).await?;
         pt!("Running Installer");
         let mut command = Command::new(&java_path);
         command
@@ -288,7 +288,7 @@ async fn compile_and_run_installer(
     is_server: bool,
 ) -> Result<(), ForgeInstallError> {
     send_progress(f_progress, ForgeInstallProgress::P4RunningInstaller);
-    let javac_path = get_java_binary(JavaVersion::Java21, "javac", j_progress).await?;
+    let javac_path = get_java_binary(JavaVersion::JAVA_21, "javac", j_progress).await?;
     let java_source_file = include_str!("../../../../assets/installers/ForgeInstaller.java")
         .replace("CLIENT", if is_server { "SERVER" } else { "CLIENT" })
         .replace("new File(\".\")", "new File(\".\"), a -> true");
@@ -312,7 +312,7 @@ async fn compile_and_run_installer(
         ));
     }
 
-    let java_path = get_java_binary(JavaVersion::Java21, JAVA, None).await?;
+    let java_path = get_java_binary(JavaVersion::JAVA_21, JAVA, None).await?;
 
     pt!("Running Installer");
     let mut command = Command::new(&java_path);
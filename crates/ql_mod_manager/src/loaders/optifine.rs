@@ -258,7 +258,7 @@ async fn download_libraries(
 }
 
 async fn run_hook(new_installer_path: &Path, optifine_path: &Path) -> Result<(), OptifineError> {
-    let java_path = get_java_binary(JavaVersion::Java21, JAVA, None).await?;
+    let java_path = get_java_binary(JavaVersion::JAVA_21, JAVA, None).await?;
     let mut command = Command::new(&java_path);
     command
         .args([
@@ -286,7 +286,7 @@ async fn compile_hook(
     optifine_path: &Path,
     java_progress_sender: Option<&Sender<GenericProgress>>,
 ) -> Result<(), OptifineError> {
-    let javac_path = get_java_binary(JavaVersion::Java21, "javac", java_progress_sender).await?;
+    let javac_path = get_java_binary(JavaVersion::JAVA_21, "javac", java_progress_sender).await?;
     let mut command = Command::new(&javac_path);
     command
         .arg("-cp")
@@ -5,6 +5,7 @@ use ql_core::{err, pt, GenericProgress, InstanceSelection, IntoIoError};
 use crate::presets;
 
 use super::{
+    cache,
     modpack::{self, PackError},
     CurseforgeNotAllowed,
 };
@@ -49,9 +50,8 @@ pub async fn add_files(
 
         match extension.as_str() {
             "jar" => {
-                tokio::fs::copy(&path, mods_dir.join(filename))
-                    .await
-                    .path(&path)?;
+                let file = tokio::fs::read(&path).await.path(&path)?;
+                cache::store(&file, &mods_dir.join(filename)).await?;
             }
             "zip" | "mrpack" => {
                 let file = tokio::fs::read(&path).await.path(&path)?;
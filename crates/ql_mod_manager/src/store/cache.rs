@@ -0,0 +1,312 @@
+//! A content-addressed cache of downloaded mods,
+//! resource packs and shaders, shared across all instances.
+//!
+//! Files are stored in `QuantumLauncher/content_cache/`,
+//! keyed by their SHA-1 hash (the only hash that both
+//! Modrinth and Curseforge provide). They are placed into
+//! instances as hardlinks, or reflinks/copies if that fails,
+//! so a mod used by ten instances only takes up space once.
+//!
+//! Unused files can be removed with [`clean_cache`].
+
+use std::{
+    collections::HashSet,
+    fs::Metadata,
+    path::{Path, PathBuf},
+};
+
+use ql_core::{err, info, pt, IntoIoError, IoError, LAUNCHER_DIR};
+use sha1::Digest;
+use tokio::{fs, io::AsyncReadExt};
+
+use super::ModHashes;
+
+const CACHE_DIR: &str = "content_cache";
+
+/// Folders (inside `.minecraft`) whose files may
+/// come from the cache.
+pub(crate) const CACHED_DIRS: &[&str] = &["mods", "resourcepacks", "shaderpacks"];
+
+fn object_path(sha1: &str) -> Option<PathBuf> {
+    let sha1 = sha1.to_lowercase();
+    if sha1.len() < 3 || !sha1.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    Some(LAUNCHER_DIR.join(CACHE_DIR).join(&sha1[..2]).join(sha1))
+}
+
+/// Returns the path of the cached file with these hashes,
+/// if it's present and not corrupted.
+///
+/// Corrupted cache entries are removed.
+pub(crate) async fn get(hashes: &ModHashes) -> Result<Option<PathBuf>, IoError> {
+    let Some(path) = hashes.sha1.as_deref().and_then(object_path) else {
+        return Ok(None);
+    };
+    if !path.is_file() {
+        return Ok(None);
+    }
+
+    let bytes = fs::read(&path).await.path(&path)?;
+    if hashes.matches(&bytes) {
+        Ok(Some(path))
+    } else {
+        err!("Cached file {path:?} is corrupted, removing it");
+        fs::remove_file(&path).await.path(path)?;
+        Ok(None)
+    }
+}
+
+/// Adds `bytes` to the cache (if not already present),
+/// and places them at `dest`.
+pub async fn store(bytes: &[u8], dest: &Path) -> Result<(), IoError> {
+    let hash = format!("{:x}", sha1::Sha1::digest(bytes));
+    let path = object_path(&hash).expect("sha1 digest should be valid hex");
+
+    if !path.is_file() {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await.path(parent)?;
+        }
+        // Write to a temporary file first, so that
+        // an interrupted write doesn't leave a broken entry
+        let temp = path.with_extension("part");
+        fs::write(&temp, bytes).await.path(&temp)?;
+        fs::rename(&temp, &path).await.path(&path)?;
+    }
+
+    place(&path, dest).await
+}
+
+/// Places a cached file at `dest`, replacing
+/// anything that was there before.
+///
+/// Tries a hardlink first, then a reflink (copy-on-write),
+/// then falls back to a plain copy.
+pub(crate) async fn place(cached: &Path, dest: &Path) -> Result<(), IoError> {
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent).await.path(parent)?;
+    }
+    if fs::try_exists(dest).await.path(dest)? {
+        fs::remove_file(dest).await.path(dest)?;
+    }
+
+    if fs::hard_link(cached, dest).await.is_ok() {
+        return Ok(());
+    }
+    place_copy(cached, dest).await
+}
+
+async fn place_copy(cached: &Path, dest: &Path) -> Result<(), IoError> {
+    let (cached, dest) = (cached.to_owned(), dest.to_owned());
+    tokio::task::spawn_blocking(move || reflink_copy::reflink_or_copy(&cached, &dest).path(dest))
+        .await
+        .expect("reflink task shouldn't panic")?;
+    Ok(())
+}
+
+/// Deletes files from the shared content cache
+/// that aren't used by any instance or server.
+///
+/// Returns the number of bytes freed.
+pub async fn clean_cache() -> Result<u64, IoError> {
+    clean_cache_in(&LAUNCHER_DIR).await
+}
+
+async fn clean_cache_in(launcher_dir: &Path) -> Result<u64, IoError> {
+    let cache_dir = launcher_dir.join(CACHE_DIR);
+    if !cache_dir.is_dir() {
+        return Ok(0);
+    }
+    info!("Cleaning up content cache");
+
+    let mut objects = Vec::new();
+    let mut cache_ids = HashSet::new();
+    let mut cache_sizes = HashSet::new();
+
+    let mut cache = fs::read_dir(&cache_dir).await.path(&cache_dir)?;
+    while let Some(next) = cache.next_entry().await.path(&cache_dir)? {
+        let object_dir_path = next.path();
+        if !object_dir_path.is_dir() {
+            continue;
+        }
+        let mut object_dir = fs::read_dir(&object_dir_path)
+            .await
+            .path(&object_dir_path)?;
+        while let Some(object) = object_dir.next_entry().await.path(&object_dir_path)? {
+            let path = object.path();
+            let metadata = object.metadata().await.path(&path)?;
+            if let Some(id) = file_id(&metadata) {
+                cache_ids.insert(id);
+            }
+            cache_sizes.insert(metadata.len());
+            objects.push((path, object.file_name(), metadata));
+        }
+    }
+
+    let (used_hashes, used_ids) = get_used_files(launcher_dir, &cache_ids, &cache_sizes).await?;
+
+    let mut cleaned_size = 0;
+    for (path, name, metadata) in objects {
+        let is_used = file_id(&metadata).is_some_and(|n| used_ids.contains(&n))
+            || used_hashes.contains(&*name.to_string_lossy());
+        if !is_used {
+            cleaned_size += metadata.len();
+            fs::remove_file(&path).await.path(path)?;
+        }
+    }
+
+    // Remove empty object dirs
+    let mut cache = fs::read_dir(&cache_dir).await.path(&cache_dir)?;
+    while let Some(next) = cache.next_entry().await.path(&cache_dir)? {
+        let path = next.path();
+        let mut dir = fs::read_dir(&path).await.path(&path)?;
+        if dir.next_entry().await.path(&path)?.is_none() {
+            fs::remove_dir(&path).await.path(path)?;
+        }
+    }
+
+    pt!("Cleaned {:.1} MB", cleaned_size as f64 / (1024.0 * 1024.0));
+    Ok(cleaned_size)
+}
+
+/// Finds the files used by instances and servers.
+///
+/// Files that are hardlinked to a cache entry are identified
+/// by their inode (in `cache_ids`), everything else
+/// (reflinks/copies) is hashed, unless no cache entry
+/// has the same size (in `cache_sizes`).
+async fn get_used_files(
+    launcher_dir: &Path,
+    cache_ids: &HashSet<(u64, u64)>,
+    cache_sizes: &HashSet<u64>,
+) -> Result<(HashSet<String>, HashSet<(u64, u64)>), IoError> {
+    let mut hashes = HashSet::new();
+    let mut ids = HashSet::new();
+
+    for (kind, dot_minecraft) in [("instances", ".minecraft"), ("servers", "")] {
+        let dir = launcher_dir.join(kind);
+        if !dir.is_dir() {
+            continue;
+        }
+        let mut instances = fs::read_dir(&dir).await.path(&dir)?;
+        while let Some(instance) = instances.next_entry().await.path(&dir)? {
+            let mc_dir = instance.path().join(dot_minecraft);
+            for content_dir in CACHED_DIRS {
+                let content_dir = mc_dir.join(content_dir);
+                if !content_dir.is_dir() {
+                    continue;
+                }
+                let mut files = fs::read_dir(&content_dir).await.path(&content_dir)?;
+                while let Some(file) = files.next_entry().await.path(&content_dir)? {
+                    let path = file.path();
+                    let metadata = file.metadata().await.path(&path)?;
+                    if !metadata.is_file() {
+                        continue;
+                    }
+                    if let Some(id) = file_id(&metadata).filter(|n| cache_ids.contains(n)) {
+                        ids.insert(id);
+                        continue;
+                    }
+                    if cache_sizes.contains(&metadata.len()) {
+                        hashes.insert(hash_file(&path).await?);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok((hashes, ids))
+}
+
+/// SHA-1 of a file, without reading it all into memory.
+async fn hash_file(path: &Path) -> Result<String, IoError> {
+    let mut file = fs::File::open(path).await.path(path)?;
+    let mut hasher = sha1::Sha1::new();
+    let mut buf = vec![0; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf).await.path(path)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+#[cfg(unix)]
+#[allow(clippy::unnecessary_wraps)]
+fn file_id(metadata: &Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_id(_: &Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache_entry(launcher_dir: &Path, bytes: &[u8]) -> PathBuf {
+        let hash = format!("{:x}", sha1::Sha1::digest(bytes));
+        let path = launcher_dir.join(CACHE_DIR).join(&hash[..2]).join(hash);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    #[tokio::test]
+    async fn place_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let cached = cache_entry(dir.path(), b"mod");
+        let dest = dir.path().join("mods/a.jar");
+        std::fs::create_dir_all(dest.parent().unwrap()).unwrap();
+        std::fs::write(&dest, b"old version").unwrap();
+
+        place(&cached, &dest).await.unwrap();
+        assert_eq!(std::fs::read(&dest).unwrap(), b"mod");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            let (a, b) = (cached.metadata().unwrap(), dest.metadata().unwrap());
+            assert_eq!((a.dev(), a.ino()), (b.dev(), b.ino()));
+        }
+
+        // Copies are independent of the cache
+        let copy = dir.path().join("mods/b.jar");
+        place_copy(&cached, &copy).await.unwrap();
+        std::fs::write(&copy, b"edited").unwrap();
+        assert_eq!(std::fs::read(&cached).unwrap(), b"mod");
+    }
+
+    #[tokio::test]
+    async fn clean_keeps_used() {
+        let dir = tempfile::tempdir().unwrap();
+        let linked = cache_entry(dir.path(), b"linked");
+        let copied = cache_entry(dir.path(), b"copied");
+        let server = cache_entry(dir.path(), b"server");
+        let unused = cache_entry(dir.path(), b"unused");
+
+        let mods = dir.path().join("instances/Main/.minecraft/mods");
+        std::fs::create_dir_all(&mods).unwrap();
+        place(&linked, &mods.join("linked.jar")).await.unwrap();
+        place_copy(&copied, &mods.join("copied.jar")).await.unwrap();
+        std::fs::write(mods.join("other.jar"), b"not cached").unwrap();
+
+        let server_mods = dir.path().join("servers/Server/mods");
+        std::fs::create_dir_all(&server_mods).unwrap();
+        place_copy(&server, &server_mods.join("server.jar"))
+            .await
+            .unwrap();
+
+        let cleaned = clean_cache_in(dir.path()).await.unwrap();
+        assert_eq!(cleaned, b"unused".len() as u64);
+        assert!(linked.is_file());
+        assert!(copied.is_file());
+        assert!(server.is_file());
+        assert!(!unused.exists());
+        assert!(!unused.parent().unwrap().exists());
+    }
+}
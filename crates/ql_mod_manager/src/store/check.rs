@@ -0,0 +1,824 @@
+//! Offline sanity checks for an instance's mod set.
+//!
+//! Combines the [`ModIndex`] with the metadata inside
+//! each enabled jar (see [`JarMetadata`]) to find problems
+//! that would otherwise only show up as a crash on launch:
+//!
+//! - Missing required dependencies
+//! - Dependencies present, but in an unsupported version
+//! - Mods that declare they break (or conflict with) each other
+//! - The same mod id provided by more than one jar
+//! - Mods made for a different loader
+//!
+//! No network access is needed.
+
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    fmt::Display,
+    hash::{DefaultHasher, Hash, Hasher},
+    path::Path,
+};
+
+use ql_core::{err, info, json::VersionDetails, pt, InstanceSelection, IntoIoError, Loader};
+
+use serde::{Deserialize, Serialize};
+
+use crate::loaders::fabric::version_compare::compare_versions;
+
+use super::{get_loader, DependencyKind, JarMetadata, MetadataFormat, ModError, ModIndex};
+
+/// A problem found in an instance's mods.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModProblem {
+    /// `mod_name` requires `dependency`, which isn't installed
+    /// (or is disabled).
+    MissingDependency {
+        mod_name: String,
+        dependency: String,
+        versions: Vec<String>,
+    },
+    /// `mod_name` requires `dependency` in one of `versions`,
+    /// but `found` is installed.
+    WrongDependencyVersion {
+        mod_name: String,
+        dependency: String,
+        versions: Vec<String>,
+        found: String,
+    },
+    /// `mod_name` declares it doesn't work with `other`.
+    ///
+    /// If `fatal` is `false`, the game still launches
+    /// but may have issues (`conflicts`/`discouraged`).
+    Incompatible {
+        mod_name: String,
+        other: String,
+        fatal: bool,
+    },
+    /// More than one jar provides the mod `id`.
+    DuplicateId { id: String, files: Vec<String> },
+    /// `mod_name` is made for `made_for`,
+    /// not the instance's loader.
+    WrongLoader {
+        mod_name: String,
+        made_for: &'static str,
+        loader: Loader,
+    },
+}
+
+impl ModProblem {
+    /// Whether this problem will (most likely)
+    /// stop the game from launching.
+    #[must_use]
+    pub fn is_fatal(&self) -> bool {
+        match self {
+            ModProblem::Incompatible { fatal, .. } => *fatal,
+            ModProblem::MissingDependency { .. }
+            | ModProblem::WrongDependencyVersion { .. }
+            | ModProblem::DuplicateId { .. }
+            | ModProblem::WrongLoader { .. } => true,
+        }
+    }
+}
+
+impl Display for ModProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ModProblem::MissingDependency {
+                mod_name,
+                dependency,
+                versions,
+            } => {
+                write!(f, "{mod_name} requires {dependency}")?;
+                if !versions.is_empty() {
+                    write!(f, " ({})", versions.join(" or "))?;
+                }
+                write!(f, ", which is not installed")
+            }
+            ModProblem::WrongDependencyVersion {
+                mod_name,
+                dependency,
+                versions,
+                found,
+            } => write!(
+                f,
+                "{mod_name} requires {dependency} {}, but {found} is installed",
+                versions.join(" or ")
+            ),
+            ModProblem::Incompatible {
+                mod_name,
+                other,
+                fatal: true,
+            } => write!(f, "{mod_name} is incompatible with {other}"),
+            ModProblem::Incompatible {
+                mod_name,
+                other,
+                fatal: false,
+            } => write!(f, "{mod_name} may have issues with {other}"),
+            ModProblem::DuplicateId { id, files } => write!(
+                f,
+                "Mod {id} is installed more than once: {}",
+                files.join(", ")
+            ),
+            ModProblem::WrongLoader {
+                mod_name,
+                made_for,
+                loader,
+            } => write!(
+                f,
+                "{mod_name} is made for {made_for}, but this instance uses {loader:?}"
+            ),
+        }
+    }
+}
+
+/// A mod jar to be checked by [`find_problems`].
+#[derive(Debug, Clone)]
+pub struct CheckedJar {
+    /// The file name, in the `mods/` directory.
+    pub file_name: String,
+    /// The name shown in problems. Usually the store name
+    /// (if any), else the name from the jar metadata.
+    pub name: String,
+    pub metadata: JarMetadata,
+}
+
+/// Checks the enabled mods of an instance for problems.
+///
+/// Jars without recognizable metadata are skipped, except
+/// for store-installed dependencies tracked in the [`ModIndex`].
+///
+/// Vanilla instances, and instances without a `mods/`
+/// directory (eg: never launched yet), have no problems.
+pub async fn check_mods(instance: &InstanceSelection) -> Result<Vec<ModProblem>, ModError> {
+    let loader = get_loader(instance).await?;
+    let mods_dir = instance.get_dot_minecraft_path().join("mods");
+    if loader.is_none() || !mods_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let index = ModIndex::load(instance).await?;
+    let version_json = VersionDetails::load(instance).await?;
+
+    let mut file_names = Vec::new();
+    let mut dir = tokio::fs::read_dir(&mods_dir).await.path(&mods_dir)?;
+    while let Some(entry) = dir.next_entry().await.path(&mods_dir)? {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.ends_with(".jar") && entry.path().is_file() {
+            file_names.push(name);
+        }
+    }
+    file_names.sort();
+
+    let mut jars = Vec::new();
+    let mut jars_without_metadata = Vec::new();
+    for file_name in file_names {
+        let store_name = index
+            .mods
+            .values()
+            .find(|n| n.files.iter().any(|f| f.filename == file_name))
+            .map(|n| n.name.clone());
+
+        match JarMetadata::read(&mods_dir.join(&file_name), loader).await {
+            Ok(Some(metadata)) => jars.push(CheckedJar {
+                name: store_name.unwrap_or_else(|| metadata.main().display_name().to_owned()),
+                file_name,
+                metadata,
+            }),
+            Ok(None) => jars_without_metadata.push(file_name),
+            Err(error) => {
+                err!("Couldn't read mod metadata of {file_name}: {error}");
+                jars_without_metadata.push(file_name);
+            }
+        }
+    }
+
+    let mut problems = find_problems(&jars, loader, Some(version_json.get_id()));
+
+    // The jar metadata is more accurate, so the index
+    // is only used for mods we couldn't read.
+    for config in index.mods.values() {
+        let is_unreadable = config
+            .files
+            .iter()
+            .any(|f| jars_without_metadata.contains(&f.filename));
+        if !config.enabled || !is_unreadable {
+            continue;
+        }
+        for dependency in &config.dependencies {
+            if !index.mods.get(dependency).is_some_and(|n| n.enabled) {
+                problems.push(ModProblem::MissingDependency {
+                    mod_name: config.name.clone(),
+                    dependency: dependency.clone(),
+                    versions: Vec::new(),
+                });
+            }
+        }
+    }
+
+    Ok(problems)
+}
+
+/// Logs any problems with an instance's mods.
+///
+/// Meant to be run right before launching, so that
+/// if the game crashes, the user knows why. This never
+/// stops the launch, as the checks can't be perfect.
+///
+/// The results are cached in the instance directory,
+/// so the jars are only read again after the mods
+/// (or the instance's loader/version) change.
+pub async fn log_mod_problems(instance: &InstanceSelection) {
+    let problems = match check_mods_cached(instance).await {
+        Ok(n) => n,
+        Err(error) => {
+            err!("Couldn't check mods for problems: {error}");
+            return;
+        }
+    };
+    if problems.is_empty() {
+        return;
+    }
+    info!("Found {} problem(s) with your mods:", problems.len());
+    for (fatal, problem) in problems {
+        if fatal {
+            err!("{problem}");
+        } else {
+            pt!("{problem}");
+        }
+    }
+}
+
+const CHECK_CACHE_FILE: &str = "mod_check_cache.json";
+
+#[derive(Serialize, Deserialize)]
+struct CheckCache {
+    fingerprint: u64,
+    /// `(is_fatal, message)`
+    problems: Vec<(bool, String)>,
+}
+
+async fn check_mods_cached(instance: &InstanceSelection) -> Result<Vec<(bool, String)>, ModError> {
+    let mods_dir = instance.get_dot_minecraft_path().join("mods");
+    if !mods_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let cache_path = instance.get_instance_path().join(CHECK_CACHE_FILE);
+    let fingerprint = fingerprint_mods(instance, &mods_dir).await?;
+    if let Some(cache) = tokio::fs::read_to_string(&cache_path)
+        .await
+        .ok()
+        .and_then(|n| serde_json::from_str::<CheckCache>(&n).ok())
+        .filter(|n| n.fingerprint == fingerprint)
+    {
+        return Ok(cache.problems);
+    }
+
+    let problems: Vec<(bool, String)> = check_mods(instance)
+        .await?
+        .iter()
+        .map(|n| (n.is_fatal(), n.to_string()))
+        .collect();
+
+    let cache = CheckCache {
+        fingerprint,
+        problems,
+    };
+    match serde_json::to_string(&cache) {
+        Ok(json) => {
+            if let Err(error) = tokio::fs::write(&cache_path, json).await {
+                err!("Couldn't save mod check results: {error}");
+            }
+        }
+        Err(error) => err!("Couldn't save mod check results: {error}"),
+    }
+    Ok(cache.problems)
+}
+
+/// A hash of everything [`check_mods`] looks at:
+/// the names, sizes and modification times of the jars,
+/// and of the mod index and instance config files.
+async fn fingerprint_mods(instance: &InstanceSelection, mods_dir: &Path) -> Result<u64, ModError> {
+    let mut jars = Vec::new();
+    let mut dir = tokio::fs::read_dir(mods_dir).await.path(mods_dir)?;
+    while let Some(entry) = dir.next_entry().await.path(mods_dir)? {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !name.ends_with(".jar") {
+            continue;
+        }
+        let metadata = entry.metadata().await.path(entry.path())?;
+        jars.push((name, metadata.len(), metadata.modified().ok()));
+    }
+    jars.sort();
+
+    let instance_dir = instance.get_instance_path();
+    let mut hasher = DefaultHasher::new();
+    jars.hash(&mut hasher);
+    for path in [
+        instance_dir.join("config.json"),
+        instance_dir.join("details.json"),
+        instance.get_dot_minecraft_path().join("mod_index.json"),
+    ] {
+        let modified = tokio::fs::metadata(&path)
+            .await
+            .ok()
+            .and_then(|n| n.modified().ok());
+        modified.hash(&mut hasher);
+    }
+    Ok(hasher.finish())
+}
+
+/// Finds problems in a set of mod jars.
+///
+/// `mc_version` is the instance's Minecraft version.
+/// Version requirements on Minecraft are skipped if it's
+/// `None` or not a regular release (eg: snapshots, betas).
+#[must_use]
+pub fn find_problems(
+    jars: &[CheckedJar],
+    loader: Option<Loader>,
+    mc_version: Option<&str>,
+) -> Vec<ModProblem> {
+    let mut problems = Vec::new();
+
+    // mod id -> (jar index, version)
+    let mut provided: HashMap<&str, Vec<(usize, Option<&str>)>> = HashMap::new();
+    for (i, jar) in jars.iter().enumerate() {
+        for info in &jar.metadata.mods {
+            let version = info.version.as_deref();
+            provided.entry(&info.id).or_default().push((i, version));
+            for id in &info.provides {
+                provided.entry(id).or_default().push((i, version));
+            }
+        }
+    }
+
+    // Bundled (jar-in-jar) mods only satisfy dependencies.
+    // The loader picks one copy if several jars bundle
+    // the same mod, so they're never duplicates.
+    let mut bundled: HashMap<&str, Option<&str>> = HashMap::new();
+    for info in jars.iter().flat_map(|n| &n.metadata.bundled) {
+        let version = info.version.as_deref();
+        bundled.entry(&info.id).or_insert(version);
+        for id in &info.provides {
+            bundled.entry(id).or_insert(version);
+        }
+    }
+
+    let mut duplicates: Vec<(&str, Vec<String>)> = provided
+        .iter()
+        .filter_map(|(id, providers)| {
+            let mut files: Vec<String> = providers
+                .iter()
+                .map(|(i, _)| jars[*i].file_name.clone())
+                .collect();
+            files.sort();
+            files.dedup();
+            (files.len() > 1).then_some((*id, files))
+        })
+        .collect();
+    duplicates.sort();
+    problems.extend(
+        duplicates
+            .into_iter()
+            .map(|(id, files)| ModProblem::DuplicateId {
+                id: id.to_owned(),
+                files,
+            }),
+    );
+
+    let mc_version = mc_version.filter(|v| is_release_version(v));
+
+    for (i, jar) in jars.iter().enumerate() {
+        if let Some(loader) = loader {
+            if jar.metadata.is_wrong_loader(Some(loader)) {
+                problems.push(ModProblem::WrongLoader {
+                    mod_name: jar.name.clone(),
+                    made_for: jar.metadata.format.loader_name(),
+                    loader,
+                });
+                // Dependencies would just be noise here
+                continue;
+            }
+        }
+
+        for info in &jar.metadata.mods {
+            for dep in &info.dependencies {
+                let builtin = builtin_version(&dep.id, loader, mc_version);
+                let providers = provided.get(dep.id.as_str());
+                let in_range = |version: &str| {
+                    dep.versions.is_empty()
+                        || dep
+                            .versions
+                            .iter()
+                            .any(|range| version_matches(jar.metadata.format, range, version))
+                };
+
+                match dep.kind {
+                    DependencyKind::Required => {
+                        let found = builtin
+                            .or_else(|| providers.and_then(|n| n.first()).map(|(_, v)| *v))
+                            .or_else(|| bundled.get(dep.id.as_str()).copied());
+                        match found {
+                            None => problems.push(ModProblem::MissingDependency {
+                                mod_name: jar.name.clone(),
+                                dependency: dep.id.clone(),
+                                versions: dep.versions.clone(),
+                            }),
+                            // Unknown versions are given the benefit of the doubt
+                            Some(Some(version)) if !in_range(version) => {
+                                problems.push(ModProblem::WrongDependencyVersion {
+                                    mod_name: jar.name.clone(),
+                                    dependency: dep.id.clone(),
+                                    versions: dep.versions.clone(),
+                                    found: version.to_owned(),
+                                });
+                            }
+                            Some(_) => {}
+                        }
+                    }
+                    DependencyKind::Optional => {}
+                    DependencyKind::Breaks | DependencyKind::Conflicts => {
+                        // Here the range is the versions that *don't* work.
+                        // A jar can't break itself.
+                        let other = providers.and_then(|n| n.iter().find(|(n, _)| *n != i));
+                        let (other_name, version) = match (builtin, other) {
+                            (Some(version), _) => (dep.id.clone(), version),
+                            (None, Some((n, version))) => (jars[*n].name.clone(), *version),
+                            (None, None) => continue,
+                        };
+                        let is_match = match version {
+                            Some(version) => in_range(version),
+                            None => dep.versions.is_empty(),
+                        };
+                        if is_match {
+                            problems.push(ModProblem::Incompatible {
+                                mod_name: jar.name.clone(),
+                                other: other_name,
+                                fatal: dep.kind == DependencyKind::Breaks,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    problems
+}
+
+/// Ids that are provided by the game or loader itself,
+/// rather than a mod jar.
+///
+/// Returns `Some(None)` if the id is present
+/// but its version is unknown.
+fn builtin_version<'a>(
+    id: &str,
+    loader: Option<Loader>,
+    mc_version: Option<&'a str>,
+) -> Option<Option<&'a str>> {
+    match id {
+        "minecraft" => Some(mc_version),
+        "java" => Some(None),
+        "fabricloader" => matches!(loader, Some(Loader::Fabric | Loader::Quilt)).then_some(None),
+        "quilt_loader" => matches!(loader, Some(Loader::Quilt)).then_some(None),
+        "forge" | "Forge" | "FML" | "mcp" => matches!(loader, Some(Loader::Forge)).then_some(None),
+        "neoforge" => matches!(loader, Some(Loader::Neoforge)).then_some(None),
+        "fml" | "javafml" | "lowcodefml" | "mclanguage" => {
+            matches!(loader, Some(Loader::Forge | Loader::Neoforge)).then_some(None)
+        }
+        _ => None,
+    }
+}
+
+/// Regular releases, eg: `1.20.1`.
+/// Snapshots and old alphas/betas don't follow
+/// the versioning mods expect.
+fn is_release_version(version: &str) -> bool {
+    version.contains('.')
+        && version
+            .split('.')
+            .all(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+}
+
+fn version_matches(format: MetadataFormat, range: &str, version: &str) -> bool {
+    match format {
+        MetadataFormat::Fabric | MetadataFormat::Quilt => fabric_predicate_matches(range, version),
+        MetadataFormat::ModsToml | MetadataFormat::NeoforgeModsToml | MetadataFormat::McmodInfo => {
+            maven_range_matches(range, version)
+        }
+    }
+}
+
+/// Fabric/Quilt version predicates, eg:
+/// `*`, `>=1.2.0 <2`, `~1.20`, `^0.5.0`, `1.20.x`
+fn fabric_predicate_matches(predicate: &str, version: &str) -> bool {
+    predicate.split_whitespace().all(|term| {
+        let (op, target) = [">=", "<=", ">", "<", "=", "~", "^"]
+            .iter()
+            .find_map(|op| term.strip_prefix(op).map(|rest| (*op, rest)))
+            .unwrap_or(("", term));
+
+        if target == "*" || target.is_empty() {
+            return true;
+        }
+        if let Some(prefix) = target
+            .strip_suffix(".x")
+            .or_else(|| target.strip_suffix(".X"))
+            .or_else(|| target.strip_suffix(".*"))
+        {
+            return version == prefix || version.starts_with(&format!("{prefix}."));
+        }
+
+        let cmp = compare_versions(version, target);
+        match op {
+            ">=" => cmp.is_ge(),
+            "<=" => cmp.is_le(),
+            ">" => cmp.is_gt(),
+            "<" => cmp.is_lt(),
+            "~" => cmp.is_ge() && compare_versions(version, &bump(target, 1)).is_lt(),
+            "^" => {
+                let major = target
+                    .split(['.', '-', '+'])
+                    .position(|n| n != "0")
+                    .unwrap_or(0);
+                cmp.is_ge() && compare_versions(version, &bump(target, major)).is_lt()
+            }
+            _ => cmp.is_eq(),
+        }
+    })
+}
+
+/// Increments the version component at `index`
+/// and drops everything after it.
+/// `bump("1.20.4", 1) == "1.21"`
+fn bump(version: &str, index: usize) -> String {
+    let core = version.split(['-', '+']).next().unwrap_or(version);
+    let mut parts: Vec<u64> = core
+        .split('.')
+        .map(|n| n.parse().unwrap_or_default())
+        .collect();
+    let index = index.min(parts.len().saturating_sub(1));
+    parts.truncate(index + 1);
+    parts[index] += 1;
+    parts
+        .iter()
+        .map(u64::to_string)
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Maven version ranges (used by Forge/NeoForge), eg:
+/// `[1.20.1,1.21)`, `[47,)`, `[1.0]`, `(,1.0],[1.2,)`
+///
+/// A bare version like `1.0` is only a recommendation
+/// in Maven, so it matches anything.
+fn maven_range_matches(range: &str, version: &str) -> bool {
+    let range = range.trim();
+    if range.is_empty() || range == "*" || !range.starts_with(['[', '(']) {
+        return true;
+    }
+
+    let mut rest = range;
+    while let Some(start) = rest.find(['[', '(']) {
+        let Some(end) = rest[start..].find([']', ')']).map(|n| n + start) else {
+            break;
+        };
+        let inclusive_start = rest[start..].starts_with('[');
+        let inclusive_end = rest[end..].starts_with(']');
+        let inner = &rest[start + 1..end];
+
+        let is_match = match inner.split_once(',') {
+            None => compare_versions(version, inner.trim()).is_eq(),
+            Some((low, high)) => {
+                let (low, high) = (low.trim(), high.trim());
+                let above = low.is_empty()
+                    || match compare_versions(version, low) {
+                        Ordering::Greater => true,
+                        Ordering::Equal => inclusive_start,
+                        Ordering::Less => false,
+                    };
+                let below = high.is_empty()
+                    || match compare_versions(version, high) {
+                        Ordering::Less => true,
+                        Ordering::Equal => inclusive_end,
+                        Ordering::Greater => false,
+                    };
+                above && below
+            }
+        };
+        if is_match {
+            return true;
+        }
+        rest = &rest[end + 1..];
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::mod_metadata::tests::make_jar;
+
+    fn jar(file_name: &str, files: &[(&str, &[u8])], loader: Loader) -> CheckedJar {
+        let metadata = JarMetadata::from_bytes(&make_jar(files), Some(loader))
+            .unwrap()
+            .unwrap();
+        CheckedJar {
+            file_name: file_name.to_owned(),
+            name: metadata.main().display_name().to_owned(),
+            metadata,
+        }
+    }
+
+    fn fabric_jar(file_name: &str, json: &str) -> CheckedJar {
+        jar(
+            file_name,
+            &[("fabric.mod.json", json.as_bytes())],
+            Loader::Fabric,
+        )
+    }
+
+    #[test]
+    fn predicates() {
+        assert!(fabric_predicate_matches("*", "1.0"));
+        assert!(fabric_predicate_matches(">=0.15.0", "0.16.2"));
+        assert!(!fabric_predicate_matches(">=0.15.0", "0.14.9"));
+        assert!(fabric_predicate_matches(">=1.20 <1.21", "1.20.4"));
+        assert!(!fabric_predicate_matches(">=1.20 <1.21", "1.21.1"));
+        assert!(fabric_predicate_matches("~1.20.1", "1.20.4"));
+        assert!(!fabric_predicate_matches("~1.20.1", "1.21"));
+        assert!(fabric_predicate_matches("^0.5.0", "0.5.8"));
+        assert!(!fabric_predicate_matches("^0.5.0", "0.6.0"));
+        assert!(fabric_predicate_matches("1.20.x", "1.20.1"));
+        assert!(!fabric_predicate_matches("1.20.x", "1.21"));
+
+        assert!(maven_range_matches("[1.20.1,1.21)", "1.20.4"));
+        assert!(!maven_range_matches("[1.20.1,1.21)", "1.21"));
+        assert!(maven_range_matches("[47,)", "47.2.0"));
+        assert!(!maven_range_matches("[1.0]", "1.1"));
+        assert!(maven_range_matches("(,1.0],[1.2,)", "1.3"));
+        assert!(!maven_range_matches("(,1.0],[1.2,)", "1.1"));
+        assert!(maven_range_matches("1.0", "0.1"));
+    }
+
+    #[test]
+    fn missing_and_wrong_version() {
+        let jars = [
+            fabric_jar(
+                "a.jar",
+                r#"{ "id": "a", "version": "1.0.0", "depends": {
+                    "fabricloader": ">=0.15", "minecraft": "1.20.x",
+                    "b": ">=2.0.0", "c": "*"
+                } }"#,
+            ),
+            fabric_jar("b.jar", r#"{ "id": "b", "version": "1.5.0" }"#),
+        ];
+
+        let problems = find_problems(&jars, Some(Loader::Fabric), Some("1.20.1"));
+        assert_eq!(
+            problems,
+            [
+                ModProblem::WrongDependencyVersion {
+                    mod_name: "a".to_owned(),
+                    dependency: "b".to_owned(),
+                    versions: vec![">=2.0.0".to_owned()],
+                    found: "1.5.0".to_owned(),
+                },
+                ModProblem::MissingDependency {
+                    mod_name: "a".to_owned(),
+                    dependency: "c".to_owned(),
+                    versions: vec!["*".to_owned()],
+                },
+            ]
+        );
+
+        let problems = find_problems(&jars[..1], Some(Loader::Fabric), Some("1.21"));
+        assert!(problems.iter().any(|n| matches!(
+            n,
+            ModProblem::WrongDependencyVersion { dependency, .. } if dependency == "minecraft"
+        )));
+    }
+
+    #[test]
+    fn breaks_and_duplicates() {
+        let jars = [
+            fabric_jar(
+                "sodium.jar",
+                r#"{ "id": "sodium", "version": "0.5.8", "breaks": { "optifabric": "*" },
+                    "conflicts": { "other": "<1.0" } }"#,
+            ),
+            fabric_jar(
+                "optifabric.jar",
+                r#"{ "id": "optifabric", "version": "1.0" }"#,
+            ),
+            fabric_jar("other.jar", r#"{ "id": "other", "version": "2.0" }"#),
+            fabric_jar(
+                "sodium-old.jar",
+                r#"{ "id": "sodium", "version": "0.4.0" }"#,
+            ),
+        ];
+
+        let problems = find_problems(&jars, Some(Loader::Fabric), None);
+        assert_eq!(
+            problems,
+            [
+                ModProblem::DuplicateId {
+                    id: "sodium".to_owned(),
+                    files: vec!["sodium-old.jar".to_owned(), "sodium.jar".to_owned()],
+                },
+                ModProblem::Incompatible {
+                    mod_name: "sodium".to_owned(),
+                    other: "optifabric".to_owned(),
+                    fatal: true,
+                },
+            ]
+        );
+        assert!(problems.iter().all(ModProblem::is_fatal));
+    }
+
+    #[test]
+    fn bundled_mods() {
+        let module = make_jar(&[(
+            "fabric.mod.json",
+            br#"{ "id": "fabric-api-base", "version": "0.4.31" }"#,
+        )]);
+        let library = make_jar(&[(
+            "fabric.mod.json",
+            br#"{ "id": "mixinextras", "version": "0.3.5" }"#,
+        )]);
+        let jars = [
+            jar(
+                "fabric-api.jar",
+                &[
+                    (
+                        "fabric.mod.json",
+                        br#"{ "id": "fabric-api", "version": "0.92.0",
+                            "jars": [{ "file": "META-INF/jars/fabric-api-base.jar" }] }"#,
+                    ),
+                    ("META-INF/jars/fabric-api-base.jar", &module),
+                ],
+                Loader::Fabric,
+            ),
+            jar(
+                "other.jar",
+                &[
+                    (
+                        "fabric.mod.json",
+                        br#"{ "id": "other", "version": "1.0",
+                            "depends": { "fabric-api-base": ">=0.4", "mixinextras": "*" } }"#,
+                    ),
+                    ("META-INF/jars/mixinextras.jar", &library),
+                ],
+                Loader::Fabric,
+            ),
+            fabric_jar(
+                "another.jar",
+                r#"{ "id": "another", "version": "1.0", "depends": { "mixinextras": "*" },
+                    "jars": [{ "file": "META-INF/jars/mixinextras.jar" }] }"#,
+            ),
+        ];
+
+        assert_eq!(jars[0].metadata.bundled.len(), 1);
+        assert!(find_problems(&jars, Some(Loader::Fabric), None).is_empty());
+    }
+
+    #[test]
+    fn wrong_loader() {
+        let jars = [
+            jar(
+                "forge_mod.jar",
+                &[(
+                    "META-INF/mods.toml",
+                    br#"
+                    modLoader = "javafml"
+                    loaderVersion = "[47,)"
+                    [[mods]]
+                    modId = "forge_mod"
+                    version = "1.0"
+                    [[dependencies.forge_mod]]
+                    modId = "forge"
+                    mandatory = true
+                    versionRange = "[47,)"
+                    "#,
+                )],
+                Loader::Forge,
+            ),
+            fabric_jar(
+                "fabric_mod.jar",
+                r#"{ "id": "fabric_mod", "version": "1.0" }"#,
+            ),
+        ];
+
+        assert_eq!(
+            find_problems(&jars, Some(Loader::Fabric), Some("1.20.1")),
+            [ModProblem::WrongLoader {
+                mod_name: "forge_mod".to_owned(),
+                made_for: "Forge",
+                loader: Loader::Fabric,
+            }]
+        );
+        assert!(find_problems(&jars[..1], Some(Loader::Forge), Some("1.20.1")).is_empty());
+    }
+}
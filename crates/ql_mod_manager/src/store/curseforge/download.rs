@@ -10,8 +10,9 @@ use ql_core::{
 
 use crate::store::{
     curseforge::{get_query_type, ModQuery},
-    get_loader, get_mods_resourcepacks_shaderpacks_dir, install_modpack, CurseforgeNotAllowed,
-    ModConfig, ModError, ModFile, ModIndex, QueryType, SOURCE_ID_CURSEFORGE,
+    download_verified, get_loader, get_mods_resourcepacks_shaderpacks_dir, install_modpack,
+    set_icon_from_store, CurseforgeNotAllowed, ModConfig, ModError, ModFile, ModIndex, QueryType,
+    SOURCE_ID_CURSEFORGE,
 };
 
 use super::Mod;
@@ -132,6 +133,8 @@ impl<'a> ModDownloader<'a> {
                         .map_err(Box::new)?
                 {
                     self.not_allowed.extend(not_allowed_new);
+                    let icon_url = response.logo.as_ref().map(|n| n.url.as_str());
+                    set_icon_from_store(&self.instance, icon_url).await;
                 } else {
                     err!("Invalid modpack downloaded from curseforge! Corrupted?");
                 }
@@ -141,7 +144,7 @@ impl<'a> ModDownloader<'a> {
         };
 
         let file_dir = dir.join(&file_query.data.fileName);
-        file_utils::download_file_to_path(&url, true, &file_dir).await?;
+        download_verified(&url, &file_dir, &file_query.data.get_hashes()).await?;
 
         let id_str = response.id.to_string();
         let id_mod = ModId::Curseforge(id_str.clone());
@@ -196,6 +199,7 @@ impl<'a> ModDownloader<'a> {
                 project_source: SOURCE_ID_CURSEFORGE.to_owned(),
                 project_id: id_index_str.clone(),
                 files: vec![ModFile {
+                    hashes: file_query.data.get_hashes(),
                     url,
                     filename: file_query.data.fileName,
                     primary: true,
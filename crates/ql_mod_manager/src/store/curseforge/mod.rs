@@ -14,7 +14,10 @@ use serde::Deserialize;
 
 use crate::{rate_limiter::RATE_LIMITER, store::SearchMod};
 
-use super::{Backend, CurseforgeNotAllowed, ModError, QueryType, SearchResult};
+use super::{
+    Backend, CurseforgeNotAllowed, LatestVersion, ModError, ModHashes, QueryType, ReleaseChannel,
+    SearchResult,
+};
 use categories::get_categories;
 use ql_core::file_utils::check_for_success;
 
@@ -138,6 +141,32 @@ pub struct CurseforgeFile {
     pub fileDate: String,
     pub displayName: String,
     pub fileLength: u64,
+    /// 1: Release, 2: Beta, 3: Alpha
+    #[serde(default)]
+    pub releaseType: u8,
+    #[serde(default)]
+    pub hashes: Vec<CurseforgeHash>,
+}
+
+impl CurseforgeFile {
+    #[must_use]
+    pub fn get_hashes(&self) -> ModHashes {
+        // Algorithm 1 is SHA-1, 2 is MD5
+        ModHashes {
+            sha512: None,
+            sha1: self
+                .hashes
+                .iter()
+                .find(|n| n.algo == 1)
+                .map(|n| n.value.clone()),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct CurseforgeHash {
+    pub value: String,
+    pub algo: u8,
 }
 
 #[derive(Deserialize, Clone, Debug)]
@@ -265,16 +294,16 @@ impl Backend for CurseforgeBackend {
         Ok((ModId::Curseforge(id.to_string()), description.data))
     }
 
-    async fn get_latest_version_date(
+    async fn get_latest_version(
         id: &str,
         version: &str,
         loader: Option<ql_core::Loader>,
-    ) -> Result<(DateTime<chrono::FixedOffset>, String), ModError> {
+    ) -> Result<LatestVersion, ModError> {
         let response = ModQuery::load(id).await?;
         let loader = loader.map(|n| n.to_curseforge());
 
         let query_type = get_query_type(response.data.classId).await?;
-        let (file_query, _) = response
+        let (file_query, file_id) = response
             .data
             .get_file(
                 response.data.name.clone(),
@@ -285,9 +314,28 @@ impl Backend for CurseforgeBackend {
             )
             .await?;
 
-        let download_version_time = DateTime::parse_from_rfc3339(&file_query.data.fileDate)?;
-
-        Ok((download_version_time, response.data.name))
+        let release_time = DateTime::parse_from_rfc3339(&file_query.data.fileDate)?;
+        let file = file_query.data;
+
+        Ok(LatestVersion {
+            version_id: file_id.to_string(),
+            // Curseforge doesn't have version numbers,
+            // the display name is the closest thing
+            version_number: file.displayName.clone(),
+            name: file.displayName,
+            release_time,
+            channel: match file.releaseType {
+                2 => ReleaseChannel::Beta,
+                3 => ReleaseChannel::Alpha,
+                _ => ReleaseChannel::Release,
+            },
+            changelog: None,
+            dependencies: file
+                .dependencies
+                .iter()
+                .map(|n| format!("CF:{}", n.modId))
+                .collect(),
+        })
     }
 
     async fn download(
@@ -360,6 +408,24 @@ impl Backend for CurseforgeBackend {
     }
 }
 
+impl CurseforgeBackend {
+    /// Gets the changelog of a mod file, as HTML.
+    pub async fn get_changelog(mod_id: &str, file_id: &str) -> Result<String, ModError> {
+        #[derive(Deserialize)]
+        struct Response {
+            data: String,
+        }
+
+        let response = send_request(
+            &format!("mods/{mod_id}/files/{file_id}/changelog"),
+            &HashMap::new(),
+        )
+        .await?;
+        let response: Response = serde_json::from_str(&response).json(response)?;
+        Ok(response.data)
+    }
+}
+
 pub async fn send_request(
     api: &str,
     params: &HashMap<&str, String>,
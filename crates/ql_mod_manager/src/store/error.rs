@@ -37,6 +37,8 @@ pub enum ModError {
     Pack(#[from] Box<PackError>),
     #[error("{MOD_ERR_PREFIX}not a valid modpack or QMP preset!")]
     NotValidPack,
+    #[error("{MOD_ERR_PREFIX}downloaded file {0} is corrupted (hash mismatch)")]
+    HashMismatch(String),
 }
 
 impl_3_errs_jri!(ModError, Json, RequestError, Io);
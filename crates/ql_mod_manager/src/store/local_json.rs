@@ -135,7 +135,11 @@ impl ModIndex {
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ModFile {
-    // pub hashes: ModHashes,
+    /// Expected hashes of the file, used to verify
+    /// downloads. Empty for mods installed with
+    /// older launcher versions.
+    #[serde(default)]
+    pub hashes: ModHashes,
     pub url: String,
     pub filename: String,
     pub primary: bool,
@@ -143,8 +147,37 @@ pub struct ModFile {
     // pub file_type: Option<String>,
 }
 
-// #[derive(Serialize, Deserialize, Debug, Clone)]
-// pub struct ModHashes {
-//     pub sha512: String,
-//     pub sha1: String,
-// }
+/// Hashes of a mod file, as hex strings.
+///
+/// Modrinth provides both, Curseforge only provides `sha1`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ModHashes {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha512: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sha1: Option<String>,
+}
+
+impl ModHashes {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.sha512.is_none() && self.sha1.is_none()
+    }
+
+    /// Checks whether `bytes` match the expected hashes.
+    /// The strongest available hash is used.
+    ///
+    /// Returns `true` if no hashes are known.
+    #[must_use]
+    pub fn matches(&self, bytes: &[u8]) -> bool {
+        use sha2::Digest;
+
+        if let Some(expected) = &self.sha512 {
+            format!("{:x}", sha2::Sha512::digest(bytes)).eq_ignore_ascii_case(expected)
+        } else if let Some(expected) = &self.sha1 {
+            format!("{:x}", sha1::Sha1::digest(bytes)).eq_ignore_ascii_case(expected)
+        } else {
+            true
+        }
+    }
+}
@@ -8,28 +8,40 @@ use ql_core::{
 };
 
 mod add_file;
+mod cache;
+mod check;
 mod curseforge;
 mod delete;
 mod error;
 mod image;
 mod local_json;
+mod mod_metadata;
 mod modpack;
 mod modrinth;
 mod recommended;
 mod toggle;
 mod update;
+mod verify;
 
 pub use add_file::add_files;
+pub use cache::clean_cache;
+pub use check::{check_mods, find_problems, log_mod_problems, CheckedJar, ModProblem};
 pub use curseforge::CurseforgeBackend;
 pub use delete::delete_mods;
 pub use error::{GameExpectation, ModError};
 pub use image::{download_image, ImageResult};
-pub use local_json::{ModConfig, ModFile, ModIndex};
+pub use local_json::{ModConfig, ModFile, ModHashes, ModIndex};
+pub use mod_metadata::{
+    read_local_mods_metadata, DependencyKind, JarMetadata, JarModInfo, MetadataFormat,
+    ModDependency, ModEnvironment,
+};
+use modpack::set_icon_from_store;
 pub use modpack::{install_modpack, PackError};
 pub use modrinth::ModrinthBackend;
 pub use recommended::{RecommendedMod, RECOMMENDED_MODS};
 pub use toggle::{flip_filename, toggle_mods, toggle_mods_local};
-pub use update::{apply_updates, check_for_updates};
+pub use update::{apply_updates, check_for_updates, ModUpdate};
+pub use verify::{download_verified, verify_mods, VerifyReport};
 
 pub const SOURCE_ID_MODRINTH: &str = "modrinth";
 pub const SOURCE_ID_CURSEFORGE: &str = "curseforge";
@@ -52,11 +64,13 @@ pub trait Backend {
     ///
     /// This supports both Markdown and HTML.
     async fn get_description(id: &str) -> Result<(ModId, String), ModError>;
-    async fn get_latest_version_date(
+    /// Gets the newest version of a mod compatible
+    /// with the given Minecraft version and loader.
+    async fn get_latest_version(
         id: &str,
         version: &str,
         loader: Option<Loader>,
-    ) -> Result<(DateTime<chrono::FixedOffset>, String), ModError>;
+    ) -> Result<LatestVersion, ModError>;
 
     async fn download(
         id: &str,
@@ -138,16 +152,14 @@ pub async fn download_mods_bulk(
     Ok(not_allowed)
 }
 
-pub async fn get_latest_version_date(
+pub async fn get_latest_version(
     loader: Option<Loader>,
     mod_id: &ModId,
     version: &str,
-) -> Result<(DateTime<chrono::FixedOffset>, String), ModError> {
+) -> Result<LatestVersion, ModError> {
     Ok(match mod_id {
-        ModId::Modrinth(n) => ModrinthBackend::get_latest_version_date(n, version, loader).await?,
-        ModId::Curseforge(n) => {
-            CurseforgeBackend::get_latest_version_date(n, version, loader).await?
-        }
+        ModId::Modrinth(n) => ModrinthBackend::get_latest_version(n, version, loader).await?,
+        ModId::Curseforge(n) => CurseforgeBackend::get_latest_version(n, version, loader).await?,
     })
 }
 
@@ -256,6 +268,49 @@ pub struct SearchMod {
     pub icon_url: String,
 }
 
+/// The newest version of a mod compatible with an instance.
+/// See [`Backend::get_latest_version`].
+#[derive(Debug, Clone)]
+pub struct LatestVersion {
+    /// Modrinth version id, or Curseforge file id.
+    pub version_id: String,
+    /// The version number, eg: `0.5.8`
+    pub version_number: String,
+    /// The display name of the version.
+    /// Often includes the mod name.
+    pub name: String,
+    pub release_time: DateTime<chrono::FixedOffset>,
+    pub channel: ReleaseChannel,
+    /// May be Markdown or HTML, depending on the store.
+    /// Curseforge needs a separate request for this,
+    /// so it's `None` there.
+    pub changelog: Option<String>,
+    /// Store ids of the dependencies (in [`ModIndex`] format).
+    pub dependencies: HashSet<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReleaseChannel {
+    #[default]
+    Release,
+    Beta,
+    Alpha,
+}
+
+impl Display for ReleaseChannel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                ReleaseChannel::Release => "Release",
+                ReleaseChannel::Beta => "Beta",
+                ReleaseChannel::Alpha => "Alpha",
+            }
+        )
+    }
+}
+
 async fn get_loader(instance: &InstanceSelection) -> Result<Option<Loader>, ModError> {
     let instance_dir = instance.get_instance_path();
     let config_json = InstanceConfigJson::read_from_dir(&instance_dir).await?;
@@ -0,0 +1,1007 @@
+//! Reading mod metadata directly from `.jar` files.
+//!
+//! Mods that weren't downloaded through the store
+//! (eg: dragged in, or copied to the `mods/` folder)
+//! have no entry in the [`super::ModIndex`], so this
+//! is the only way to know what they actually are.
+//!
+//! # Supported formats
+//! - `fabric.mod.json` (Fabric, also loaded by Quilt)
+//! - `quilt.mod.json` (Quilt)
+//! - `META-INF/mods.toml` (Forge 1.13+, NeoForge 1.20.1)
+//! - `META-INF/neoforge.mods.toml` (NeoForge 1.20.2+)
+//! - `mcmod.info` (legacy Forge, 1.12.2 and below)
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{Cursor, Read, Seek},
+    path::Path,
+};
+
+use ql_core::{err, InstanceSelection, IntoIoError, Loader};
+use serde::Deserialize;
+use zip::ZipArchive;
+
+use super::{get_loader, ModError};
+
+/// Where the mod metadata was read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MetadataFormat {
+    /// `fabric.mod.json`
+    Fabric,
+    /// `quilt.mod.json`
+    Quilt,
+    /// `META-INF/mods.toml`
+    ModsToml,
+    /// `META-INF/neoforge.mods.toml`
+    NeoforgeModsToml,
+    /// `mcmod.info`
+    McmodInfo,
+}
+
+impl MetadataFormat {
+    const ALL: &'static [Self] = &[
+        Self::Quilt,
+        Self::Fabric,
+        Self::NeoforgeModsToml,
+        Self::ModsToml,
+        Self::McmodInfo,
+    ];
+
+    #[must_use]
+    pub fn file_name(self) -> &'static str {
+        match self {
+            MetadataFormat::Fabric => "fabric.mod.json",
+            MetadataFormat::Quilt => "quilt.mod.json",
+            MetadataFormat::ModsToml => "META-INF/mods.toml",
+            MetadataFormat::NeoforgeModsToml => "META-INF/neoforge.mods.toml",
+            MetadataFormat::McmodInfo => "mcmod.info",
+        }
+    }
+
+    /// The name of the loader this format is
+    /// meant for, as shown to the user.
+    #[must_use]
+    pub fn loader_name(self) -> &'static str {
+        match self {
+            MetadataFormat::Fabric => "Fabric",
+            MetadataFormat::Quilt => "Quilt",
+            MetadataFormat::ModsToml | MetadataFormat::McmodInfo => "Forge",
+            MetadataFormat::NeoforgeModsToml => "NeoForge",
+        }
+    }
+
+    /// Whether a mod in this format can be loaded by `loader`.
+    ///
+    /// Quilt can load Fabric mods, and early NeoForge
+    /// (1.20.1) still used Forge's `mods.toml`.
+    #[must_use]
+    pub fn is_loadable_by(self, loader: Loader) -> bool {
+        matches!(
+            (self, loader),
+            (MetadataFormat::Fabric, Loader::Fabric | Loader::Quilt)
+                | (MetadataFormat::Quilt, Loader::Quilt)
+                | (MetadataFormat::ModsToml, Loader::Forge | Loader::Neoforge)
+                | (MetadataFormat::NeoforgeModsToml, Loader::Neoforge)
+                | (MetadataFormat::McmodInfo, Loader::Forge)
+        )
+    }
+
+    /// The formats to look at first when the
+    /// instance runs `loader`.
+    fn preferred_for(loader: Loader) -> &'static [Self] {
+        match loader {
+            Loader::Fabric => &[Self::Fabric],
+            Loader::Quilt => &[Self::Quilt, Self::Fabric],
+            Loader::Forge => &[Self::ModsToml, Self::McmodInfo],
+            Loader::Neoforge => &[Self::NeoforgeModsToml, Self::ModsToml],
+            _ => &[],
+        }
+    }
+}
+
+/// Which side of the game a mod is meant to run on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ModEnvironment {
+    #[default]
+    Both,
+    Client,
+    Server,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DependencyKind {
+    Required,
+    Optional,
+    /// The game will refuse to launch with both mods
+    /// (`breaks` in Fabric/Quilt, `incompatible` in NeoForge).
+    Breaks,
+    /// Works, but with issues (`conflicts` in Fabric,
+    /// `discouraged` in NeoForge).
+    Conflicts,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModDependency {
+    pub id: String,
+    pub kind: DependencyKind,
+    /// The accepted version ranges, in the syntax
+    /// of the metadata format (Fabric/Quilt predicates
+    /// or Maven ranges for Forge). Any one of these
+    /// matching is enough. Empty means any version.
+    pub versions: Vec<String>,
+}
+
+/// A single mod declared inside a jar.
+/// A jar may contain more than one
+/// (eg: `mcmod.info` lists, or `[[mods]]` in `mods.toml`).
+#[derive(Debug, Clone, Default)]
+pub struct JarModInfo {
+    pub id: String,
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub description: Option<String>,
+    pub authors: Vec<String>,
+    /// Other mod ids this mod also counts as
+    /// (`provides` in Fabric/Quilt).
+    pub provides: Vec<String>,
+    pub dependencies: Vec<ModDependency>,
+    pub environment: ModEnvironment,
+}
+
+impl JarModInfo {
+    #[must_use]
+    pub fn display_name(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.id)
+    }
+}
+
+/// Everything known about a mod jar,
+/// read from its metadata file(s).
+#[derive(Clone)]
+pub struct JarMetadata {
+    /// The format [`JarMetadata::mods`] was read from.
+    pub format: MetadataFormat,
+    /// Every metadata format present in the jar.
+    /// Multi-loader jars may have more than one.
+    pub formats_present: Vec<MetadataFormat>,
+    /// Never empty. The first entry is the main mod.
+    pub mods: Vec<JarModInfo>,
+    /// Raw bytes of the embedded icon image, if any.
+    pub icon: Option<Vec<u8>>,
+    /// Mods bundled inside this jar (jar-in-jar), which the
+    /// loader also loads: Fabric/Quilt `jars` and
+    /// Forge/NeoForge `jarjar`. Includes nested ones.
+    pub bundled: Vec<JarModInfo>,
+}
+
+impl std::fmt::Debug for JarMetadata {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JarMetadata")
+            .field("format", &self.format)
+            .field("formats_present", &self.formats_present)
+            .field("mods", &self.mods)
+            .field("bundled", &self.bundled)
+            .field(
+                "icon",
+                &format_args!("{:?} bytes", self.icon.as_ref().map(Vec::len)),
+            )
+            .finish()
+    }
+}
+
+impl JarMetadata {
+    /// Reads the metadata of the jar file at `path`.
+    ///
+    /// If the jar supports multiple loaders, the metadata
+    /// for `loader` (the instance's loader) is picked.
+    ///
+    /// Returns `Ok(None)` if the jar has no
+    /// recognizable metadata.
+    pub async fn read(path: &Path, loader: Option<Loader>) -> Result<Option<Self>, ModError> {
+        let bytes = tokio::fs::read(path).await.path(path)?;
+        Self::from_bytes(&bytes, loader)
+    }
+
+    /// Same as [`JarMetadata::read`] but for an
+    /// in-memory jar file.
+    pub fn from_bytes(bytes: &[u8], loader: Option<Loader>) -> Result<Option<Self>, ModError> {
+        Self::from_bytes_nested(bytes, loader, 0)
+    }
+
+    fn from_bytes_nested(
+        bytes: &[u8],
+        loader: Option<Loader>,
+        depth: usize,
+    ) -> Result<Option<Self>, ModError> {
+        let mut zip = ZipArchive::new(Cursor::new(bytes))?;
+
+        let formats_present: Vec<MetadataFormat> = MetadataFormat::ALL
+            .iter()
+            .copied()
+            .filter(|n| zip.index_for_name(n.file_name()).is_some())
+            .collect();
+
+        let preferred = loader.map_or(&[][..], MetadataFormat::preferred_for);
+        let mut order: Vec<MetadataFormat> = Vec::new();
+        for format in preferred.iter().chain(formats_present.iter()) {
+            if formats_present.contains(format) && !order.contains(format) {
+                order.push(*format);
+            }
+        }
+
+        for format in &order {
+            let Some(text) = read_text(&mut zip, format.file_name()) else {
+                continue;
+            };
+            let parsed = match format {
+                MetadataFormat::Fabric => parse_fabric(&text),
+                MetadataFormat::Quilt => parse_quilt(&text),
+                MetadataFormat::ModsToml | MetadataFormat::NeoforgeModsToml => {
+                    let jar_version = read_text(&mut zip, "META-INF/MANIFEST.MF")
+                        .and_then(|n| manifest_implementation_version(&n));
+                    parse_mods_toml(&text, jar_version.as_deref())
+                }
+                MetadataFormat::McmodInfo => parse_mcmod_info(&text),
+            };
+            let (mods, icon_path) = match parsed {
+                Ok(n) => n,
+                Err(error) => {
+                    err!("Invalid {} in mod jar: {error}", format.file_name());
+                    continue;
+                }
+            };
+            if mods.is_empty() {
+                continue;
+            }
+
+            let icon = icon_path
+                .and_then(|path| read_bytes(&mut zip, path.trim_start_matches(['/', '\\'])));
+            let bundled = read_bundled(&mut zip, *format, &text, loader, depth);
+
+            return Ok(Some(Self {
+                format: *format,
+                formats_present,
+                mods,
+                icon,
+                bundled,
+            }));
+        }
+
+        Ok(None)
+    }
+
+    /// The main mod of this jar.
+    #[must_use]
+    pub fn main(&self) -> &JarModInfo {
+        &self.mods[0]
+    }
+
+    /// Returns `true` if `loader` can't load this jar.
+    ///
+    /// Returns `false` if unsure (eg: no loader, or
+    /// loaders that don't have their own mod format).
+    #[must_use]
+    pub fn is_wrong_loader(&self, loader: Option<Loader>) -> bool {
+        let Some(loader) = loader else {
+            return false;
+        };
+        if MetadataFormat::preferred_for(loader).is_empty() {
+            return false;
+        }
+        !self
+            .formats_present
+            .iter()
+            .any(|n| n.is_loadable_by(loader))
+    }
+}
+
+/// Reads the metadata of the given files
+/// in the instance's `mods/` directory.
+///
+/// `file_names` may include the `.disabled` suffix.
+/// Files without recognizable metadata (or that
+/// couldn't be read) map to `None`.
+pub async fn read_local_mods_metadata(
+    instance: InstanceSelection,
+    file_names: Vec<String>,
+) -> Result<HashMap<String, Option<JarMetadata>>, ModError> {
+    let loader = get_loader(&instance).await?;
+    let mods_dir = instance.get_dot_minecraft_path().join("mods");
+
+    let mut out = HashMap::new();
+    for name in file_names {
+        let metadata = match JarMetadata::read(&mods_dir.join(&name), loader).await {
+            Ok(n) => n,
+            Err(error) => {
+                err!("Couldn't read mod metadata of {name}: {error}");
+                None
+            }
+        };
+        out.insert(name, metadata);
+    }
+    Ok(out)
+}
+
+/// Fabric API bundles its modules, which don't bundle
+/// anything further. This is just a safety limit.
+const MAX_BUNDLE_DEPTH: usize = 3;
+
+/// Reads the mods bundled in a jar (see [`JarMetadata::bundled`]).
+///
+/// Fabric/Quilt list them in their metadata (usually under
+/// `META-INF/jars/`), Forge/NeoForge keep them in `META-INF/jarjar/`.
+fn read_bundled<R: Read + Seek>(
+    zip: &mut ZipArchive<R>,
+    format: MetadataFormat,
+    metadata_text: &str,
+    loader: Option<Loader>,
+    depth: usize,
+) -> Vec<JarModInfo> {
+    if depth >= MAX_BUNDLE_DEPTH {
+        return Vec::new();
+    }
+
+    let mut paths: Vec<String> = zip
+        .file_names()
+        .filter(|n| {
+            (n.starts_with("META-INF/jars/") || n.starts_with("META-INF/jarjar/"))
+                && n.ends_with(".jar")
+        })
+        .map(str::to_owned)
+        .collect();
+    if let Ok(json) = serde_json::from_str::<serde_json::Value>(metadata_text) {
+        let declared = match format {
+            MetadataFormat::Fabric => json.get("jars"),
+            MetadataFormat::Quilt => json.get("quilt_loader").and_then(|n| n.get("jars")),
+            _ => None,
+        };
+        for entry in declared.and_then(|n| n.as_array()).into_iter().flatten() {
+            // Fabric: `{"file": "..."}`, Quilt: `"..."`
+            let path = entry
+                .get("file")
+                .and_then(|n| n.as_str())
+                .or_else(|| entry.as_str());
+            if let Some(path) = path.map(|n| n.trim_start_matches('/')) {
+                if !paths.iter().any(|n| n == path) {
+                    paths.push(path.to_owned());
+                }
+            }
+        }
+    }
+
+    let mut bundled = Vec::new();
+    for path in paths {
+        let Some(bytes) = read_bytes(zip, &path) else {
+            continue;
+        };
+        match JarMetadata::from_bytes_nested(&bytes, loader, depth + 1) {
+            Ok(Some(metadata)) => {
+                bundled.extend(metadata.mods);
+                bundled.extend(metadata.bundled);
+            }
+            // Plain libraries, not mods
+            Ok(None) => {}
+            Err(error) => err!("Couldn't read bundled jar {path}: {error}"),
+        }
+    }
+    bundled
+}
+
+fn read_bytes<R: Read + Seek>(zip: &mut ZipArchive<R>, name: &str) -> Option<Vec<u8>> {
+    let mut file = zip.by_name(name).ok()?;
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf).ok()?;
+    Some(buf)
+}
+
+fn read_text<R: Read + Seek>(zip: &mut ZipArchive<R>, name: &str) -> Option<String> {
+    let bytes = read_bytes(zip, name)?;
+    let text = String::from_utf8_lossy(&bytes);
+    // Some editors add a BOM, which serde doesn't like
+    Some(text.trim_start_matches('\u{feff}').to_owned())
+}
+
+fn manifest_implementation_version(manifest: &str) -> Option<String> {
+    manifest.lines().find_map(|line| {
+        line.strip_prefix("Implementation-Version:")
+            .map(|n| n.trim().to_owned())
+    })
+}
+
+type Parsed = (Vec<JarModInfo>, Option<String>);
+
+/// A JSON field that may either be a single string
+/// or a list of them (seen a lot in Fabric/Quilt).
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OneOrMany {
+    One(String),
+    Many(Vec<serde_json::Value>),
+}
+
+impl OneOrMany {
+    fn into_vec(self) -> Vec<String> {
+        match self {
+            OneOrMany::One(n) => vec![n],
+            OneOrMany::Many(n) => n.iter().filter_map(value_to_name).collect(),
+        }
+    }
+}
+
+/// Gets a name out of either `"name"` or `{ "name": "..." }`
+fn value_to_name(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::String(n) => Some(n.clone()),
+        serde_json::Value::Object(n) => n
+            .get("name")
+            .or(n.get("id"))
+            .and_then(|n| n.as_str())
+            .map(str::to_owned),
+        _ => None,
+    }
+}
+
+/// Icons may be a single path or a map of `size -> path`,
+/// in which case the biggest is picked.
+fn icon_path(value: Option<serde_json::Value>) -> Option<String> {
+    match value? {
+        serde_json::Value::String(n) => Some(n),
+        serde_json::Value::Object(map) => map
+            .iter()
+            .max_by_key(|(size, _)| size.parse::<u32>().unwrap_or(0))
+            .and_then(|(_, path)| path.as_str().map(str::to_owned)),
+        _ => None,
+    }
+}
+
+fn parse_fabric(text: &str) -> Result<Parsed, String> {
+    #[derive(Deserialize)]
+    struct FabricModJson {
+        id: String,
+        version: Option<String>,
+        name: Option<String>,
+        description: Option<String>,
+        #[serde(default)]
+        authors: Vec<serde_json::Value>,
+        icon: Option<serde_json::Value>,
+        environment: Option<String>,
+        #[serde(default)]
+        provides: Vec<String>,
+        #[serde(default)]
+        depends: BTreeMap<String, OneOrMany>,
+        #[serde(default)]
+        recommends: BTreeMap<String, OneOrMany>,
+        #[serde(default)]
+        suggests: BTreeMap<String, OneOrMany>,
+        #[serde(default)]
+        breaks: BTreeMap<String, OneOrMany>,
+        #[serde(default)]
+        conflicts: BTreeMap<String, OneOrMany>,
+    }
+
+    let json: FabricModJson = serde_json::from_str(text).map_err(|n| n.to_string())?;
+
+    let mut dependencies = Vec::new();
+    for (map, kind) in [
+        (json.depends, DependencyKind::Required),
+        (json.recommends, DependencyKind::Optional),
+        (json.suggests, DependencyKind::Optional),
+        (json.breaks, DependencyKind::Breaks),
+        (json.conflicts, DependencyKind::Conflicts),
+    ] {
+        dependencies.extend(map.into_iter().map(|(id, versions)| ModDependency {
+            id,
+            kind,
+            versions: versions.into_vec(),
+        }));
+    }
+
+    let environment = match json.environment.as_deref() {
+        Some("client") => ModEnvironment::Client,
+        Some("server") => ModEnvironment::Server,
+        _ => ModEnvironment::Both,
+    };
+
+    let info = JarModInfo {
+        id: json.id,
+        name: json.name,
+        version: json.version,
+        description: json.description,
+        authors: json.authors.iter().filter_map(value_to_name).collect(),
+        provides: json.provides,
+        dependencies,
+        environment,
+    };
+    Ok((vec![info], icon_path(json.icon)))
+}
+
+fn parse_quilt(text: &str) -> Result<Parsed, String> {
+    #[derive(Deserialize)]
+    struct QuiltModJson {
+        quilt_loader: QuiltLoader,
+        minecraft: Option<QuiltMinecraft>,
+    }
+
+    #[derive(Deserialize)]
+    struct QuiltLoader {
+        id: String,
+        version: Option<String>,
+        #[serde(default)]
+        provides: Vec<serde_json::Value>,
+        #[serde(default)]
+        depends: Vec<serde_json::Value>,
+        #[serde(default)]
+        breaks: Vec<serde_json::Value>,
+        #[serde(default)]
+        metadata: QuiltMetadata,
+    }
+
+    #[derive(Deserialize, Default)]
+    struct QuiltMetadata {
+        name: Option<String>,
+        description: Option<String>,
+        #[serde(default)]
+        contributors: BTreeMap<String, serde_json::Value>,
+        icon: Option<serde_json::Value>,
+    }
+
+    #[derive(Deserialize)]
+    struct QuiltMinecraft {
+        environment: Option<String>,
+    }
+
+    fn quilt_dependency(value: &serde_json::Value, kind: DependencyKind) -> Option<ModDependency> {
+        match value {
+            serde_json::Value::String(id) => Some(ModDependency {
+                id: id.clone(),
+                kind,
+                versions: Vec::new(),
+            }),
+            serde_json::Value::Object(obj) => {
+                let id = obj.get("id")?.as_str()?.to_owned();
+                let optional = obj
+                    .get("optional")
+                    .and_then(serde_json::Value::as_bool)
+                    .unwrap_or(false);
+                let versions = match obj.get("versions") {
+                    Some(serde_json::Value::String(n)) => vec![n.clone()],
+                    Some(serde_json::Value::Array(n)) => n
+                        .iter()
+                        .filter_map(|n| n.as_str().map(str::to_owned))
+                        .collect(),
+                    Some(serde_json::Value::Object(n)) => n
+                        .get("any")
+                        .and_then(serde_json::Value::as_array)
+                        .map(|n| {
+                            n.iter()
+                                .filter_map(|n| n.as_str().map(str::to_owned))
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                    _ => Vec::new(),
+                };
+                Some(ModDependency {
+                    id,
+                    kind: if optional && kind == DependencyKind::Required {
+                        DependencyKind::Optional
+                    } else {
+                        kind
+                    },
+                    versions,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    let json: QuiltModJson = serde_json::from_str(text).map_err(|n| n.to_string())?;
+    let loader = json.quilt_loader;
+
+    let dependencies = loader
+        .depends
+        .iter()
+        .filter_map(|n| quilt_dependency(n, DependencyKind::Required))
+        .chain(
+            loader
+                .breaks
+                .iter()
+                .filter_map(|n| quilt_dependency(n, DependencyKind::Breaks)),
+        )
+        .collect();
+
+    let environment = match json.minecraft.and_then(|n| n.environment).as_deref() {
+        Some("client") => ModEnvironment::Client,
+        Some("dedicated_server") => ModEnvironment::Server,
+        _ => ModEnvironment::Both,
+    };
+
+    let info = JarModInfo {
+        id: loader.id,
+        name: loader.metadata.name,
+        version: loader.version,
+        description: loader.metadata.description,
+        authors: loader.metadata.contributors.into_keys().collect(),
+        provides: loader.provides.iter().filter_map(value_to_name).collect(),
+        dependencies,
+        environment,
+    };
+    Ok((vec![info], icon_path(loader.metadata.icon)))
+}
+
+fn parse_mods_toml(text: &str, jar_version: Option<&str>) -> Result<Parsed, String> {
+    #[derive(Deserialize)]
+    struct ModsToml {
+        #[serde(default)]
+        mods: Vec<TomlMod>,
+        #[serde(default)]
+        dependencies: HashMap<String, Vec<TomlDependency>>,
+        #[serde(rename = "logoFile")]
+        logo_file: Option<String>,
+        authors: Option<String>,
+    }
+
+    #[derive(Deserialize)]
+    struct TomlMod {
+        #[serde(rename = "modId")]
+        mod_id: String,
+        version: Option<String>,
+        #[serde(rename = "displayName")]
+        display_name: Option<String>,
+        description: Option<String>,
+        authors: Option<String>,
+        #[serde(rename = "logoFile")]
+        logo_file: Option<String>,
+    }
+
+    #[derive(Deserialize)]
+    struct TomlDependency {
+        #[serde(rename = "modId")]
+        mod_id: String,
+        /// Forge
+        mandatory: Option<bool>,
+        /// NeoForge: `required`, `optional`, `incompatible`, `discouraged`
+        #[serde(rename = "type")]
+        kind: Option<String>,
+        #[serde(rename = "versionRange")]
+        version_range: Option<String>,
+        side: Option<String>,
+    }
+
+    let mut toml: ModsToml = toml::from_str(text).map_err(|n| n.to_string())?;
+
+    let mut icon = toml.logo_file.clone();
+    let mods = toml
+        .mods
+        .into_iter()
+        .map(|m| {
+            let deps = toml.dependencies.remove(&m.mod_id).unwrap_or_default();
+
+            let environment = deps
+                .iter()
+                .find(|n| n.mod_id == "minecraft")
+                .and_then(|n| n.side.as_deref())
+                .map_or(ModEnvironment::Both, |side| match side {
+                    "CLIENT" => ModEnvironment::Client,
+                    "SERVER" => ModEnvironment::Server,
+                    _ => ModEnvironment::Both,
+                });
+
+            let dependencies = deps
+                .into_iter()
+                .map(|dep| {
+                    let kind = match dep.kind.as_deref() {
+                        Some("required") => DependencyKind::Required,
+                        Some("incompatible") => DependencyKind::Breaks,
+                        Some("discouraged") => DependencyKind::Conflicts,
+                        Some(_) => DependencyKind::Optional,
+                        None => {
+                            if dep.mandatory.unwrap_or(true) {
+                                DependencyKind::Required
+                            } else {
+                                DependencyKind::Optional
+                            }
+                        }
+                    };
+                    ModDependency {
+                        id: dep.mod_id,
+                        kind,
+                        versions: dep.version_range.into_iter().collect(),
+                    }
+                })
+                .collect();
+
+            let version = match (m.version, jar_version) {
+                (Some(v), Some(jar_version)) if v == "${file.jarVersion}" => {
+                    Some(jar_version.to_owned())
+                }
+                (v, _) => v,
+            };
+
+            if icon.is_none() {
+                icon.clone_from(&m.logo_file);
+            }
+
+            JarModInfo {
+                id: m.mod_id,
+                name: m.display_name,
+                version,
+                description: m.description.map(|n| n.trim().to_owned()),
+                authors: m
+                    .authors
+                    .or(toml.authors.clone())
+                    .map(|n| split_authors(&n))
+                    .unwrap_or_default(),
+                provides: Vec::new(),
+                dependencies,
+                environment,
+            }
+        })
+        .collect();
+
+    Ok((mods, icon))
+}
+
+fn parse_mcmod_info(text: &str) -> Result<Parsed, String> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum McmodInfo {
+        List(Vec<McmodEntry>),
+        V2 {
+            #[serde(rename = "modList")]
+            mod_list: Vec<McmodEntry>,
+        },
+    }
+
+    #[derive(Deserialize)]
+    struct McmodEntry {
+        modid: String,
+        name: Option<String>,
+        description: Option<String>,
+        version: Option<String>,
+        #[serde(default, rename = "authorList", alias = "authors")]
+        author_list: Vec<String>,
+        #[serde(rename = "logoFile")]
+        logo_file: Option<String>,
+        #[serde(default, rename = "requiredMods")]
+        required_mods: Vec<String>,
+    }
+
+    let json: McmodInfo = serde_json::from_str(text).map_err(|n| n.to_string())?;
+    let entries = match json {
+        McmodInfo::List(n) | McmodInfo::V2 { mod_list: n } => n,
+    };
+
+    let mut icon = None;
+    let mods = entries
+        .into_iter()
+        .map(|entry| {
+            if icon.is_none() {
+                icon = entry.logo_file.filter(|n| !n.is_empty());
+            }
+            JarModInfo {
+                id: entry.modid,
+                name: entry.name,
+                version: entry.version,
+                description: entry.description,
+                authors: entry.author_list,
+                provides: Vec::new(),
+                // Format: `modid@[version range]`
+                dependencies: entry
+                    .required_mods
+                    .into_iter()
+                    .map(|n| {
+                        let (id, range) = n.split_once('@').unwrap_or((&n, ""));
+                        ModDependency {
+                            id: id.to_owned(),
+                            kind: DependencyKind::Required,
+                            versions: (!range.is_empty())
+                                .then(|| range.to_owned())
+                                .into_iter()
+                                .collect(),
+                        }
+                    })
+                    .collect(),
+                environment: ModEnvironment::Both,
+            }
+        })
+        .collect();
+
+    Ok((mods, icon))
+}
+
+fn split_authors(authors: &str) -> Vec<String> {
+    authors
+        .split([',', '&'])
+        .map(str::trim)
+        .filter(|n| !n.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    /// Builds an in-memory jar out of `(path, contents)` pairs.
+    pub(crate) fn make_jar(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut buf = Vec::new();
+        let mut zip = zip::ZipWriter::new(Cursor::new(&mut buf));
+        for (name, contents) in files {
+            zip.start_file(*name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            zip.write_all(contents).unwrap();
+        }
+        zip.finish().unwrap();
+        buf
+    }
+
+    #[test]
+    fn fabric() {
+        let jar = make_jar(&[
+            (
+                "fabric.mod.json",
+                br#"{
+                    "schemaVersion": 1,
+                    "id": "sodium",
+                    "version": "0.5.8",
+                    "name": "Sodium",
+                    "authors": ["JellySquid", { "name": "IMS" }],
+                    "icon": "assets/sodium/icon.png",
+                    "environment": "client",
+                    "depends": { "fabricloader": ">=0.12.0", "minecraft": ["1.20.3", "1.20.4"] },
+                    "breaks": { "optifabric": "*" }
+                }"#,
+            ),
+            ("assets/sodium/icon.png", b"png"),
+        ]);
+        let meta = JarMetadata::from_bytes(&jar, Some(Loader::Fabric))
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(meta.format, MetadataFormat::Fabric);
+        let main = meta.main();
+        assert_eq!(main.id, "sodium");
+        assert_eq!(main.display_name(), "Sodium");
+        assert_eq!(main.authors, ["JellySquid", "IMS"]);
+        assert_eq!(main.environment, ModEnvironment::Client);
+        assert_eq!(meta.icon.as_deref(), Some(&b"png"[..]));
+
+        let mc = main
+            .dependencies
+            .iter()
+            .find(|n| n.id == "minecraft")
+            .unwrap();
+        assert_eq!(mc.versions, ["1.20.3", "1.20.4"]);
+        assert!(main
+            .dependencies
+            .iter()
+            .any(|n| n.id == "optifabric" && n.kind == DependencyKind::Breaks));
+
+        assert!(!meta.is_wrong_loader(Some(Loader::Quilt)));
+        assert!(meta.is_wrong_loader(Some(Loader::Forge)));
+    }
+
+    #[test]
+    fn quilt() {
+        let jar = make_jar(&[(
+            "quilt.mod.json",
+            br#"{
+                "schema_version": 1,
+                "quilt_loader": {
+                    "id": "example",
+                    "version": "1.0.0",
+                    "metadata": { "name": "Example", "contributors": { "Someone": "Owner" } },
+                    "depends": [
+                        "quilt_loader",
+                        { "id": "qsl", "versions": ">=6.0.0", "optional": true }
+                    ]
+                },
+                "minecraft": { "environment": "dedicated_server" }
+            }"#,
+        )]);
+        let meta = JarMetadata::from_bytes(&jar, None).unwrap().unwrap();
+        let main = meta.main();
+        assert_eq!(main.authors, ["Someone"]);
+        assert_eq!(main.environment, ModEnvironment::Server);
+        assert_eq!(main.dependencies[1].kind, DependencyKind::Optional);
+        assert_eq!(main.dependencies[1].versions, [">=6.0.0"]);
+        assert!(meta.is_wrong_loader(Some(Loader::Fabric)));
+    }
+
+    #[test]
+    fn mods_toml() {
+        let jar = make_jar(&[
+            (
+                "META-INF/mods.toml",
+                br#"
+modLoader="javafml"
+loaderVersion="[47,)"
+license="MIT"
+logoFile="logo.png"
+
+[[mods]]
+modId="examplemod"
+version="${file.jarVersion}"
+displayName="Example Mod"
+authors="Alice, Bob"
+
+[[dependencies.examplemod]]
+modId="forge"
+mandatory=true
+versionRange="[47,)"
+side="BOTH"
+
+[[dependencies.examplemod]]
+modId="minecraft"
+mandatory=true
+versionRange="[1.20.1,1.21)"
+side="CLIENT"
+"#,
+            ),
+            (
+                "META-INF/MANIFEST.MF",
+                b"Manifest-Version: 1.0\r\nImplementation-Version: 2.3.4\r\n",
+            ),
+        ]);
+        let meta = JarMetadata::from_bytes(&jar, Some(Loader::Forge))
+            .unwrap()
+            .unwrap();
+        let main = meta.main();
+        assert_eq!(meta.format, MetadataFormat::ModsToml);
+        assert_eq!(main.version.as_deref(), Some("2.3.4"));
+        assert_eq!(main.authors, ["Alice", "Bob"]);
+        assert_eq!(main.environment, ModEnvironment::Client);
+        assert_eq!(main.dependencies.len(), 2);
+        assert!(!meta.is_wrong_loader(Some(Loader::Neoforge)));
+        assert!(meta.is_wrong_loader(Some(Loader::Fabric)));
+    }
+
+    #[test]
+    fn mcmod_info() {
+        let jar = make_jar(&[(
+            "mcmod.info",
+            br#"[{
+                "modid": "oldmod",
+                "name": "Old Mod",
+                "version": "1.0",
+                "authorList": ["Notch"],
+                "requiredMods": ["Forge@[10.13,)", "codechickencore"]
+            }]"#,
+        )]);
+        let meta = JarMetadata::from_bytes(&jar, None).unwrap().unwrap();
+        let main = meta.main();
+        assert_eq!(main.id, "oldmod");
+        assert_eq!(main.dependencies[0].id, "Forge");
+        assert_eq!(main.dependencies[0].versions, ["[10.13,)"]);
+        assert!(main.dependencies[1].versions.is_empty());
+    }
+
+    #[test]
+    fn multi_loader_prefers_instance_loader() {
+        let jar = make_jar(&[
+            ("fabric.mod.json", br#"{ "id": "multi_fabric" }"#),
+            (
+                "META-INF/neoforge.mods.toml",
+                b"[[mods]]\nmodId=\"multi_neo\"\n",
+            ),
+        ]);
+        let fabric = JarMetadata::from_bytes(&jar, Some(Loader::Fabric))
+            .unwrap()
+            .unwrap();
+        assert_eq!(fabric.main().id, "multi_fabric");
+        let neo = JarMetadata::from_bytes(&jar, Some(Loader::Neoforge))
+            .unwrap()
+            .unwrap();
+        assert_eq!(neo.main().id, "multi_neo");
+        assert!(!neo.is_wrong_loader(Some(Loader::Fabric)));
+    }
+
+    #[test]
+    fn no_metadata() {
+        let jar = make_jar(&[("some/Class.class", b"")]);
+        assert!(JarMetadata::from_bytes(&jar, None).unwrap().is_none());
+    }
+}
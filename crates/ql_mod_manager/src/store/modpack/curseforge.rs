@@ -4,7 +4,7 @@ use std::{
 };
 
 use ql_core::{
-    do_jobs, file_utils,
+    do_jobs,
     json::{InstanceConfigJson, VersionDetails},
     pt, GenericProgress, InstanceSelection, IntoIoError,
 };
@@ -13,7 +13,8 @@ use tokio::sync::Mutex;
 
 use crate::store::{
     curseforge::{self, get_query_type, CFSearchResult, CurseforgeFileQuery, ModQuery},
-    get_dir, CurseforgeNotAllowed, ModConfig, ModFile, ModIndex, QueryType, SOURCE_ID_CURSEFORGE,
+    download_verified, get_dir, CurseforgeNotAllowed, ModConfig, ModFile, ModIndex, QueryType,
+    SOURCE_ID_CURSEFORGE,
 };
 
 use super::PackError;
@@ -91,7 +92,7 @@ impl PackFile {
             }
         }
 
-        file_utils::download_file_to_path(&url, true, &path).await?;
+        download_verified(&url, &path, &query.data.get_hashes()).await?;
         add_to_index(index, project_id, &mod_info, query, url).await;
 
         send_progress(sender, i, len, &mod_info).await;
@@ -137,6 +138,7 @@ async fn add_to_index(
                 project_source: SOURCE_ID_CURSEFORGE.to_owned(),
                 project_id,
                 files: vec![ModFile {
+                    hashes: query.data.get_hashes(),
                     url,
                     filename: query.data.fileName,
                     primary: true,
@@ -5,7 +5,7 @@ use std::{
 };
 
 use ql_core::{
-    err, info,
+    err, icon, info,
     json::{InstanceConfigJson, VersionDetails},
     pt, GenericProgress, InstanceSelection, IntoIoError, IntoJsonError,
 };
@@ -16,7 +16,10 @@ mod modrinth;
 
 pub use error::PackError;
 
-use super::CurseforgeNotAllowed;
+use super::{cache, CurseforgeNotAllowed};
+
+/// Icon at the root of some modpack files
+const PACK_ICON: &str = "icon.png";
 
 /// Installs a modpack file.
 ///
@@ -79,12 +82,26 @@ pub async fn install_modpack(
         return Ok(None);
     }
 
+    if let Ok(mut icon_file) = zip.by_name(PACK_ICON) {
+        let mut buf = Vec::new();
+        icon_file
+            .read_to_end(&mut buf)
+            .map_err(|n| PackError::ZipIoError(n, PACK_ICON.to_owned()))?;
+        if let Err(err) = icon::set(&instance, &buf).await {
+            err!("Couldn't use modpack icon: {err}");
+        }
+    }
+
     let len = zip.len();
     for i in 0..len {
         let mut file = zip.by_index(i)?;
         let name = file.name().to_owned();
 
-        if name == "modrinth.index.json" || name == "manifest.json" || name == "modlist.html" {
+        if name == "modrinth.index.json"
+            || name == "manifest.json"
+            || name == "modlist.html"
+            || name == PACK_ICON
+        {
             continue;
         }
 
@@ -120,7 +137,11 @@ pub async fn install_modpack(
                 file.read_to_end(&mut buf)
                     .map_err(|n| PackError::ZipIoError(n, name.to_owned()))?;
 
-                tokio::fs::write(&path, &buf).await.path(&path)?;
+                if is_cached_content(name) {
+                    cache::store(&buf, &path).await?;
+                } else {
+                    tokio::fs::write(&path, &buf).await.path(&path)?;
+                }
             }
         } else {
             err!("Unrecognised file: {name}");
@@ -132,6 +153,33 @@ pub async fn install_modpack(
     Ok(Some(not_allowed))
 }
 
+/// Uses the icon of a modpack's store page as the instance icon,
+/// unless the pack came with its own.
+pub(crate) async fn set_icon_from_store(instance: &InstanceSelection, icon_url: Option<&str>) {
+    let Some(icon_url) = icon_url.filter(|n| !n.is_empty()) else {
+        return;
+    };
+    if icon::path(instance).exists() {
+        return;
+    }
+    if let Err(err) = icon::set_from_url(instance, icon_url).await {
+        err!("Couldn't download modpack icon: {err}");
+    }
+}
+
+/// Whether an override file (like `mods/sodium.jar`)
+/// should go through the shared content cache.
+///
+/// Config files and such are left out, as users
+/// often edit them (which would affect other instances
+/// if hardlinked).
+fn is_cached_content(name: &str) -> bool {
+    let name = name.replace('\\', "/");
+    name.split_once('/').is_some_and(|(dir, file)| {
+        cache::CACHED_DIRS.contains(&dir) && !file.is_empty() && !file.contains('/')
+    })
+}
+
 fn read_json_from_zip<T: serde::de::DeserializeOwned>(
     zip: &mut zip::ZipArchive<Cursor<Vec<u8>>>,
     name: &str,
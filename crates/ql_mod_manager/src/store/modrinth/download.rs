@@ -13,10 +13,10 @@ use ql_core::{
 };
 
 use crate::store::{
-    get_mods_resourcepacks_shaderpacks_dir, install_modpack,
+    download_verified, get_mods_resourcepacks_shaderpacks_dir, install_modpack,
     local_json::{ModConfig, ModIndex},
     modrinth::versions::ModVersion,
-    ModError, QueryType, SOURCE_ID_MODRINTH,
+    set_icon_from_store, ModError, QueryType, SOURCE_ID_MODRINTH,
 };
 
 use super::info::ProjectInfo;
@@ -118,6 +118,9 @@ impl ModDownloader {
                     self.download_file(query_type, file).await?;
                 }
             }
+            if let QueryType::ModPacks = query_type {
+                set_icon_from_store(&self.instance, project_info.icon_url.as_deref()).await;
+            }
 
             self.add_mod_to_index(
                 &project_info,
@@ -257,7 +260,7 @@ impl ModDownloader {
             return Ok(());
         }
         let file_path = self.get_dir(project_type).unwrap().join(&file.filename);
-        file_utils::download_file_to_path(&file.url, true, &file_path).await?;
+        download_verified(&file.url, &file_path, &file.hashes).await?;
         Ok(())
     }
 
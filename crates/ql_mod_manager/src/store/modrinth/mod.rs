@@ -11,7 +11,10 @@ use crate::{
     store::{SearchMod, StoreBackendType},
 };
 
-use super::{Backend, CurseforgeNotAllowed, ModError, Query, QueryType, SearchResult};
+use super::{
+    Backend, CurseforgeNotAllowed, LatestVersion, ModError, Query, QueryType, ReleaseChannel,
+    SearchResult,
+};
 
 mod download;
 mod info;
@@ -60,11 +63,11 @@ impl Backend for ModrinthBackend {
         Ok((ModId::Modrinth(info.id), info.body))
     }
 
-    async fn get_latest_version_date(
+    async fn get_latest_version(
         id: &str,
         version: &str,
         loader: Option<Loader>,
-    ) -> Result<(DateTime<chrono::FixedOffset>, String), ModError> {
+    ) -> Result<LatestVersion, ModError> {
         let download_info = ModVersion::download(id).await?;
         let version = version.to_owned();
 
@@ -99,9 +102,26 @@ impl Backend for ModrinthBackend {
                         .unwrap_or_default(),
                 ))?;
 
-        let download_version_time = DateTime::parse_from_rfc3339(&download_version.date_published)?;
-
-        Ok((download_version_time, download_version.name))
+        let release_time = DateTime::parse_from_rfc3339(&download_version.date_published)?;
+
+        Ok(LatestVersion {
+            version_id: download_version.id,
+            version_number: download_version.version_number,
+            name: download_version.name,
+            release_time,
+            channel: match download_version.version_type.as_str() {
+                "beta" => ReleaseChannel::Beta,
+                "alpha" => ReleaseChannel::Alpha,
+                _ => ReleaseChannel::Release,
+            },
+            changelog: download_version.changelog,
+            dependencies: download_version
+                .dependencies
+                .into_iter()
+                .filter(|n| n.dependency_type == "required")
+                .filter_map(|n| n.project_id)
+                .collect(),
+        })
     }
 
     async fn download(
@@ -9,17 +9,17 @@ use super::ModError;
 pub struct ModVersion {
     pub game_versions: Vec<String>,
     pub loaders: Vec<String>,
-    // pub id: String,
+    pub id: String,
     // pub project_id: String,
     // pub author_id: String,
     // pub featured: bool,
     pub name: String,
     pub version_number: String,
-    // pub changelog: String,
+    pub changelog: Option<String>,
     // pub changelog_url: Option<String>,
     pub date_published: String,
     // pub downloads: usize,
-    // pub version_type: String,
+    pub version_type: String,
     // pub status: String,
     // pub requested_status: Option<String>,
     pub files: Vec<ModFile>,
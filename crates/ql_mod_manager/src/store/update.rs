@@ -2,18 +2,57 @@ use std::sync::mpsc::Sender;
 
 use chrono::DateTime;
 use ql_core::{
-    do_jobs, info_no_log, json::VersionDetails, GenericProgress, InstanceSelection, Loader,
+    do_jobs, err_no_log, info_no_log, json::VersionDetails, GenericProgress, InstanceSelection,
+    Loader,
 };
 
-use crate::store::{get_latest_version_date, get_loader};
+use crate::store::{get_latest_version, get_loader, CurseforgeBackend, ReleaseChannel};
 
 use super::{delete_mods, download_mods_bulk, ModError, ModId, ModIndex};
 
+/// An available update for an installed mod.
+/// See [`check_for_updates`].
+#[derive(Debug, Clone)]
+pub struct ModUpdate {
+    pub id: ModId,
+    /// The name of the mod.
+    pub name: String,
+    pub current_version: String,
+    pub new_version: String,
+    /// The display name of the new version.
+    /// Often includes the mod name.
+    pub new_version_name: String,
+    pub release_time: DateTime<chrono::FixedOffset>,
+    pub channel: ReleaseChannel,
+    /// May be Markdown or HTML, depending on the store.
+    pub changelog: Option<String>,
+    /// Dependencies (store ids) that the new
+    /// version needs, but the current one doesn't.
+    pub added_dependencies: Vec<String>,
+    /// Dependencies (store ids) that the new
+    /// version no longer needs.
+    pub removed_dependencies: Vec<String>,
+}
+
+impl ModUpdate {
+    #[must_use]
+    pub fn changes_dependencies(&self) -> bool {
+        !self.added_dependencies.is_empty() || !self.removed_dependencies.is_empty()
+    }
+}
+
+/// Updates the given mods to their latest versions.
+///
+/// Pass in only the updates you want to apply,
+/// eg: a subset of the ones from [`check_for_updates`].
 pub async fn apply_updates(
     selected_instance: InstanceSelection,
     updates: Vec<ModId>,
     progress: Option<Sender<GenericProgress>>,
 ) -> Result<(), ModError> {
+    if updates.is_empty() {
+        return Ok(());
+    }
     // It's as simple as that!
     delete_mods(updates.clone(), selected_instance.clone()).await?;
     download_mods_bulk(updates, selected_instance, progress).await?;
@@ -22,7 +61,7 @@ pub async fn apply_updates(
 
 pub async fn check_for_updates(
     selected_instance: InstanceSelection,
-) -> Result<Vec<(ModId, String)>, ModError> {
+) -> Result<Vec<ModUpdate>, ModError> {
     let index = ModIndex::load(&selected_instance).await?;
 
     let version_json = VersionDetails::load(&selected_instance).await?;
@@ -38,25 +77,67 @@ pub async fn check_for_updates(
 
     let version = version_json.get_id();
 
-    let updated_mods: Result<Vec<Option<(ModId, String)>>, ModError> = do_jobs(
+    let updated_mods: Result<Vec<Option<ModUpdate>>, ModError> = do_jobs(
         index
             .mods
             .into_iter()
             .map(|(id, installed_mod)| async move {
                 let mod_id = ModId::from_index_str(&id);
 
-                let (download_version_time, download_version) =
-                    get_latest_version_date(loader, &mod_id, version).await?;
+                let latest = get_latest_version(loader, &mod_id, version).await?;
 
                 let installed_version_time =
                     DateTime::parse_from_rfc3339(&installed_mod.version_release_time)?;
+                if latest.release_time <= installed_version_time {
+                    return Ok(None);
+                }
+
+                let changelog = match (&latest.changelog, &mod_id) {
+                    (None, ModId::Curseforge(n)) => {
+                        match CurseforgeBackend::get_changelog(n, &latest.version_id).await {
+                            Ok(n) => Some(n),
+                            Err(err) => {
+                                err_no_log!(
+                                    "Couldn't get changelog of {}: {err}",
+                                    installed_mod.name
+                                );
+                                None
+                            }
+                        }
+                    }
+                    _ => latest.changelog,
+                };
+
+                let mut added_dependencies: Vec<String> = latest
+                    .dependencies
+                    .difference(&installed_mod.dependencies)
+                    .cloned()
+                    .collect();
+                added_dependencies.sort();
+                let mut removed_dependencies: Vec<String> = installed_mod
+                    .dependencies
+                    .difference(&latest.dependencies)
+                    .cloned()
+                    .collect();
+                removed_dependencies.sort();
 
-                Ok((download_version_time > installed_version_time)
-                    .then_some((mod_id, download_version)))
+                Ok(Some(ModUpdate {
+                    id: mod_id,
+                    name: installed_mod.name,
+                    current_version: installed_mod.installed_version,
+                    new_version: latest.version_number,
+                    new_version_name: latest.name,
+                    release_time: latest.release_time,
+                    channel: latest.channel,
+                    changelog,
+                    added_dependencies,
+                    removed_dependencies,
+                }))
             }),
     )
     .await;
-    let updated_mods: Vec<(ModId, String)> = updated_mods?.into_iter().flatten().collect();
+    let mut updated_mods: Vec<ModUpdate> = updated_mods?.into_iter().flatten().collect();
+    updated_mods.sort_by(|a, b| a.name.cmp(&b.name));
 
     if updated_mods.is_empty() {
         info_no_log!("No mod updates found");
@@ -0,0 +1,211 @@
+use std::{collections::HashSet, path::Path, sync::mpsc::Sender};
+
+use ql_core::{err, file_utils, info, pt, retry, GenericProgress, InstanceSelection, IntoIoError};
+
+use super::{cache, ModError, ModFile, ModHashes, ModIndex};
+
+/// Downloads a file to `path`, checking it against `hashes`.
+///
+/// If the downloaded file is corrupted, the download is retried.
+/// Files go through the shared content cache (see [`super::cache`]),
+/// so if another instance already has this file it isn't downloaded again.
+pub async fn download_verified(url: &str, path: &Path, hashes: &ModHashes) -> Result<(), ModError> {
+    if let Some(cached) = cache::get(hashes).await? {
+        cache::place(&cached, path).await?;
+        return Ok(());
+    }
+
+    let bytes = retry(|| async {
+        let bytes = file_utils::download_file_to_bytes(url, true).await?;
+        if !hashes.matches(&bytes) {
+            let name = path
+                .file_name()
+                .map_or_else(|| url.to_owned(), |n| n.to_string_lossy().to_string());
+            err!("Downloaded file {name} is corrupted, retrying...");
+            return Err(ModError::HashMismatch(name));
+        }
+        Ok(bytes)
+    })
+    .await?;
+    cache::store(&bytes, path).await?;
+    Ok(())
+}
+
+/// The result of [`verify_mods`].
+#[derive(Debug, Clone, Default)]
+pub struct VerifyReport {
+    /// Files that were missing or corrupted,
+    /// and have been redownloaded.
+    pub repaired: Vec<String>,
+    /// Files that were missing or corrupted,
+    /// but couldn't be redownloaded (with the error).
+    pub failed: Vec<(String, String)>,
+    /// Files in `mods/` that aren't in the [`ModIndex`]
+    /// (eg: added manually), so they can't be verified.
+    pub untracked: Vec<String>,
+    /// Files installed by older launcher versions,
+    /// which didn't store hashes. These can't be verified.
+    pub unverified: Vec<String>,
+}
+
+impl VerifyReport {
+    #[must_use]
+    pub fn is_ok(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// Checks the mods of an instance against the hashes
+/// in the [`ModIndex`], redownloading any files that are
+/// missing or corrupted.
+///
+/// Disabled mods are checked too, and stay disabled.
+pub async fn verify_mods(
+    instance: &InstanceSelection,
+    sender: Option<Sender<GenericProgress>>,
+) -> Result<VerifyReport, ModError> {
+    let index = ModIndex::load(instance).await?;
+    let mods_dir = instance.get_dot_minecraft_path().join("mods");
+
+    info!("Verifying mods");
+    let mut report = VerifyReport::default();
+    let mut tracked = HashSet::new();
+
+    let files: Vec<_> = index
+        .mods
+        .values()
+        .flat_map(|config| {
+            installed_files(&config.files)
+                .into_iter()
+                .map(move |file| (config, file))
+        })
+        .collect();
+    let len = files.len();
+
+    for (i, (config, file)) in files.into_iter().enumerate() {
+        tracked.insert(file.filename.clone());
+        if let Some(sender) = &sender {
+            _ = sender.send(GenericProgress {
+                done: i,
+                total: len,
+                message: Some(format!("Verifying {} ({}/{len})", config.name, i + 1)),
+                has_finished: false,
+            });
+        }
+
+        let enabled_path = mods_dir.join(&file.filename);
+        let disabled_path = mods_dir.join(format!("{}.disabled", file.filename));
+        let path = if enabled_path.is_file() {
+            Some(enabled_path.clone())
+        } else if disabled_path.is_file() {
+            Some(disabled_path.clone())
+        } else {
+            None
+        };
+
+        let is_ok = if let Some(path) = &path {
+            if file.hashes.is_empty() {
+                report.unverified.push(file.filename.clone());
+                continue;
+            }
+            let bytes = tokio::fs::read(path).await.path(path)?;
+            file.hashes.matches(&bytes)
+        } else {
+            false
+        };
+        if is_ok {
+            continue;
+        }
+
+        pt!(
+            "{} file {} of {}, redownloading",
+            if path.is_some() {
+                "Corrupted"
+            } else {
+                "Missing"
+            },
+            file.filename,
+            config.name
+        );
+        let target = path.unwrap_or(if config.enabled {
+            enabled_path
+        } else {
+            disabled_path
+        });
+        match download_verified(&file.url, &target, &file.hashes).await {
+            Ok(()) => report.repaired.push(file.filename.clone()),
+            Err(error) => {
+                err!("Couldn't repair {}: {error}", file.filename);
+                report
+                    .failed
+                    .push((file.filename.clone(), error.to_string()));
+            }
+        }
+    }
+
+    let mut dir = tokio::fs::read_dir(&mods_dir).await.path(&mods_dir)?;
+    while let Some(entry) = dir.next_entry().await.path(&mods_dir)? {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let real_name = name.strip_suffix(".disabled").unwrap_or(&name);
+        let is_mod = real_name.ends_with(".jar") || real_name.ends_with(".zip");
+        if is_mod && entry.path().is_file() && !tracked.contains(real_name) {
+            report.untracked.push(name);
+        }
+    }
+    report.untracked.sort();
+
+    if let Some(sender) = &sender {
+        _ = sender.send(GenericProgress::finished());
+    }
+    info!(
+        "Finished verifying mods ({} repaired, {} failed, {} untracked)",
+        report.repaired.len(),
+        report.failed.len(),
+        report.untracked.len()
+    );
+    Ok(report)
+}
+
+/// The files of a mod that are actually in `mods/`.
+///
+/// The index stores every file of the version, but only
+/// the primary one is downloaded (or all of them, if none
+/// is primary). The others are usually sources/dev jars
+/// or builds for other loaders.
+fn installed_files(files: &[ModFile]) -> Vec<&ModFile> {
+    match files.iter().find(|n| n.primary) {
+        Some(primary) => vec![primary],
+        None => files.iter().collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(filename: &str, primary: bool) -> ModFile {
+        ModFile {
+            hashes: ModHashes::default(),
+            url: format!("https://example.com/{filename}"),
+            filename: filename.to_owned(),
+            primary,
+        }
+    }
+
+    #[test]
+    fn only_primary_file() {
+        let files = [
+            file("sodium-sources.jar", false),
+            file("sodium.jar", true),
+            file("sodium-dev.jar", false),
+        ];
+        let installed: Vec<&str> = installed_files(&files)
+            .into_iter()
+            .map(|n| n.filename.as_str())
+            .collect();
+        assert_eq!(installed, ["sodium.jar"]);
+
+        let files = [file("a.jar", false), file("b.jar", false)];
+        assert_eq!(installed_files(&files).len(), 2);
+    }
+}
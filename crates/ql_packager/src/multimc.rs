@@ -7,7 +7,7 @@ use std::{
 
 use crate::{import::pipe_progress, import::OUT_OF, InstancePackageError};
 use ql_core::{
-    do_jobs, err, file_utils, info,
+    do_jobs, err, file_utils, icon, info,
     json::{
         FabricJSON, InstanceConfigJson, Manifest, VersionDetails, V_1_12_2,
         V_OFFICIAL_FABRIC_SUPPORT,
@@ -158,6 +158,12 @@ pub async fn import(
     }
 
     copy_files(temp_dir, sender, &instance_selection).await?;
+    if let Some(icon_key) = ini
+        .get_from(Some("General"), "iconKey")
+        .or(ini.get_from(None::<String>, "iconKey"))
+    {
+        import_icon(temp_dir, icon_key, &instance_selection).await;
+    }
 
     let mut config = InstanceConfigJson::read(&instance_selection).await?;
     if let Some(jvmargs) = ini.get_from(Some("General"), "JvmArgs") {
@@ -170,6 +176,22 @@ pub async fn import(
     Ok(instance_selection)
 }
 
+/// Custom icons are exported along with the instance,
+/// as `<iconKey>.png` (or another image format).
+/// Built-in ones (like `grass`) aren't, so they're skipped.
+async fn import_icon(temp_dir: &Path, icon_key: &str, instance: &InstanceSelection) {
+    for extension in ["png", "jpg", "jpeg", "webp"] {
+        let path = temp_dir.join(format!("{icon_key}.{extension}"));
+        let Ok(image) = fs::read(&path).await else {
+            continue;
+        };
+        if let Err(err) = icon::set(instance, &image).await {
+            err!("Couldn't import MultiMC icon {icon_key:?}: {err}");
+        }
+        return;
+    }
+}
+
 async fn install_fabric(
     sender: Option<&Sender<GenericProgress>>,
     instance_selection: &InstanceSelection,
@@ -1,9 +1,10 @@
 use std::sync::mpsc::Sender;
 
 use ql_core::{
-    file_utils, info,
-    json::{InstanceConfigJson, Manifest, VersionDetails},
-    pt, GenericProgress, IntoIoError, IntoJsonError, IntoStringError, ListEntry, LAUNCHER_DIR,
+    err, file_utils, info,
+    json::{InstanceConfigJson, InstanceMetaFile, Manifest, VersionDetails},
+    pt, GenericProgress, InstanceSelection, IntoIoError, IntoJsonError, IntoStringError, ListEntry,
+    LAUNCHER_DIR,
 };
 
 use crate::ServerError;
@@ -89,6 +90,11 @@ pub async fn create_server(
     let mods_dir = server_dir.join("mods");
     tokio::fs::create_dir(&mods_dir).await.path(mods_dir)?;
 
+    let instance = InstanceSelection::Server(name.clone());
+    if let Err(err) = InstanceMetaFile::record_created(&instance).await {
+        err!("Couldn't save creation date of server: {err}");
+    }
+
     pt!("Finished");
 
     Ok(name)
@@ -120,10 +126,12 @@ async fn write_config(
         // This won't do anything on servers. Who wants to lose their *only way*
         // to control the server instantly after starting it?
         close_on_start: None,
+        preferred_account: None,
         global_settings: None,
         java_args_mode: None,
         custom_jar: None,
         pre_launch_prefix_mode: None,
+        env_vars_mode: None,
         main_class_override: None,
     };
     let server_config_path = server_dir.join("config.json");
@@ -119,7 +119,7 @@ async fn get_java(
     let version = if let Some(version) = version_json.javaVersion.clone() {
         version.into()
     } else {
-        JavaVersion::Java8
+        JavaVersion::JAVA_8
     };
     let java_path = get_java_path(config_json, version, java_install_progress).await?;
     Ok(java_path)
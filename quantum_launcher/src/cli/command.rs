@@ -1,16 +1,30 @@
 use owo_colors::{OwoColorize, Style};
 use ql_core::{
     err, info,
-    json::{InstanceConfigJson, VersionDetails},
+    json::{
+        history, instance_config::PreferredAccount, instance_meta::sort_instances,
+        InstanceConfigJson, InstanceHistory, InstanceMetaFile, VersionDetails,
+    },
     InstanceSelection, IntoIoError, IntoJsonError, IntoStringError, ListEntry, Loader,
     LAUNCHER_DIR,
 };
-use ql_instances::auth::{self, AccountType};
+use ql_instances::{
+    auth::{
+        self,
+        health::{self, AccountStatus},
+        AccountType,
+    },
+    QuickPlay,
+};
 use std::process::exit;
 
-use crate::{cli::helpers::render_row, config::LauncherConfig, state::get_entries};
+use crate::{
+    cli::helpers::render_row,
+    config::{ConfigAccount, LauncherConfig},
+    state::get_entries,
+};
 
-use super::PrintCmd;
+use super::{ListFilter, PrintCmd};
 
 pub fn list_available_versions() {
     use std::io::Write;
@@ -36,15 +50,40 @@ pub fn list_available_versions() {
 pub fn list_instances(
     cmds: &[PrintCmd],
     is_server: bool,
+    filter: &ListFilter,
 ) -> Result<(), Box<dyn std::error::Error>> {
     use std::fmt::Write;
 
     let dirname = if is_server { "servers" } else { "instances" };
-    let (instances, _) = tokio::runtime::Runtime::new()?.block_on(get_entries(is_server))?;
+    let runtime = tokio::runtime::Runtime::new()?;
+    let (mut instances, _) = runtime.block_on(get_entries(is_server))?;
+    let meta = runtime.block_on(InstanceMetaFile::load())?;
+
+    instances.retain(|name| {
+        let instance_meta = meta.get(&InstanceSelection::new(name, is_server));
+        let group = instance_meta.and_then(|n| n.group.as_deref());
+        let group_matches = filter
+            .group
+            .as_deref()
+            .is_none_or(|n| group.is_some_and(|g| g.eq_ignore_ascii_case(n)));
+        let tags_match = filter
+            .tags
+            .iter()
+            .all(|tag| instance_meta.is_some_and(|n| n.has_tag(tag)));
+        group_matches && tags_match
+    });
+    runtime.block_on(sort_instances(
+        &mut instances,
+        is_server,
+        filter.sort,
+        &meta,
+    ));
 
     let mut cmds_name = String::new();
     let mut cmds_version = String::new();
     let mut cmds_loader = String::new();
+    let mut cmds_playtime = String::new();
+    let mut cmds_last_played = String::new();
 
     for instance in instances {
         for cmd in cmds {
@@ -97,24 +136,52 @@ pub fn list_instances(
                         }
                     }
                 }
+                PrintCmd::Playtime | PrintCmd::LastPlayed => {
+                    let instance_dir = LAUNCHER_DIR.join(dirname).join(&instance);
+                    let history =
+                        runtime.block_on(InstanceHistory::read_from_dir(&instance_dir))?;
+                    if let PrintCmd::Playtime = cmd {
+                        _ = writeln!(
+                            cmds_playtime,
+                            "{}",
+                            history::format_playtime(history.total_playtime())
+                        );
+                    } else if let Some(last_played) = history
+                        .last_played()
+                        .and_then(|n| chrono::DateTime::from_timestamp(n, 0))
+                    {
+                        _ = writeln!(
+                            cmds_last_played,
+                            "{}",
+                            last_played
+                                .with_timezone(&chrono::Local)
+                                .format("%Y-%m-%d %H:%M")
+                        );
+                    } else {
+                        _ = writeln!(cmds_last_played, "{}", "Never".bright_black());
+                    }
+                }
             }
         }
     }
 
-    let Some((terminal_size::Width(width), _)) = terminal_size::terminal_size() else {
-        println!("{cmds_name}\n\n{cmds_loader}\n\n{cmds_version}");
-        return Ok(());
-    };
-
     let cmds: Vec<(String, Option<Style>)> = cmds
         .iter()
         .map(|n| match n {
             PrintCmd::Name => (cmds_name.clone(), None),
             PrintCmd::Version => (cmds_version.clone(), None),
             PrintCmd::Loader => (cmds_loader.clone(), None),
+            PrintCmd::Playtime => (cmds_playtime.clone(), None),
+            PrintCmd::LastPlayed => (cmds_last_played.clone(), None),
         })
         .collect();
 
+    let Some((terminal_size::Width(width), _)) = terminal_size::terminal_size() else {
+        let columns: Vec<&str> = cmds.iter().map(|(n, _)| n.as_str()).collect();
+        println!("{}", columns.join("\n\n"));
+        return Ok(());
+    };
+
     println!("{}", render_row(width, &cmds, true).unwrap());
 
     Ok(())
@@ -169,6 +236,40 @@ pub fn delete_instance(
     Ok(())
 }
 
+pub fn rename_instance(
+    subcommand: (&str, &clap::ArgMatches),
+) -> Result<(), Box<dyn std::error::Error>> {
+    let instance_name: &String = subcommand.1.get_one("instance_name").unwrap();
+    let new_name: &String = subcommand.1.get_one("new_name").unwrap();
+    let is_server: bool = *subcommand.1.get_one("--server").unwrap();
+    let instance = InstanceSelection::new(instance_name, is_server);
+
+    let runtime = tokio::runtime::Runtime::new()?;
+    runtime.block_on(ql_instances::rename_instance(&instance, new_name))?;
+    Ok(())
+}
+
+pub fn clone_instance(
+    subcommand: (&str, &clap::ArgMatches),
+) -> Result<(), Box<dyn std::error::Error>> {
+    let instance_name: &String = subcommand.1.get_one("instance_name").unwrap();
+    let new_name: &String = subcommand.1.get_one("new_name").unwrap();
+    let is_server: bool = *subcommand.1.get_one("--server").unwrap();
+    let flag = |n: &str| -> bool { *subcommand.1.get_one(n).unwrap() };
+
+    let options = ql_instances::CloneOptions {
+        copy_saves: !flag("--no-saves"),
+        copy_logs: flag("--logs"),
+        copy_screenshots: flag("--screenshots"),
+        share_libraries: !flag("--copy-libraries"),
+    };
+    let instance = InstanceSelection::new(instance_name, is_server);
+
+    let runtime = tokio::runtime::Runtime::new()?;
+    runtime.block_on(ql_instances::clone_instance(&instance, new_name, options))?;
+    Ok(())
+}
+
 fn confirm_action() -> bool {
     use std::io::Write;
 
@@ -196,13 +297,54 @@ pub fn launch_instance(
     subcommand: (&str, &clap::ArgMatches),
 ) -> Result<(), Box<dyn std::error::Error>> {
     let instance_name: &String = subcommand.1.get_one("instance_name").unwrap();
-    let username: &String = subcommand.1.get_one("username").unwrap();
-    let use_account: bool = *subcommand.1.get_one("--use-account").unwrap();
+    let quick_play = get_quick_play(subcommand.1);
+    let is_dry_run: bool = *subcommand.1.get_one("--dry-run").unwrap();
+    let output_path = subcommand.1.get_one::<std::path::PathBuf>("--output");
+    if is_dry_run && output_path.is_none() {
+        // Keep stdout for the command itself, so it can be piped
+        ql_core::print::set_logs_to_stderr(true);
+    }
 
     let runtime = tokio::runtime::Runtime::new()?;
 
-    let account = refresh_account(username, use_account, &runtime)?;
+    let (username, use_account) = match subcommand.1.get_one::<String>("username") {
+        Some(username) => (
+            username.clone(),
+            *subcommand.1.get_one("--use-account").unwrap(),
+        ),
+        None => get_preferred_account(instance_name, &runtime)?,
+    };
+    if is_dry_run {
+        let account = stored_account(&username, use_account)?;
+        let command = runtime.block_on(ql_instances::launch_dry_run(
+            instance_name.clone(),
+            username.clone(),
+            account,
+            None,
+            Vec::new(),
+            quick_play,
+        ))?;
+        let format: &String = subcommand.1.get_one("--format").unwrap();
+        let output = if format == "json" {
+            serde_json::to_string_pretty(&command)?
+        } else {
+            command.to_script()
+        };
+        if let Some(path) = output_path {
+            std::fs::write(path, output)?;
+            info!("Wrote launch command to {path:?}");
+        } else {
+            println!("{output}");
+        }
+        return Ok(());
+    }
+    let account = refresh_account(&username, use_account, &runtime)?;
+
+    runtime.block_on(ql_mod_manager::store::log_mod_problems(
+        &InstanceSelection::Instance(instance_name.clone()),
+    ));
 
+    let started = chrono::Utc::now().timestamp();
     let child = runtime.block_on(ql_instances::launch(
         instance_name.clone(),
         username.clone(),
@@ -211,8 +353,25 @@ pub fn launch_instance(
         // No global defaults in CLI mode
         None,
         Vec::new(),
+        quick_play,
     ))?;
 
+    let account_type = account.as_ref().map(|n| n.account_type.clone());
+    let on_exit = |code: Option<i32>| {
+        // Errors are logged by the functions themselves
+        _ = runtime.block_on(ql_instances::record_play_session(
+            instance_name.clone(),
+            started,
+            code,
+            account_type,
+        ));
+        _ = runtime.block_on(ql_instances::run_post_exit_hook(
+            instance_name.clone(),
+            code,
+            None,
+        ));
+    };
+
     if let (Some(stdout), Some(stderr)) = {
         let mut child = child.lock().unwrap();
         (child.stdout.take(), child.stderr.take())
@@ -232,6 +391,7 @@ pub fn launch_instance(
         )) {
             Ok((s, _)) => {
                 info!("Game exited with code {s}");
+                on_exit(s.code());
                 exit(s.code().unwrap_or_default());
             }
             Err(err) => {
@@ -240,55 +400,308 @@ pub fn launch_instance(
             }
         }
     } else {
-        exit(0);
+        // Logging is disabled, but the game still has to exit
+        // before the session is recorded and the post-exit hook runs
+        let code = std::sync::Arc::try_unwrap(child)
+            .ok()
+            .and_then(|n| n.into_inner().ok())
+            .and_then(|mut n| runtime.block_on(n.wait()).ok())
+            .and_then(|n| n.code());
+        on_exit(code);
+        exit(code.unwrap_or_default());
     }
 }
 
-fn refresh_account(
-    username: &String,
-    use_account: bool,
+/// The username to launch with (and whether it's a logged-in
+/// account) when none is given, from the instance's preferred account.
+fn get_preferred_account(
+    instance_name: &str,
     runtime: &tokio::runtime::Runtime,
-) -> Result<Option<auth::AccountData>, Box<dyn std::error::Error>> {
-    Ok(if use_account {
-        let config = LauncherConfig::load_s()?;
-        let Some(accounts) = config.accounts else {
-            err!("You haven't paired any accounts yet! Use the graphical interface to add some.");
-            exit(1);
-        };
-        let Some((real_name, account)) = accounts.get_key_value(username).or_else(|| {
-            accounts
-                .iter()
-                .find(|n| n.1.username_nice.as_ref().is_some_and(|n| n == username))
-        }) else {
-            err!("No logged-in account called {username:?} was found!");
+) -> Result<(String, bool), Box<dyn std::error::Error>> {
+    let instance = InstanceSelection::Instance(instance_name.to_owned());
+    let config = runtime.block_on(InstanceConfigJson::read(&instance))?;
+    match config.preferred_account {
+        Some(PreferredAccount::Offline(username)) => Ok((username, false)),
+        Some(PreferredAccount::Account(name)) => {
+            let launcher_config = LauncherConfig::load_s()?;
+            if launcher_config
+                .accounts
+                .as_ref()
+                .is_some_and(|n| n.contains_key(&name))
+            {
+                return Ok((name, true));
+            }
+            err!("The preferred account of {instance_name:?} ({name}) is logged out!");
+            if launcher_config.username.is_empty() {
+                exit(1);
+            }
+            info!(
+                "Launching offline as {:?} instead",
+                launcher_config.username
+            );
+            Ok((launcher_config.username, false))
+        }
+        None => {
+            err!("No username given, and {instance_name:?} has no preferred account!\nPass a username, or set one in the instance's settings.");
             exit(1);
+        }
+    }
+}
+
+pub fn export_script(
+    subcommand: (&str, &clap::ArgMatches),
+) -> Result<(), Box<dyn std::error::Error>> {
+    let instance_name: &String = subcommand.1.get_one("instance_name").unwrap();
+    let username: &String = subcommand.1.get_one("username").unwrap();
+    let use_account: bool = *subcommand.1.get_one("--use-account").unwrap();
+    let output: &std::path::PathBuf = subcommand.1.get_one("--output").unwrap();
+
+    let runtime = tokio::runtime::Runtime::new()?;
+    // The token isn't stored in the script, so no need to refresh
+    let account = stored_account(username, use_account)?;
+
+    let written = runtime.block_on(ql_instances::export_launch_script(
+        instance_name.clone(),
+        username.clone(),
+        account,
+        // No global defaults in CLI mode
+        None,
+        Vec::new(),
+        output.clone(),
+    ))?;
+    for path in written {
+        println!("{}", path.to_string_lossy());
+    }
+    Ok(())
+}
+
+fn get_quick_play(matches: &clap::ArgMatches) -> Option<QuickPlay> {
+    let get = |name: &str| matches.get_one::<String>(name).cloned();
+    get("--world")
+        .map(QuickPlay::Singleplayer)
+        .or_else(|| get("--server").map(QuickPlay::Multiplayer))
+        .or_else(|| get("--realm").map(QuickPlay::Realms))
+}
+
+pub fn check_mods(subcommand: (&str, &clap::ArgMatches)) -> Result<(), Box<dyn std::error::Error>> {
+    let instance_name: &String = subcommand.1.get_one("instance_name").unwrap();
+    let instance = InstanceSelection::Instance(instance_name.clone());
+
+    let runtime = tokio::runtime::Runtime::new()?;
+    let problems = runtime.block_on(ql_mod_manager::store::check_mods(&instance))?;
+
+    if problems.is_empty() {
+        println!("{}", "No problems found".green());
+        return Ok(());
+    }
+    for problem in &problems {
+        if problem.is_fatal() {
+            println!("{} {problem}", "[error]".red());
+        } else {
+            println!("{} {problem}", "[warn]".yellow());
+        }
+    }
+    if problems
+        .iter()
+        .any(ql_mod_manager::store::ModProblem::is_fatal)
+    {
+        exit(1);
+    }
+    Ok(())
+}
+
+pub fn verify_mods(
+    subcommand: (&str, &clap::ArgMatches),
+) -> Result<(), Box<dyn std::error::Error>> {
+    let instance_name: &String = subcommand.1.get_one("instance_name").unwrap();
+    let instance = InstanceSelection::Instance(instance_name.clone());
+
+    let runtime = tokio::runtime::Runtime::new()?;
+    let report = runtime.block_on(ql_mod_manager::store::verify_mods(&instance, None))?;
+
+    for file in &report.repaired {
+        println!("{} {file}", "[repaired]".green());
+    }
+    for (file, err) in &report.failed {
+        println!("{} {file}: {err}", "[failed]".red());
+    }
+    for file in &report.untracked {
+        println!("{} {file}", "[untracked]".yellow());
+    }
+    if !report.unverified.is_empty() {
+        println!(
+            "{} file(s) installed by older launcher versions couldn't be checked",
+            report.unverified.len()
+        );
+    }
+    if !report.is_ok() {
+        exit(1);
+    }
+    Ok(())
+}
+
+pub fn clean_cache() -> Result<(), Box<dyn std::error::Error>> {
+    let runtime = tokio::runtime::Runtime::new()?;
+    let cleaned = runtime.block_on(ql_mod_manager::store::clean_cache())?;
+    println!("Reclaimed {:.1} MB", cleaned as f64 / (1024.0 * 1024.0));
+    Ok(())
+}
+
+/// Lists accounts, checking them without refreshing
+/// their tokens unless `refresh` is set.
+pub fn list_accounts(refresh: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let config = LauncherConfig::load_s()?;
+    auth::credentials::set_store(config.credential_store.unwrap_or_default());
+    let accounts = config.accounts.unwrap_or_default();
+    if accounts.is_empty() {
+        println!("No accounts logged in. Use the graphical interface to add some.");
+        return Ok(());
+    }
+
+    let runtime = tokio::runtime::Runtime::new()?;
+    let now = chrono::Utc::now().timestamp();
+    let custom_servers = config.custom_auth_servers.as_deref().unwrap_or_default();
+
+    for (name, account) in &accounts {
+        let nice_name = account.username_nice.as_deref().unwrap_or(name);
+        let Some(account_type) = account.get_account_type(name, custom_servers) else {
+            println!(
+                "{nice_name} {}",
+                format!(
+                    "(auth server {} was removed)",
+                    account.auth_server.as_deref().unwrap_or_default()
+                )
+                .bright_black()
+            );
+            continue;
         };
 
-        match account.account_type.as_deref() {
-            // Hook: Account types
-            Some(kind @ ("ElyBy" | "LittleSkin")) => {
-                let account_type = if kind == "ElyBy" {
-                    AccountType::ElyBy
-                } else {
-                    AccountType::LittleSkin
-                };
-                let refresh_token = auth::read_refresh_token(real_name, account_type)?;
-                Some(runtime.block_on(auth::yggdrasil::login_refresh(
-                    real_name.clone(),
+        let keyring_username = account.get_keyring_identifier(name, &account_type);
+        let status = match auth::read_refresh_token(keyring_username, &account_type) {
+            Ok(refresh_token) => {
+                let data = auth::AccountData {
+                    access_token: None,
+                    uuid: account.uuid.clone(),
                     refresh_token,
-                    account_type,
-                ))?)
-            }
-            _ => {
-                let refresh_token = auth::read_refresh_token(real_name, AccountType::Microsoft)?;
-                Some(runtime.block_on(auth::ms::login_refresh(
-                    real_name.clone(),
-                    refresh_token,
-                    None,
-                ))?)
+                    needs_refresh: true,
+                    account_type: account_type.clone(),
+                    username: keyring_username.to_owned(),
+                    nice_username: nice_name.to_owned(),
+                };
+                if refresh {
+                    runtime.block_on(health::check(&data)).1
+                } else {
+                    runtime.block_on(health::check_stored(&data))
+                }
             }
-        }
+            Err(err) => AccountStatus::Error(err.to_string()),
+        };
+
+        let summary = status.summary(now);
+        let summary = if status.is_expiring(now)
+            || matches!(status, AccountStatus::Expired | AccountStatus::Error(_))
+        {
+            summary.yellow().to_string()
+        } else if status == AccountStatus::Unchecked {
+            summary.bright_black().to_string()
+        } else if status.is_usable() {
+            summary.green().to_string()
+        } else {
+            summary.red().to_string()
+        };
+        println!(
+            "{nice_name} {} {summary}\n  {}",
+            format!("({account_type})").bright_black(),
+            status.bright_black()
+        );
+    }
+    Ok(())
+}
+
+fn refresh_account(
+    username: &String,
+    use_account: bool,
+    runtime: &tokio::runtime::Runtime,
+) -> Result<Option<auth::AccountData>, Box<dyn std::error::Error>> {
+    if !use_account {
+        return Ok(None);
+    }
+    let (keyring_username, account_type, _) = find_account(username)?;
+    let refresh_token = auth::read_refresh_token(&keyring_username, &account_type)?;
+
+    Ok(Some(if let AccountType::Microsoft = account_type {
+        runtime.block_on(auth::ms::login_refresh(
+            keyring_username,
+            refresh_token,
+            None,
+        ))?
     } else {
-        None
-    })
+        runtime.block_on(auth::yggdrasil::login_refresh(
+            keyring_username,
+            refresh_token,
+            account_type,
+        ))?
+    }))
+}
+
+/// The saved details of an account, without refreshing
+/// it (so without an access token). Used by `--dry-run`,
+/// which shouldn't have any side effects.
+fn stored_account(
+    username: &String,
+    use_account: bool,
+) -> Result<Option<auth::AccountData>, Box<dyn std::error::Error>> {
+    if !use_account {
+        return Ok(None);
+    }
+    let (keyring_username, account_type, account) = find_account(username)?;
+    Ok(Some(auth::AccountData {
+        access_token: None,
+        uuid: account.uuid,
+        refresh_token: String::new(),
+        needs_refresh: false,
+        nice_username: account
+            .username_nice
+            .unwrap_or_else(|| keyring_username.clone()),
+        username: keyring_username,
+        account_type,
+    }))
+}
+
+/// Finds a logged-in account by its username (or nice username),
+/// returning its keyring identifier, type and config entry.
+///
+/// Exits if there's no such account.
+fn find_account(
+    username: &String,
+) -> Result<(String, AccountType, ConfigAccount), Box<dyn std::error::Error>> {
+    let config = LauncherConfig::load_s()?;
+    auth::credentials::set_store(config.credential_store.unwrap_or_default());
+    let Some(accounts) = config.accounts else {
+        err!("You haven't paired any accounts yet! Use the graphical interface to add some.");
+        exit(1);
+    };
+    let Some((real_name, account)) = accounts.get_key_value(username).or_else(|| {
+        accounts
+            .iter()
+            .find(|n| n.1.username_nice.as_ref().is_some_and(|n| n == username))
+    }) else {
+        err!("No logged-in account called {username:?} was found!");
+        exit(1);
+    };
+
+    let Some(account_type) = account.get_account_type(
+        real_name,
+        config.custom_auth_servers.as_deref().unwrap_or_default(),
+    ) else {
+        err!(
+            "The auth server of {username:?} ({}) was removed!",
+            account.auth_server.as_deref().unwrap_or_default()
+        );
+        exit(1);
+    };
+    let keyring_username = account
+        .get_keyring_identifier(real_name, &account_type)
+        .to_owned();
+    Ok((keyring_username, account_type, account.clone()))
 }
@@ -1,7 +1,7 @@
 use clap::{Arg, ArgAction, Command};
 use itertools::Itertools;
 use owo_colors::{OwoColorize, Style};
-use ql_core::{err, LAUNCHER_VERSION_NAME, WEBSITE};
+use ql_core::{err, json::InstanceSort, LAUNCHER_VERSION_NAME, WEBSITE};
 use ql_instances::ARG_REDACT_SECTIONS;
 
 use crate::{
@@ -61,6 +61,84 @@ fn command() -> Command {
         ])
         .about("Deletes an instance of Minecraft")
     )
+    .subcommand(Command::new("rename")
+        .args([
+            Arg::new("instance_name").help("The name of the instance to rename").required(true),
+            Arg::new("new_name").help("The new name").required(true),
+            Arg::new("--server")
+                .short('s')
+                .long("server")
+                .help("Rename a server instead of an instance")
+                .action(ArgAction::SetTrue),
+        ])
+        .about("Renames an instance, updating its settings to match. Don't use while it's running")
+    )
+    .subcommand(Command::new("clone")
+        .args([
+            Arg::new("instance_name").help("The name of the instance to clone").required(true),
+            Arg::new("new_name").help("The name of the copy").required(true),
+            Arg::new("--server")
+                .short('s')
+                .long("server")
+                .help("Clone a server instead of an instance")
+                .action(ArgAction::SetTrue),
+            Arg::new("--no-saves")
+                .long("no-saves")
+                .help("Don't copy worlds")
+                .action(ArgAction::SetTrue),
+            Arg::new("--logs")
+                .long("logs")
+                .help("Also copy logs and crash reports")
+                .action(ArgAction::SetTrue),
+            Arg::new("--screenshots")
+                .long("screenshots")
+                .help("Also copy screenshots")
+                .action(ArgAction::SetTrue),
+            Arg::new("--copy-libraries")
+                .long("copy-libraries")
+                .help("Copy libraries instead of sharing them (hardlinking) with the original")
+                .action(ArgAction::SetTrue),
+        ])
+        .about("Makes a copy of an instance, with its mods, settings and (optionally) worlds")
+    )
+    .subcommand(Command::new("export-script")
+        .args([
+            Arg::new("instance_name").help("The name of the instance to export").required(true),
+            Arg::new("username").help("Username of the player").required(true),
+            Arg::new("--use-account")
+                .short('a')
+                .long("use-account")
+                .help("Use a logged in account of the given username (the script will read the token from QL_ACCESS_TOKEN)")
+                .action(ArgAction::SetTrue),
+            Arg::new("--output")
+                .short('o')
+                .long("output")
+                .help("Directory to write the script to")
+                .value_parser(clap::value_parser!(std::path::PathBuf))
+                .default_value("."),
+        ])
+        .about("Exports a script (and .desktop entry) that launches the instance without the launcher")
+    )
+    .subcommand(Command::new("check-mods")
+        .arg(Arg::new("instance_name").help("The name of the instance to check").required(true))
+        .about("Checks an instance's mods for missing dependencies, incompatibilities and other problems")
+    )
+    .subcommand(Command::new("verify-mods")
+        .arg(Arg::new("instance_name").help("The name of the instance to verify").required(true))
+        .about("Checks an instance's mod files for corruption, redownloading broken or missing ones")
+    )
+    .subcommand(Command::new("clean-cache")
+        .about("Deletes files in the shared mod cache that aren't used by any instance")
+    )
+    .subcommand(Command::new("accounts")
+        .about("Lists logged-in accounts and checks whether they still work")
+        .arg(
+            Arg::new("--refresh")
+                .long("refresh")
+                .help("Refreshes each account's login to check it (Microsoft accounts can only be checked this way)")
+                .action(ArgAction::SetTrue),
+        )
+    )
     .subcommand(Command::new("list-available-versions").short_flag('a').about("Lists all downloadable Minecraft versions"))
     .subcommand(Command::new("--no-sandbox").hide(true)) // This one doesn't do anything, but on Windows i686 it's automatically passed?
     .arg(
@@ -78,25 +156,68 @@ fn get_launch_subcommand() -> Command {
                 .help("The name of the instance to launch")
                 .required(true),
             Arg::new("username")
-                .help("Username of the player")
-                .required(true),
+                .help("Username of the player (default: the instance's preferred account)")
+                .required(false),
             Arg::new("--use-account")
                 .short('a')
                 .long("use-account")
                 .help("Whether to use a logged in account of the given username (if any)")
                 .required(false)
                 .action(ArgAction::SetTrue),
+            Arg::new("--world")
+                .short('w')
+                .long("world")
+                .help("Directly opens the given world (folder name in saves/)")
+                .conflicts_with_all(["--server", "--realm"]),
+            Arg::new("--server")
+                .short('s')
+                .long("server")
+                .help("Directly joins the given server address (host or host:port)")
+                .conflicts_with("--realm"),
+            Arg::new("--realm")
+                .long("realm")
+                .help("Directly joins the Realm with the given ID (1.20+ only)"),
+            Arg::new("--dry-run")
+                .long("dry-run")
+                .help("Prints the resolved launch command instead of starting the game (without installing or downloading anything)")
+                .action(ArgAction::SetTrue),
+            Arg::new("--format")
+                .long("format")
+                .help("Output format of --dry-run")
+                .value_parser(["shell", "json"])
+                .default_value("shell")
+                .requires("--dry-run"),
+            Arg::new("--output")
+                .short('o')
+                .long("output")
+                .help("Writes the --dry-run output to a file instead of the terminal")
+                .value_parser(clap::value_parser!(std::path::PathBuf))
+                .requires("--dry-run"),
         ])
 }
 
 fn get_list_instance_command(name: &'static str) -> Command {
-    Command::new(name).arg(
+    Command::new(name).args([
         Arg::new("fields")
-            .help("Fields to display (any combination of name, version, loader)")
+            .help("Fields to display (any combination of name, version, loader, playtime, last-played)")
             .num_args(1..) // accept 1 or more
             .action(ArgAction::Append)
-            .value_parser(["name", "version", "loader"]),
-    )
+            .value_parser(["name", "version", "loader", "playtime", "last-played"]),
+        Arg::new("--sort")
+            .long("sort")
+            .help("Order of the list (favourites always come first)")
+            .value_parser(["name", "version", "loader", "last-played", "created"])
+            .default_value("name"),
+        Arg::new("--group")
+            .long("group")
+            .short('g')
+            .help("Only list instances in this group"),
+        Arg::new("--tag")
+            .long("tag")
+            .short('t')
+            .help("Only list instances with this tag (can be repeated)")
+            .action(ArgAction::Append),
+    ])
 }
 
 fn long_about() -> String {
@@ -115,6 +236,8 @@ enum PrintCmd {
     Name,
     Version,
     Loader,
+    Playtime,
+    LastPlayed,
 }
 
 /// Prints the "intro" to the screen
@@ -191,11 +314,13 @@ pub fn start_cli(is_dir_err: bool) {
         match subcommand.0 {
             "list" | "list-instances" => {
                 let command = get_list_instance_subcommand(subcommand.1);
-                quit(command::list_instances(&command, false));
+                let filter = get_list_filter(subcommand.1);
+                quit(command::list_instances(&command, false, &filter));
             }
             "list-servers" => {
                 let command = get_list_instance_subcommand(subcommand.1);
-                quit(command::list_instances(&command, true));
+                let filter = get_list_filter(subcommand.1);
+                quit(command::list_instances(&command, true, &filter));
             }
             "list-available-versions" => {
                 command::list_available_versions();
@@ -204,6 +329,16 @@ pub fn start_cli(is_dir_err: bool) {
             "launch" => quit(command::launch_instance(subcommand)),
             "create" => quit(command::create_instance(subcommand)),
             "delete" => quit(command::delete_instance(subcommand)),
+            "rename" => quit(command::rename_instance(subcommand)),
+            "clone" => quit(command::clone_instance(subcommand)),
+            "export-script" => quit(command::export_script(subcommand)),
+            "check-mods" => quit(command::check_mods(subcommand)),
+            "verify-mods" => quit(command::verify_mods(subcommand)),
+            "clean-cache" => quit(command::clean_cache()),
+            "accounts" => {
+                let refresh: &bool = subcommand.1.get_one("--refresh").unwrap();
+                quit(command::list_accounts(*refresh));
+            }
             "--no-sandbox" => {}
             err => panic!("Unimplemented command! {err}"),
         }
@@ -221,6 +356,32 @@ fn quit(res: Result<(), Box<dyn std::error::Error + 'static>>) {
     });
 }
 
+/// How `list` sorts and filters instances.
+pub struct ListFilter {
+    pub sort: InstanceSort,
+    pub group: Option<String>,
+    /// Instances must have all of these
+    pub tags: Vec<String>,
+}
+
+fn get_list_filter(matches: &clap::ArgMatches) -> ListFilter {
+    let sort: &String = matches.get_one("--sort").unwrap();
+    ListFilter {
+        sort: match sort.as_str() {
+            "version" => InstanceSort::Version,
+            "loader" => InstanceSort::Loader,
+            "last-played" => InstanceSort::LastPlayed,
+            "created" => InstanceSort::Created,
+            _ => InstanceSort::Name,
+        },
+        group: matches.get_one::<String>("--group").cloned(),
+        tags: matches
+            .get_many::<String>("--tag")
+            .map(|n| n.cloned().collect())
+            .unwrap_or_default(),
+    }
+}
+
 fn get_list_instance_subcommand(matches: &clap::ArgMatches) -> Vec<PrintCmd> {
     if let Some(values) = matches.get_many::<String>("fields") {
         values
@@ -228,8 +389,10 @@ fn get_list_instance_subcommand(matches: &clap::ArgMatches) -> Vec<PrintCmd> {
                 "name" => PrintCmd::Name,
                 "version" => PrintCmd::Version,
                 "loader" => PrintCmd::Loader,
+                "playtime" => PrintCmd::Playtime,
+                "last-played" => PrintCmd::LastPlayed,
                 invalid => panic!(
-                    "Invalid field {invalid}! Use any combination of name, version, and loader."
+                    "Invalid field {invalid}! Use any combination of name, version, loader, playtime and last-played."
                 ),
             })
             .unique()
@@ -1,10 +1,17 @@
-use crate::{WINDOW_HEIGHT, WINDOW_WIDTH};
-use ql_core::json::GlobalSettings;
+use crate::{
+    keybinds::{KeyAction, KeyChord},
+    WINDOW_HEIGHT, WINDOW_WIDTH,
+};
+use ql_core::json::{GlobalSettings, InstanceSort};
 use ql_core::{
     err, IntoIoError, IntoJsonError, JsonFileError, LAUNCHER_DIR, LAUNCHER_VERSION_NAME,
 };
+use ql_instances::auth::{credentials::CredentialStore, AccountType, CustomAuthServer};
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, path::Path};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+};
 
 pub const SIDEBAR_WIDTH_DEFAULT: u32 = 190;
 
@@ -62,7 +69,7 @@ pub struct LauncherConfig {
     pub sidebar_width: Option<u32>,
     /// A list of Minecraft accounts logged into the launcher.
     ///
-    /// `String (username) : ConfigAccount { uuid: String, skin: None (unimplemented) }`
+    /// `String (username) : ConfigAccount { uuid: String, skin: Option<String> }`
     ///
     /// Upon opening the launcher,
     /// `read_refresh_token(username)` (in [`ql_instances::auth`])
@@ -101,6 +108,34 @@ pub struct LauncherConfig {
     // Since: v0.4.2
     pub global_settings: Option<GlobalSettings>,
     pub extra_java_args: Option<Vec<String>>,
+
+    /// The order of the instance list in the sidebar.
+    ///
+    /// Default: [`InstanceSort::Name`]
+    // Since: v0.4.2
+    pub instance_sort: Option<InstanceSort>,
+    /// Instance groups that are collapsed in the sidebar.
+    // Since: v0.4.2
+    pub collapsed_groups: Option<Vec<String>>,
+
+    /// User-defined Yggdrasil (authlib-injector) servers
+    /// that accounts can be logged into, in addition to
+    /// the built-in ely.by and littleskin.
+    // Since: v0.4.2
+    pub custom_auth_servers: Option<Vec<CustomAuthServer>>,
+
+    /// Where account tokens are saved: the system keyring
+    /// (default, falls back to the encrypted file if unavailable)
+    /// or always an encrypted file (`credentials.json`).
+    // Since: v0.4.2
+    pub credential_store: Option<CredentialStore>,
+
+    /// Keyboard shortcuts changed from their defaults
+    /// (see [`KeyAction::default_chords`]), like
+    /// `"OpenModStore": "Ctrl+Shift+M"`.
+    /// An empty string unbinds the action.
+    // Since: v0.4.2
+    pub keybinds: Option<BTreeMap<KeyAction, KeyChord>>,
 }
 
 impl Default for LauncherConfig {
@@ -120,6 +155,11 @@ impl Default for LauncherConfig {
             window: None,
             global_settings: None,
             extra_java_args: None,
+            instance_sort: None,
+            collapsed_groups: None,
+            custom_auth_servers: None,
+            credential_store: None,
+            keybinds: None,
         }
     }
 }
@@ -197,6 +237,77 @@ impl LauncherConfig {
             .pre_launch_prefix
             .get_or_insert_with(Vec::new)
     }
+
+    /// The chords bound to `action` (empty if it's unbound).
+    #[must_use]
+    pub fn get_keybinds(&self, action: KeyAction) -> Vec<KeyChord> {
+        match self.keybinds.as_ref().and_then(|n| n.get(&action)) {
+            Some(chord) if chord.is_unbound() => Vec::new(),
+            Some(chord) => vec![chord.clone()],
+            None => action.default_chords(),
+        }
+    }
+
+    /// Binds `action` to `chord` only, replacing its defaults.
+    ///
+    /// # Errors
+    /// If another action already uses `chord`
+    /// (returning that action), nothing is changed.
+    pub fn set_keybind(
+        &mut self,
+        action: KeyAction,
+        chord: KeyChord,
+    ) -> Result<(), (KeyAction, KeyChord)> {
+        self.check_keybind_conflict(action, std::slice::from_ref(&chord))?;
+        if action.default_chords() == [chord.clone()] {
+            self.reset_keybind(action)
+        } else {
+            self.keybinds
+                .get_or_insert_with(BTreeMap::new)
+                .insert(action, chord);
+            Ok(())
+        }
+    }
+
+    /// Binds `action` back to its default chords.
+    ///
+    /// # Errors
+    /// If another action was bound to one of
+    /// them in the meantime, nothing is changed.
+    pub fn reset_keybind(&mut self, action: KeyAction) -> Result<(), (KeyAction, KeyChord)> {
+        self.check_keybind_conflict(action, &action.default_chords())?;
+        if let Some(keybinds) = &mut self.keybinds {
+            keybinds.remove(&action);
+            if keybinds.is_empty() {
+                self.keybinds = None;
+            }
+        }
+        Ok(())
+    }
+
+    fn check_keybind_conflict(
+        &self,
+        action: KeyAction,
+        chords: &[KeyChord],
+    ) -> Result<(), (KeyAction, KeyChord)> {
+        for other in KeyAction::ALL.iter().filter(|n| **n != action) {
+            let other_chords = self.get_keybinds(*other);
+            if let Some(chord) = chords
+                .iter()
+                .find(|n| !n.is_unbound() && other_chords.contains(n))
+            {
+                return Err((*other, chord.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get_env_var_list(&mut self) -> &mut Vec<String> {
+        self.global_settings
+            .get_or_insert_with(GlobalSettings::default)
+            .env_vars
+            .get_or_insert_with(Vec::new)
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -212,15 +323,22 @@ pub struct ConfigAccount {
     /// You can find someone's UUID through many online services where you
     /// input their username.
     pub uuid: String,
-    /// Currently unimplemented, does nothing.
-    pub skin: Option<String>, // TODO: Add skin visualization?
+    /// URL of the account's skin, as of the last
+    /// time it was viewed in the skins menu.
+    pub skin: Option<String>,
 
     /// Type of account:
     ///
     /// - `"Microsoft"`
     /// - `"ElyBy"`
     /// - `"LittleSkin"`
+    /// - `"Custom"` (see `auth_server`)
     pub account_type: Option<String>,
+    /// For `"Custom"` accounts, the name of the
+    /// [`LauncherConfig::custom_auth_servers`] entry
+    /// the account belongs to.
+    // Since: v0.4.2
+    pub auth_server: Option<String>,
 
     /// The original login identifier used for keyring operations.
     /// This is the email address or username that was used during login.
@@ -240,6 +358,68 @@ pub struct ConfigAccount {
     pub username_nice: Option<String>,
 }
 
+impl LauncherConfig {
+    /// All accounts, as `(keyring identifier, type)`
+    /// (for moving their tokens between credential stores).
+    pub fn get_keyring_accounts(&self) -> Vec<(String, AccountType)> {
+        let custom_auth_servers = self.custom_auth_servers.as_deref().unwrap_or_default();
+        self.accounts
+            .iter()
+            .flatten()
+            .filter_map(|(username, account)| {
+                let account_type = account.get_account_type(username, custom_auth_servers)?;
+                let identifier = account
+                    .get_keyring_identifier(username, &account_type)
+                    .to_owned();
+                Some((identifier, account_type))
+            })
+            .collect()
+    }
+}
+
+impl ConfigAccount {
+    /// Works out the type of this account.
+    ///
+    /// `username` is the account's key in [`LauncherConfig::accounts`]
+    /// (used to detect the type of accounts from older versions).
+    ///
+    /// Returns `None` for custom server accounts
+    /// whose server has been removed.
+    pub fn get_account_type(
+        &self,
+        username: &str,
+        custom_auth_servers: &[CustomAuthServer],
+    ) -> Option<AccountType> {
+        // Hook: Account types
+        Some(match self.account_type.as_deref() {
+            Some("ElyBy") => AccountType::ElyBy,
+            Some("LittleSkin") => AccountType::LittleSkin,
+            Some("Custom") => AccountType::Custom(
+                custom_auth_servers
+                    .iter()
+                    .find(|n| Some(&n.name) == self.auth_server.as_ref())?
+                    .clone(),
+            ),
+            _ if username.ends_with(" (elyby)") => AccountType::ElyBy,
+            _ if username.ends_with(" (littleskin)") => AccountType::LittleSkin,
+            _ => AccountType::Microsoft,
+        })
+    }
+
+    /// The username the account's token is stored
+    /// under in the keyring.
+    pub fn get_keyring_identifier<'a>(
+        &'a self,
+        username: &'a str,
+        account_type: &AccountType,
+    ) -> &'a str {
+        self.keyring_identifier
+            .as_deref()
+            // Fallback to old behavior for backwards compatibility
+            .unwrap_or_else(|| account_type.strip_name(username))
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct WindowProperties {
     /// Whether to retain window size in the first place.
@@ -0,0 +1,328 @@
+use std::fmt::Display;
+
+use iced::keyboard::{key::Named, Key, Modifiers};
+use serde::{Deserialize, Serialize};
+
+/// Something that can be done with a keyboard shortcut.
+///
+/// The bindings are stored in [`crate::config::LauncherConfig::keybinds`].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum KeyAction {
+    Quit,
+    LaunchInstance,
+    KillInstance,
+    PreviousInstance,
+    NextInstance,
+    TabPlay,
+    TabEdit,
+    TabLogs,
+    CreateInstance,
+    OpenSettings,
+    OpenModStore,
+    OpenInstanceFolder,
+    UploadLog,
+    /// In the mod and jarmod lists
+    SelectAll,
+}
+
+impl KeyAction {
+    pub const ALL: &'static [Self] = &[
+        Self::Quit,
+        Self::LaunchInstance,
+        Self::KillInstance,
+        Self::PreviousInstance,
+        Self::NextInstance,
+        Self::TabPlay,
+        Self::TabEdit,
+        Self::TabLogs,
+        Self::CreateInstance,
+        Self::OpenSettings,
+        Self::OpenModStore,
+        Self::OpenInstanceFolder,
+        Self::UploadLog,
+        Self::SelectAll,
+    ];
+
+    /// The chords an action has until it's changed in the settings.
+    ///
+    /// Some have more than one, to keep shortcuts
+    /// from older versions (like `Alt+1` for the Play tab).
+    #[must_use]
+    pub fn default_chords(self) -> Vec<KeyChord> {
+        match self {
+            KeyAction::Quit => vec![KeyChord::command("q")],
+            KeyAction::LaunchInstance => vec![KeyChord::command(&named(Named::Enter))],
+            KeyAction::KillInstance => vec![KeyChord::command(&named(Named::Backspace))],
+            KeyAction::PreviousInstance => vec![KeyChord::plain(&named(Named::ArrowUp))],
+            KeyAction::NextInstance => vec![KeyChord::plain(&named(Named::ArrowDown))],
+            KeyAction::TabPlay => vec![KeyChord::command("1"), KeyChord::alt("1")],
+            KeyAction::TabEdit => vec![KeyChord::command("2"), KeyChord::alt("2")],
+            KeyAction::TabLogs => vec![KeyChord::command("3"), KeyChord::alt("3")],
+            KeyAction::CreateInstance => vec![KeyChord::command("n")],
+            KeyAction::OpenSettings => vec![KeyChord::command(",")],
+            KeyAction::OpenModStore => vec![KeyChord::command("m")],
+            KeyAction::OpenInstanceFolder => vec![KeyChord::command("o")],
+            KeyAction::UploadLog => vec![KeyChord::command("u")],
+            KeyAction::SelectAll => vec![KeyChord::command("a")],
+        }
+    }
+}
+
+impl Display for KeyAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                KeyAction::Quit => "Quit the launcher",
+                KeyAction::LaunchInstance => "Launch instance",
+                KeyAction::KillInstance => "Kill instance",
+                KeyAction::PreviousInstance => "Select previous instance",
+                KeyAction::NextInstance => "Select next instance",
+                KeyAction::TabPlay => "Play tab",
+                KeyAction::TabEdit => "Edit tab",
+                KeyAction::TabLogs => "Logs tab",
+                KeyAction::CreateInstance => "Create instance",
+                KeyAction::OpenSettings => "Open launcher settings",
+                KeyAction::OpenModStore => "Open mod store",
+                KeyAction::OpenInstanceFolder => "Open instance folder",
+                KeyAction::UploadLog => "Upload log",
+                KeyAction::SelectAll => "Select all/none (mods)",
+            }
+        )
+    }
+}
+
+/// A key together with the modifiers held with it,
+/// like `Ctrl+Shift+K`.
+///
+/// Stored as that text in the config, so it can be edited
+/// by hand. "Ctrl" means "Command" on macOS.
+///
+/// Keys are matched by the character they type, so
+/// with Shift held, `1` is pressed as `!` (on US layouts):
+/// write `Ctrl+Shift+!`, not `Ctrl+Shift+1`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "String", into = "String")]
+pub struct KeyChord {
+    /// Lowercase character, or the name of
+    /// a special key (`Enter`, `ArrowUp`, `F5`, ...).
+    ///
+    /// Empty if the action is unbound.
+    pub key: String,
+    pub command: bool,
+    pub alt: bool,
+    pub shift: bool,
+}
+
+impl KeyChord {
+    #[must_use]
+    pub fn unbound() -> Self {
+        Self::plain("")
+    }
+
+    #[must_use]
+    pub fn is_unbound(&self) -> bool {
+        self.key.is_empty()
+    }
+
+    fn plain(key: &str) -> Self {
+        Self {
+            key: key.to_owned(),
+            command: false,
+            alt: false,
+            shift: false,
+        }
+    }
+
+    fn command(key: &str) -> Self {
+        Self {
+            command: true,
+            ..Self::plain(key)
+        }
+    }
+
+    fn alt(key: &str) -> Self {
+        Self {
+            alt: true,
+            ..Self::plain(key)
+        }
+    }
+
+    /// The same key, without any modifiers.
+    #[must_use]
+    pub fn without_modifiers(&self) -> Self {
+        Self::plain(&self.key)
+    }
+
+    /// The chord for a key press, or `None` if it's
+    /// only a modifier (or not a recognizable key).
+    #[must_use]
+    pub fn from_press(key: &Key, modifiers: Modifiers) -> Option<Self> {
+        let key = match key {
+            Key::Named(
+                Named::Shift
+                | Named::Control
+                | Named::Alt
+                | Named::AltGraph
+                | Named::Super
+                | Named::Meta
+                | Named::Hyper
+                | Named::Fn
+                | Named::CapsLock,
+            )
+            | Key::Unidentified => return None,
+            Key::Named(key) => named(*key),
+            Key::Character(ch) => ch.to_lowercase(),
+        };
+        Some(Self {
+            key,
+            command: modifiers.command(),
+            alt: modifiers.alt(),
+            shift: modifiers.shift(),
+        })
+    }
+}
+
+impl Display for KeyChord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_unbound() {
+            return Ok(());
+        }
+        if self.command {
+            write!(f, "Ctrl+")?;
+        }
+        if self.alt {
+            write!(f, "Alt+")?;
+        }
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+        if self.key.chars().count() == 1 {
+            write!(f, "{}", self.key.to_uppercase())
+        } else {
+            write!(f, "{}", self.key)
+        }
+    }
+}
+
+impl From<KeyChord> for String {
+    fn from(value: KeyChord) -> Self {
+        value.to_string()
+    }
+}
+
+impl From<String> for KeyChord {
+    /// Never fails, so a hand-edited typo can't make the
+    /// whole config invalid. Unknown keys just never match.
+    fn from(value: String) -> Self {
+        let mut chord = Self::unbound();
+        let mut rest = value.trim();
+        // The key itself can be `+`, as in `Ctrl++`
+        loop {
+            if let Some(n) = rest.strip_prefix("Ctrl+").filter(|n| !n.is_empty()) {
+                chord.command = true;
+                rest = n;
+            } else if let Some(n) = rest.strip_prefix("Alt+").filter(|n| !n.is_empty()) {
+                chord.alt = true;
+                rest = n;
+            } else if let Some(n) = rest.strip_prefix("Shift+").filter(|n| !n.is_empty()) {
+                chord.shift = true;
+                rest = n;
+            } else {
+                break;
+            }
+        }
+        // Single characters are shown uppercase
+        // for readability, but matched lowercase
+        chord.key = if rest.chars().count() == 1 {
+            rest.to_lowercase()
+        } else {
+            rest.to_owned()
+        };
+        chord
+    }
+}
+
+/// The name of a special key, as used in [`KeyChord::key`].
+///
+/// `Named` doesn't implement `Display`, but the variant
+/// names (`ArrowUp`, `F5`, ...) are what we want anyway.
+fn named(key: Named) -> String {
+    format!("{key:?}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LauncherConfig;
+
+    fn parse(s: &str) -> KeyChord {
+        KeyChord::from(s.to_owned())
+    }
+
+    #[test]
+    fn chord_round_trip() {
+        for s in ["Ctrl+Q", "Ctrl+Alt+Shift+F5", "ArrowUp", "Ctrl+,", "Alt+1"] {
+            assert_eq!(parse(s).to_string(), s);
+        }
+
+        let plus = parse("Ctrl++");
+        assert_eq!(plus.key, "+");
+        assert!(plus.command && !plus.alt && !plus.shift);
+        assert_eq!(plus.to_string(), "Ctrl++");
+
+        assert_eq!(parse("Ctrl+q"), parse("Ctrl+Q"));
+        assert!(parse("").is_unbound());
+        assert_eq!(KeyChord::unbound().to_string(), "");
+    }
+
+    #[test]
+    fn keybind_conflicts() {
+        let mut config = LauncherConfig::default();
+
+        // Used by `Quit`
+        assert_eq!(
+            config.set_keybind(KeyAction::UploadLog, parse("Ctrl+Q")),
+            Err((KeyAction::Quit, parse("Ctrl+Q")))
+        );
+        // Only the second default of `TabPlay`
+        assert_eq!(
+            config.set_keybind(KeyAction::UploadLog, parse("Alt+1")),
+            Err((KeyAction::TabPlay, parse("Alt+1")))
+        );
+        assert_eq!(config.keybinds, None);
+
+        config
+            .set_keybind(KeyAction::UploadLog, parse("Ctrl+Shift+U"))
+            .unwrap();
+        assert_eq!(
+            config.get_keybinds(KeyAction::UploadLog),
+            vec![parse("Ctrl+Shift+U")]
+        );
+
+        // Any number of actions can be unbound
+        config
+            .set_keybind(KeyAction::Quit, KeyChord::unbound())
+            .unwrap();
+        config
+            .set_keybind(KeyAction::SelectAll, KeyChord::unbound())
+            .unwrap();
+        assert!(config.get_keybinds(KeyAction::Quit).is_empty());
+
+        // `Ctrl+Q` is free now, but resetting `Quit` after
+        // taking it would make two actions use it
+        config
+            .set_keybind(KeyAction::OpenModStore, parse("Ctrl+Q"))
+            .unwrap();
+        assert_eq!(
+            config.reset_keybind(KeyAction::Quit),
+            Err((KeyAction::OpenModStore, parse("Ctrl+Q")))
+        );
+        config.reset_keybind(KeyAction::OpenModStore).unwrap();
+        config.reset_keybind(KeyAction::Quit).unwrap();
+        config.reset_keybind(KeyAction::SelectAll).unwrap();
+        config.reset_keybind(KeyAction::UploadLog).unwrap();
+        assert_eq!(config.keybinds, None);
+    }
+}
@@ -34,7 +34,7 @@ use state::{get_entries, Launcher, Message, ServerProcess};
 use ql_core::{err, err_no_log, file_utils, info, info_no_log, IntoStringError, JsonFileError};
 use ql_instances::OS_NAME;
 
-use crate::state::CustomJarState;
+use crate::state::{CustomJarState, EditInstanceMessage};
 
 /// The CLI interface of the launcher.
 mod cli;
@@ -43,6 +43,8 @@ mod config;
 /// Definitions of certain icons (like Download,
 /// Play, Settings and so on) as `iced::widget`.
 mod icon_manager;
+/// Customizable keyboard shortcuts.
+mod keybinds;
 /// All the main structs and enums used in the launcher.
 mod state;
 
@@ -100,10 +102,15 @@ impl Launcher {
 
         let get_entries_command = Task::perform(get_entries(false), Message::CoreListLoaded);
 
+        let mut launcher =
+            Launcher::load_new(None, is_new_user, config).unwrap_or_else(Launcher::with_error);
+        let check_accounts_command = launcher.check_accounts(false);
+
         (
-            Launcher::load_new(None, is_new_user, config).unwrap_or_else(Launcher::with_error),
+            launcher,
             Task::batch([
                 check_for_updates_command,
+                check_accounts_command,
                 get_entries_command,
                 Task::perform(ql_core::clean::dir("logs"), |n| {
                     Message::CoreCleanComplete(n.strerr())
@@ -112,6 +119,9 @@ impl Launcher {
                     Message::CoreCleanComplete(n.strerr())
                 }),
                 CustomJarState::load(),
+                Task::perform(ql_instances::find_java_installs(false), |n| {
+                    Message::EditInstance(EditInstanceMessage::JavaInstallsLoaded(n))
+                }),
             ]),
         )
     }
@@ -1,15 +1,20 @@
 use crate::{
     icon_manager,
     menu_renderer::{button_with_icon, FONT_MONO},
-    state::{CustomJarState, EditInstanceMessage, MenuEditInstance, Message, NONE_JAR_NAME},
+    state::{
+        CustomJarState, EditInstanceMessage, MenuEditInstance, Message, NEW_ACCOUNT_NAME,
+        NONE_JAR_NAME, OFFLINE_ACCOUNT_NAME, SELECTED_ACCOUNT_NAME,
+    },
     stylesheet::{color::Color, styles::LauncherTheme},
 };
-use iced::{widget, Length};
+use iced::{widget, Alignment, Length};
 use ql_core::json::{
-    instance_config::{JavaArgsMode, PreLaunchPrefixMode},
+    instance_config::{JavaArgsMode, PreLaunchPrefixMode, PreferredAccount},
     GlobalSettings,
 };
 use ql_core::InstanceSelection;
+use ql_instances::{validate_name, CloneOptions, JavaInstall};
+use std::path::Path;
 
 use super::Element;
 
@@ -18,6 +23,9 @@ impl MenuEditInstance {
         &'a self,
         selected_instance: &InstanceSelection,
         jar_choices: Option<&'a CustomJarState>,
+        java_installs: Option<&'a [JavaInstall]>,
+        accounts: &[String],
+        icon: Option<&widget::image::Handle>,
     ) -> Element<'a> {
         let ts = |n: &LauncherTheme| n.style_text(Color::SecondLight);
 
@@ -35,16 +43,16 @@ impl MenuEditInstance {
                             }
                         ).style(|t: &LauncherTheme| t.style_text(Color::Mid)).size(14),
                         widget::Space::with_height(1),
-                        widget::row!(
-                            widget::button("Rename").on_press(Message::EditInstance(EditInstanceMessage::RenameApply)),
-                            widget::text_input("Rename Instance", &self.instance_name).on_input(|n| Message::EditInstance(EditInstanceMessage::RenameEdit(n))),
-                        ).spacing(5),
+                        self.item_rename(),
+                        self.item_group_tags(),
+                        self.item_clone(),
+                        item_icon(icon),
                     ].padding(10).spacing(5),
                 )
                 .style(|n: &LauncherTheme| n.style_container_sharp_box(0.0, Color::Dark)),
 
                 widget::container(
-                    self.item_java_override()
+                    self.item_java_override(java_installs)
                 ).style(|n: &LauncherTheme| n.style_container_sharp_box(0.0, Color::ExtraDark)),
                 widget::container(
                     self.item_custom_jar(jar_choices)
@@ -54,9 +62,11 @@ impl MenuEditInstance {
                 ).style(|n: &LauncherTheme| n.style_container_sharp_box(0.0, Color::ExtraDark)),
                 widget::container(
                     widget::Column::new()
-                    .push_maybe((!selected_instance.is_server()).then_some(widget::column![
+                    .push_maybe((!selected_instance.is_server()).then(|| widget::column![
                         widget::checkbox("Close launcher after game opens", self.config.close_on_start.unwrap_or(false))
                             .on_toggle(|t| Message::EditInstance(EditInstanceMessage::CloseLauncherToggle(t))),
+                        widget::Space::with_height(5),
+                        self.item_preferred_account(accounts),
                     ].spacing(5)))
                     .push(
                         widget::column![
@@ -87,8 +97,23 @@ impl MenuEditInstance {
                 .padding(10)
                 .width(Length::Fill),
                 widget::container(
-                    button_with_icon(icon_manager::delete(), "Delete Instance", 16)
-                        .on_press(Message::DeleteInstanceMenu)
+                    widget::column![
+                        widget::row![
+                            button_with_icon(icon_manager::delete(), "Delete Instance", 16)
+                                .on_press(Message::DeleteInstanceMenu),
+                        ]
+                        .push_maybe((!selected_instance.is_server()).then_some(
+                            widget::button("Export Launch Script")
+                                .on_press(Message::EditInstance(EditInstanceMessage::ExportLaunchScript))
+                        ))
+                        .spacing(5)
+                    ]
+                    .push_maybe(self.export_notice.as_deref().map(|notice| {
+                        widget::text(notice)
+                            .size(12)
+                            .style(|n: &LauncherTheme| n.style_text(Color::SecondLight))
+                    }))
+                    .spacing(10)
                 )
                 .width(Length::Fill)
                 .padding(10)
@@ -97,6 +122,139 @@ impl MenuEditInstance {
         ).style(LauncherTheme::style_scrollable_flat_extra_dark).into()
     }
 
+    fn item_group_tags(&self) -> widget::Column<'_, Message, LauncherTheme> {
+        let apply = self
+            .group_tags_changed
+            .then_some(Message::EditInstance(EditInstanceMessage::GroupTagsApply));
+
+        widget::column![
+            widget::row![
+                widget::button("Save").on_press_maybe(apply.clone()),
+                widget::text_input("Group", &self.group_input)
+                    .on_input(|n| Message::EditInstance(EditInstanceMessage::GroupEdit(n)))
+                    .on_submit_maybe(apply.clone()),
+                widget::text_input("Tags (comma separated)", &self.tags_input)
+                    .on_input(|n| Message::EditInstance(EditInstanceMessage::TagsEdit(n)))
+                    .on_submit_maybe(apply),
+            ]
+            .spacing(5),
+            widget::checkbox("Favourite (shown at the top)", self.is_favourite)
+                .on_toggle(|t| Message::EditInstance(EditInstanceMessage::FavouriteToggle(t))),
+        ]
+        .spacing(5)
+    }
+
+    fn item_rename(&self) -> widget::Column<'_, Message, LauncherTheme> {
+        let new_name = self.instance_name.trim();
+        let is_changed = new_name != self.old_instance_name;
+        let error = is_changed.then(|| validate_name(new_name).err()).flatten();
+        let apply = (is_changed && error.is_none())
+            .then_some(Message::EditInstance(EditInstanceMessage::RenameApply));
+
+        widget::column![widget::row![
+            widget::button("Rename").on_press_maybe(apply.clone()),
+            widget::text_input("Rename Instance", &self.instance_name)
+                .on_input(|n| Message::EditInstance(EditInstanceMessage::RenameEdit(n)))
+                .on_submit_maybe(apply),
+        ]
+        .spacing(5)]
+        .push_maybe(error.map(|n| {
+            widget::text!("Invalid name: {n}")
+                .size(12)
+                .style(|t: &LauncherTheme| t.style_text(Color::SecondLight))
+        }))
+        .spacing(5)
+    }
+
+    fn item_clone(&self) -> widget::Column<'_, Message, LauncherTheme> {
+        let options = self.clone_options;
+        let toggle = move |f: fn(&mut CloneOptions, bool)| {
+            move |t| {
+                let mut options = options;
+                f(&mut options, t);
+                Message::EditInstance(EditInstanceMessage::CloneOptionsChanged(options))
+            }
+        };
+
+        widget::column![
+            widget::row![
+                widget::button(if self.is_cloning {
+                    "Cloning..."
+                } else {
+                    "Clone"
+                })
+                .on_press_maybe(
+                    (!self.is_cloning)
+                        .then_some(Message::EditInstance(EditInstanceMessage::CloneStart))
+                ),
+                widget::text_input("Name of copy", &self.clone_name)
+                    .on_input(|n| Message::EditInstance(EditInstanceMessage::CloneNameEdit(n))),
+            ]
+            .spacing(5),
+            widget::row![
+                widget::checkbox("Worlds", options.copy_saves)
+                    .on_toggle(toggle(|o, t| o.copy_saves = t)),
+                widget::checkbox("Logs", options.copy_logs)
+                    .on_toggle(toggle(|o, t| o.copy_logs = t)),
+                widget::checkbox("Screenshots", options.copy_screenshots)
+                    .on_toggle(toggle(|o, t| o.copy_screenshots = t)),
+                widget::checkbox("Share libraries (saves space)", options.share_libraries)
+                    .on_toggle(toggle(|o, t| o.share_libraries = t)),
+            ]
+            .spacing(10),
+        ]
+        .spacing(5)
+    }
+
+    fn item_preferred_account(
+        &self,
+        accounts: &[String],
+    ) -> widget::Column<'_, Message, LauncherTheme> {
+        let selected = match &self.config.preferred_account {
+            None => SELECTED_ACCOUNT_NAME,
+            Some(PreferredAccount::Offline(_)) => OFFLINE_ACCOUNT_NAME,
+            Some(PreferredAccount::Account(name)) => name,
+        };
+        let choices: Vec<String> = [SELECTED_ACCOUNT_NAME, OFFLINE_ACCOUNT_NAME]
+            .into_iter()
+            .chain(
+                accounts
+                    .iter()
+                    .map(String::as_str)
+                    .filter(|n| *n != OFFLINE_ACCOUNT_NAME && *n != NEW_ACCOUNT_NAME),
+            )
+            .map(str::to_owned)
+            .collect();
+
+        widget::column![widget::row![
+            "Account:",
+            widget::pick_list(choices, Some(selected.to_owned()), |n| {
+                Message::EditInstance(EditInstanceMessage::PreferredAccountPicked(n))
+            }),
+        ]
+        .align_y(Alignment::Center)
+        .spacing(10),]
+        .push_maybe(
+            if let Some(PreferredAccount::Offline(username)) = &self.config.preferred_account {
+                Some(
+                    widget::text_input("Offline username", username).on_input(|n| {
+                        Message::EditInstance(EditInstanceMessage::PreferredOfflineNameEdit(n))
+                    }),
+                )
+            } else {
+                None
+            },
+        )
+        .push(
+            widget::text(
+                "Used instead of the launcher's selected account when playing this instance",
+            )
+            .size(12)
+            .style(|n: &LauncherTheme| n.style_text(Color::SecondLight)),
+        )
+        .spacing(5)
+    }
+
     fn item_args(&self) -> widget::Column<'_, Message, LauncherTheme> {
         let current_mode = self.config.java_args_mode.unwrap_or_default();
 
@@ -177,6 +335,49 @@ impl MenuEditInstance {
                     Message::EditInstance(EditInstanceMessage::PreLaunchPrefixAdd)
                 )
             ),
+            widget::text("Environment variables:").size(20),
+            widget::container(
+                widget::column![
+                    widget::text("Interaction with global environment variables:").size(14),
+                    widget::pick_list(
+                        PreLaunchPrefixMode::ALL,
+                        Some(self.config.env_vars_mode.unwrap_or_default()),
+                        |mode| {
+                            Message::EditInstance(EditInstanceMessage::EnvVarsModeChanged(mode))
+                        }
+                    )
+                    .placeholder("Select mode...")
+                    .width(200)
+                    .text_size(14),
+                    widget::text(
+                        "In the form KEY=value. If a variable is set twice, the later one wins"
+                    )
+                    .size(12)
+                    .style(|theme: &LauncherTheme| theme.style_text(Color::SecondLight)),
+                ]
+                .padding(10)
+                .spacing(7)
+            ),
+            widget::column!(
+                Self::get_java_args_list(
+                    self.config
+                        .global_settings
+                        .as_ref()
+                        .and_then(|n| n.env_vars.as_deref()),
+                    |n| Message::EditInstance(EditInstanceMessage::EnvVarDelete(n)),
+                    |n| Message::EditInstance(EditInstanceMessage::EnvVarShiftUp(n)),
+                    |n| Message::EditInstance(EditInstanceMessage::EnvVarShiftDown(n)),
+                    &|n, i| Message::EditInstance(EditInstanceMessage::EnvVarEdit(n, i))
+                ),
+                button_with_icon(icon_manager::create(), "Add", 16)
+                    .on_press(Message::EditInstance(EditInstanceMessage::EnvVarAdd))
+            ),
+            hooks_dialog(
+                self.config.global_settings.as_ref(),
+                |n| Message::EditInstance(EditInstanceMessage::PreLaunchHookChanged(n)),
+                |n| Message::EditInstance(EditInstanceMessage::PostExitHookChanged(n)),
+                false,
+            ),
         )
         .padding(10)
         .spacing(10)
@@ -232,15 +433,56 @@ impl MenuEditInstance {
         .spacing(5)
     }
 
-    fn item_java_override(&self) -> widget::Column<'_, Message, LauncherTheme> {
+    fn item_java_override<'a>(
+        &'a self,
+        java_installs: Option<&'a [JavaInstall]>,
+    ) -> widget::Column<'a, Message, LauncherTheme> {
+        let ts = |n: &LauncherTheme| n.style_text(Color::SecondLight);
+
+        let picker: Element = if let Some(installs) = java_installs {
+            let selected = installs.iter().find(|n| {
+                self.config
+                    .java_override
+                    .as_deref()
+                    .is_some_and(|path| n.path == Path::new(path))
+            });
+            widget::pick_list(installs, selected, |n| {
+                Message::EditInstance(EditInstanceMessage::JavaInstallPicked(n))
+            })
+            .placeholder(if installs.is_empty() {
+                "No installed Java found"
+            } else {
+                "Pick an installed Java"
+            })
+            .width(Length::Fill)
+            .into()
+        } else {
+            widget::text("Looking for installed Java...").into()
+        };
+
         widget::column![
             "Custom Java executable (full path)",
             widget::text_input(
                 "Leave blank if none",
                 self.config.java_override.as_deref().unwrap_or_default()
             )
-            .on_input(|t| Message::EditInstance(EditInstanceMessage::JavaOverride(t)))
+            .on_input(|t| Message::EditInstance(EditInstanceMessage::JavaOverride(t))),
+            widget::row![
+                picker,
+                widget::button("Rescan").on_press_maybe(java_installs.is_some().then_some(
+                    Message::EditInstance(EditInstanceMessage::JavaInstallsRescan)
+                )),
+            ]
+            .spacing(5),
+            widget::text!("This instance requires Java {}", self.java_required)
+                .size(12)
+                .style(ts),
         ]
+        .push_maybe(self.java_error.as_deref().map(|err| {
+            widget::text(err)
+                .size(12)
+                .style(|n: &LauncherTheme| n.style_text(Color::Light))
+        }))
         .padding(10)
         .spacing(10)
     }
@@ -348,6 +590,23 @@ use "Mods->Jar Mods""#
     }
 }
 
+fn item_icon<'a>(icon: Option<&widget::image::Handle>) -> widget::Row<'a, Message, LauncherTheme> {
+    widget::row!["Icon:"]
+        .push_maybe(icon.map(|n| widget::image(n.clone()).width(32).height(32)))
+        .push(
+            widget::button(widget::text("Change").size(14))
+                .on_press(Message::EditInstance(EditInstanceMessage::IconPick)),
+        )
+        .push_maybe(
+            icon.is_some().then_some(
+                widget::button(widget::text("Remove").size(14))
+                    .on_press(Message::EditInstance(EditInstanceMessage::IconRemove)),
+            ),
+        )
+        .align_y(Alignment::Center)
+        .spacing(10)
+}
+
 pub fn resolution_dialog<'a>(
     global_settings: Option<&GlobalSettings>,
     width: impl Fn(String) -> Message + 'a,
@@ -424,6 +683,75 @@ You can override or customize their behaviour on a per-instance basis too."
     .spacing(5)
 }
 
+pub fn global_env_vars_dialog<'a>(
+    env_vars: Option<&'a [String]>,
+    add_msg: Message,
+    delete_msg: impl Fn(usize) -> Message + 'a,
+    edit_msg: &'a dyn Fn(String, usize) -> Message,
+    up_msg: impl Fn(usize) -> Message + 'a,
+    down_msg: impl Fn(usize) -> Message + 'a,
+) -> widget::Column<'a, Message, LauncherTheme> {
+    let ts = |n: &LauncherTheme| n.style_text(Color::SecondLight);
+
+    widget::column![
+        "Global Environment Variables:",
+        widget::text(
+            r"Environment variables (in the form KEY=value) to launch all instances with.
+You can override or customize their behaviour on a per-instance basis too."
+        )
+        .size(12)
+        .style(ts),
+        widget::column!(
+            MenuEditInstance::get_java_args_list(env_vars, delete_msg, up_msg, down_msg, edit_msg),
+            button_with_icon(icon_manager::create(), "Add Variable", 16).on_press(add_msg)
+        )
+        .spacing(5),
+    ]
+    .spacing(5)
+}
+
+pub fn hooks_dialog<'a>(
+    global_settings: Option<&'a GlobalSettings>,
+    pre_launch: impl Fn(String) -> Message + 'a,
+    post_exit: impl Fn(String) -> Message + 'a,
+    global: bool,
+) -> widget::Column<'a, Message, LauncherTheme> {
+    let ts = |n: &LauncherTheme| n.style_text(Color::SecondLight);
+
+    widget::column![
+        if global { "Global Hooks:" } else { "Hooks:" },
+        widget::text!(
+            r"Shell commands to run before launching the game (it won't launch if this fails),
+and after the game exits. They get these environment variables:
+QL_INSTANCE_NAME, QL_INSTANCE_DIR, QL_MINECRAFT_DIR, QL_LAUNCHER_DIR, QL_EXIT_CODE (after exit){}",
+            if global {
+                "\nIndividual instances can override these."
+            } else {
+                "\nLeave empty to use the global hooks."
+            }
+        )
+        .size(12)
+        .style(ts),
+        widget::text("Pre-launch:").size(14),
+        widget::text_input(
+            "Command",
+            global_settings
+                .and_then(|n| n.pre_launch_hook.as_deref())
+                .unwrap_or_default()
+        )
+        .on_input(pre_launch),
+        widget::text("Post-exit:").size(14),
+        widget::text_input(
+            "Command",
+            global_settings
+                .and_then(|n| n.post_exit_hook.as_deref())
+                .unwrap_or_default()
+        )
+        .on_input(post_exit),
+    ]
+    .spacing(5)
+}
+
 pub fn global_pre_launch_prefix_dialog<'a>(
     prefix_args: Option<&'a [String]>,
     add_msg: Message,
@@ -4,7 +4,11 @@ use iced::advanced::text::Wrapping;
 use iced::keyboard::Modifiers;
 use iced::widget::tooltip::Position;
 use iced::{widget, Length, Padding};
-use ql_core::{InstanceSelection, LAUNCHER_VERSION_NAME};
+use ql_core::{
+    json::{history::format_playtime, InstanceHistory, InstanceSort},
+    InstanceSelection, LAUNCHER_VERSION_NAME,
+};
+use ql_instances::{ProcessMonitor, QuickPlay};
 
 use crate::menu_renderer::underline;
 use crate::{
@@ -13,8 +17,8 @@ use crate::{
     message_handler::SIDEBAR_DRAG_LEEWAY,
     state::{
         AccountMessage, CreateInstanceMessage, InstanceLog, LaunchTabId, Launcher,
-        LauncherSettingsMessage, ManageModsMessage, MenuLaunch, Message, State, NEW_ACCOUNT_NAME,
-        OFFLINE_ACCOUNT_NAME,
+        LauncherSettingsMessage, ManageModsMessage, MenuLaunch, Message, SkinsMessage, State,
+        NEW_ACCOUNT_NAME, OFFLINE_ACCOUNT_NAME,
     },
     stylesheet::{color::Color, styles::LauncherTheme, widgets::StyleButton},
 };
@@ -93,27 +97,34 @@ impl Launcher {
                     .spacing(5)
                     .wrap();
 
-                    widget::column!(
-                        main_buttons,
-                        widget::horizontal_rule(10)
-                            .style(|n: &LauncherTheme| n.style_rule(Color::SecondDark, 2)),
+                    widget::column!(main_buttons)
+                        .push_maybe(
+                            (!menu.is_viewing_server)
+                                .then_some(menu.play_history.as_ref())
+                                .flatten()
+                                .and_then(get_playtime_text),
+                        )
+                        .push_maybe((!menu.is_viewing_server).then(|| get_quick_play_row(menu)))
+                        .push(
+                            widget::horizontal_rule(10)
+                                .style(|n: &LauncherTheme| n.style_rule(Color::SecondDark, 2)),
+                        )
                         // widget::button("Export Instance").on_press(Message::ExportInstanceOpen),
-                    )
-                    .push_maybe({
-                        if let Some(selected_instance) = selected_instance_s {
-                            if self.is_process_running(menu, selected_instance) {
-                                Some(widget::text("Running...").size(20))
+                        .push_maybe({
+                            if let Some(selected_instance) = selected_instance_s {
+                                if self.is_process_running(menu, selected_instance) {
+                                    Some(widget::text("Running...").size(20))
+                                } else {
+                                    None
+                                }
                             } else {
                                 None
                             }
-                        } else {
-                            None
-                        }
-                    })
-                    .push(last_parts)
-                    .padding(10)
-                    .spacing(5)
-                    .into()
+                        })
+                        .push(last_parts)
+                        .padding(10)
+                        .spacing(5)
+                        .into()
                 }
                 LaunchTabId::Log => self
                     .get_log_pane(
@@ -128,7 +139,13 @@ impl Launcher {
                     .into(),
                 LaunchTabId::Edit => {
                     if let Some(menu) = &menu.edit_instance {
-                        menu.view(selected, self.custom_jar.as_ref())
+                        menu.view(
+                            selected,
+                            self.custom_jar.as_ref(),
+                            self.java_installs.as_deref(),
+                            &self.accounts_dropdown,
+                            self.instance_icons.get(selected),
+                        )
                     } else {
                         widget::column!(
                             "Error: Could not read config json!",
@@ -228,6 +245,12 @@ impl Launcher {
                     .size(18),
                 ),
             )
+            .push_maybe(
+                selected_instance
+                    .filter(|_| !menu.is_viewing_server)
+                    .and_then(|n| self.client_processes.get(n))
+                    .and_then(|n| view_process_monitor(&n.monitor)),
+            )
             .push_maybe(
                 menu.is_viewing_server.then_some(
                     widget::text_input("Enter command...", command)
@@ -265,52 +288,18 @@ impl Launcher {
             && difference > 0.0
             && (!self.is_log_open || (self.mouse_pos.1 < self.window_size.1 / 2.0));
 
-        let list = widget::row!(if let Some(instances) = list {
+        let list = widget::row!(if list.is_some() {
             widget::column![
                 get_sidebar_new_button(menu),
-                widget::scrollable(widget::column(instances.iter().map(|name| {
-                    let playing_icon = if self.is_process_running(menu, name) {
-                        Some(widget::row![
-                            widget::horizontal_space(),
-                            icon_manager::play(),
-                            widget::Space::with_width(10),
-                        ])
-                    } else {
-                        None
-                    };
-
-                    let text = widget::text(name)
-                        .size(15)
-                        .style(|t: &LauncherTheme| t.style_text(Color::SecondLight));
-
-                    let selector: Element = if selected_instance_s == Some(name) {
-                        widget::container(widget::row!(widget::Space::with_width(5), text))
-                            .style(LauncherTheme::style_container_selected_flat_button)
-                            .width(Length::Fill)
-                            .padding(5)
-                            .into()
-                    } else {
-                        widget::button(widget::row![text].push_maybe(playing_icon))
-                            .style(|n: &LauncherTheme, status| {
-                                n.style_button(status, StyleButton::FlatExtraDark)
-                            })
-                            .on_press(Message::LaunchInstanceSelected {
-                                name: name.clone(),
-                                is_server: menu.is_viewing_server,
-                            })
-                            .width(Length::Fill)
-                            .into()
-                    };
-
-                    underline(selector, Color::Dark).into()
-                })))
-                .height(Length::Fill)
-                .style(LauncherTheme::style_scrollable_flat_extra_dark)
-                .id(widget::scrollable::Id::new("MenuLaunch:sidebar"))
-                .on_scroll(|n| {
-                    let total = n.content_bounds().height - n.bounds().height;
-                    Message::LaunchScrollSidebar(total)
-                }),
+                self.get_sidebar_filter(menu),
+                widget::scrollable(self.get_sidebar_instances(selected_instance_s, menu))
+                    .height(Length::Fill)
+                    .style(LauncherTheme::style_scrollable_flat_extra_dark)
+                    .id(widget::scrollable::Id::new("MenuLaunch:sidebar"))
+                    .on_scroll(|n| {
+                        let total = n.content_bounds().height - n.bounds().height;
+                        Message::LaunchScrollSidebar(total)
+                    }),
                 widget::horizontal_rule(1).style(|t: &LauncherTheme| t.style_rule(Color::Dark, 1)),
                 self.get_accounts_bar(menu),
             ]
@@ -329,6 +318,128 @@ impl Launcher {
             .into()
     }
 
+    fn get_sidebar_filter<'a>(&'a self, menu: &'a MenuLaunch) -> Element<'a> {
+        widget::row![
+            widget::text_input("Search...", &menu.instance_filter)
+                .on_input(Message::LaunchInstanceFilterEdit)
+                .size(13)
+                .padding(4),
+            widget::pick_list(
+                InstanceSort::ALL,
+                Some(self.config.instance_sort.unwrap_or_default()),
+                Message::LaunchInstanceSortPicked
+            )
+            .text_size(13)
+            .padding(4),
+        ]
+        .padding(Padding::from(0).left(5).right(5))
+        .spacing(5)
+        .into()
+    }
+
+    fn get_sidebar_instances<'a>(
+        &'a self,
+        selected_instance_s: Option<&'a str>,
+        menu: &'a MenuLaunch,
+    ) -> widget::Column<'a, Message, LauncherTheme> {
+        let is_searching = !menu.instance_filter.trim().is_empty();
+        let mut column = widget::Column::new();
+
+        for (group, instances) in
+            self.get_sidebar_groups(menu.is_viewing_server, &menu.instance_filter)
+        {
+            let mut is_collapsed = false;
+            if let Some(group) = group {
+                is_collapsed = !is_searching && self.is_group_collapsed(group);
+                let header = widget::button(
+                    widget::row![
+                        if is_collapsed {
+                            icon_manager::arrow_down_with_size(10)
+                        } else {
+                            icon_manager::arrow_up_with_size(10)
+                        },
+                        widget::text!("{group} ({})", instances.len())
+                            .size(13)
+                            .style(|t: &LauncherTheme| t.style_text(Color::Mid)),
+                    ]
+                    .align_y(iced::alignment::Vertical::Center)
+                    .spacing(7),
+                )
+                .style(|n: &LauncherTheme, status| {
+                    n.style_button(status, StyleButton::FlatExtraDark)
+                })
+                .on_press(Message::LaunchGroupToggle(group.to_owned()))
+                .width(Length::Fill);
+                column = column.push(underline(header, Color::Dark));
+            }
+            if is_collapsed {
+                continue;
+            }
+            for name in instances {
+                column = column.push(self.get_sidebar_instance(
+                    name,
+                    group.is_some(),
+                    selected_instance_s,
+                    menu,
+                ));
+            }
+        }
+        column
+    }
+
+    fn get_sidebar_instance<'a>(
+        &'a self,
+        name: &'a String,
+        is_in_group: bool,
+        selected_instance_s: Option<&'a str>,
+        menu: &'a MenuLaunch,
+    ) -> Element<'a> {
+        let playing_icon = if self.is_process_running(menu, name) {
+            Some(widget::row![
+                widget::horizontal_space(),
+                icon_manager::play(),
+                widget::Space::with_width(10),
+            ])
+        } else {
+            None
+        };
+
+        let text = widget::text(name)
+            .size(15)
+            .style(|t: &LauncherTheme| t.style_text(Color::SecondLight));
+        let indent = widget::Space::with_width(if is_in_group { 10 } else { 0 });
+        let icon = self
+            .instance_icons
+            .get(&InstanceSelection::new(name, menu.is_viewing_server))
+            .map(|n| widget::image(n.clone()).width(16).height(16));
+        let label = widget::row![indent]
+            .push_maybe(icon)
+            .push(text)
+            .spacing(5)
+            .align_y(iced::alignment::Vertical::Center);
+
+        let selector: Element = if selected_instance_s == Some(name) {
+            widget::container(widget::row!(widget::Space::with_width(5), label))
+                .style(LauncherTheme::style_container_selected_flat_button)
+                .width(Length::Fill)
+                .padding(5)
+                .into()
+        } else {
+            widget::button(label.push_maybe(playing_icon))
+                .style(|n: &LauncherTheme, status| {
+                    n.style_button(status, StyleButton::FlatExtraDark)
+                })
+                .on_press(Message::LaunchInstanceSelected {
+                    name: name.clone(),
+                    is_server: menu.is_viewing_server,
+                })
+                .width(Length::Fill)
+                .into()
+        };
+
+        underline(selector, Color::Dark).into()
+    }
+
     fn is_process_running(&self, menu: &MenuLaunch, name: &str) -> bool {
         (!menu.is_viewing_server && self.client_processes.contains_key(name))
             || (menu.is_viewing_server && self.server_processes.contains_key(name))
@@ -356,6 +467,15 @@ impl Launcher {
                 widget::text(" Accounts:").size(14),
                 widget::horizontal_space(),
             ]
+            .push_maybe(
+                self.is_account_selected().then_some(
+                    widget::button(widget::text("Skin").size(11))
+                        .padding(3)
+                        .on_press(Message::Skins(SkinsMessage::Open))
+                        .style(|n: &LauncherTheme, status| n
+                            .style_button(status, StyleButton::FlatExtraDark))
+                )
+            )
             .push_maybe(
                 self.is_account_selected().then_some(
                     widget::button(widget::text("Logout").size(11))
@@ -367,6 +487,7 @@ impl Launcher {
             ),
             dropdown
         ]
+        .push_maybe(self.get_account_status())
         .push_maybe(
             (self.accounts_selected.as_deref() == Some(OFFLINE_ACCOUNT_NAME)).then_some(
                 widget::text_input("Enter username...", &self.config.username)
@@ -379,6 +500,24 @@ impl Launcher {
         .into()
     }
 
+    fn get_account_status(&self) -> Option<Element<'_>> {
+        let status = self
+            .accounts_selected
+            .as_ref()
+            .and_then(|n| self.account_status.get(n))?;
+        let now = chrono::Utc::now().timestamp();
+
+        let (prefix, color) = if status.is_usable() {
+            ("", Color::Mid)
+        } else {
+            ("(!) ", Color::White)
+        };
+        let text = widget::text!(" {prefix}{}", status.summary(now))
+            .size(12)
+            .style(move |n: &LauncherTheme| n.style_text(color));
+        Some(tooltip(text, widget::text(status.to_string()), Position::Bottom).into())
+    }
+
     pub fn is_account_selected(&self) -> bool {
         !(self.accounts_selected.is_none()
             || self.accounts_selected.as_deref() == Some(NEW_ACCOUNT_NAME)
@@ -454,6 +593,42 @@ impl Launcher {
     }
 }
 
+/// Lets you pick a world or server to join
+/// directly when launching (Quick Play).
+fn get_quick_play_row(menu: &MenuLaunch) -> Element<'_> {
+    let selected_world = match &menu.quick_play {
+        Some(QuickPlay::Singleplayer(world)) => Some(world),
+        _ => None,
+    };
+
+    tooltip(
+        widget::row![
+            widget::text("Join directly:").size(14),
+            widget::pick_list(
+                menu.quick_play_worlds.as_slice(),
+                selected_world,
+                Message::LaunchQuickPlayWorld
+            )
+            .placeholder("Open world...")
+            .text_size(14)
+            .width(160),
+            widget::text_input("Join server...", &menu.quick_play_server)
+                .on_input(Message::LaunchQuickPlayServer)
+                .size(14)
+                .width(160),
+        ]
+        .push_maybe(menu.quick_play.is_some().then_some(
+            widget::button(widget::text("Clear").size(14)).on_press(Message::LaunchQuickPlayClear),
+        ))
+        .spacing(5)
+        .align_y(iced::Alignment::Center)
+        .wrap(),
+        "Skips the title screen. Opening worlds needs Minecraft 1.20 or above",
+        Position::Bottom,
+    )
+    .into()
+}
+
 fn get_sidebar_new_button(menu: &MenuLaunch) -> widget::Button<'_, Message, LauncherTheme> {
     widget::button(
         widget::row![icon_manager::create(), widget::text("New").size(16)]
@@ -584,3 +759,71 @@ fn get_footer_text(menu: &'_ MenuLaunch) -> Element<'_> {
     .spacing(10)
     .into()
 }
+
+/// A small graph of the game's memory usage
+/// (over the last minute) along with its current stats.
+fn view_process_monitor(monitor: &ProcessMonitor) -> Option<Element<'static>> {
+    const GRAPH_SAMPLES: usize = 60;
+    const GRAPH_HEIGHT: f32 = 32.0;
+    const MB: u64 = 1024 * 1024;
+
+    let latest = monitor.latest()?;
+    let samples = monitor.samples();
+    let samples = &samples[samples.len().saturating_sub(GRAPH_SAMPLES)..];
+
+    let max_bytes = monitor.max_memory_mb().map_or_else(
+        || samples.iter().map(|n| n.memory_bytes).max().unwrap_or(1),
+        |n| n as u64 * MB,
+    );
+    #[allow(clippy::cast_precision_loss)]
+    let bars = samples.iter().map(|sample| {
+        let fraction = (sample.memory_bytes as f32 / max_bytes.max(1) as f32).min(1.0);
+        widget::container(widget::Space::new(3, (fraction * GRAPH_HEIGHT).max(1.0)))
+            .style(|n: &LauncherTheme| n.style_container_sharp_box(0.0, Color::Light))
+            .into()
+    });
+    let graph = widget::Row::with_children(bars)
+        .spacing(1)
+        .height(GRAPH_HEIGHT)
+        .align_y(iced::alignment::Vertical::Bottom);
+
+    let memory = if let Some(max) = monitor.max_memory_mb() {
+        format!("{} / {max} MB", latest.memory_bytes / MB)
+    } else {
+        format!("{} MB", latest.memory_bytes / MB)
+    };
+    let stats = widget::text!(
+        "Memory: {memory}{}\nCPU: {:.0}%  Threads: {}",
+        if monitor.is_memory_high() {
+            " (almost full!)"
+        } else {
+            ""
+        },
+        latest.cpu_percent,
+        latest.threads
+    )
+    .size(12);
+
+    Some(
+        widget::row![graph, stats]
+            .spacing(10)
+            .align_y(iced::alignment::Vertical::Center)
+            .into(),
+    )
+}
+
+/// "Played for 3h 25m, last played 12 March 2025"
+fn get_playtime_text<'a>(history: &InstanceHistory) -> Option<widget::Text<'a, LauncherTheme>> {
+    let last_played = history.last_played()?;
+    let last_played = chrono::DateTime::from_timestamp(last_played, 0)?
+        .with_timezone(&chrono::Local)
+        .format("%-d %B %Y");
+    Some(
+        widget::text!(
+            "Played for {}, last played {last_played}",
+            format_playtime(history.total_playtime())
+        )
+        .size(12)
+        .style(|t: &LauncherTheme| t.style_text(Color::Mid)),
+    )
+}
@@ -1,32 +1,36 @@
 use iced::{widget, Alignment, Length};
+use ql_instances::auth::AccountType;
 
 use crate::{
     icon_manager,
-    state::{AccountMessage, MenuLoginAlternate, MenuLoginMS, Message, NEW_ACCOUNT_NAME},
+    state::{
+        AccountMessage, MenuAddAuthServer, MenuLoginAlternate, MenuLoginMS, Message,
+        NEW_ACCOUNT_NAME,
+    },
 };
 
 use super::{back_button, button_with_icon, center_x, Element};
 
 impl MenuLoginAlternate {
     pub fn view(&'_ self, tick_timer: usize) -> Element<'_> {
-        let status: Element =
-            if self.is_loading {
-                let dots = ".".repeat((tick_timer % 3) + 1);
-                widget::column![widget::text!("Loading{dots}")]
-                    .padding(8)
-                    .into()
-            } else {
-                widget::column![button_with_icon(icon_manager::tick(), "Login", 16)
-                    .on_press(Message::Account(AccountMessage::AltLogin))]
-                .align_x(Alignment::Center)
-                .push_maybe(self.is_littleskin.then_some(
-                    widget::button("Login with OAuth").on_press(Message::Account(
-                        AccountMessage::LittleSkinOauthButtonClicked,
-                    )),
-                ))
-                .spacing(5)
+        let status: Element = if self.is_loading {
+            let dots = ".".repeat((tick_timer % 3) + 1);
+            widget::column![widget::text!("Loading{dots}")]
+                .padding(8)
                 .into()
-            };
+        } else {
+            widget::column![button_with_icon(icon_manager::tick(), "Login", 16)
+                .on_press(Message::Account(AccountMessage::AltLogin))]
+            .align_x(Alignment::Center)
+            .push_maybe(
+                self.account_type.supports_oauth().then_some(
+                    widget::button("Login with OAuth")
+                        .on_press(Message::Account(AccountMessage::AltOauthButtonClicked)),
+                ),
+            )
+            .spacing(5)
+            .into()
+        };
 
         let padding = iced::Padding {
             top: 5.0,
@@ -54,12 +58,7 @@ impl MenuLoginAlternate {
                     Message::Account(AccountMessage::Selected(NEW_ACCOUNT_NAME.to_owned()))
                 }),
                 widget::column![
-                    widget::text(if self.is_littleskin {
-                        "LittleSkin Login"
-                    } else {
-                        "ElyBy Login"
-                    })
-                    .size(20),
+                    widget::text!("{} Login", self.account_type).size(20),
                     widget::vertical_space(),
                     widget::text("Username/Email:").size(12),
                     center_x(
@@ -84,22 +83,16 @@ impl MenuLoginAlternate {
                     })),
                     status,
                     widget::Space::with_height(5),
-                    widget::row![
-                        widget::text("Or").size(14),
-                        widget::button(widget::text("Create an account").size(14)).on_press(
-                            Message::CoreOpenLink(
-                                if self.is_littleskin {
-                                    "https://littleskin.cn/auth/register"
-                                } else {
-                                    "https://account.ely.by/register"
-                                }
-                                .to_owned()
-                            )
-                        )
-                    ]
-                    .align_y(iced::Alignment::Center)
-                    .spacing(5)
-                    .wrap(),
+                    widget::Column::new().push_maybe(self.get_register_link().map(|link| {
+                        widget::row![
+                            widget::text("Or").size(14),
+                            widget::button(widget::text("Create an account").size(14))
+                                .on_press(Message::CoreOpenLink(link.to_owned()))
+                        ]
+                        .align_y(iced::Alignment::Center)
+                        .spacing(5)
+                        .wrap()
+                    })),
                     widget::vertical_space(),
                 ]
                 .width(Length::Fill)
@@ -111,7 +104,15 @@ impl MenuLoginAlternate {
         }
     }
 
-    fn view_oauth(&'_ self, oauth: &crate::state::LittleSkinOauth) -> Element<'_> {
+    fn get_register_link(&self) -> Option<&'static str> {
+        match self.account_type {
+            AccountType::ElyBy => Some("https://account.ely.by/register"),
+            AccountType::LittleSkin => Some("https://littleskin.cn/auth/register"),
+            AccountType::Microsoft | AccountType::Custom(_) => None,
+        }
+    }
+
+    fn view_oauth(&'_ self, oauth: &crate::state::AltOauth) -> Element<'_> {
         let time_left = {
             let now = std::time::Instant::now();
             if oauth.device_code_expires_at > now {
@@ -137,7 +138,7 @@ impl MenuLoginAlternate {
         .spacing(10);
         widget::column![
             widget::vertical_space(),
-            widget::text("LittleSkin Device Login").size(20),
+            widget::text!("{} Device Login", self.account_type).size(20),
             widget::text("Open this link and enter the code:").size(14),
             widget::Space::with_height(5),
             widget::container(widget::column![code_row, url_row]).padding(10),
@@ -159,6 +160,99 @@ impl MenuLoginAlternate {
     }
 }
 
+impl MenuAddAuthServer {
+    pub fn view(&'_ self, tick_timer: usize) -> Element<'_> {
+        let padding = iced::Padding {
+            top: 5.0,
+            bottom: 5.0,
+            right: 10.0,
+            left: 10.0,
+        };
+        let input = |label, placeholder, value, message: fn(String) -> AccountMessage| {
+            widget::column![
+                widget::text(label).size(12),
+                center_x(
+                    widget::text_input(placeholder, value)
+                        .padding(padding)
+                        .on_input(move |n| Message::Account(message(n)))
+                ),
+            ]
+            .spacing(5)
+        };
+
+        let status: Element = if self.is_loading {
+            let dots = ".".repeat((tick_timer % 3) + 1);
+            widget::text!("Loading{dots}").into()
+        } else {
+            button_with_icon(icon_manager::tick(), "Add", 16)
+                .on_press_maybe(
+                    (!self.url.trim().is_empty())
+                        .then_some(Message::Account(AccountMessage::AuthServerAdd)),
+                )
+                .into()
+        };
+
+        widget::column![
+            back_button().on_press(Message::Account(AccountMessage::Selected(
+                NEW_ACCOUNT_NAME.to_owned()
+            ))),
+            widget::column![
+                widget::vertical_space(),
+                widget::text("Add Auth Server").size(20),
+                widget::text("Any Yggdrasil (authlib-injector) compatible server").size(12),
+                input(
+                    "Website or API URL:",
+                    "https://example.com",
+                    &self.url,
+                    AccountMessage::AuthServerUrlInput
+                ),
+                input(
+                    "Name (optional):",
+                    "Leave empty to use the server's name...",
+                    &self.name,
+                    AccountMessage::AuthServerNameInput
+                ),
+                widget::checkbox("Supports OAuth device login", self.oauth_enabled)
+                    .size(14)
+                    .text_size(14)
+                    .on_toggle(|t| Message::Account(AccountMessage::AuthServerOauthToggle(t))),
+                widget::Column::new()
+                    .push_maybe(self.oauth_enabled.then(|| {
+                        widget::column![
+                            input(
+                                "OAuth Client ID:",
+                                "Enter Client ID...",
+                                &self.oauth_client_id,
+                                AccountMessage::AuthServerOauthClientIdInput
+                            ),
+                            input(
+                                "Device Code URL:",
+                                "https://example.com/oauth/device_code",
+                                &self.oauth_device_code_url,
+                                AccountMessage::AuthServerOauthDeviceCodeUrlInput
+                            ),
+                            input(
+                                "Token URL:",
+                                "https://example.com/oauth/token",
+                                &self.oauth_token_url,
+                                AccountMessage::AuthServerOauthTokenUrlInput
+                            ),
+                        ]
+                        .spacing(5)
+                    }))
+                    .width(Length::Fill),
+                status,
+                widget::vertical_space(),
+            ]
+            .width(Length::Fill)
+            .align_x(iced::Alignment::Center)
+            .spacing(5)
+        ]
+        .padding(10)
+        .into()
+    }
+}
+
 impl MenuLoginMS {
     pub fn view<'a>(&self) -> Element<'a> {
         widget::column![
@@ -1,6 +1,7 @@
 use iced::widget::tooltip::Position;
 use iced::{widget, Alignment, Length};
 use ql_core::{InstanceSelection, Progress, WEBSITE};
+use ql_instances::auth::CustomAuthServer;
 
 use crate::state::ImageState;
 use crate::{
@@ -21,6 +22,7 @@ mod login;
 mod mods;
 mod onboarding;
 mod settings;
+mod skins;
 
 pub use onboarding::changelog;
 
@@ -522,7 +524,7 @@ impl MenuLicense {
     }
 }
 
-pub fn view_account_login<'a>() -> Element<'a> {
+pub fn view_account_login(custom_auth_servers: &[CustomAuthServer]) -> Element<'_> {
     widget::column![
         back_button().on_press(Message::LaunchScreenOpen {
             message: None,
@@ -548,6 +550,26 @@ pub fn view_account_login<'a>() -> Element<'a> {
                         is_from_welcome_screen: false
                     }
                 )),
+                widget::column(custom_auth_servers.iter().map(|server| {
+                    widget::row![
+                        widget::button(widget::text!("Login with {}", server.name)).on_press(
+                            Message::Account(AccountMessage::OpenCustom {
+                                server: server.clone(),
+                                is_from_welcome_screen: false
+                            })
+                        ),
+                        widget::button(widget::text("Remove").size(12)).on_press(Message::Account(
+                            AccountMessage::AuthServerRemove(server.name.clone())
+                        )),
+                    ]
+                    .align_y(iced::Alignment::Center)
+                    .spacing(5)
+                    .into()
+                }))
+                .align_x(iced::Alignment::Center)
+                .spacing(5),
+                widget::button("Add custom auth server...")
+                    .on_press(Message::Account(AccountMessage::AuthServerOpen)),
             ]
             .align_x(iced::Alignment::Center)
             .spacing(5),
@@ -1,6 +1,7 @@
 use iced::widget::tooltip::Position;
 use iced::{widget, Alignment, Length};
-use ql_core::{InstanceSelection, SelectedMod};
+use ql_core::{InstanceSelection, Loader, SelectedMod};
+use ql_mod_manager::store::{ModUpdate, ReleaseChannel};
 
 use crate::menu_renderer::{select_box, subbutton_with_icon, FONT_MONO};
 use crate::state::ImageState;
@@ -56,6 +57,10 @@ impl MenuEditMods {
                 ctx_button("See recommended mods").on_press(Message::RecommendedMods(
                     crate::state::RecommendedModMessage::Open
                 )),
+                ctx_button("Check for problems")
+                    .on_press(Message::ManageMods(ManageModsMessage::CheckProblems)),
+                ctx_button("Verify & repair files")
+                    .on_press(Message::ManageMods(ManageModsMessage::VerifyMods)),
             ]
             .spacing(4);
 
@@ -99,6 +104,8 @@ impl MenuEditMods {
                 .spacing(5),
                 Self::open_mod_folder_button(selected_instance),
                 self.get_mod_update_pane(tick_timer),
+                self.get_mod_problems_pane(),
+                self.get_verify_pane(tick_timer),
             )
             .padding(10)
             .spacing(10),
@@ -120,29 +127,33 @@ impl MenuEditMods {
                 widget::column!(
                     widget::text("Mod Updates Available!").size(15),
                     widget::column(self.available_updates.iter().enumerate().map(
-                        |(i, (id, update_name, is_enabled))| {
-                            let title = self
-                                .mods
-                                .mods
-                                .get(&id.get_index_str())
-                                .map(|n| n.name.clone())
-                                .unwrap_or_default();
-
-                            let text = if title.is_empty()
-                                || update_name.contains(&title)
-                                || update_name.contains(&title.replace(' ', ""))
-                            {
-                                update_name.clone()
-                            } else {
-                                format!("{title} - {update_name}")
-                            };
-
-                            widget::checkbox(text, *is_enabled)
+                        |(i, (update, is_enabled))| {
+                            let mut text = format!(
+                                "{}\n{} -> {}",
+                                update.name, update.current_version, update.new_version
+                            );
+                            if update.channel != ReleaseChannel::Release {
+                                text.push_str(&format!(" ({})", update.channel));
+                            }
+                            if !update.added_dependencies.is_empty() {
+                                text.push_str(&format!(
+                                    "\n+{} dependencies",
+                                    update.added_dependencies.len()
+                                ));
+                            }
+
+                            let checkbox = widget::checkbox(text, *is_enabled)
                                 .on_toggle(move |b| {
                                     Message::ManageMods(ManageModsMessage::UpdateCheckToggle(i, b))
                                 })
-                                .text_size(12)
-                                .into()
+                                .text_size(12);
+
+                            tooltip(
+                                checkbox,
+                                widget::text(changelog_preview(update)).size(12),
+                                Position::Bottom,
+                            )
+                            .into()
                         }
                     ))
                     .spacing(10),
@@ -157,6 +168,82 @@ impl MenuEditMods {
         }
     }
 
+    fn get_mod_problems_pane(&'_ self) -> Element<'_> {
+        let Some(problems) = &self.mod_problems else {
+            return widget::column!().into();
+        };
+
+        let list: Element = if problems.is_empty() {
+            widget::text("No problems found").size(12).into()
+        } else {
+            widget::column(problems.iter().map(|problem| {
+                widget::text(problem.to_string())
+                    .size(12)
+                    .style(move |t: &LauncherTheme| {
+                        if problem.is_fatal() {
+                            t.style_text(Color::White)
+                        } else {
+                            t.style_text(Color::SecondLight)
+                        }
+                    })
+                    .into()
+            }))
+            .spacing(10)
+            .into()
+        };
+
+        widget::container(
+            widget::column!(widget::text("Mod Problems").size(15), list)
+                .padding(10)
+                .spacing(10)
+                .width(MODS_SIDEBAR_WIDTH),
+        )
+        .into()
+    }
+
+    fn get_verify_pane(&'_ self, tick_timer: usize) -> Element<'_> {
+        if self.is_verifying {
+            let dots = ".".repeat((tick_timer % 3) + 1);
+            return widget::text!("Verifying mods{dots}").size(13).into();
+        }
+        let Some(report) = &self.verify_report else {
+            return widget::column!().into();
+        };
+
+        let mut col = widget::column!(widget::text("Verify Results").size(15)).spacing(10);
+        if report.repaired.is_empty() && report.failed.is_empty() {
+            col = col.push(widget::text("All files are OK").size(12));
+        }
+        if !report.repaired.is_empty() {
+            col = col.push(widget::text!("Repaired {} file(s)", report.repaired.len()).size(12));
+        }
+        for (file, err) in &report.failed {
+            col = col.push(widget::text!("Couldn't repair {file}: {err}").size(12));
+        }
+        if !report.untracked.is_empty() {
+            col = col.push(
+                widget::text!(
+                    "Not installed from store (can't be checked):\n{}",
+                    report.untracked.join("\n")
+                )
+                .size(12)
+                .style(|t: &LauncherTheme| t.style_text(Color::SecondLight)),
+            );
+        }
+        if !report.unverified.is_empty() {
+            col = col.push(
+                widget::text!(
+                    "{} file(s) from older launcher versions can't be checked",
+                    report.unverified.len()
+                )
+                .size(12)
+                .style(|t: &LauncherTheme| t.style_text(Color::SecondLight)),
+            );
+        }
+
+        widget::container(col.padding(10).width(MODS_SIDEBAR_WIDTH)).into()
+    }
+
     fn get_mod_installer_buttons(&'_ self, selected_instance: &InstanceSelection) -> Element<'_> {
         match self.config.mod_type.as_str() {
             "Vanilla" => match selected_instance {
@@ -370,9 +457,16 @@ impl MenuEditMods {
         images: &'a ImageState,
     ) -> Element<'a> {
         widget::scrollable(widget::column({
-            self.sorted_mods_list
-                .iter()
-                .map(|mod_list_entry| self.get_mod_entry(mod_list_entry, size, images))
+            // Only these have their own mod formats (see `JarMetadata::is_wrong_loader`)
+            let instance_loader = match self.config.mod_type.as_str() {
+                mod_type @ ("Fabric" | "Quilt" | "Forge" | "NeoForge") => {
+                    Loader::try_from(mod_type).ok()
+                }
+                _ => None,
+            };
+            self.sorted_mods_list.iter().map(move |mod_list_entry| {
+                self.get_mod_entry(mod_list_entry, size, images, instance_loader)
+            })
         }))
         .direction(widget::scrollable::Direction::Both {
             vertical: widget::scrollable::Scrollbar::new(),
@@ -389,6 +483,7 @@ impl MenuEditMods {
         entry: &'a ModListEntry,
         size: iced::Size,
         images: &'a ImageState,
+        instance_loader: Option<Loader>,
     ) -> Element<'a> {
         const PADDING: iced::Padding = iced::Padding {
             top: 2.0,
@@ -501,15 +596,54 @@ impl MenuEditMods {
                     file_name: file_name.clone(),
                 });
 
-                let checkbox = select_box(
-                    widget::row![
-                        no_icon,
-                        widget::text(
-                            file_name
-                                .strip_suffix(".disabled")
-                                .unwrap_or(file_name)
-                                .to_owned(),
-                        )
+                let stripped_name = file_name.strip_suffix(".disabled").unwrap_or(file_name);
+                let metadata = self
+                    .local_mods_metadata
+                    .get(stripped_name)
+                    .and_then(Option::as_ref);
+                let image: Element = if let Some(handle) = self.local_mods_icons.get(stripped_name)
+                {
+                    widget::image(handle.clone())
+                        .width(ICON_SIZE)
+                        .height(ICON_SIZE)
+                        .into()
+                } else {
+                    no_icon
+                };
+
+                let title: Element = if let Some(metadata) = metadata {
+                    widget::row![widget::text(metadata.main().display_name())
+                        .style(move |t: &LauncherTheme| {
+                            t.style_text(if is_enabled {
+                                Color::SecondLight
+                            } else {
+                                Color::Mid
+                            })
+                        })
+                        .size(14)
+                        .width(self.width_name)]
+                    .push_maybe(metadata.main().version.as_deref().map(|version| {
+                        widget::text(version)
+                            .style(move |t: &LauncherTheme| {
+                                t.style_text(if is_enabled {
+                                    Color::Mid
+                                } else {
+                                    Color::SecondDark
+                                })
+                            })
+                            .font(FONT_MONO)
+                            .size(12)
+                    }))
+                    .push_maybe(metadata.is_wrong_loader(instance_loader).then(|| {
+                        widget::text!("(made for {})", metadata.format.loader_name())
+                            .style(|t: &LauncherTheme| t.style_text(Color::Light))
+                            .size(12)
+                    }))
+                    .align_y(Alignment::Center)
+                    .spacing(SPACING)
+                    .into()
+                } else {
+                    widget::text(stripped_name.to_owned())
                         .font(FONT_MONO)
                         .style(move |t: &LauncherTheme| {
                             t.style_text(if is_enabled {
@@ -519,8 +653,13 @@ impl MenuEditMods {
                             })
                         })
                         .size(14)
-                    ]
-                    .spacing(SPACING),
+                        .into()
+                };
+
+                let checkbox = select_box(
+                    widget::row![image, title]
+                        .align_y(Alignment::Center)
+                        .spacing(SPACING),
                     is_selected,
                     Message::ManageMods(ManageModsMessage::ToggleCheckbox(file_name.clone(), None)),
                 )
@@ -528,7 +667,17 @@ impl MenuEditMods {
                 .width(size.width);
 
                 if is_enabled {
-                    checkbox.into()
+                    if metadata.is_some() {
+                        // Show the file name, as it's hidden otherwise
+                        tooltip(
+                            checkbox,
+                            widget::text(stripped_name.to_owned()).size(12),
+                            widget::tooltip::Position::FollowCursor,
+                        )
+                        .into()
+                    } else {
+                        checkbox.into()
+                    }
                 } else {
                     tooltip(
                         checkbox,
@@ -546,6 +695,41 @@ fn install_ldr(fabric: &str) -> widget::Button<'_, Message, LauncherTheme> {
     widget::button(fabric).width(97)
 }
 
+/// A short, plain-text version of the changelog,
+/// to show when hovering over an update.
+fn changelog_preview(update: &ModUpdate) -> String {
+    const MAX_LEN: usize = 600;
+
+    let Some(changelog) = update.changelog.as_deref().filter(|n| !n.trim().is_empty()) else {
+        return format!("{}\n(No changelog provided)", update.new_version_name);
+    };
+
+    // Curseforge changelogs are HTML
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in changelog.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    let text = text.replace("&nbsp;", " ").replace("&amp;", "&");
+    let mut text = text
+        .lines()
+        .map(str::trim_end)
+        .filter(|n| !n.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    if let Some((cut, _)) = text.char_indices().nth(MAX_LEN) {
+        text.truncate(cut);
+        text.push_str("...");
+    }
+    format!("{}\n\n{text}", update.new_version_name)
+}
+
 fn ctx_button(e: &'_ str) -> widget::Button<'_, Message, LauncherTheme> {
     widget::button(widget::text(e).size(13))
         .width(Length::Fill)
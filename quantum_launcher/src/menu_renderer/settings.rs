@@ -1,16 +1,19 @@
-use iced::{widget, Length};
+use iced::{widget, Alignment, Length};
 use ql_core::{LAUNCHER_DIR, WEBSITE};
+use ql_instances::auth::credentials::{CredentialStore, PASSPHRASE_ENV_VAR};
 
 use super::{
     back_button, button_with_icon, get_theme_selector, sidebar_button, underline, Element, DISCORD,
-    GITHUB,
+    FONT_MONO, GITHUB,
 };
 use crate::menu_renderer::edit_instance::{
-    global_java_args_dialog, global_pre_launch_prefix_dialog, resolution_dialog,
+    global_env_vars_dialog, global_java_args_dialog, global_pre_launch_prefix_dialog, hooks_dialog,
+    resolution_dialog,
 };
 use crate::{
     config::LauncherConfig,
     icon_manager,
+    keybinds::{KeyAction, KeyChord},
     state::{LauncherSettingsMessage, LauncherSettingsTab, MenuLauncherSettings, Message},
     stylesheet::{
         color::Color,
@@ -158,6 +161,131 @@ impl MenuLauncherSettings {
     }
 }
 
+impl MenuLauncherSettings {
+    fn view_keybinds_tab<'a>(&'a self, config: &'a LauncherConfig) -> Element<'a> {
+        let msg = |n| Message::LauncherSettings(n);
+
+        let keybinds = widget::column(KeyAction::ALL.iter().map(|action| {
+            let action = *action;
+            let chords = config.get_keybinds(action);
+            let label = if self.rebinding == Some(action) {
+                "Press a key...".to_owned()
+            } else if chords.is_empty() {
+                "(none)".to_owned()
+            } else {
+                chords
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(" / ")
+            };
+
+            widget::row![
+                widget::text(action.to_string()).size(14).width(200),
+                widget::button(widget::text(label).size(14).font(FONT_MONO))
+                    .width(170)
+                    .on_press(msg(LauncherSettingsMessage::KeybindEdit(action))),
+            ]
+            .push_maybe((!chords.is_empty()).then(|| {
+                widget::button(widget::text("Unbind").size(12)).on_press(msg(
+                    LauncherSettingsMessage::KeybindSet(action, KeyChord::unbound()),
+                ))
+            }))
+            .push_maybe((chords != action.default_chords()).then(|| {
+                widget::button(widget::text("Reset").size(12))
+                    .on_press(msg(LauncherSettingsMessage::KeybindReset(action)))
+            }))
+            .spacing(10)
+            .align_y(Alignment::Center)
+            .into()
+        }))
+        .spacing(5);
+
+        widget::column![
+            widget::text("Keybinds").size(20),
+            widget::text("Click a keybind, then press the new key combination (Esc to cancel).\n\"Ctrl\" means \"Command\" on macOS. Esc always goes back to the previous menu.").size(12),
+        ]
+        .push_maybe(
+            self.keybind_error
+                .as_ref()
+                .map(|n| widget::text(n).size(14).style(|t: &LauncherTheme| t.style_text(Color::SecondLight))),
+        )
+        .push(keybinds)
+        .push(
+            widget::button(widget::text("Reset All").size(14))
+                .on_press(msg(LauncherSettingsMessage::KeybindResetAll)),
+        )
+        .padding(10)
+        .spacing(10)
+        .into()
+    }
+
+    fn view_java_runtimes(&self) -> Element<'_> {
+        let list: Element = match &self.java_runtimes {
+            None => widget::text("Loading...").size(12).into(),
+            Some(runtimes) if runtimes.is_empty() => widget::text("No Java runtimes installed yet")
+                .size(12)
+                .into(),
+            Some(runtimes) => widget::column(runtimes.iter().map(|runtime| {
+                let major = runtime.major;
+                let msg =
+                    |m: fn(usize) -> LauncherSettingsMessage| Message::LauncherSettings(m(major));
+                let source = runtime
+                    .source
+                    .as_ref()
+                    .map_or_else(|| "Unknown source".to_owned(), ToString::to_string);
+
+                widget::column![
+                    widget::row![
+                        widget::text!("Java {major}").size(16),
+                        widget::text!(
+                            "{:.1} MB - {source}{}",
+                            runtime.size as f64 / (1024.0 * 1024.0),
+                            if runtime.is_incomplete {
+                                " (incomplete)"
+                            } else {
+                                ""
+                            }
+                        )
+                        .size(12),
+                    ]
+                    .spacing(10)
+                    .align_y(iced::Alignment::Center),
+                    widget::row![
+                        widget::button(widget::text("Verify").size(12))
+                            .on_press(msg(LauncherSettingsMessage::JavaRuntimeVerify)),
+                        widget::button(widget::text("Repair").size(12))
+                            .on_press(msg(LauncherSettingsMessage::JavaRuntimeRepair)),
+                        widget::button(widget::text("Update").size(12))
+                            .on_press(msg(LauncherSettingsMessage::JavaRuntimeUpdate)),
+                        widget::button(widget::text("Delete").size(12))
+                            .on_press(msg(LauncherSettingsMessage::JavaRuntimeDelete)),
+                    ]
+                    .spacing(5),
+                ]
+                .push_maybe(
+                    self.java_status
+                        .get(&major)
+                        .map(|status| widget::text(status).size(12)),
+                )
+                .spacing(5)
+                .into()
+            }))
+            .spacing(10)
+            .into(),
+        };
+
+        widget::column![
+            widget::text("Java runtimes").size(20),
+            widget::text("Installed automatically by the launcher").size(12),
+            list,
+        ]
+        .padding(10)
+        .spacing(10)
+        .into()
+    }
+}
+
 impl LauncherSettingsTab {
     pub fn view<'a>(
         &'a self,
@@ -176,6 +304,27 @@ impl LauncherSettingsTab {
                 .spacing(10)
                 .padding(10),
                 widget::horizontal_rule(1),
+                widget::column![
+                    widget::row![
+                        widget::text("Save account logins in:").size(14),
+                        widget::pick_list(
+                            CredentialStore::ALL,
+                            Some(config.credential_store.unwrap_or_default()),
+                            |n| Message::LauncherSettings(
+                                LauncherSettingsMessage::CredentialStorePicked(n)
+                            )
+                        )
+                    ]
+                    .align_y(Alignment::Center)
+                    .spacing(10),
+                    widget::text(format!(
+                        "The keyring automatically falls back to the encrypted file if unavailable.\nExisting logins are moved when switching.\n\nThe file is encrypted with a machine-specific key,\nor with the {PASSPHRASE_ENV_VAR} environment variable if set."
+                    ))
+                    .size(12),
+                ]
+                .spacing(10)
+                .padding(10),
+                widget::horizontal_rule(1),
                 widget::column![resolution_dialog(
                     config.global_settings.as_ref(),
                     |n| Message::LauncherSettings(
@@ -230,6 +379,43 @@ impl LauncherSettingsTab {
                 .padding(10)
                 .spacing(10),
                 widget::horizontal_rule(1),
+                widget::column![global_env_vars_dialog(
+                    config
+                        .global_settings
+                        .as_ref()
+                        .and_then(|n| n.env_vars.as_deref()),
+                    Message::LauncherSettings(LauncherSettingsMessage::GlobalEnvVarAdd),
+                    |idx| Message::LauncherSettings(LauncherSettingsMessage::GlobalEnvVarDelete(
+                        idx
+                    )),
+                    &|arg, idx| Message::LauncherSettings(
+                        LauncherSettingsMessage::GlobalEnvVarEdit(arg, idx)
+                    ),
+                    |idx| Message::LauncherSettings(LauncherSettingsMessage::GlobalEnvVarShiftUp(
+                        idx
+                    )),
+                    |idx| Message::LauncherSettings(
+                        LauncherSettingsMessage::GlobalEnvVarShiftDown(idx)
+                    ),
+                )]
+                .padding(10)
+                .spacing(10),
+                widget::horizontal_rule(1),
+                widget::column![hooks_dialog(
+                    config.global_settings.as_ref(),
+                    |n| Message::LauncherSettings(
+                        LauncherSettingsMessage::GlobalPreLaunchHookChanged(n)
+                    ),
+                    |n| Message::LauncherSettings(
+                        LauncherSettingsMessage::GlobalPostExitHookChanged(n)
+                    ),
+                    true
+                )]
+                .padding(10)
+                .spacing(10),
+                widget::horizontal_rule(1),
+                menu.view_java_runtimes(),
+                widget::horizontal_rule(1),
                 widget::column![
                     button_with_icon(icon_manager::delete(), "Clear Java installs", 16).on_press(
                         Message::LauncherSettings(LauncherSettingsMessage::ClearJavaInstalls)
@@ -244,6 +430,7 @@ impl LauncherSettingsTab {
             ]
             .spacing(SETTINGS_SPACING)
             .into(),
+            LauncherSettingsTab::Keybinds => menu.view_keybinds_tab(config),
             LauncherSettingsTab::About => {
                 let gpl3_button =
                     // widget::button(widget::rich_text![widget::span("GNU GPLv3 License").underline(true)].size(12))
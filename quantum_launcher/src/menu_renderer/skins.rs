@@ -0,0 +1,182 @@
+use iced::{
+    widget::{self, image::FilterMethod},
+    Alignment, Length,
+};
+use ql_instances::auth::skins::SkinModel;
+
+use crate::{
+    icon_manager,
+    state::{MenuSkins, Message, SkinsMessage},
+    stylesheet::{color::Color, styles::LauncherTheme},
+};
+
+use super::{back_button, button_with_icon, Element};
+
+impl MenuSkins {
+    pub fn view(&'_ self, tick_timer: usize) -> Element<'_> {
+        let preview: Element = match (&self.preview, self.show_back) {
+            (Some((front, _)), false) | (Some((_, front)), true) => widget::image(front.clone())
+                .filter_method(FilterMethod::Nearest)
+                .width(128)
+                .height(256)
+                .into(),
+            (None, _) => {
+                widget::container(widget::text(if self.is_loading { "" } else { "No skin" }))
+                    .center(Length::Fixed(128.0))
+                    .height(256)
+                    .into()
+            }
+        };
+
+        let status: Element = if self.is_loading {
+            let dots = ".".repeat((tick_timer % 3) + 1);
+            widget::text!("Loading{dots}").into()
+        } else {
+            widget::Space::with_height(0).into()
+        };
+
+        widget::scrollable(
+            widget::column![
+                back_button().on_press(Message::LaunchScreenOpen {
+                    message: None,
+                    clear_selection: false
+                }),
+                widget::row![widget::text!("Skin: {}", self.account).size(20), status]
+                    .align_y(Alignment::Center)
+                    .spacing(10),
+                widget::row![
+                    widget::column![
+                        preview,
+                        widget::checkbox("Show back", self.show_back)
+                            .size(14)
+                            .text_size(14)
+                            .on_toggle(|t| Message::Skins(SkinsMessage::ToggleBack(t))),
+                    ]
+                    .spacing(5),
+                    self.view_controls(),
+                ]
+                .spacing(20),
+                self.view_library(),
+            ]
+            .padding(10)
+            .spacing(10),
+        )
+        .style(LauncherTheme::style_scrollable_flat_extra_dark)
+        .height(Length::Fill)
+        .into()
+    }
+
+    fn view_controls(&'_ self) -> Element<'_> {
+        let can_act = !self.is_loading && self.textures.is_some();
+
+        let capes: Element = match &self.textures {
+            Some(textures) if !textures.capes.is_empty() => {
+                // Only Microsoft capes can be switched between
+                let can_switch = can_act && textures.capes.iter().all(|n| n.id.is_some());
+                let has_cape = textures.active_cape().is_some();
+                let cape_button = |name: String, is_active: bool, message| {
+                    widget::button(widget::text(name).size(14)).on_press_maybe(
+                        (can_switch && !is_active)
+                            .then(|| Message::Skins(SkinsMessage::CapeSelected(message))),
+                    )
+                };
+
+                widget::column![
+                    widget::text("Capes:").size(14),
+                    widget::row(textures.capes.iter().map(|cape| {
+                        let name = cape.alias.clone().unwrap_or_else(|| "Cape".to_owned());
+                        let name = if cape.active {
+                            format!("{name} (wearing)")
+                        } else {
+                            name
+                        };
+                        cape_button(name, cape.active, cape.id.clone()).into()
+                    }))
+                    .push_maybe(can_switch.then(|| cape_button(
+                        "No cape".to_owned(),
+                        !has_cape,
+                        None
+                    )))
+                    .spacing(5)
+                    .wrap(),
+                ]
+                .spacing(5)
+                .into()
+            }
+            Some(_) => widget::text("No capes").size(14).into(),
+            None => widget::Space::with_height(0).into(),
+        };
+
+        widget::column![
+            widget::text("Model (for uploads):").size(14),
+            widget::pick_list(SkinModel::ALL, Some(self.model), |n| {
+                Message::Skins(SkinsMessage::ModelSelected(n))
+            }),
+            button_with_icon(icon_manager::folder(), "Upload skin...", 14).on_press_maybe(
+                (can_act && self.can_upload).then_some(Message::Skins(SkinsMessage::UploadFile))
+            ),
+            button_with_icon(icon_manager::save(), "Save current skin to library", 14)
+                .on_press_maybe(
+                    (can_act && self.current_skin.is_some())
+                        .then_some(Message::Skins(SkinsMessage::LibrarySaveCurrent))
+                ),
+            widget::Space::with_height(5),
+            capes,
+        ]
+        .spacing(5)
+        .into()
+    }
+
+    fn view_library(&'_ self) -> Element<'_> {
+        let can_act = !self.is_loading && self.textures.is_some();
+        let skins = widget::row(self.library.skins.iter().map(|skin| {
+            let preview: Element = match self.library_previews.get(&skin.name) {
+                Some(handle) => widget::image(handle.clone())
+                    .filter_method(FilterMethod::Nearest)
+                    .width(48)
+                    .height(96)
+                    .into(),
+                None => widget::Space::new(48, 96).into(),
+            };
+            widget::container(
+                widget::column![
+                    preview,
+                    widget::text(&skin.name).size(12),
+                    widget::row![
+                        widget::button(widget::text("Use").size(12)).on_press_maybe(
+                            (can_act && self.can_upload).then(|| Message::Skins(
+                                SkinsMessage::UploadFromLibrary(skin.name.clone())
+                            ))
+                        ),
+                        widget::button(widget::text("Delete").size(12)).on_press(Message::Skins(
+                            SkinsMessage::LibraryRemove(skin.name.clone())
+                        )),
+                    ]
+                    .spacing(5),
+                ]
+                .align_x(Alignment::Center)
+                .spacing(5)
+                .width(130),
+            )
+            .padding(5)
+            .style(|n: &LauncherTheme| n.style_container_sharp_box(0.0, Color::Dark))
+            .into()
+        }))
+        .spacing(5)
+        .wrap();
+
+        widget::column![
+            widget::row![
+                widget::text("Library").size(18),
+                button_with_icon(icon_manager::create(), "Add PNG...", 14)
+                    .on_press(Message::Skins(SkinsMessage::LibraryAddFile)),
+            ]
+            .align_y(Alignment::Center)
+            .spacing(10),
+            widget::text("New skins are added with the model selected above").size(12),
+            skins,
+        ]
+        .spacing(5)
+        .into()
+    }
+}
@@ -1,10 +1,12 @@
 use super::{SIDEBAR_DRAG_LEEWAY, SIDEBAR_LIMIT_LEFT, SIDEBAR_LIMIT_RIGHT};
+use crate::keybinds::{KeyAction, KeyChord};
 use crate::message_update::MSG_RESIZE;
 use crate::state::{
-    CreateInstanceMessage, LaunchTabId, Launcher, LauncherSettingsMessage, LauncherSettingsTab,
-    MenuCreateInstance, MenuEditJarMods, MenuEditMods, MenuEditPresets, MenuExportInstance,
-    MenuInstallFabric, MenuInstallOptifine, MenuLaunch, MenuLauncherSettings, MenuLauncherUpdate,
-    MenuLoginAlternate, MenuLoginMS, MenuRecommendedMods, MenuServerCreate, Message, State,
+    CreateInstanceMessage, InstallModsMessage, LaunchTabId, Launcher, LauncherSettingsMessage,
+    LauncherSettingsTab, MenuAddAuthServer, MenuCreateInstance, MenuEditJarMods, MenuEditMods,
+    MenuEditPresets, MenuExportInstance, MenuInstallFabric, MenuInstallOptifine, MenuLaunch,
+    MenuLauncherSettings, MenuLauncherUpdate, MenuLoginAlternate, MenuLoginMS, MenuRecommendedMods,
+    MenuServerCreate, MenuSkins, Message, State,
 };
 use iced::{
     keyboard::{self, key::Named, Key},
@@ -12,6 +14,7 @@ use iced::{
 };
 use ql_core::jarmod::JarMods;
 use ql_core::{err, info, info_no_log, jarmod::JarMod, InstanceSelection};
+use std::collections::HashMap;
 use std::ffi::{OsStr, OsString};
 use std::path::Path;
 
@@ -158,59 +161,111 @@ impl Launcher {
     }
 
     fn handle_key_press(&mut self, key: Key, modifiers: keyboard::Modifiers) -> Task<Message> {
+        if let State::LauncherSettings(MenuLauncherSettings {
+            rebinding: Some(action),
+            ..
+        }) = &mut self.state
+        {
+            let action = *action;
+            return if let Key::Named(Named::Escape) = key {
+                self.cancel_rebinding();
+                Task::none()
+            } else if let Some(chord) = KeyChord::from_press(&key, modifiers) {
+                Task::done(Message::LauncherSettings(
+                    LauncherSettingsMessage::KeybindSet(action, chord),
+                ))
+            } else {
+                Task::none()
+            };
+        }
+
         if let Key::Named(Named::Escape) = key {
             return self.key_escape_back(true).1;
         }
-        if let Key::Named(Named::ArrowUp) = key {
-            return self.key_change_selected_instance(false);
-        } else if let Key::Named(Named::ArrowDown) = key {
-            return self.key_change_selected_instance(true);
-        } else if let Key::Named(Named::Enter) = key {
-            if modifiers.command() {
-                return self.launch_start();
-            }
-        } else if let Key::Named(Named::Backspace) = key {
-            if modifiers.command() {
-                return Task::done(Message::LaunchKill);
-            }
-        } else if let Key::Character(ch) = &key {
-            let msg = match (
-                ch.as_str(),
-                modifiers.command(),
-                modifiers.alt(),
-                &self.state,
-            ) {
-                ("q", true, _, _) => Message::CoreTryQuit,
-                ("a", true, _, State::EditMods(_)) => {
-                    Message::ManageMods(crate::state::ManageModsMessage::SelectAll)
-                }
-                ("a", true, _, State::EditJarMods(_)) => {
-                    Message::ManageJarMods(crate::state::ManageJarModsMessage::SelectAll)
-                }
-                ("n", true, _, State::Launch(_)) => {
-                    Message::CreateInstance(CreateInstanceMessage::ScreenOpen)
-                }
-                ("1", ctrl, alt, State::Launch(_)) if ctrl | alt => {
-                    Message::LaunchChangeTab(LaunchTabId::Buttons)
-                }
-                ("2", ctrl, alt, State::Launch(_)) if ctrl | alt => {
-                    Message::LaunchChangeTab(LaunchTabId::Edit)
-                }
-                ("3", ctrl, alt, State::Launch(_)) if ctrl | alt => {
-                    Message::LaunchChangeTab(LaunchTabId::Log)
-                }
-                (",", true, _, State::Launch(_)) => {
-                    Message::LauncherSettings(LauncherSettingsMessage::Open)
+        if let Some(chord) = KeyChord::from_press(&key, modifiers) {
+            // Special keys bound without modifiers (like the arrow keys)
+            // also work with modifiers held, unless that's bound to something
+            let plain = matches!(key, Key::Named(_))
+                .then(|| chord.without_modifiers())
+                .filter(|n| *n != chord);
+            for chord in std::iter::once(chord).chain(plain) {
+                for action in KeyAction::ALL {
+                    if self.config.get_keybinds(*action).contains(&chord) {
+                        if let Some(task) = self.key_action(*action) {
+                            return task;
+                        }
+                    }
                 }
-                _ => Message::Nothing,
-            };
-            return Task::done(msg);
+            }
         }
         self.keys_pressed.insert(key);
 
         Task::none()
     }
 
+    fn cancel_rebinding(&mut self) {
+        if let State::LauncherSettings(menu) = &mut self.state {
+            menu.rebinding = None;
+        }
+    }
+
+    /// Does what's bound to a key, if it
+    /// makes sense in the current menu.
+    fn key_action(&mut self, action: KeyAction) -> Option<Task<Message>> {
+        let msg = match (action, &self.state) {
+            (KeyAction::Quit, _) => Message::CoreTryQuit,
+            (KeyAction::LaunchInstance, _) => return Some(self.launch_start()),
+            (KeyAction::KillInstance, _) => Message::LaunchKill,
+            (KeyAction::PreviousInstance, State::Launch(_)) => {
+                return Some(self.key_change_selected_instance(false));
+            }
+            (KeyAction::NextInstance, State::Launch(_)) => {
+                return Some(self.key_change_selected_instance(true));
+            }
+            (KeyAction::TabPlay, State::Launch(_)) => {
+                Message::LaunchChangeTab(LaunchTabId::Buttons)
+            }
+            (KeyAction::TabEdit, State::Launch(_)) => Message::LaunchChangeTab(LaunchTabId::Edit),
+            (KeyAction::TabLogs, State::Launch(_)) => Message::LaunchChangeTab(LaunchTabId::Log),
+            (KeyAction::CreateInstance, State::Launch(_)) => {
+                Message::CreateInstance(CreateInstanceMessage::ScreenOpen)
+            }
+            (KeyAction::OpenSettings, State::Launch(_)) => {
+                Message::LauncherSettings(LauncherSettingsMessage::Open)
+            }
+            (KeyAction::OpenModStore, State::Launch(_) | State::EditMods(_))
+                if self.selected_instance.is_some() =>
+            {
+                Message::InstallMods(InstallModsMessage::Open)
+            }
+            (KeyAction::OpenInstanceFolder, State::Launch(_)) => {
+                Message::CoreOpenPath(self.selected_instance.as_ref()?.get_dot_minecraft_path())
+            }
+            (
+                KeyAction::UploadLog,
+                State::Launch(MenuLaunch {
+                    is_uploading_mclogs: false,
+                    ..
+                }),
+            ) => {
+                let (name, is_server) = self.selected_instance.as_ref()?.get_pair();
+                let log = self.get_logs(is_server).get(name)?;
+                if log.log.iter().all(|n| n.trim().is_empty()) {
+                    return None;
+                }
+                Message::LaunchUploadLog
+            }
+            (KeyAction::SelectAll, State::EditMods(_)) => {
+                Message::ManageMods(crate::state::ManageModsMessage::SelectAll)
+            }
+            (KeyAction::SelectAll, State::EditJarMods(_)) => {
+                Message::ManageJarMods(crate::state::ManageJarModsMessage::SelectAll)
+            }
+            _ => return None,
+        };
+        Some(Task::done(msg))
+    }
+
     fn drag_and_drop(&mut self, path: &Path, extension: OsString, filename: &str) -> Task<Message> {
         if let State::EditMods(_) = &self.state {
             if extension == "jar" || extension == "disabled" {
@@ -322,6 +377,12 @@ impl Launcher {
             | State::LoginAlternate(MenuLoginAlternate {
                 is_loading: false, ..
             })
+            | State::AddAuthServer(MenuAddAuthServer {
+                is_loading: false, ..
+            })
+            | State::Skins(MenuSkins {
+                is_loading: false, ..
+            })
             | State::Welcome(_) => {
                 should_return_to_main_screen = true;
             }
@@ -332,6 +393,10 @@ impl Launcher {
                         self.state = State::LauncherSettings(MenuLauncherSettings {
                             temp_scale: self.config.ui_scale.unwrap_or(1.0),
                             selected_tab: LauncherSettingsTab::About,
+                            java_runtimes: None,
+                            java_status: HashMap::new(),
+                            rebinding: None,
+                            keybind_error: None,
                         });
                     }
                 }
@@ -381,6 +446,8 @@ impl Launcher {
             | State::ImportModpack(_)
             | State::CurseforgeManualDownload(_)
             | State::LoginAlternate(_)
+            | State::AddAuthServer(_)
+            | State::Skins(_)
             | State::LogUploadResult { .. }
             | State::RecommendedMods(MenuRecommendedMods::Loading { .. })
             | State::Launch(_) => {}
@@ -422,20 +489,12 @@ impl Launcher {
     }
 
     fn key_change_selected_instance(&mut self, down: bool) -> Task<Message> {
-        let (is_viewing_server, sidebar_height) = {
+        let (is_viewing_server, sidebar_height, list) = {
             let State::Launch(menu) = &self.state else {
                 return Task::none();
             };
-            (menu.is_viewing_server, menu.sidebar_height)
-        };
-        let list = if is_viewing_server {
-            self.server_list.clone()
-        } else {
-            self.client_list.clone()
-        };
-
-        let Some(list) = list else {
-            return Task::none();
+            let list = self.get_visible_instances(menu.is_viewing_server, &menu.instance_filter);
+            (menu.is_viewing_server, menu.sidebar_height, list)
         };
 
         // If the user actually switched instances,
@@ -466,10 +525,7 @@ impl Launcher {
                     idx
                 }
             } else {
-                debug_assert!(
-                    false,
-                    "Selected instance {selected_instance:?}, not found in list?"
-                );
+                // Hidden by the search or in a collapsed group
                 0
             }
         } else {
@@ -5,21 +5,24 @@ use crate::{
     state::{
         ClientProcess, EditPresetsMessage, ManageModsMessage, MenuEditInstance, MenuEditMods,
         MenuInstallForge, MenuLaunch, MenuLauncherUpdate, ProgressBar, SelectedState, State,
-        OFFLINE_ACCOUNT_NAME,
     },
     Launcher, Message, ServerProcess,
 };
 use iced::futures::executor::block_on;
-use iced::Task;
-use ql_core::json::VersionDetails;
+use iced::{widget, Task};
+use ql_core::json::{instance_meta::sort_instances, InstanceHistory, InstanceMeta, VersionDetails};
 use ql_core::{
-    err, json::instance_config::InstanceConfigJson, GenericProgress, InstanceSelection,
-    IntoIoError, IntoJsonError, IntoStringError, JsonFileError,
+    err, icon,
+    json::instance_config::{InstanceConfigJson, PreferredAccount},
+    GenericProgress, InstanceSelection, IntoIoError, IntoJsonError, IntoStringError, JsonFileError,
+};
+use ql_instances::{
+    auth::{health::AccountStatus, AccountData, AccountType},
+    ProcessMonitor, ReadError,
 };
-use ql_instances::{auth::AccountData, ReadError};
 use ql_mod_manager::{loaders, store::ModIndex};
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     ffi::OsStr,
     path::{Path, PathBuf},
     process::ExitStatus,
@@ -38,15 +41,15 @@ pub const SIDEBAR_LIMIT_LEFT: f32 = 135.0;
 mod iced_event;
 
 impl Launcher {
-    pub fn launch_game(&mut self, account_data: Option<AccountData>) -> Task<Message> {
+    pub fn launch_game(
+        &mut self,
+        account_data: Option<AccountData>,
+        username: String,
+    ) -> Task<Message> {
         let selected_instance = self.selected_instance.as_ref().unwrap().get_name();
-        let username = if let Some(account_data) = &account_data {
-            // Logged in account
-            account_data.nice_username.clone()
-        } else {
-            // Offline username
-            self.config.username.clone()
-        };
+        let account = account_data
+            .as_ref()
+            .map(|n| (n.account_type.clone(), n.get_username_modified()));
 
         let (sender, receiver) = std::sync::mpsc::channel();
         self.java_recv = Some(ProgressBar::with_recv(receiver));
@@ -56,9 +59,19 @@ impl Launcher {
         let global_settings = self.config.global_settings.clone();
         let extra_java_args = self.config.extra_java_args.clone().unwrap_or_default();
 
+        let quick_play = if let State::Launch(menu) = &self.state {
+            menu.quick_play.clone()
+        } else {
+            None
+        };
+
         let instance_name = selected_instance.to_owned();
         Task::perform(
             async move {
+                ql_mod_manager::store::log_mod_problems(&InstanceSelection::Instance(
+                    instance_name.clone(),
+                ))
+                .await;
                 ql_instances::launch(
                     instance_name,
                     username,
@@ -66,17 +79,23 @@ impl Launcher {
                     account_data,
                     global_settings,
                     extra_java_args,
+                    quick_play,
                 )
                 .await
                 .strerr()
             },
-            Message::LaunchEnd,
+            move |n| Message::LaunchEnd(n, account.clone()),
         )
     }
 
-    pub fn finish_launching(&mut self, result: Result<Arc<Mutex<Child>>, String>) -> Task<Message> {
+    pub fn finish_launching(
+        &mut self,
+        result: Result<Arc<Mutex<Child>>, String>,
+        account: Option<(AccountType, String)>,
+    ) -> Task<Message> {
         self.java_recv = None;
         self.is_launching_game = false;
+        let (account_type, account) = account.unzip();
         match result {
             Ok(child) => {
                 let Some(InstanceSelection::Instance(selected_instance)) =
@@ -85,6 +104,14 @@ impl Launcher {
                     err!("Game Launched, but unknown instance!\n          This is a bug, please report it if found.");
                     return Task::none();
                 };
+                let max_memory_mb = block_on(InstanceConfigJson::read(
+                    &InstanceSelection::Instance(selected_instance.clone()),
+                ))
+                .ok()
+                .map(|n| n.ram_in_mb);
+                let monitor = ProcessMonitor::new(child.lock().unwrap().id(), max_memory_mb);
+                let started = chrono::Utc::now().timestamp();
+
                 if let (Some(stdout), Some(stderr)) = {
                     let mut child = child.lock().unwrap();
                     (child.stdout.take(), child.stderr.take())
@@ -96,6 +123,10 @@ impl Launcher {
                         ClientProcess {
                             child: child.clone(),
                             receiver: Some(receiver),
+                            monitor,
+                            started,
+                            account_type,
+                            account,
                         },
                     );
 
@@ -136,6 +167,10 @@ impl Launcher {
                     ClientProcess {
                         child: child.clone(),
                         receiver: None,
+                        monitor,
+                        started,
+                        account_type,
+                        account,
                     },
                 );
             }
@@ -153,7 +188,13 @@ impl Launcher {
                 return Task::none();
             }
 
-            self.selected_instance = None;
+            if let Some(instance) = self.selected_instance.take() {
+                if self.instance_meta.remove(&instance) {
+                    if let Err(err) = block_on(self.instance_meta.save()) {
+                        err!("Couldn't save instance groups/tags: {err}");
+                    }
+                }
+            }
             return self.go_to_launch_screen(Some("Deleted Instance".to_owned()));
         }
         Task::none()
@@ -162,6 +203,7 @@ impl Launcher {
     pub fn load_edit_instance_inner(
         edit_instance: &mut Option<MenuEditInstance>,
         selected_instance: &InstanceSelection,
+        meta: Option<&InstanceMeta>,
     ) -> Result<(), JsonFileError> {
         let config_path = selected_instance.get_instance_path().join("config.json");
 
@@ -177,16 +219,155 @@ impl Launcher {
 
         let instance_name = selected_instance.get_name();
 
+        // Instances without `javaVersion` are old enough to need Java 8
+        let java_required =
+            std::fs::read_to_string(selected_instance.get_instance_path().join("details.json"))
+                .ok()
+                .and_then(|n| serde_json::from_str::<VersionDetails>(&n).ok())
+                .and_then(|n| n.javaVersion)
+                .map_or(8, |n| n.majorVersion);
+
         *edit_instance = Some(MenuEditInstance {
             config: config_json,
             slider_value,
             instance_name: instance_name.to_owned(),
             old_instance_name: instance_name.to_owned(),
             slider_text: format_memory(memory_mb),
+            java_required,
+            java_error: None,
+            group_input: meta.and_then(|n| n.group.clone()).unwrap_or_default(),
+            tags_input: meta.map(|n| n.tags.join(", ")).unwrap_or_default(),
+            group_tags_changed: false,
+            is_favourite: meta.is_some_and(|n| n.favourite),
+            clone_name: format!("{instance_name} (copy)"),
+            clone_options: ql_instances::CloneOptions::default(),
+            is_cloning: false,
+            export_notice: None,
         });
         Ok(())
     }
 
+    /// Lists the worlds of the selected instance
+    /// (for Quick Play), clearing any previous choice.
+    pub fn load_quick_play_worlds(&mut self) {
+        let State::Launch(menu) = &mut self.state else {
+            return;
+        };
+        menu.quick_play = None;
+        menu.quick_play_server.clear();
+        menu.quick_play_worlds.clear();
+
+        let Some(instance @ InstanceSelection::Instance(_)) = &self.selected_instance else {
+            return;
+        };
+        let saves_dir = instance.get_dot_minecraft_path().join("saves");
+        if let Ok(entries) = std::fs::read_dir(&saves_dir) {
+            menu.quick_play_worlds = entries
+                .filter_map(Result::ok)
+                .filter(|n| n.path().join("level.dat").is_file())
+                .map(|n| n.file_name().to_string_lossy().to_string())
+                .collect();
+            menu.quick_play_worlds.sort();
+        }
+    }
+
+    pub fn load_play_history(&mut self) {
+        let State::Launch(menu) = &mut self.state else {
+            return;
+        };
+        menu.play_history = match &self.selected_instance {
+            Some(instance @ InstanceSelection::Instance(_)) => {
+                block_on(InstanceHistory::read(instance)).ok()
+            }
+            _ => None,
+        };
+    }
+
+    pub fn sort_instance_lists(&mut self) {
+        let sort = self.config.instance_sort.unwrap_or_default();
+        if let Some(list) = &mut self.client_list {
+            block_on(sort_instances(list, false, sort, &self.instance_meta));
+        }
+        if let Some(list) = &mut self.server_list {
+            block_on(sort_instances(list, true, sort, &self.instance_meta));
+        }
+    }
+
+    /// Loads the custom icons of all instances (or servers).
+    pub fn load_instance_icons(&mut self, is_server: bool) {
+        self.instance_icons
+            .retain(|n, _| n.is_server() != is_server);
+        let list = if is_server {
+            self.server_list.clone()
+        } else {
+            self.client_list.clone()
+        };
+        for name in list.iter().flatten() {
+            self.load_instance_icon(InstanceSelection::new(name, is_server));
+        }
+    }
+
+    pub fn load_instance_icon(&mut self, instance: InstanceSelection) {
+        if let Some(image) = block_on(icon::read(&instance)) {
+            self.instance_icons
+                .insert(instance, widget::image::Handle::from_bytes(image));
+        } else {
+            self.instance_icons.remove(&instance);
+        }
+    }
+
+    /// The instances (or servers) shown in the sidebar, filtered by
+    /// the search text. Ungrouped ones come first, then each group
+    /// (sorted by name) with its instances.
+    pub fn get_sidebar_groups<'a>(
+        &'a self,
+        is_server: bool,
+        filter: &str,
+    ) -> Vec<(Option<&'a str>, Vec<&'a String>)> {
+        let list = if is_server {
+            self.server_list.as_deref()
+        } else {
+            self.client_list.as_deref()
+        };
+        let no_meta = InstanceMeta::default();
+        let mut groups: Vec<(Option<&str>, Vec<&String>)> = vec![(None, Vec::new())];
+        for name in list.unwrap_or_default() {
+            let instance = InstanceSelection::new(name, is_server);
+            let meta = self.instance_meta.get(&instance);
+            if !meta.unwrap_or(&no_meta).matches_filter(name, filter) {
+                continue;
+            }
+            let group = meta.and_then(|n| n.group.as_deref());
+            if let Some((_, entries)) = groups.iter_mut().find(|(n, _)| *n == group) {
+                entries.push(name);
+            } else {
+                groups.push((group, vec![name]));
+            }
+        }
+        groups[1..].sort_by_key(|(n, _)| n.map(str::to_lowercase));
+        groups
+    }
+
+    pub fn is_group_collapsed(&self, group: &str) -> bool {
+        self.config
+            .collapsed_groups
+            .as_ref()
+            .is_some_and(|n| n.iter().any(|n| n == group))
+    }
+
+    /// The instances in the order they're shown in the sidebar,
+    /// skipping collapsed groups (unless searching).
+    pub fn get_visible_instances(&self, is_server: bool, filter: &str) -> Vec<String> {
+        self.get_sidebar_groups(is_server, filter)
+            .into_iter()
+            .filter(|(group, _)| {
+                !filter.trim().is_empty() || group.is_none_or(|n| !self.is_group_collapsed(n))
+            })
+            .flat_map(|(_, n)| n)
+            .cloned()
+            .collect()
+    }
+
     pub fn go_to_edit_mods_menu(&mut self, check_updates: bool) -> Task<Message> {
         async fn inner(
             this: &mut Launcher,
@@ -232,8 +413,13 @@ impl Launcher {
                 sorted_mods_list,
                 selected_state: SelectedState::None,
                 available_updates,
+                mod_problems: None,
+                is_verifying: false,
+                verify_report: None,
                 mod_update_progress: None,
                 locally_installed_mods,
+                local_mods_metadata: HashMap::new(),
+                local_mods_icons: HashMap::new(),
                 drag_and_drop_hovered: false,
                 update_check_handle,
                 version_json,
@@ -270,9 +456,9 @@ impl Launcher {
         if let State::EditMods(menu) = &mut self.state {
             let updates = menu
                 .available_updates
-                .clone()
-                .into_iter()
-                .map(|(n, _, _)| n)
+                .iter()
+                .filter(|(_, is_enabled)| *is_enabled)
+                .map(|(n, _)| n.id.clone())
                 .collect();
             let (sender, receiver) = std::sync::mpsc::channel();
             menu.mod_update_progress = Some(ProgressBar::with_recv_and_msg(
@@ -518,6 +704,33 @@ impl Launcher {
         }
     }
 
+    /// Records the play session and runs the post-exit hook
+    /// of an instance (if it has one) after the game exits.
+    /// Any errors are only logged.
+    pub fn on_game_exited(
+        &self,
+        instance_name: String,
+        process: &ClientProcess,
+        exit_code: Option<i32>,
+    ) -> Task<Message> {
+        let global_settings = self.config.global_settings.clone();
+        Task::batch([
+            Task::perform(
+                ql_instances::record_play_session(
+                    instance_name.clone(),
+                    process.started,
+                    exit_code,
+                    process.account_type.clone(),
+                ),
+                |_| Message::LaunchPlayHistoryRecorded,
+            ),
+            Task::perform(
+                ql_instances::run_post_exit_hook(instance_name, exit_code, global_settings),
+                |_| Message::Nothing,
+            ),
+        ])
+    }
+
     pub fn kill_selected_instance(&mut self) -> Task<Message> {
         let Some(selected_instance) = &self.selected_instance else {
             return Task::none();
@@ -525,13 +738,15 @@ impl Launcher {
         match selected_instance {
             InstanceSelection::Instance(n) => {
                 if let Some(process) = self.client_processes.remove(n) {
+                    let on_exit = self.on_game_exited(n.clone(), &process, None);
                     return Task::perform(
                         async move {
                             let mut child = process.child.lock().unwrap();
                             child.start_kill().strerr()
                         },
                         Message::LaunchKillEnd,
-                    );
+                    )
+                    .chain(on_exit);
                 }
             }
             InstanceSelection::Server(n) => {
@@ -580,20 +795,36 @@ impl Launcher {
 
         match selected_instance {
             InstanceSelection::Instance(name) => {
-                if let Some(account) = &self.accounts_selected {
-                    if account == OFFLINE_ACCOUNT_NAME
-                        && (self.config.username.is_empty() || self.config.username.contains(' '))
-                    {
-                        return Task::none();
-                    }
+                if self.client_processes.contains_key(name) {
+                    return Task::none();
                 }
 
-                if self.client_processes.contains_key(name) {
+                let preferred = block_on(InstanceConfigJson::read(selected_instance))
+                    .ok()
+                    .and_then(|n| n.preferred_account);
+                if let (Some(PreferredAccount::Account(account)), State::Launch(menu)) =
+                    (&preferred, &mut self.state)
+                {
+                    if !self.accounts.contains_key(account) {
+                        menu.message = format!("This instance's account ({account}) is logged out!\nUsing the selected account instead.");
+                    }
+                }
+                let (account_data, username) = self.get_launch_account(preferred.as_ref());
+                if account_data.is_none() && (username.is_empty() || username.contains(' ')) {
                     return Task::none();
                 }
 
                 self.is_launching_game = true;
-                let account_data = self.get_selected_account_data();
+                if let Some(account) = &account_data {
+                    let key = account.get_username_modified();
+                    // A background check is already refreshing this login.
+                    // Refreshing again would reuse (and for Yggdrasil
+                    // logins, revoke) the same token, so wait for it instead
+                    if self.account_status.get(&key) == Some(&AccountStatus::Checking) {
+                        self.launch_after_check = Some(key);
+                        return Task::none();
+                    }
+                }
                 // If the user is loading an existing login from disk
                 // then first refresh the tokens
                 if let Some(account) = &account_data {
@@ -603,7 +834,7 @@ impl Launcher {
                 }
                 // Or, if the account is already refreshed/freshly added,
                 // directly launch the game
-                self.launch_game(account_data)
+                self.launch_game(account_data, username)
             }
             InstanceSelection::Server(server) => {
                 self.server_logs.remove(server);
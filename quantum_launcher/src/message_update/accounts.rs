@@ -2,14 +2,18 @@ use std::time::{Duration, Instant};
 
 use auth::AccountData;
 use iced::Task;
-use ql_core::IntoStringError;
-use ql_instances::auth::{self, AccountType};
+use ql_core::{err, json::instance_config::PreferredAccount, IntoStringError};
+use ql_instances::auth::{
+    self,
+    health::{self, AccountStatus},
+    AccountType, CustomAuthServer, CustomOauth,
+};
 
 use crate::{
     config::ConfigAccount,
     state::{
-        AccountMessage, Launcher, LittleSkinOauth, MenuLoginAlternate, MenuLoginMS, Message,
-        ProgressBar, State, NEW_ACCOUNT_NAME, OFFLINE_ACCOUNT_NAME,
+        AccountMessage, AltOauth, Launcher, MenuAddAuthServer, MenuLoginAlternate, MenuLoginMS,
+        Message, ProgressBar, State, NEW_ACCOUNT_NAME, OFFLINE_ACCOUNT_NAME,
     },
 };
 
@@ -20,6 +24,7 @@ impl Launcher {
             | AccountMessage::Response2(Err(err))
             | AccountMessage::Response3(Err(err))
             | AccountMessage::AltLoginResponse(Err(err))
+            | AccountMessage::AuthServerAdded(Err(err))
             | AccountMessage::RefreshComplete(Err(err)) => {
                 self.set_error(err);
             }
@@ -50,22 +55,23 @@ impl Launcher {
                     },
                 }
             }
-            AccountMessage::LittleSkinDeviceCodeReady {
+            AccountMessage::AltDeviceCodeReady {
                 user_code,
                 verification_uri,
                 expires_in,
                 interval,
                 device_code,
             } => {
-                if let State::LoginAlternate(menu) = &mut self.state {
-                    menu.oauth = Some(LittleSkinOauth {
-                        // device_code: device_code.clone(),
-                        user_code: user_code.clone(),
-                        verification_uri: verification_uri.clone(),
-                        device_code_expires_at: Instant::now() + Duration::from_secs(expires_in),
-                    });
-                    menu.is_loading = false;
-                }
+                let State::LoginAlternate(menu) = &mut self.state else {
+                    return Task::none();
+                };
+                menu.oauth = Some(AltOauth {
+                    // device_code: device_code.clone(),
+                    user_code: user_code.clone(),
+                    verification_uri: verification_uri.clone(),
+                    device_code_expires_at: Instant::now() + Duration::from_secs(expires_in),
+                });
+                menu.is_loading = false;
 
                 // Start polling for token
                 let device_code_clone = device_code.clone();
@@ -74,18 +80,19 @@ impl Launcher {
                         device_code_clone,
                         interval,
                         expires_in,
+                        menu.account_type.clone(),
                     ),
                     |resp| match resp {
                         Ok(account) => {
                             Message::Account(AccountMessage::AltLoginResponse(Ok(account)))
                         }
-                        Err(e) => Message::Account(AccountMessage::LittleSkinDeviceCodeError(
-                            e.to_string(),
-                        )),
+                        Err(e) => {
+                            Message::Account(AccountMessage::AltDeviceCodeError(e.to_string()))
+                        }
                     },
                 );
             }
-            AccountMessage::LittleSkinDeviceCodeError(err_msg) => {
+            AccountMessage::AltDeviceCodeError(err_msg) => {
                 if let State::LoginAlternate(menu) = &mut self.state {
                     menu.is_loading = false;
                     menu.device_code_error = Some(err_msg);
@@ -93,46 +100,39 @@ impl Launcher {
             }
             AccountMessage::LogoutConfirm => {
                 let username = self.accounts_selected.clone().unwrap();
-                let account_type = self
-                    .accounts
-                    .get(&username)
-                    .map_or(AccountType::Microsoft, |n| n.account_type);
-
-                if let Err(err) = auth::logout(account_type.strip_name(&username), account_type) {
-                    self.set_error(err);
-                }
-                if let Some(accounts) = &mut self.config.accounts {
-                    accounts.remove(&username);
-                }
-                self.accounts.remove(&username);
-                if let Some(idx) = self
-                    .accounts_dropdown
-                    .iter()
-                    .enumerate()
-                    .find_map(|(i, n)| (*n == username).then_some(i))
-                {
-                    self.accounts_dropdown.remove(idx);
-                }
-                let selected_account = self
-                    .accounts_dropdown
-                    .first()
-                    .cloned()
-                    .unwrap_or_else(|| OFFLINE_ACCOUNT_NAME.to_owned());
-                self.accounts_selected = Some(selected_account);
-
+                self.logout(&username);
                 return self.go_to_launch_screen(Option::<String>::None);
             }
             AccountMessage::RefreshComplete(Ok(data)) => {
-                self.accounts.insert(data.get_username_modified(), data);
-
-                let account_data = self.get_selected_account_data();
+                self.set_account_status(&data);
+                self.accounts
+                    .insert(data.get_username_modified(), data.clone());
+                let username = data.nice_username.clone();
 
                 return Task::batch([
                     self.go_to_launch_screen::<String>(None),
-                    self.launch_game(account_data),
+                    self.launch_game(Some(data), username),
                 ]);
             }
 
+            AccountMessage::StatusChecked(username, result) => {
+                let (account, status) = *result;
+                if let Some(account) = account {
+                    // Only if it wasn't logged out in the meantime
+                    if self.accounts.contains_key(&username) {
+                        self.accounts.insert(username.clone(), account);
+                    }
+                }
+                if self.accounts.contains_key(&username) {
+                    self.account_status.insert(username.clone(), status);
+                }
+                if self.launch_after_check.as_ref() == Some(&username) {
+                    self.launch_after_check = None;
+                    self.is_launching_game = false;
+                    return self.launch_start();
+                }
+            }
+
             AccountMessage::OpenMicrosoft {
                 is_from_welcome_screen,
             } => {
@@ -147,18 +147,7 @@ impl Launcher {
             AccountMessage::OpenElyBy {
                 is_from_welcome_screen,
             } => {
-                self.state = State::LoginAlternate(MenuLoginAlternate {
-                    username: String::new(),
-                    password: String::new(),
-                    is_loading: false,
-                    otp: None,
-                    show_password: false,
-                    is_from_welcome_screen,
-
-                    is_littleskin: false,
-                    device_code_error: None,
-                    oauth: None,
-                });
+                self.open_alt_login(AccountType::ElyBy, is_from_welcome_screen);
             }
 
             AccountMessage::AltUsernameInput(username) => {
@@ -195,11 +184,7 @@ impl Launcher {
                         auth::yggdrasil::login_new(
                             menu.username.clone(),
                             password,
-                            if menu.is_littleskin {
-                                AccountType::LittleSkin
-                            } else {
-                                AccountType::ElyBy
-                            },
+                            menu.account_type.clone(),
                         ),
                         |n| Message::Account(AccountMessage::AltLoginResponse(n.strerr())),
                     );
@@ -221,41 +206,180 @@ impl Launcher {
             AccountMessage::OpenLittleSkin {
                 is_from_welcome_screen,
             } => {
-                self.state = State::LoginAlternate(MenuLoginAlternate {
-                    username: String::new(),
-                    password: String::new(),
+                self.open_alt_login(AccountType::LittleSkin, is_from_welcome_screen);
+            }
+            AccountMessage::OpenCustom {
+                server,
+                is_from_welcome_screen,
+            } => {
+                self.open_alt_login(AccountType::Custom(server), is_from_welcome_screen);
+            }
+
+            AccountMessage::AltOauthButtonClicked => {
+                let State::LoginAlternate(menu) = &mut self.state else {
+                    return Task::none();
+                };
+                menu.is_loading = true;
+
+                return Task::perform(
+                    auth::yggdrasil::oauth::request_device_code(menu.account_type.clone()),
+                    |resp| {
+                        Message::Account(match resp {
+                            Ok(code) => AccountMessage::AltDeviceCodeReady {
+                                user_code: code.user_code,
+                                verification_uri: code.verification_uri,
+                                expires_in: code.expires_in,
+                                interval: code.interval,
+                                device_code: code.device_code,
+                            },
+                            Err(e) => AccountMessage::AltDeviceCodeError(e.to_string()),
+                        })
+                    },
+                );
+            }
+
+            AccountMessage::AuthServerOpen => {
+                self.state = State::AddAuthServer(MenuAddAuthServer {
+                    url: String::new(),
+                    name: String::new(),
+                    oauth_enabled: false,
+                    oauth_client_id: String::new(),
+                    oauth_device_code_url: String::new(),
+                    oauth_token_url: String::new(),
                     is_loading: false,
-                    otp: None,
-                    show_password: false,
-                    is_from_welcome_screen,
-                    oauth: None,
-                    device_code_error: None,
-                    is_littleskin: true,
                 });
             }
-
-            AccountMessage::LittleSkinOauthButtonClicked => {
-                if let State::LoginAlternate(menu) = &mut self.state {
-                    menu.is_loading = true;
+            AccountMessage::AuthServerUrlInput(n) => {
+                if let State::AddAuthServer(menu) = &mut self.state {
+                    menu.url = n;
                 }
-
-                return Task::perform(auth::yggdrasil::oauth::request_device_code(), |resp| {
-                    Message::Account(match resp {
-                        Ok(code) => AccountMessage::LittleSkinDeviceCodeReady {
-                            user_code: code.user_code,
-                            verification_uri: code.verification_uri,
-                            expires_in: code.expires_in,
-                            interval: code.interval,
-                            device_code: code.device_code,
-                        },
-                        Err(e) => AccountMessage::LittleSkinDeviceCodeError(e.to_string()),
-                    })
+            }
+            AccountMessage::AuthServerNameInput(n) => {
+                if let State::AddAuthServer(menu) = &mut self.state {
+                    menu.name = n;
+                }
+            }
+            AccountMessage::AuthServerOauthToggle(t) => {
+                if let State::AddAuthServer(menu) = &mut self.state {
+                    menu.oauth_enabled = t;
+                }
+            }
+            AccountMessage::AuthServerOauthClientIdInput(n) => {
+                if let State::AddAuthServer(menu) = &mut self.state {
+                    menu.oauth_client_id = n;
+                }
+            }
+            AccountMessage::AuthServerOauthDeviceCodeUrlInput(n) => {
+                if let State::AddAuthServer(menu) = &mut self.state {
+                    menu.oauth_device_code_url = n;
+                }
+            }
+            AccountMessage::AuthServerOauthTokenUrlInput(n) => {
+                if let State::AddAuthServer(menu) = &mut self.state {
+                    menu.oauth_token_url = n;
+                }
+            }
+            AccountMessage::AuthServerAdd => {
+                let State::AddAuthServer(menu) = &mut self.state else {
+                    return Task::none();
+                };
+                menu.is_loading = true;
+                let url = menu.url.clone();
+                let name = menu.name.clone();
+                let oauth = menu.oauth_enabled.then(|| CustomOauth {
+                    client_id: menu.oauth_client_id.trim().to_owned(),
+                    device_code_url: menu.oauth_device_code_url.trim().to_owned(),
+                    token_url: menu.oauth_token_url.trim().to_owned(),
+                    scope: None,
                 });
+                return Task::perform(
+                    async move {
+                        let server = CustomAuthServer::discover(&url, Some(name)).await?;
+                        Ok::<_, auth::yggdrasil::Error>(CustomAuthServer { oauth, ..server })
+                    },
+                    |n| Message::Account(AccountMessage::AuthServerAdded(n.strerr())),
+                );
+            }
+            AccountMessage::AuthServerAdded(Ok(server)) => {
+                let servers = self.config.custom_auth_servers.get_or_insert_with(Vec::new);
+                if servers.iter().any(|n| n.name == server.name) {
+                    self.set_error(format!(
+                        "An auth server called {:?} already exists",
+                        server.name
+                    ));
+                    return Task::none();
+                }
+                servers.push(server);
+                self.state = State::AccountLogin;
+            }
+            AccountMessage::AuthServerRemove(name) => {
+                // Log out of the server's accounts, they can't be used without it
+                let usernames: Vec<String> = self
+                    .accounts
+                    .iter()
+                    .filter(|(_, n)| {
+                        matches!(&n.account_type, AccountType::Custom(server) if server.name == name)
+                    })
+                    .map(|(k, _)| k.clone())
+                    .collect();
+                for username in usernames {
+                    self.logout(&username);
+                }
+                if let Some(servers) = &mut self.config.custom_auth_servers {
+                    servers.retain(|n| n.name != name);
+                }
             }
         }
         Task::none()
     }
 
+    fn open_alt_login(&mut self, account_type: AccountType, is_from_welcome_screen: bool) {
+        self.state = State::LoginAlternate(MenuLoginAlternate {
+            username: String::new(),
+            password: String::new(),
+            is_loading: false,
+            otp: None,
+            show_password: false,
+            is_from_welcome_screen,
+
+            account_type,
+            device_code_error: None,
+            oauth: None,
+        });
+    }
+
+    fn logout(&mut self, username: &str) {
+        let account_type = self
+            .accounts
+            .get(username)
+            .map_or(AccountType::Microsoft, |n| n.account_type.clone());
+
+        if let Err(err) = auth::logout(account_type.strip_name(username), &account_type) {
+            self.set_error(err);
+        }
+        if let Some(accounts) = &mut self.config.accounts {
+            accounts.remove(username);
+        }
+        self.accounts.remove(username);
+        self.account_status.remove(username);
+        if let Some(idx) = self
+            .accounts_dropdown
+            .iter()
+            .enumerate()
+            .find_map(|(i, n)| (n == username).then_some(i))
+        {
+            self.accounts_dropdown.remove(idx);
+        }
+        if self.accounts_selected.as_deref() == Some(username) {
+            let selected_account = self
+                .accounts_dropdown
+                .first()
+                .cloned()
+                .unwrap_or_else(|| OFFLINE_ACCOUNT_NAME.to_owned());
+            self.accounts_selected = Some(selected_account);
+        }
+    }
+
     fn account_selected(&mut self, account: String) -> Task<Message> {
         if account == NEW_ACCOUNT_NAME {
             self.state = State::AccountLogin;
@@ -283,11 +407,11 @@ impl Launcher {
                     |n| Message::Account(AccountMessage::RefreshComplete(n.strerr())),
                 )
             }
-            AccountType::ElyBy | AccountType::LittleSkin => Task::perform(
+            AccountType::ElyBy | AccountType::LittleSkin | AccountType::Custom(_) => Task::perform(
                 auth::yggdrasil::login_refresh(
                     account.username.clone(),
                     account.refresh_token.clone(),
-                    account.account_type,
+                    account.account_type.clone(),
                 ),
                 |n| Message::Account(AccountMessage::RefreshComplete(n.strerr())),
             ),
@@ -312,13 +436,21 @@ impl Launcher {
             ConfigAccount {
                 uuid: data.uuid.clone(),
                 skin: None,
-                account_type: Some(data.account_type.to_string()),
+                account_type: Some(match &data.account_type {
+                    AccountType::Custom(_) => "Custom".to_owned(),
+                    account_type => account_type.to_string(),
+                }),
+                auth_server: match &data.account_type {
+                    AccountType::Custom(server) => Some(server.name.clone()),
+                    _ => None,
+                },
                 keyring_identifier: Some(data.username.clone()),
                 username_nice: Some(data.nice_username.clone()),
             },
         );
 
         self.accounts_selected = Some(username.clone());
+        self.set_account_status(&data);
         self.accounts.insert(username.clone(), data);
 
         self.go_to_launch_screen::<String>(None)
@@ -352,6 +484,84 @@ impl Launcher {
         task
     }
 
+    /// Refreshes logged-in accounts in the background, to find
+    /// out about expired/revoked logins before pressing play.
+    ///
+    /// If `only_expiring`, only accounts whose tokens
+    /// are about to expire are refreshed. Accounts that are
+    /// being launched or are in use by a running game are skipped,
+    /// as refreshing revokes the token the game is using.
+    pub fn check_accounts(&mut self, only_expiring: bool) -> Task<Message> {
+        if only_expiring && self.is_launching_game {
+            return Task::none();
+        }
+        let now = chrono::Utc::now().timestamp();
+        let mut tasks = Vec::new();
+        for (username, account) in &self.accounts {
+            let status = self.account_status.get(username);
+            let should_check = if only_expiring {
+                status.is_some_and(|n| n.is_expiring(now))
+                    && !self
+                        .client_processes
+                        .values()
+                        .any(|n| n.account.as_ref() == Some(username))
+            } else {
+                status != Some(&AccountStatus::Checking)
+            };
+            if !should_check {
+                continue;
+            }
+            self.account_status
+                .insert(username.clone(), AccountStatus::Checking);
+            let (username, account) = (username.clone(), account.clone());
+            tasks.push(Task::perform(
+                async move { health::check(&account).await },
+                move |n| {
+                    Message::Account(AccountMessage::StatusChecked(username.clone(), Box::new(n)))
+                },
+            ));
+        }
+        Task::batch(tasks)
+    }
+
+    /// For freshly logged in/refreshed accounts.
+    fn set_account_status(&mut self, account: &AccountData) {
+        let expires_at = account
+            .access_token
+            .as_deref()
+            .and_then(health::token_expiry);
+        self.account_status.insert(
+            account.get_username_modified(),
+            AccountStatus::Valid { expires_at },
+        );
+    }
+
+    /// The account (`None` for offline mode) and username to launch
+    /// an instance with, following its preferred account if set.
+    ///
+    /// If the preferred account is logged out,
+    /// this falls back to the selected one.
+    pub fn get_launch_account(
+        &self,
+        preferred: Option<&PreferredAccount>,
+    ) -> (Option<AccountData>, String) {
+        match preferred {
+            Some(PreferredAccount::Offline(username)) => return (None, username.clone()),
+            Some(PreferredAccount::Account(name)) => {
+                if let Some(account) = self.accounts.get(name) {
+                    return (Some(account.clone()), account.nice_username.clone());
+                }
+                err!("The instance's preferred account ({name}) is logged out! Using the selected account instead");
+            }
+            None => {}
+        }
+        let account = self.get_selected_account_data();
+        let username = account
+            .as_ref()
+            .map_or_else(|| self.config.username.clone(), |n| n.nice_username.clone());
+        (account, username)
+    }
+
     pub fn get_selected_account_data(&self) -> Option<AccountData> {
         if let Some(account) = &self.accounts_selected {
             if account == NEW_ACCOUNT_NAME || account == OFFLINE_ACCOUNT_NAME {
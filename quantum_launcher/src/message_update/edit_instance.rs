@@ -1,16 +1,21 @@
+use std::path::PathBuf;
+
 use iced::Task;
 use ql_core::{
-    err,
-    json::{instance_config::CustomJarConfig, GlobalSettings, InstanceConfigJson},
-    IntoIoError, IntoStringError, LAUNCHER_DIR,
+    err, icon,
+    json::{
+        instance_config::{CustomJarConfig, PreferredAccount},
+        GlobalSettings, InstanceConfigJson, InstanceMeta,
+    },
+    InstanceSelection, IntoIoError, IntoStringError, LAUNCHER_DIR,
 };
 
 use crate::{
     message_handler::format_memory,
     state::{
         dir_watch, get_entries, CustomJarState, EditInstanceMessage, Launcher, MenuEditInstance,
-        MenuLaunch, Message, State, ADD_JAR_NAME, NONE_JAR_NAME, OPEN_FOLDER_JAR_NAME,
-        REMOVE_JAR_NAME,
+        MenuLaunch, Message, State, ADD_JAR_NAME, NONE_JAR_NAME, OFFLINE_ACCOUNT_NAME,
+        OPEN_FOLDER_JAR_NAME, REMOVE_JAR_NAME, SELECTED_ACCOUNT_NAME,
     },
 };
 
@@ -76,7 +81,71 @@ impl Launcher {
                     ..
                 }) = &mut self.state
                 {
-                    menu.config.java_override = Some(n);
+                    menu.java_error = None;
+                    let path = PathBuf::from(&n);
+                    menu.config.java_override = Some(n.clone());
+                    // Only probe once it points to an actual file,
+                    // not for every partially typed path
+                    if path.is_file() {
+                        return Ok(Task::perform(
+                            async move { ql_instances::probe_java(&path).await },
+                            move |install| {
+                                Message::EditInstance(EditInstanceMessage::JavaOverrideChecked(
+                                    n.clone(),
+                                    install,
+                                ))
+                            },
+                        ));
+                    } else if !n.trim().is_empty() {
+                        menu.java_error = Some("File not found".to_owned());
+                    }
+                }
+            }
+            EditInstanceMessage::JavaOverrideChecked(path, install) => {
+                if let State::Launch(MenuLaunch {
+                    edit_instance: Some(menu),
+                    ..
+                }) = &mut self.state
+                {
+                    // The text may have changed while probing
+                    if menu.config.java_override.as_deref() != Some(path.as_str()) {
+                        return Ok(Task::none());
+                    }
+                    menu.java_error = match install {
+                        None => Some("This isn't a working Java executable".to_owned()),
+                        Some(install) if !install.matches(menu.java_required) => Some(format!(
+                            "This instance needs Java {}, but this is Java {}",
+                            menu.java_required, install.major
+                        )),
+                        Some(_) => None,
+                    };
+                }
+            }
+            EditInstanceMessage::JavaInstallsLoaded(installs) => {
+                self.java_installs = Some(installs);
+            }
+            EditInstanceMessage::JavaInstallsRescan => {
+                self.java_installs = None;
+                return Ok(Task::perform(ql_instances::find_java_installs(true), |n| {
+                    Message::EditInstance(EditInstanceMessage::JavaInstallsLoaded(n))
+                }));
+            }
+            EditInstanceMessage::JavaInstallPicked(install) => {
+                if let State::Launch(MenuLaunch {
+                    edit_instance: Some(menu),
+                    ..
+                }) = &mut self.state
+                {
+                    if install.matches(menu.java_required) {
+                        menu.config.java_override =
+                            Some(install.path.to_string_lossy().to_string());
+                        menu.java_error = None;
+                    } else {
+                        menu.java_error = Some(format!(
+                            "This instance needs Java {}, but the selected runtime is Java {}",
+                            menu.java_required, install.major
+                        ));
+                    }
                 }
             }
             EditInstanceMessage::MemoryChanged(new_slider_value) => {
@@ -108,6 +177,21 @@ impl Launcher {
                     menu.config.close_on_start = Some(t);
                 }
             }
+            EditInstanceMessage::PreferredAccountPicked(account) => {
+                let offline_name = self.config.username.clone();
+                iflet_config!(&mut self.state, preferred_account, {
+                    *preferred_account = match account.as_str() {
+                        SELECTED_ACCOUNT_NAME => None,
+                        OFFLINE_ACCOUNT_NAME => Some(PreferredAccount::Offline(offline_name)),
+                        _ => Some(PreferredAccount::Account(account)),
+                    };
+                });
+            }
+            EditInstanceMessage::PreferredOfflineNameEdit(username) => {
+                iflet_config!(&mut self.state, preferred_account: Some(PreferredAccount::Offline(name)), {
+                    *name = username;
+                });
+            }
             EditInstanceMessage::JavaArgsAdd => {
                 iflet_config!(&mut self.state, get, java_args, {
                     java_args.push(String::new());
@@ -181,6 +265,51 @@ impl Launcher {
                     *pre_launch_prefix_mode = Some(mode);
                 });
             }
+            EditInstanceMessage::EnvVarAdd => {
+                if let Some(config) = self.e_config() {
+                    config.get_env_var_list().push(String::new());
+                }
+            }
+            EditInstanceMessage::EnvVarEdit(var, idx) => {
+                if let Some(n) = self
+                    .e_config()
+                    .and_then(|n| n.get_env_var_list().get_mut(idx))
+                {
+                    *n = var;
+                }
+            }
+            EditInstanceMessage::EnvVarDelete(idx) => {
+                if let Some(vars) = self.e_config().map(InstanceConfigJson::get_env_var_list) {
+                    if idx < vars.len() {
+                        vars.remove(idx);
+                    }
+                }
+            }
+            EditInstanceMessage::EnvVarShiftUp(idx) => {
+                if let Some(config) = self.e_config() {
+                    Self::e_list_shift_up(idx, config.get_env_var_list());
+                }
+            }
+            EditInstanceMessage::EnvVarShiftDown(idx) => {
+                if let Some(config) = self.e_config() {
+                    Self::e_list_shift_down(idx, config.get_env_var_list());
+                }
+            }
+            EditInstanceMessage::EnvVarsModeChanged(mode) => {
+                iflet_config!(&mut self.state, env_vars_mode, {
+                    *env_vars_mode = Some(mode);
+                });
+            }
+            EditInstanceMessage::PreLaunchHookChanged(hook) => {
+                iflet_config!(&mut self.state, get, global_settings, {
+                    global_settings.pre_launch_hook = (!hook.is_empty()).then_some(hook);
+                });
+            }
+            EditInstanceMessage::PostExitHookChanged(hook) => {
+                iflet_config!(&mut self.state, get, global_settings, {
+                    global_settings.post_exit_hook = (!hook.is_empty()).then_some(hook);
+                });
+            }
             EditInstanceMessage::RenameEdit(n) => {
                 if let State::Launch(MenuLaunch {
                     edit_instance: Some(menu),
@@ -191,6 +320,178 @@ impl Launcher {
                 }
             }
             EditInstanceMessage::RenameApply => return self.rename_instance(),
+            EditInstanceMessage::RenameDone(res) => {
+                let (old, new) = res?;
+                return Ok(self.rename_instance_finish(&old, &new));
+            }
+            EditInstanceMessage::CloneNameEdit(n) => {
+                if let State::Launch(MenuLaunch {
+                    edit_instance: Some(menu),
+                    ..
+                }) = &mut self.state
+                {
+                    menu.clone_name = n;
+                }
+            }
+            EditInstanceMessage::CloneOptionsChanged(options) => {
+                if let State::Launch(MenuLaunch {
+                    edit_instance: Some(menu),
+                    ..
+                }) = &mut self.state
+                {
+                    menu.clone_options = options;
+                }
+            }
+            EditInstanceMessage::CloneStart => {
+                if let (
+                    State::Launch(MenuLaunch {
+                        edit_instance: Some(menu),
+                        ..
+                    }),
+                    Some(instance),
+                ) = (&mut self.state, self.selected_instance.clone())
+                {
+                    if menu.is_cloning {
+                        return Ok(Task::none());
+                    }
+                    menu.is_cloning = true;
+                    let (name, options) = (menu.clone_name.clone(), menu.clone_options);
+                    return Ok(Task::perform(
+                        async move {
+                            ql_instances::clone_instance(&instance, &name, options)
+                                .await
+                                .strerr()
+                        },
+                        |n| Message::EditInstance(EditInstanceMessage::CloneDone(n)),
+                    ));
+                }
+            }
+            EditInstanceMessage::CloneDone(res) => {
+                if let State::Launch(MenuLaunch {
+                    edit_instance: Some(menu),
+                    ..
+                }) = &mut self.state
+                {
+                    menu.is_cloning = false;
+                }
+                let instance = res?;
+                return Ok(Task::batch([
+                    Task::perform(get_entries(instance.is_server()), Message::CoreListLoaded),
+                    Task::done(Message::LaunchInstanceSelected {
+                        name: instance.get_name().to_owned(),
+                        is_server: instance.is_server(),
+                    }),
+                ]));
+            }
+            EditInstanceMessage::GroupEdit(group) => {
+                if let State::Launch(MenuLaunch {
+                    edit_instance: Some(menu),
+                    ..
+                }) = &mut self.state
+                {
+                    menu.group_input = group;
+                    menu.group_tags_changed = true;
+                }
+            }
+            EditInstanceMessage::TagsEdit(tags) => {
+                if let State::Launch(MenuLaunch {
+                    edit_instance: Some(menu),
+                    ..
+                }) = &mut self.state
+                {
+                    menu.tags_input = tags;
+                    menu.group_tags_changed = true;
+                }
+            }
+            EditInstanceMessage::GroupTagsApply => {
+                if let State::Launch(MenuLaunch {
+                    edit_instance: Some(menu),
+                    ..
+                }) = &mut self.state
+                {
+                    let trimmed = menu.group_input.trim();
+                    let new_group = (!trimmed.is_empty()).then(|| trimmed.to_owned());
+
+                    let mut new_tags: Vec<String> = Vec::new();
+                    for tag in menu
+                        .tags_input
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|n| !n.is_empty())
+                    {
+                        if !new_tags.iter().any(|n| n.eq_ignore_ascii_case(tag)) {
+                            new_tags.push(tag.to_owned());
+                        }
+                    }
+                    menu.group_tags_changed = false;
+
+                    return Ok(self.edit_instance_meta(|meta| {
+                        meta.group = new_group;
+                        meta.tags = new_tags;
+                    }));
+                }
+            }
+            EditInstanceMessage::FavouriteToggle(t) => {
+                if let State::Launch(MenuLaunch {
+                    edit_instance: Some(menu),
+                    ..
+                }) = &mut self.state
+                {
+                    menu.is_favourite = t;
+                    let task = self.edit_instance_meta(|meta| meta.favourite = t);
+                    self.sort_instance_lists();
+                    return Ok(task);
+                }
+            }
+            EditInstanceMessage::MetaSaved(res) => res?,
+            EditInstanceMessage::IconPick => {
+                let Some(instance) = self.selected_instance.clone() else {
+                    return Ok(Task::none());
+                };
+                let Some(path) = rfd::FileDialog::new()
+                    .add_filter("Image", &["png", "jpg", "jpeg", "webp"])
+                    .set_title("Select instance icon")
+                    .pick_file()
+                else {
+                    return Ok(Task::none());
+                };
+                return Ok(Task::perform(
+                    async move {
+                        let image = tokio::fs::read(&path).await.path(&path).strerr()?;
+                        icon::set(&instance, &image).await.strerr()
+                    },
+                    |n| Message::EditInstance(EditInstanceMessage::IconChanged(n)),
+                ));
+            }
+            EditInstanceMessage::IconRemove => {
+                let Some(instance) = self.selected_instance.clone() else {
+                    return Ok(Task::none());
+                };
+                return Ok(Task::perform(
+                    async move { icon::remove(&instance).await.strerr() },
+                    |n| Message::EditInstance(EditInstanceMessage::IconChanged(n)),
+                ));
+            }
+            EditInstanceMessage::IconChanged(res) => {
+                res?;
+                if let Some(instance) = self.selected_instance.clone() {
+                    self.load_instance_icon(instance);
+                }
+            }
+            EditInstanceMessage::ExportLaunchScript => return Ok(self.export_launch_script()),
+            EditInstanceMessage::ExportLaunchScriptDone(res) => {
+                let (out_dir, needs_token) = res?;
+                if let State::Launch(MenuLaunch {
+                    edit_instance: Some(menu),
+                    ..
+                }) = &mut self.state
+                {
+                    menu.export_notice = needs_token.then(|| {
+                        "The script needs the QL_ACCESS_TOKEN environment variable set to your account's access token. For offline mode, export with the offline account selected.".to_owned()
+                    });
+                }
+                return Ok(Task::done(Message::CoreOpenPath(out_dir)));
+            }
             EditInstanceMessage::ConfigSaved(res) => res?,
             EditInstanceMessage::WindowWidthChanged(width) => {
                 if let State::Launch(MenuLaunch {
@@ -319,6 +620,63 @@ impl Launcher {
         Ok(Task::none())
     }
 
+    /// Changes the group/tags/favourite of the
+    /// selected instance, saving them to disk.
+    fn edit_instance_meta(&mut self, f: impl FnOnce(&mut InstanceMeta)) -> Task<Message> {
+        let Some(instance) = &self.selected_instance else {
+            return Task::none();
+        };
+        f(self.instance_meta.get_mut(instance));
+        let meta = self.instance_meta.clone();
+        Task::perform(async move { meta.save().await.strerr() }, |n| {
+            Message::EditInstance(EditInstanceMessage::MetaSaved(n))
+        })
+    }
+
+    fn export_launch_script(&self) -> Task<Message> {
+        let Some(InstanceSelection::Instance(instance_name)) = self.selected_instance.clone()
+        else {
+            return Task::none();
+        };
+        let Some(out_dir) = rfd::FileDialog::new()
+            .set_title("Select where to save the launch script")
+            .pick_folder()
+        else {
+            return Task::none();
+        };
+
+        let preferred = if let State::Launch(MenuLaunch {
+            edit_instance: Some(menu),
+            ..
+        }) = &self.state
+        {
+            menu.config.preferred_account.as_ref()
+        } else {
+            None
+        };
+        let (account, username) = self.get_launch_account(preferred);
+        let needs_token = account.is_some();
+        let global_settings = self.config.global_settings.clone();
+        let extra_java_args = self.config.extra_java_args.clone().unwrap_or_default();
+
+        Task::perform(
+            async move {
+                ql_instances::export_launch_script(
+                    instance_name,
+                    username,
+                    account,
+                    global_settings,
+                    extra_java_args,
+                    out_dir.clone(),
+                )
+                .await
+                .strerr()
+                .map(|_| (out_dir, needs_token))
+            },
+            |n| Message::EditInstance(EditInstanceMessage::ExportLaunchScriptDone(n)),
+        )
+    }
+
     fn add_custom_jar(&mut self) -> Task<Message> {
         if let (
             Some(custom_jars),
@@ -359,57 +717,79 @@ impl Launcher {
     }
 
     fn rename_instance(&mut self) -> Result<Task<Message>, String> {
-        let State::Launch(MenuLaunch {
-            edit_instance: Some(menu),
-            ..
-        }) = &mut self.state
+        let (
+            State::Launch(MenuLaunch {
+                edit_instance: Some(menu),
+                ..
+            }),
+            Some(instance),
+        ) = (&self.state, self.selected_instance.clone())
         else {
             return Ok(Task::none());
         };
-        let mut disallowed = vec![
-            '/', '\\', ':', '*', '?', '"', '<', '>', '|', '\'', '\0', '\u{7F}',
-        ];
-
-        disallowed.extend('\u{1}'..='\u{1F}');
-
-        // Remove disallowed characters
-
-        let mut instance_name = menu.instance_name.clone();
-        instance_name.retain(|c| !disallowed.contains(&c));
-        let instance_name = instance_name.trim();
-
-        if instance_name.is_empty() {
-            err!("New name is empty or invalid");
+        let new_name = menu.instance_name.trim().to_owned();
+        if new_name == instance.get_name() {
+            // Don't waste time talking to OS
+            // and "renaming" instance if nothing has changed.
             return Ok(Task::none());
         }
 
-        if menu.old_instance_name == menu.instance_name {
-            // Don't waste time talking to OS
-            // and "renaming" instance if nothing has changed.
-            Ok(Task::none())
+        // Its files are in use, and the process
+        // and logs are tracked by name
+        let is_running = if instance.is_server() {
+            self.server_processes.contains_key(instance.get_name())
         } else {
-            let instances_dir =
-                LAUNCHER_DIR.join(if self.selected_instance.as_ref().unwrap().is_server() {
-                    "servers"
-                } else {
-                    "instances"
-                });
+            self.client_processes.contains_key(instance.get_name())
+        };
+        if is_running {
+            return Err("Can't rename a running instance, close it first".to_owned());
+        }
 
-            let old_path = instances_dir.join(&menu.old_instance_name);
-            let new_path = instances_dir.join(&menu.instance_name);
+        Ok(Task::perform(
+            async move {
+                let new = ql_instances::rename_instance(&instance, &new_name)
+                    .await
+                    .strerr()?;
+                Ok((instance, new))
+            },
+            |n| Message::EditInstance(EditInstanceMessage::RenameDone(n)),
+        ))
+    }
 
-            menu.old_instance_name = menu.instance_name.clone();
-            if let Some(n) = &mut self.selected_instance {
-                n.set_name(&menu.instance_name);
-            }
-            std::fs::rename(&old_path, &new_path)
-                .path(&old_path)
-                .strerr()?;
+    /// Moves everything kept in memory about
+    /// the `old` instance over to its new name.
+    fn rename_instance_finish(
+        &mut self,
+        old: &InstanceSelection,
+        new: &InstanceSelection,
+    ) -> Task<Message> {
+        let logs = if old.is_server() {
+            &mut self.server_logs
+        } else {
+            &mut self.client_logs
+        };
+        if let Some(log) = logs.remove(old.get_name()) {
+            logs.insert(new.get_name().to_owned(), log);
+        }
+        if let Some(updates) = self.mod_updates_checked.remove(old) {
+            self.mod_updates_checked.insert(new.clone(), updates);
+        }
+        if let Some(icon) = self.instance_icons.remove(old) {
+            self.instance_icons.insert(new.clone(), icon);
+        }
+        self.instance_meta.rename(old, new.get_name());
 
-            Ok(Task::perform(
-                get_entries(self.selected_instance.as_ref().unwrap().is_server()),
-                Message::CoreListLoaded,
-            ))
+        let list = Task::perform(get_entries(new.is_server()), Message::CoreListLoaded);
+        if self.selected_instance.as_ref() == Some(old) {
+            Task::batch([
+                list,
+                Task::done(Message::LaunchInstanceSelected {
+                    name: new.get_name().to_owned(),
+                    is_server: new.is_server(),
+                }),
+            ])
+        } else {
+            list
         }
     }
 
@@ -477,6 +857,18 @@ impl Launcher {
         }
     }
 
+    fn e_config(&mut self) -> Option<&mut InstanceConfigJson> {
+        if let State::Launch(MenuLaunch {
+            edit_instance: Some(menu),
+            ..
+        }) = &mut self.state
+        {
+            Some(&mut menu.config)
+        } else {
+            None
+        }
+    }
+
     fn e_pre_launch_prefix_edit(&mut self, msg: String, idx: usize) {
         let State::Launch(MenuLaunch {
             edit_instance: Some(menu),
@@ -4,7 +4,7 @@ use ql_core::{
     err, err_no_log, jarmod::JarMods, InstanceSelection, IntoIoError, IntoStringError, ModId,
     SelectedMod,
 };
-use ql_mod_manager::store::ModIndex;
+use ql_mod_manager::store::{ModIndex, ModUpdate};
 use std::{collections::HashSet, path::PathBuf};
 
 use crate::state::{
@@ -178,9 +178,55 @@ impl Launcher {
             }
             ManageModsMessage::LocalIndexLoaded(hash_set) => {
                 if let State::EditMods(menu) = &mut self.state {
+                    let new_files: Vec<String> = hash_set
+                        .iter()
+                        .filter(|n| {
+                            !menu
+                                .local_mods_metadata
+                                .contains_key(n.strip_suffix(".disabled").unwrap_or(n))
+                        })
+                        .cloned()
+                        .collect();
                     menu.locally_installed_mods = hash_set;
+
+                    if !new_files.is_empty() {
+                        // Placeholders, so that these aren't requested again
+                        // while loading (this message is sent every tick)
+                        for file in &new_files {
+                            let name = file.strip_suffix(".disabled").unwrap_or(file);
+                            menu.local_mods_metadata.insert(name.to_owned(), None);
+                        }
+                        return Task::perform(
+                            ql_mod_manager::store::read_local_mods_metadata(
+                                self.selected_instance.clone().unwrap(),
+                                new_files,
+                            ),
+                            |n| {
+                                Message::ManageMods(ManageModsMessage::LocalMetadataLoaded(
+                                    n.strerr(),
+                                ))
+                            },
+                        );
+                    }
                 }
             }
+            ManageModsMessage::LocalMetadataLoaded(result) => match result {
+                Ok(metadata) => {
+                    if let State::EditMods(menu) = &mut self.state {
+                        for (file, metadata) in metadata {
+                            let name = file.strip_suffix(".disabled").unwrap_or(&file);
+                            if let Some(icon) = metadata.as_ref().and_then(|n| n.icon.clone()) {
+                                menu.local_mods_icons.insert(
+                                    name.to_owned(),
+                                    iced::widget::image::Handle::from_bytes(icon),
+                                );
+                            }
+                            menu.local_mods_metadata.insert(name.to_owned(), metadata);
+                        }
+                    }
+                }
+                Err(err) => err!("Could not read local mod metadata: {err}"),
+            },
             ManageModsMessage::ToggleSelected => {
                 if let State::EditMods(menu) = &mut self.state {
                     let (ids_downloaded, ids_local) = menu.get_kinds_of_ids();
@@ -245,8 +291,8 @@ impl Launcher {
                     menu.update_check_handle = None;
                     match updates {
                         Ok(updates) => {
-                            let available_updates: Vec<(ModId, String, bool)> =
-                                updates.into_iter().map(|(a, b)| (a, b, true)).collect();
+                            let available_updates: Vec<(ModUpdate, bool)> =
+                                updates.into_iter().map(|n| (n, true)).collect();
                             self.mod_updates_checked.insert(
                                 self.selected_instance.clone().unwrap(),
                                 available_updates.clone(),
@@ -264,7 +310,7 @@ impl Launcher {
                     available_updates, ..
                 }) = &mut self.state
                 {
-                    if let Some((_, _, b)) = available_updates.get_mut(idx) {
+                    if let Some((_, b)) = available_updates.get_mut(idx) {
                         *b = t;
                     }
                 }
@@ -335,6 +381,60 @@ impl Launcher {
                     menu.submenu1_shown = !menu.submenu1_shown;
                 }
             }
+            ManageModsMessage::CheckProblems => {
+                if let State::EditMods(menu) = &mut self.state {
+                    menu.submenu1_shown = false;
+                }
+                let selected_instance = self.selected_instance.clone().unwrap();
+                return Task::perform(
+                    async move {
+                        ql_mod_manager::store::check_mods(&selected_instance)
+                            .await
+                            .strerr()
+                    },
+                    |n| Message::ManageMods(ManageModsMessage::CheckProblemsResult(n)),
+                );
+            }
+            ManageModsMessage::CheckProblemsResult(res) => match res {
+                Ok(problems) => {
+                    if let State::EditMods(menu) = &mut self.state {
+                        menu.mod_problems = Some(problems);
+                    }
+                }
+                Err(err) => self.set_error(err),
+            },
+            ManageModsMessage::VerifyMods => {
+                if let State::EditMods(menu) = &mut self.state {
+                    if menu.is_verifying {
+                        return Task::none();
+                    }
+                    menu.submenu1_shown = false;
+                    menu.is_verifying = true;
+                    menu.verify_report = None;
+                }
+                let selected_instance = self.selected_instance.clone().unwrap();
+                return Task::perform(
+                    async move {
+                        ql_mod_manager::store::verify_mods(&selected_instance, None)
+                            .await
+                            .strerr()
+                    },
+                    |n| Message::ManageMods(ManageModsMessage::VerifyModsResult(n)),
+                );
+            }
+            ManageModsMessage::VerifyModsResult(res) => {
+                if let State::EditMods(menu) = &mut self.state {
+                    menu.is_verifying = false;
+                }
+                match res {
+                    Ok(report) => {
+                        if let State::EditMods(menu) = &mut self.state {
+                            menu.verify_report = Some(report);
+                        }
+                    }
+                    Err(err) => self.set_error(err),
+                }
+            }
             ManageModsMessage::CurseforgeManualToggleDelete(t) => {
                 if let State::CurseforgeManualDownload(menu) = &mut self.state {
                     menu.delete_mods = t;
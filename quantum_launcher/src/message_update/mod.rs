@@ -1,9 +1,11 @@
+use std::collections::HashMap;
 use std::path::Path;
 use std::str::FromStr;
 
 use iced::futures::executor::block_on;
 use iced::{widget::scrollable::AbsoluteOffset, Task};
 use ql_core::{err, info, InstanceSelection, IntoStringError, ModId, OptifineUniqueVersion};
+use ql_instances::auth::credentials;
 use ql_mod_manager::{
     loaders,
     store::{get_description, QueryType},
@@ -15,8 +17,10 @@ mod edit_instance;
 mod manage_mods;
 mod presets;
 mod recommended;
+mod skins;
 
 use crate::{
+    keybinds::{KeyAction, KeyChord},
     state::{
         self, InstallFabricMessage, InstallModsMessage, InstallOptifineMessage, Launcher,
         LauncherSettingsMessage, MenuCurseforgeManualDownload, MenuInstallFabric,
@@ -480,6 +484,83 @@ impl Launcher {
                 if let State::LauncherSettings(menu) = &mut self.state {
                     menu.selected_tab = tab;
                 }
+                if let state::LauncherSettingsTab::Internal = tab {
+                    return load_java_runtimes();
+                }
+            }
+            LauncherSettingsMessage::JavaRuntimesLoaded(res) => match res {
+                Ok(runtimes) => {
+                    if let State::LauncherSettings(menu) = &mut self.state {
+                        menu.java_runtimes = Some(runtimes);
+                    }
+                }
+                Err(err) => self.set_error(err),
+            },
+            LauncherSettingsMessage::JavaRuntimeVerify(major) => {
+                self.set_java_status(major, "Verifying...");
+                return Task::perform(ql_instances::verify_java_install(major), move |n| {
+                    let res = n.strerr().map(|broken| {
+                        if broken.is_empty() {
+                            "All files are OK".to_owned()
+                        } else {
+                            format!("{} missing/corrupted file(s), repair it", broken.len())
+                        }
+                    });
+                    Message::LauncherSettings(LauncherSettingsMessage::JavaRuntimeDone(major, res))
+                });
+            }
+            LauncherSettingsMessage::JavaRuntimeRepair(major) => {
+                self.set_java_status(major, "Repairing...");
+                return Task::perform(ql_instances::repair_java_install(major), move |n| {
+                    let res = n.strerr().map(|n| format!("Repaired {n} file(s)"));
+                    Message::LauncherSettings(LauncherSettingsMessage::JavaRuntimeDone(major, res))
+                });
+            }
+            LauncherSettingsMessage::JavaRuntimeUpdate(major) => {
+                self.set_java_status(major, "Updating...");
+                return Task::perform(ql_instances::update_java_install(major), move |n| {
+                    let res = n.strerr().map(|updated| {
+                        if updated {
+                            "Updated to the latest build".to_owned()
+                        } else {
+                            "Already up to date".to_owned()
+                        }
+                    });
+                    Message::LauncherSettings(LauncherSettingsMessage::JavaRuntimeDone(major, res))
+                });
+            }
+            LauncherSettingsMessage::JavaRuntimeDelete(major) => {
+                self.state = State::ConfirmAction {
+                    msg1: format!("delete the auto-installed Java {major}"),
+                    msg2: "It will get reinstalled automatically if needed".to_owned(),
+                    yes: Message::LauncherSettings(
+                        LauncherSettingsMessage::JavaRuntimeDeleteConfirm(major),
+                    ),
+                    no: Message::LauncherSettings(LauncherSettingsMessage::ChangeTab(
+                        state::LauncherSettingsTab::Internal,
+                    )),
+                }
+            }
+            LauncherSettingsMessage::JavaRuntimeDeleteConfirm(major) => {
+                self.go_to_launcher_settings();
+                if let State::LauncherSettings(menu) = &mut self.state {
+                    menu.selected_tab = state::LauncherSettingsTab::Internal;
+                }
+                return Task::perform(ql_instances::delete_java_install(major), move |n| {
+                    let res = n.strerr().map(|()| "Deleted".to_owned());
+                    Message::LauncherSettings(LauncherSettingsMessage::JavaRuntimeDone(major, res))
+                });
+            }
+            LauncherSettingsMessage::JavaRuntimeDone(major, res) => {
+                let status = match res {
+                    Ok(n) => n,
+                    Err(err) => {
+                        err!("{err}");
+                        err
+                    }
+                };
+                self.set_java_status(major, &status);
+                return load_java_runtimes();
             }
             LauncherSettingsMessage::ToggleAntialiasing(t) => {
                 self.config.antialiasing = Some(t);
@@ -566,10 +647,110 @@ impl Launcher {
                     args.swap(idx, idx + 1);
                 }
             }
+            LauncherSettingsMessage::GlobalEnvVarAdd => {
+                self.config.get_env_var_list().push(String::new());
+            }
+            LauncherSettingsMessage::GlobalEnvVarEdit(var, idx) => {
+                if let Some(n) = self.config.get_env_var_list().get_mut(idx) {
+                    *n = var;
+                }
+            }
+            LauncherSettingsMessage::GlobalEnvVarDelete(idx) => {
+                let vars = self.config.get_env_var_list();
+                if idx < vars.len() {
+                    vars.remove(idx);
+                }
+            }
+            LauncherSettingsMessage::GlobalEnvVarShiftUp(idx) => {
+                let vars = self.config.get_env_var_list();
+                if idx > 0 && idx < vars.len() {
+                    vars.swap(idx, idx - 1);
+                }
+            }
+            LauncherSettingsMessage::GlobalEnvVarShiftDown(idx) => {
+                let vars = self.config.get_env_var_list();
+                if idx + 1 < vars.len() {
+                    vars.swap(idx, idx + 1);
+                }
+            }
+            LauncherSettingsMessage::GlobalPreLaunchHookChanged(hook) => {
+                self.config
+                    .global_settings
+                    .get_or_insert_with(Default::default)
+                    .pre_launch_hook = (!hook.is_empty()).then_some(hook);
+            }
+            LauncherSettingsMessage::GlobalPostExitHookChanged(hook) => {
+                self.config
+                    .global_settings
+                    .get_or_insert_with(Default::default)
+                    .post_exit_hook = (!hook.is_empty()).then_some(hook);
+            }
+            LauncherSettingsMessage::CredentialStorePicked(store) => {
+                if self.config.credential_store.unwrap_or_default() == store {
+                    return Task::none();
+                }
+                let accounts = self.config.get_keyring_accounts();
+                return Task::perform(
+                    async move {
+                        credentials::migrate(&accounts, store)
+                            .strerr()
+                            .map(|n| (store, n))
+                    },
+                    |n| {
+                        Message::LauncherSettings(LauncherSettingsMessage::CredentialStoreMigrated(
+                            n,
+                        ))
+                    },
+                );
+            }
+            LauncherSettingsMessage::KeybindEdit(action) => {
+                if let State::LauncherSettings(menu) = &mut self.state {
+                    menu.rebinding = Some(action);
+                    menu.keybind_error = None;
+                }
+            }
+            LauncherSettingsMessage::KeybindSet(action, chord) => {
+                let result = self.config.set_keybind(action, chord);
+                self.keybind_changed(result);
+            }
+            LauncherSettingsMessage::KeybindReset(action) => {
+                let result = self.config.reset_keybind(action);
+                self.keybind_changed(result);
+            }
+            LauncherSettingsMessage::KeybindResetAll => {
+                self.config.keybinds = None;
+                if let State::LauncherSettings(menu) = &mut self.state {
+                    menu.rebinding = None;
+                    menu.keybind_error = None;
+                }
+            }
+            LauncherSettingsMessage::CredentialStoreMigrated(res) => match res {
+                Ok((store, moved)) => {
+                    info!("Switched credential store to {store} ({moved} accounts moved)");
+                    credentials::set_store(store);
+                    self.config.credential_store = Some(store);
+                }
+                Err(err) => self.set_error(err),
+            },
         }
         Task::none()
     }
 
+    fn keybind_changed(&mut self, result: Result<(), (KeyAction, KeyChord)>) {
+        if let State::LauncherSettings(menu) = &mut self.state {
+            menu.rebinding = None;
+            menu.keybind_error = result.err().map(|(other, chord)| {
+                format!("{chord} is already used for \"{other}\" (change that first)")
+            });
+        }
+    }
+
+    fn set_java_status(&mut self, major: usize, status: &str) {
+        if let State::LauncherSettings(menu) = &mut self.state {
+            menu.java_status.insert(major, status.to_owned());
+        }
+    }
+
     pub fn go_to_launcher_settings(&mut self) {
         if let State::LauncherSettings(_) = &self.state {
             return;
@@ -577,10 +758,20 @@ impl Launcher {
         self.state = State::LauncherSettings(state::MenuLauncherSettings {
             temp_scale: self.config.ui_scale.unwrap_or(1.0),
             selected_tab: state::LauncherSettingsTab::UserInterface,
+            java_runtimes: None,
+            java_status: HashMap::new(),
+            rebinding: None,
+            keybind_error: None,
         });
     }
 }
 
+fn load_java_runtimes() -> Task<Message> {
+    Task::perform(ql_instances::list_java_installs(), |n| {
+        Message::LauncherSettings(LauncherSettingsMessage::JavaRuntimesLoaded(n.strerr()))
+    })
+}
+
 fn add_to_arguments_list(msg: String, args: &mut Vec<String>, idx: usize) {
     if msg.contains(' ') {
         args.remove(idx);
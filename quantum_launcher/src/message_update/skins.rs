@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+
+use iced::{widget::image::Handle, Task};
+use ql_core::IntoStringError;
+use ql_instances::auth::{
+    self,
+    skins::{self, AccountTextures, SkinLibrary, SkinModel, SkinPreview},
+    AccountData, AccountType,
+};
+
+use crate::state::{Launcher, MenuSkins, Message, SkinsMessage, State};
+
+impl Launcher {
+    pub fn update_skins(&mut self, msg: SkinsMessage) -> Task<Message> {
+        match msg {
+            SkinsMessage::Open => {
+                let Some(account) = self.get_selected_account_data() else {
+                    return Task::none();
+                };
+                self.state = State::Skins(MenuSkins {
+                    account: self.accounts_selected.clone().unwrap_or_default(),
+                    textures: None,
+                    current_skin: None,
+                    preview: None,
+                    show_back: false,
+                    library: SkinLibrary::default(),
+                    library_previews: HashMap::new(),
+                    model: SkinModel::Classic,
+                    can_upload: !matches!(account.account_type, AccountType::ElyBy),
+                    is_loading: true,
+                });
+                return Task::batch([
+                    Task::perform(load_account_skin(account), |n| {
+                        Message::Skins(SkinsMessage::Loaded(n))
+                    }),
+                    Task::perform(
+                        async { with_previews(SkinLibrary::load().await.strerr()?).await },
+                        |n| Message::Skins(SkinsMessage::LibraryLoaded(n)),
+                    ),
+                ]);
+            }
+            SkinsMessage::Loaded(Ok((account, textures, png))) => {
+                let State::Skins(menu) = &mut self.state else {
+                    return Task::none();
+                };
+                menu.is_loading = false;
+                // The access token may have been refreshed
+                self.accounts.insert(menu.account.clone(), account);
+
+                let model = textures.skin.as_ref().map(|n| n.model).unwrap_or_default();
+                menu.model = model;
+                menu.preview = png.as_ref().and_then(|png| {
+                    let front = skins::render_preview(png, model, false).ok()?;
+                    let back = skins::render_preview(png, model, true).ok()?;
+                    Some((to_handle(front), to_handle(back)))
+                });
+                if let Some(account) = self
+                    .config
+                    .accounts
+                    .as_mut()
+                    .and_then(|n| n.get_mut(&menu.account))
+                {
+                    account.skin = textures.skin.as_ref().map(|n| n.url.clone());
+                }
+                menu.current_skin = png;
+                menu.textures = Some(textures);
+            }
+            SkinsMessage::LibraryLoaded(Ok((library, previews))) => {
+                if let State::Skins(menu) = &mut self.state {
+                    menu.library = library;
+                    menu.library_previews = previews
+                        .into_iter()
+                        .map(|(name, preview)| (name, to_handle(preview)))
+                        .collect();
+                }
+            }
+            SkinsMessage::Loaded(Err(err))
+            | SkinsMessage::LibraryLoaded(Err(err))
+            | SkinsMessage::Changed(Err(err)) => self.set_error(err),
+            SkinsMessage::ToggleBack(t) => {
+                if let State::Skins(menu) = &mut self.state {
+                    menu.show_back = t;
+                }
+            }
+            SkinsMessage::ModelSelected(model) => {
+                if let State::Skins(menu) = &mut self.state {
+                    menu.model = model;
+                }
+            }
+
+            SkinsMessage::UploadFile => {
+                let Some((account, menu)) = self.get_skins_menu() else {
+                    return Task::none();
+                };
+                let Some(path) = rfd::FileDialog::new()
+                    .add_filter("Skin", &["png"])
+                    .set_title("Select skin to upload")
+                    .pick_file()
+                else {
+                    return Task::none();
+                };
+                let model = menu.model;
+                menu.is_loading = true;
+                return Task::perform(
+                    async move {
+                        let png = tokio::fs::read(&path).await.strerr()?;
+                        skins::upload_skin(&account, png, model).await.strerr()
+                    },
+                    |n| Message::Skins(SkinsMessage::Changed(n)),
+                );
+            }
+            SkinsMessage::UploadFromLibrary(name) => {
+                let Some((account, menu)) = self.get_skins_menu() else {
+                    return Task::none();
+                };
+                let Some(skin) = menu.library.skins.iter().find(|n| n.name == name).cloned() else {
+                    return Task::none();
+                };
+                menu.is_loading = true;
+                return Task::perform(
+                    async move {
+                        let png = SkinLibrary::read_png(&skin).await.strerr()?;
+                        skins::upload_skin(&account, png, skin.model).await.strerr()
+                    },
+                    |n| Message::Skins(SkinsMessage::Changed(n)),
+                );
+            }
+            SkinsMessage::CapeSelected(cape_id) => {
+                let Some((account, menu)) = self.get_skins_menu() else {
+                    return Task::none();
+                };
+                menu.is_loading = true;
+                return Task::perform(
+                    async move { skins::set_cape(&account, cape_id).await.strerr() },
+                    |n| Message::Skins(SkinsMessage::Changed(n)),
+                );
+            }
+            SkinsMessage::Changed(Ok(())) => {
+                let Some((account, menu)) = self.get_skins_menu() else {
+                    return Task::none();
+                };
+                menu.is_loading = true;
+                return Task::perform(load_account_skin(account), |n| {
+                    Message::Skins(SkinsMessage::Loaded(n))
+                });
+            }
+
+            SkinsMessage::LibrarySaveCurrent => {
+                let Some((account, menu)) = self.get_skins_menu() else {
+                    return Task::none();
+                };
+                let (Some(png), Some(textures)) = (menu.current_skin.clone(), &menu.textures)
+                else {
+                    return Task::none();
+                };
+                let model = textures.skin.as_ref().map(|n| n.model).unwrap_or_default();
+                let name = format!(
+                    "{} ({})",
+                    account.nice_username,
+                    chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
+                );
+                let library = menu.library.clone();
+                return Task::perform(
+                    async move { with_previews(library.add(name, png, model).await.strerr()?).await },
+                    |n| Message::Skins(SkinsMessage::LibraryLoaded(n)),
+                );
+            }
+            SkinsMessage::LibraryAddFile => {
+                let State::Skins(menu) = &self.state else {
+                    return Task::none();
+                };
+                let Some(path) = rfd::FileDialog::new()
+                    .add_filter("Skin", &["png"])
+                    .set_title("Select skin to add")
+                    .pick_file()
+                else {
+                    return Task::none();
+                };
+                let name = path
+                    .file_stem()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                let (library, model) = (menu.library.clone(), menu.model);
+                return Task::perform(
+                    async move {
+                        let png = tokio::fs::read(&path).await.strerr()?;
+                        with_previews(library.add(name, png, model).await.strerr()?).await
+                    },
+                    |n| Message::Skins(SkinsMessage::LibraryLoaded(n)),
+                );
+            }
+            SkinsMessage::LibraryRemove(name) => {
+                let State::Skins(menu) = &self.state else {
+                    return Task::none();
+                };
+                let library = menu.library.clone();
+                return Task::perform(
+                    async move { with_previews(library.remove(&name).await.strerr()?).await },
+                    |n| Message::Skins(SkinsMessage::LibraryLoaded(n)),
+                );
+            }
+        }
+        Task::none()
+    }
+
+    fn get_skins_menu(&mut self) -> Option<(AccountData, &mut MenuSkins)> {
+        let State::Skins(menu) = &mut self.state else {
+            return None;
+        };
+        let account = self.accounts.get(&menu.account)?.clone();
+        Some((account, menu))
+    }
+}
+
+/// Refreshes the account if needed, and gets its
+/// skin and capes (along with the skin image).
+async fn load_account_skin(
+    account: AccountData,
+) -> Result<(AccountData, AccountTextures, Option<Vec<u8>>), String> {
+    let account = if account.access_token.is_none() || account.needs_refresh {
+        refresh_account(&account).await?
+    } else {
+        account
+    };
+    let textures = skins::get_textures(&account).await.strerr()?;
+    let png = match &textures.skin {
+        Some(skin) => Some(skins::download_texture(&skin.url).await.strerr()?),
+        None => None,
+    };
+    Ok((account, textures, png))
+}
+
+/// Refreshes the token of an account (without any progress bar).
+pub async fn refresh_account(account: &AccountData) -> Result<AccountData, String> {
+    match &account.account_type {
+        AccountType::Microsoft => auth::ms::login_refresh(
+            account.username.clone(),
+            account.refresh_token.clone(),
+            None,
+        )
+        .await
+        .strerr(),
+        AccountType::ElyBy | AccountType::LittleSkin | AccountType::Custom(_) => {
+            auth::yggdrasil::login_refresh(
+                account.username.clone(),
+                account.refresh_token.clone(),
+                account.account_type.clone(),
+            )
+            .await
+            .strerr()
+        }
+    }
+}
+
+async fn with_previews(
+    library: SkinLibrary,
+) -> Result<(SkinLibrary, Vec<(String, SkinPreview)>), String> {
+    let mut previews = Vec::new();
+    for skin in &library.skins {
+        let png = SkinLibrary::read_png(skin).await.strerr()?;
+        if let Ok(preview) = skins::render_preview(&png, skin.model, false) {
+            previews.push((skin.name.clone(), preview));
+        }
+    }
+    Ok((library, previews))
+}
+
+fn to_handle(preview: SkinPreview) -> Handle {
+    Handle::from_rgba(preview.width, preview.height, preview.pixels)
+}
@@ -7,16 +7,28 @@ use iced::{widget::scrollable::AbsoluteOffset, Task};
 use ql_core::{
     file_utils::DirItem,
     jarmod::JarMods,
-    json::{InstanceConfigJson, VersionDetails},
+    json::{InstanceConfigJson, InstanceHistory, VersionDetails},
     DownloadProgress, GenericProgress, InstanceSelection, ListEntry, ModId, OptifineUniqueVersion,
     SelectedMod, StoreBackendType,
 };
+use ql_instances::{
+    auth::{
+        skins::{AccountTextures, SkinLibrary, SkinModel},
+        AccountType,
+    },
+    ManagedJava, QuickPlay,
+};
 use ql_mod_manager::{
     loaders::{forge::ForgeInstallProgress, optifine::OptifineInstallProgress},
-    store::{CurseforgeNotAllowed, ModConfig, ModIndex, QueryType, RecommendedMod, SearchResult},
+    store::{
+        CurseforgeNotAllowed, JarMetadata, ModConfig, ModIndex, ModProblem, ModUpdate, QueryType,
+        RecommendedMod, SearchResult, VerifyReport,
+    },
 };
 
-use crate::{config::SIDEBAR_WIDTH_DEFAULT, message_handler::get_locally_installed_mods};
+use crate::{
+    config::SIDEBAR_WIDTH_DEFAULT, keybinds::KeyAction, message_handler::get_locally_installed_mods,
+};
 
 use super::{ManageModsMessage, Message, ProgressBar};
 
@@ -56,6 +68,17 @@ pub struct MenuLaunch {
     pub is_viewing_server: bool,
     pub is_uploading_mclogs: bool,
     pub log_scroll: isize,
+
+    /// A world/server to join directly when launching.
+    pub quick_play: Option<QuickPlay>,
+    pub quick_play_server: String,
+    /// Worlds in the selected instance's `saves/` folder.
+    pub quick_play_worlds: Vec<String>,
+    /// Play history of the selected instance.
+    pub play_history: Option<InstanceHistory>,
+    /// Search text for the sidebar, matching
+    /// instance names, groups and tags.
+    pub instance_filter: String,
 }
 
 impl Default for MenuLaunch {
@@ -77,6 +100,11 @@ impl MenuLaunch {
             is_viewing_server: false,
             log_scroll: 0,
             is_uploading_mclogs: false,
+            quick_play: None,
+            quick_play_server: String::new(),
+            quick_play_worlds: Vec::new(),
+            play_history: None,
+            instance_filter: String::new(),
         }
     }
 }
@@ -88,6 +116,23 @@ pub struct MenuEditInstance {
     pub old_instance_name: String,
     pub slider_value: f32,
     pub slider_text: String,
+    /// Major Java version required by the instance
+    /// (`javaVersion` in its version JSON)
+    pub java_required: usize,
+    pub java_error: Option<String>,
+    /// Group and comma-separated tags, as typed.
+    /// Only saved when pressing enter or "Save".
+    pub group_input: String,
+    pub tags_input: String,
+    pub group_tags_changed: bool,
+    pub is_favourite: bool,
+    /// Name (as typed) and options for cloning this instance.
+    pub clone_name: String,
+    pub clone_options: ql_instances::CloneOptions,
+    pub is_cloning: bool,
+    /// Shown after exporting a launch script
+    /// that needs the account's access token.
+    pub export_notice: Option<String>,
 }
 
 pub enum SelectedState {
@@ -156,6 +201,11 @@ pub struct MenuEditMods {
     pub version_json: Box<VersionDetails>,
 
     pub locally_installed_mods: HashSet<String>,
+    /// Metadata read from the jars of local mods,
+    /// keyed by file name (without `.disabled`).
+    /// `None` if still loading or not found.
+    pub local_mods_metadata: HashMap<String, Option<JarMetadata>>,
+    pub local_mods_icons: HashMap<String, iced::widget::image::Handle>,
     pub sorted_mods_list: Vec<ModListEntry>,
 
     pub selected_mods: HashSet<SelectedMod>,
@@ -163,7 +213,14 @@ pub struct MenuEditMods {
     pub selected_state: SelectedState,
 
     pub update_check_handle: Option<iced::task::Handle>,
-    pub available_updates: Vec<(ModId, String, bool)>,
+    /// Available updates, and whether they're selected to be applied.
+    pub available_updates: Vec<(ModUpdate, bool)>,
+    /// Results of the last "Check for problems".
+    /// `None` if not checked yet.
+    pub mod_problems: Option<Vec<ModProblem>>,
+    pub is_verifying: bool,
+    /// Results of the last "Verify & repair".
+    pub verify_report: Option<VerifyReport>,
 
     /// Index of the item selected before pressing shift
     pub list_shift_index: Option<usize>,
@@ -320,12 +377,20 @@ pub struct MenuModsDownload {
 pub struct MenuLauncherSettings {
     pub temp_scale: f64,
     pub selected_tab: LauncherSettingsTab,
+    pub java_runtimes: Option<Vec<ManagedJava>>,
+    /// Result of the last action on each runtime
+    /// (keyed by major version)
+    pub java_status: HashMap<usize, String>,
+    /// The action waiting for a key press to be bound to
+    pub rebinding: Option<KeyAction>,
+    pub keybind_error: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum LauncherSettingsTab {
     UserInterface,
     Internal,
+    Keybinds,
     About,
 }
 
@@ -337,6 +402,7 @@ impl std::fmt::Display for LauncherSettingsTab {
             match self {
                 LauncherSettingsTab::UserInterface => "Appearance",
                 LauncherSettingsTab::Internal => "Game",
+                LauncherSettingsTab::Keybinds => "Keybinds",
                 LauncherSettingsTab::About => "About",
             }
         )
@@ -344,7 +410,12 @@ impl std::fmt::Display for LauncherSettingsTab {
 }
 
 impl LauncherSettingsTab {
-    pub const ALL: &'static [Self] = &[Self::UserInterface, Self::Internal, Self::About];
+    pub const ALL: &'static [Self] = &[
+        Self::UserInterface,
+        Self::Internal,
+        Self::Keybinds,
+        Self::About,
+    ];
 }
 
 pub struct MenuEditPresets {
@@ -397,18 +468,54 @@ pub struct MenuLoginAlternate {
 
     pub is_from_welcome_screen: bool,
 
-    pub is_littleskin: bool,
-    pub oauth: Option<LittleSkinOauth>,
+    pub account_type: AccountType,
+    pub oauth: Option<AltOauth>,
     pub device_code_error: Option<String>,
 }
 
-pub struct LittleSkinOauth {
+pub struct AltOauth {
     // pub device_code: String,
     pub user_code: String,
     pub verification_uri: String,
     pub device_code_expires_at: Instant,
 }
 
+/// Adding a [`ql_instances::auth::CustomAuthServer`]
+pub struct MenuAddAuthServer {
+    pub url: String,
+    /// If empty, the server's advertised name is used
+    pub name: String,
+
+    pub oauth_enabled: bool,
+    pub oauth_client_id: String,
+    pub oauth_device_code_url: String,
+    pub oauth_token_url: String,
+
+    pub is_loading: bool,
+}
+
+pub struct MenuSkins {
+    /// The account's entry in [`super::Launcher::accounts`]
+    pub account: String,
+    /// `None` while loading
+    pub textures: Option<AccountTextures>,
+    /// The PNG of the account's current skin
+    pub current_skin: Option<Vec<u8>>,
+    /// Front and back render of the current skin
+    pub preview: Option<(iced::widget::image::Handle, iced::widget::image::Handle)>,
+    pub show_back: bool,
+
+    pub library: SkinLibrary,
+    /// Front renders of the library's skins, by name
+    pub library_previews: HashMap<String, iced::widget::image::Handle>,
+
+    /// Model for uploaded/imported skins
+    pub model: SkinModel,
+    /// ely.by skins can only be changed on its website
+    pub can_upload: bool,
+    pub is_loading: bool,
+}
+
 pub struct MenuLoginMS {
     pub url: String,
     pub code: String,
@@ -454,6 +561,8 @@ pub enum State {
     AccountLogin,
     LoginMS(MenuLoginMS),
     LoginAlternate(MenuLoginAlternate),
+    AddAuthServer(MenuAddAuthServer),
+    Skins(MenuSkins),
 
     InstallPaper,
     InstallFabric(MenuInstallFabric),
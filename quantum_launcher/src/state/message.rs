@@ -1,5 +1,5 @@
 use std::{
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     path::PathBuf,
     process::ExitStatus,
     sync::{Arc, Mutex},
@@ -7,21 +7,30 @@ use std::{
 
 use iced::widget;
 use ql_core::{
-    file_utils::DirItem, jarmod::JarMods, InstanceSelection, ListEntry, ModId, StoreBackendType,
+    file_utils::DirItem, jarmod::JarMods, json::InstanceSort, InstanceSelection, ListEntry, ModId,
+    StoreBackendType,
 };
 use ql_instances::{
     auth::{
+        credentials::CredentialStore,
+        health::AccountStatus,
         ms::{AuthCodeResponse, AuthTokenResponse},
-        AccountData,
+        skins::{AccountTextures, SkinLibrary, SkinModel, SkinPreview},
+        AccountData, AccountType, CustomAuthServer,
     },
     UpdateCheckInfo,
 };
 use ql_mod_manager::{
     loaders::fabric::FabricVersionListItem,
-    store::{CurseforgeNotAllowed, ImageResult, ModIndex, QueryType, RecommendedMod, SearchResult},
+    store::{
+        CurseforgeNotAllowed, ImageResult, JarMetadata, ModIndex, ModProblem, ModUpdate, QueryType,
+        RecommendedMod, SearchResult, VerifyReport,
+    },
 };
 use tokio::process::Child;
 
+use crate::keybinds::{KeyAction, KeyChord};
+
 use super::{LaunchTabId, LauncherSettingsTab, LicenseTab, Res};
 
 #[derive(Debug, Clone)]
@@ -58,6 +67,8 @@ pub enum EditInstanceMessage {
     MemoryChanged(f32),
     LoggingToggle(bool),
     CloseLauncherToggle(bool),
+    PreferredAccountPicked(String),
+    PreferredOfflineNameEdit(String),
     JavaArgsAdd,
     JavaArgEdit(String, usize),
     JavaArgDelete(usize),
@@ -75,13 +86,42 @@ pub enum EditInstanceMessage {
     PreLaunchPrefixShiftUp(usize),
     PreLaunchPrefixShiftDown(usize),
     PreLaunchPrefixModeChanged(ql_core::json::instance_config::PreLaunchPrefixMode),
+    EnvVarAdd,
+    EnvVarEdit(String, usize),
+    EnvVarDelete(usize),
+    EnvVarShiftUp(usize),
+    EnvVarShiftDown(usize),
+    EnvVarsModeChanged(ql_core::json::instance_config::PreLaunchPrefixMode),
+    PreLaunchHookChanged(String),
+    PostExitHookChanged(String),
     RenameEdit(String),
     RenameApply,
+    RenameDone(Res<(InstanceSelection, InstanceSelection)>),
+    CloneNameEdit(String),
+    CloneOptionsChanged(ql_instances::CloneOptions),
+    CloneStart,
+    CloneDone(Res<InstanceSelection>),
+    GroupEdit(String),
+    TagsEdit(String),
+    GroupTagsApply,
+    FavouriteToggle(bool),
+    MetaSaved(Res),
+    IconPick,
+    IconRemove,
+    IconChanged(Res),
+    ExportLaunchScript,
+    /// The output directory, and whether the
+    /// script needs the account's access token.
+    ExportLaunchScriptDone(Res<(std::path::PathBuf, bool)>),
     WindowWidthChanged(String),
     WindowHeightChanged(String),
 
     CustomJarPathChanged(String),
     CustomJarLoaded(Res<Vec<String>>),
+    JavaInstallsLoaded(Vec<ql_instances::JavaInstall>),
+    JavaInstallsRescan,
+    JavaInstallPicked(ql_instances::JavaInstall),
+    JavaOverrideChecked(String, Option<ql_instances::JavaInstall>),
     AutoSetMainClassToggle(bool),
 }
 
@@ -96,13 +136,14 @@ pub enum ManageModsMessage {
     DeleteFinished(Res<Vec<ModId>>),
     LocalDeleteFinished(Res),
     LocalIndexLoaded(HashSet<String>),
+    LocalMetadataLoaded(Res<HashMap<String, Option<JarMetadata>>>),
 
     ToggleSelected,
     ToggleFinished(Res),
 
     UpdateMods,
     UpdateModsFinished(Res),
-    UpdateCheckResult(Res<Vec<(ModId, String)>>),
+    UpdateCheckResult(Res<Vec<ModUpdate>>),
     UpdateCheckToggle(usize, bool),
 
     SelectAll,
@@ -112,6 +153,10 @@ pub enum ManageModsMessage {
     AddFileDone(Res<HashSet<CurseforgeNotAllowed>>),
     ExportMenuOpen,
     ToggleSubmenu1,
+    CheckProblems,
+    CheckProblemsResult(Res<Vec<ModProblem>>),
+    VerifyMods,
+    VerifyModsResult(Res<VerifyReport>),
 
     CurseforgeManualToggleDelete(bool),
 }
@@ -175,6 +220,26 @@ pub enum EditPresetsMessage {
     LoadComplete(Res<HashSet<CurseforgeNotAllowed>>),
 }
 
+#[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)]
+pub enum SkinsMessage {
+    Open,
+    Loaded(Res<(AccountData, AccountTextures, Option<Vec<u8>>)>),
+    LibraryLoaded(Res<(SkinLibrary, Vec<(String, SkinPreview)>)>),
+    ToggleBack(bool),
+    ModelSelected(SkinModel),
+
+    UploadFile,
+    UploadFromLibrary(String),
+    CapeSelected(Option<String>),
+    /// The skin or cape was changed
+    Changed(Res),
+
+    LibrarySaveCurrent,
+    LibraryAddFile,
+    LibraryRemove(String),
+}
+
 #[derive(Debug, Clone)]
 pub enum RecommendedModMessage {
     Open,
@@ -198,6 +263,8 @@ pub enum AccountMessage {
     LogoutCheck,
     LogoutConfirm,
     RefreshComplete(Res<AccountData>),
+    /// Background refresh of an account (by its name in the dropdown)
+    StatusChecked(String, Box<(Option<AccountData>, AccountStatus)>),
 
     OpenMicrosoft {
         is_from_welcome_screen: bool,
@@ -217,15 +284,30 @@ pub enum AccountMessage {
     AltLogin,
     AltLoginResponse(Res<ql_instances::auth::yggdrasil::Account>),
 
-    LittleSkinOauthButtonClicked,
-    LittleSkinDeviceCodeReady {
+    AltOauthButtonClicked,
+    AltDeviceCodeReady {
         user_code: String,
         verification_uri: String,
         expires_in: u64,
         interval: u64,
         device_code: String,
     },
-    LittleSkinDeviceCodeError(String),
+    AltDeviceCodeError(String),
+
+    OpenCustom {
+        server: CustomAuthServer,
+        is_from_welcome_screen: bool,
+    },
+    AuthServerOpen,
+    AuthServerUrlInput(String),
+    AuthServerNameInput(String),
+    AuthServerOauthToggle(bool),
+    AuthServerOauthClientIdInput(String),
+    AuthServerOauthDeviceCodeUrlInput(String),
+    AuthServerOauthTokenUrlInput(String),
+    AuthServerAdd,
+    AuthServerAdded(Res<CustomAuthServer>),
+    AuthServerRemove(String),
 }
 
 #[derive(Debug, Clone)]
@@ -237,6 +319,13 @@ pub enum LauncherSettingsMessage {
     UiScaleApply,
     ClearJavaInstalls,
     ClearJavaInstallsConfirm,
+    JavaRuntimesLoaded(Res<Vec<ql_instances::ManagedJava>>),
+    JavaRuntimeVerify(usize),
+    JavaRuntimeRepair(usize),
+    JavaRuntimeUpdate(usize),
+    JavaRuntimeDelete(usize),
+    JavaRuntimeDeleteConfirm(usize),
+    JavaRuntimeDone(usize, Res<String>),
     ChangeTab(LauncherSettingsTab),
     DefaultMinecraftWidthChanged(String),
     DefaultMinecraftHeightChanged(String),
@@ -257,6 +346,25 @@ pub enum LauncherSettingsMessage {
     GlobalPreLaunchPrefixDelete(usize),
     GlobalPreLaunchPrefixShiftUp(usize),
     GlobalPreLaunchPrefixShiftDown(usize),
+
+    // Global environment variables
+    GlobalEnvVarAdd,
+    GlobalEnvVarEdit(String, usize),
+    GlobalEnvVarDelete(usize),
+    GlobalEnvVarShiftUp(usize),
+    GlobalEnvVarShiftDown(usize),
+
+    GlobalPreLaunchHookChanged(String),
+    GlobalPostExitHookChanged(String),
+
+    CredentialStorePicked(CredentialStore),
+    CredentialStoreMigrated(Res<(CredentialStore, usize)>),
+
+    /// Start waiting for a key press to bind to the action
+    KeybindEdit(KeyAction),
+    KeybindSet(KeyAction, KeyChord),
+    KeybindReset(KeyAction),
+    KeybindResetAll,
 }
 
 #[derive(Debug, Clone)]
@@ -280,18 +388,29 @@ pub enum Message {
     EditPresets(EditPresetsMessage),
     LauncherSettings(LauncherSettingsMessage),
     RecommendedMods(RecommendedModMessage),
+    Skins(SkinsMessage),
 
     LaunchInstanceSelected {
         name: String,
         is_server: bool,
     },
     LaunchUsernameSet(String),
+    LaunchQuickPlayWorld(String),
+    LaunchPlayHistoryRecorded,
+    LaunchInstanceSortPicked(InstanceSort),
+    LaunchInstanceFilterEdit(String),
+    /// Collapses/expands an instance group in the sidebar
+    LaunchGroupToggle(String),
+    LaunchQuickPlayServer(String),
+    LaunchQuickPlayClear,
     LaunchStart,
     LaunchScreenOpen {
         message: Option<String>,
         clear_selection: bool,
     },
-    LaunchEnd(Res<Arc<Mutex<Child>>>),
+    /// The account type and key (in [`crate::state::Launcher::accounts`])
+    /// used to launch, or `None` for offline mode
+    LaunchEnd(Res<Arc<Mutex<Child>>>, Option<(AccountType, String)>),
     LaunchKill,
     LaunchKillEnd(Res),
     LaunchChangeTab(LaunchTabId),
@@ -9,16 +9,21 @@ use std::{
     },
 };
 
-use iced::Task;
+use iced::{futures::executor::block_on, Task};
 use notify::Watcher;
 use ql_core::{
-    err, file_utils, GenericProgress, InstanceSelection, IntoIoError, IntoStringError, IoError,
-    JsonFileError, ListEntry, ModId, Progress, LAUNCHER_DIR, LAUNCHER_VERSION_NAME,
+    err, file_utils, json::InstanceMetaFile, GenericProgress, InstanceSelection, IntoIoError,
+    IntoStringError, IoError, JsonFileError, ListEntry, Progress, LAUNCHER_DIR,
+    LAUNCHER_VERSION_NAME,
 };
 use ql_instances::{
-    auth::{ms::CLIENT_ID, AccountData, AccountType},
-    LogLine,
+    auth::{
+        credentials, health::AccountStatus, ms::CLIENT_ID, AccountData, AccountType,
+        CustomAuthServer,
+    },
+    JavaInstall, LogLine, ProcessMonitor,
 };
+use ql_mod_manager::store::ModUpdate;
 use tokio::process::{Child, ChildStdin};
 
 use crate::{
@@ -35,6 +40,8 @@ pub use message::*;
 
 pub const OFFLINE_ACCOUNT_NAME: &str = "(Offline)";
 pub const NEW_ACCOUNT_NAME: &str = "+ Add Account";
+/// For instances without a preferred account
+pub const SELECTED_ACCOUNT_NAME: &str = "(Selected account)";
 
 pub const ADD_JAR_NAME: &str = "+ Add JAR";
 pub const REMOVE_JAR_NAME: &str = "- Remove Selected";
@@ -60,19 +67,31 @@ pub struct Launcher {
     pub log_scroll: isize,
     pub tick_timer: usize,
     pub is_launching_game: bool,
+    /// Account that was still being checked in the background
+    /// when pressing play. The launch continues once it's done,
+    /// instead of refreshing the same token twice.
+    pub launch_after_check: Option<String>,
 
     pub java_recv: Option<ProgressBar<GenericProgress>>,
     pub custom_jar: Option<CustomJarState>,
-    pub mod_updates_checked: HashMap<InstanceSelection, Vec<(ModId, String, bool)>>,
+    pub java_installs: Option<Vec<JavaInstall>>,
+    pub mod_updates_checked: HashMap<InstanceSelection, Vec<(ModUpdate, bool)>>,
 
     pub accounts: HashMap<String, AccountData>,
     pub accounts_dropdown: Vec<String>,
     pub accounts_selected: Option<String>,
+    /// Status of the logged-in accounts, from
+    /// refreshing them in the background
+    pub account_status: HashMap<String, AccountStatus>,
 
     pub client_version_list_cache: Option<Vec<ListEntry>>,
     pub server_version_list_cache: Option<Vec<ListEntry>>,
     pub client_list: Option<Vec<String>>,
     pub server_list: Option<Vec<String>>,
+    /// Groups, tags and favourites of instances
+    pub instance_meta: InstanceMetaFile,
+    /// Custom icons of instances that have one
+    pub instance_icons: HashMap<InstanceSelection, iced::widget::image::Handle>,
     pub client_processes: HashMap<String, ClientProcess>,
     pub server_processes: HashMap<String, ServerProcess>,
     pub client_logs: HashMap<String, InstanceLog>,
@@ -102,6 +121,14 @@ impl CustomJarState {
 pub struct ClientProcess {
     pub child: Arc<Mutex<Child>>,
     pub receiver: Option<Receiver<LogLine>>,
+    pub monitor: ProcessMonitor,
+    /// Unix timestamp (seconds) of when the game was launched
+    pub started: i64,
+    /// `None` for offline mode
+    pub account_type: Option<AccountType>,
+    /// Key of the account in [`Launcher::accounts`],
+    /// `None` for offline mode
+    pub account: Option<String>,
 }
 
 pub struct ServerProcess {
@@ -152,6 +179,7 @@ impl Launcher {
             State::ChangeLog
         };
 
+        credentials::set_store(config.credential_store.unwrap_or_default());
         let mut accounts = HashMap::new();
 
         let mut accounts_dropdown =
@@ -159,6 +187,7 @@ impl Launcher {
 
         if let Some(config_accounts) = config.accounts.as_mut() {
             let mut accounts_to_remove = Vec::new();
+            let custom_auth_servers = config.custom_auth_servers.as_deref().unwrap_or_default();
 
             for (username, account) in config_accounts.iter_mut() {
                 load_account(
@@ -167,6 +196,7 @@ impl Launcher {
                     &mut accounts_to_remove,
                     username,
                     account,
+                    custom_auth_servers,
                 );
             }
 
@@ -184,6 +214,11 @@ impl Launcher {
 
         let (window_width, window_height) = config.read_window_size();
 
+        let instance_meta = block_on(InstanceMetaFile::load()).unwrap_or_else(|err| {
+            err!("Couldn't load instance groups/tags: {err}");
+            InstanceMetaFile::default()
+        });
+
         Ok(Self {
             state,
             config,
@@ -196,11 +231,14 @@ impl Launcher {
 
             client_list: None,
             server_list: None,
+            instance_meta,
+            instance_icons: HashMap::new(),
             java_recv: None,
             client_version_list_cache: None,
             server_version_list_cache: None,
             selected_instance: None,
             custom_jar: None,
+            java_installs: None,
 
             client_processes: HashMap::new(),
             client_logs: HashMap::new(),
@@ -209,9 +247,11 @@ impl Launcher {
 
             keys_pressed: HashSet::new(),
             mod_updates_checked: HashMap::new(),
+            account_status: HashMap::new(),
 
             is_log_open: false,
             is_launching_game: false,
+            launch_after_check: None,
 
             log_scroll: 0,
             tick_timer: 0,
@@ -257,13 +297,17 @@ impl Launcher {
             java_recv: None,
             client_list: None,
             server_list: None,
+            instance_meta: InstanceMetaFile::default(),
+            instance_icons: HashMap::new(),
             client_version_list_cache: None,
             selected_instance: None,
             server_version_list_cache: None,
             custom_jar: None,
+            java_installs: None,
 
             is_log_open: false,
             is_launching_game: false,
+            launch_after_check: None,
 
             log_scroll: 0,
             tick_timer: 0,
@@ -274,6 +318,7 @@ impl Launcher {
             server_processes: HashMap::new(),
             server_logs: HashMap::new(),
             accounts: HashMap::new(),
+            account_status: HashMap::new(),
             keys_pressed: HashSet::new(),
             mod_updates_checked: HashMap::new(),
 
@@ -301,6 +346,7 @@ impl Launcher {
             menu_launch.sidebar_width = width as u16;
         }
         self.state = State::Launch(menu_launch);
+        self.load_quick_play_worlds();
         Task::perform(get_entries(false), Message::CoreListLoaded)
     }
 }
@@ -311,75 +357,20 @@ fn load_account(
     accounts_to_remove: &mut Vec<String>,
     username: &str,
     account: &mut crate::config::ConfigAccount,
+    custom_auth_servers: &[CustomAuthServer],
 ) {
-    fn get_refresh_token_for_account_type(
-        account_type: AccountType,
-        username: &str,
-        keyring_identifier: Option<&str>,
-    ) -> Result<String, String> {
-        let keyring_username = if let Some(keyring_id) = keyring_identifier {
-            keyring_id
-        } else {
-            // Fallback to old behavior for backwards compatibility
-            match account_type {
-                AccountType::ElyBy => username.strip_suffix(" (elyby)").unwrap_or(username),
-                AccountType::LittleSkin => {
-                    username.strip_suffix(" (littleskin)").unwrap_or(username)
-                }
-                AccountType::Microsoft => username,
-            }
-        };
-        ql_instances::auth::read_refresh_token(keyring_username, account_type).strerr()
-    }
-
-    let (account_type, refresh_token) =
-        if account.account_type.as_deref() == Some("ElyBy") || username.ends_with(" (elyby)") {
-            (
-                AccountType::ElyBy,
-                get_refresh_token_for_account_type(
-                    AccountType::ElyBy,
-                    username,
-                    account.keyring_identifier.as_deref(),
-                ),
-            )
-        } else if account.account_type.as_deref() == Some("LittleSkin")
-            || username.ends_with(" (littleskin)")
-        {
-            (
-                AccountType::LittleSkin,
-                get_refresh_token_for_account_type(
-                    AccountType::LittleSkin,
-                    username,
-                    account.keyring_identifier.as_deref(),
-                ),
-            )
-        } else {
-            (
-                AccountType::Microsoft,
-                get_refresh_token_for_account_type(
-                    AccountType::Microsoft,
-                    username,
-                    account.keyring_identifier.as_deref(),
-                ),
-            )
-        };
-
-    let keyring_username = if let Some(keyring_id) = &account.keyring_identifier {
-        keyring_id.clone()
-    } else {
-        // Fallback to old behavior for backwards compatibility
-        match account_type {
-            AccountType::ElyBy => username
-                .strip_suffix(" (elyby)")
-                .unwrap_or(username)
-                .to_owned(),
-            AccountType::LittleSkin => username
-                .strip_suffix(" (littleskin)")
-                .unwrap_or(username)
-                .to_owned(),
-            AccountType::Microsoft => username.to_owned(),
-        }
+    let Some(account_type) = account.get_account_type(username, custom_auth_servers) else {
+        err!(
+            "Could not load account {username}: auth server {:?} not found",
+            account.auth_server.as_deref().unwrap_or_default()
+        );
+        return;
     };
+    let keyring_username = account
+        .get_keyring_identifier(username, &account_type)
+        .to_owned();
+    let refresh_token =
+        ql_instances::auth::read_refresh_token(&keyring_username, &account_type).strerr();
 
     match refresh_token {
         Ok(refresh_token) => {
@@ -6,7 +6,7 @@ use std::{
 use chrono::Datelike;
 use iced::Task;
 use ql_core::{
-    json::InstanceConfigJson, InstanceSelection, IntoIoError, IntoJsonError, IntoStringError,
+    info, json::InstanceConfigJson, InstanceSelection, IntoIoError, IntoJsonError, IntoStringError,
     JsonFileError, ModId,
 };
 use ql_mod_manager::store::{ModConfig, ModIndex};
@@ -37,7 +37,7 @@ impl Launcher {
                     let config = edit.config.clone();
                     self.tick_edit_instance(config, &mut commands);
                 }
-                self.tick_client_processes_and_logs();
+                self.tick_client_processes_and_logs(&mut commands);
                 self.tick_server_processes_and_logs();
 
                 if self.tick_timer % 5 == 0 {
@@ -47,6 +47,10 @@ impl Launcher {
                         Message::CoreTickConfigSaved,
                     ));
                 }
+                // Every minute, refresh logins that are about to expire
+                if self.tick_timer.is_multiple_of(300) {
+                    commands.push(self.check_accounts(true));
+                }
                 return Task::batch(commands);
             }
             State::Create(menu) => menu.tick(),
@@ -146,6 +150,8 @@ impl Launcher {
             // These menus don't require background ticking
             State::Error { .. }
             | State::LoginAlternate(_)
+            | State::AddAuthServer(_)
+            | State::Skins(_)
             | State::AccountLogin
             | State::ExportInstance(_)
             | State::ConfirmAction { .. }
@@ -173,17 +179,36 @@ impl Launcher {
         commands.push(cmd);
     }
 
-    fn tick_client_processes_and_logs(&mut self) {
+    fn tick_client_processes_and_logs(&mut self, commands: &mut Vec<Task<Message>>) {
         let mut killed_processes = Vec::new();
-        for (name, process) in &self.client_processes {
+        for (name, process) in &mut self.client_processes {
             Launcher::read_game_logs(&mut self.client_logs, process, name);
-            if let Ok(Some(_)) = process.child.lock().unwrap().try_wait() {
+            if process.monitor.tick() {
+                if let (Some(log), Some(max)) = (
+                    self.client_logs.get_mut(name),
+                    process.monitor.max_memory_mb(),
+                ) {
+                    log.log.push(format!(
+                        "[QuantumLauncher] Warning: The game is using over 90% of its {max} MB of memory.\nIf it lags or crashes, give it more memory in the Edit tab.\n"
+                    ));
+                }
+            }
+            if let Ok(Some(status)) = process.child.lock().unwrap().try_wait() {
                 // Game process has exited.
-                killed_processes.push(name.to_owned());
+                killed_processes.push((name.to_owned(), status.code()));
             }
         }
-        for name in killed_processes {
-            self.client_processes.remove(&name);
+        for (name, exit_code) in killed_processes {
+            let Some(process) = self.client_processes.remove(&name) else {
+                continue;
+            };
+            if let Some(summary) = process.monitor.summary() {
+                info!("{name}: {summary}");
+                if let Some(log) = self.client_logs.get_mut(&name) {
+                    log.log.push(format!("[QuantumLauncher] {summary}\n"));
+                }
+            }
+            commands.push(self.on_game_exited(name, &process, exit_code));
         }
     }
 
@@ -1,9 +1,9 @@
 use iced::{futures::executor::block_on, Task};
 use ql_core::{
-    err, err_no_log, file_utils::DirItem, info, info_no_log, open_file_explorer, InstanceSelection,
-    IntoIoError, IntoStringError, LOGGER,
+    err, err_no_log, file_utils::DirItem, info, info_no_log, json::InstanceMetaFile,
+    open_file_explorer, InstanceSelection, IntoIoError, IntoStringError, LOGGER,
 };
-use ql_instances::UpdateCheckInfo;
+use ql_instances::{QuickPlay, UpdateCheckInfo};
 use ql_mod_manager::loaders;
 use std::{collections::HashMap, fmt::Write};
 use tokio::io::AsyncWriteExt;
@@ -70,15 +70,58 @@ impl Launcher {
             Message::ExportMods(message) => return self.update_export_mods(message),
             Message::ManageJarMods(message) => return self.update_manage_jar_mods(message),
             Message::RecommendedMods(message) => return self.update_recommended_mods(message),
+            Message::Skins(msg) => return self.update_skins(msg),
             Message::LaunchInstanceSelected { name, is_server } => {
                 self.selected_instance = Some(InstanceSelection::new(&name, is_server));
                 self.load_edit_instance(None);
+                self.load_quick_play_worlds();
+                self.load_play_history();
+            }
+            Message::LaunchPlayHistoryRecorded => {
+                self.load_play_history();
+                self.sort_instance_lists();
+            }
+            Message::LaunchInstanceSortPicked(sort) => {
+                self.config.instance_sort = Some(sort);
+                self.sort_instance_lists();
+            }
+            Message::LaunchInstanceFilterEdit(filter) => {
+                if let State::Launch(menu) = &mut self.state {
+                    menu.instance_filter = filter;
+                }
+            }
+            Message::LaunchGroupToggle(group) => {
+                let collapsed = self.config.collapsed_groups.get_or_insert_with(Vec::new);
+                if let Some(idx) = collapsed.iter().position(|n| *n == group) {
+                    collapsed.remove(idx);
+                } else {
+                    collapsed.push(group);
+                }
+            }
+            Message::LaunchQuickPlayWorld(world) => {
+                if let State::Launch(menu) = &mut self.state {
+                    menu.quick_play_server.clear();
+                    menu.quick_play = Some(QuickPlay::Singleplayer(world));
+                }
+            }
+            Message::LaunchQuickPlayServer(address) => {
+                if let State::Launch(menu) = &mut self.state {
+                    menu.quick_play = (!address.trim().is_empty())
+                        .then(|| QuickPlay::Multiplayer(address.trim().to_owned()));
+                    menu.quick_play_server = address;
+                }
+            }
+            Message::LaunchQuickPlayClear => {
+                if let State::Launch(menu) = &mut self.state {
+                    menu.quick_play = None;
+                    menu.quick_play_server.clear();
+                }
             }
             Message::LaunchUsernameSet(username) => {
                 self.config.username = username;
             }
             Message::LaunchStart => return self.launch_start(),
-            Message::LaunchEnd(result) => return self.finish_launching(result),
+            Message::LaunchEnd(result, account) => return self.finish_launching(result, account),
             Message::CreateInstance(message) => return self.update_create_instance(message),
             Message::DeleteInstanceMenu => self.go_to_delete_instance_menu(),
             Message::DeleteInstance => return self.delete_instance_confirm(),
@@ -396,6 +439,13 @@ impl Launcher {
                 } else {
                     self.client_list = Some(list);
                 }
+                self.load_instance_icons(is_server);
+                // Newly created instances have their creation date saved
+                match block_on(InstanceMetaFile::load()) {
+                    Ok(meta) => self.instance_meta = meta,
+                    Err(err) => err!("Couldn't load instance groups/tags: {err}"),
+                }
+                self.sort_instance_lists();
             }
             Message::CoreCopyText(txt) => {
                 return iced::clipboard::write(txt);
@@ -582,7 +632,11 @@ impl Launcher {
             if let (LaunchTabId::Edit, Some(selected_instance)) =
                 (new_tab.unwrap_or(*tab), self.selected_instance.as_ref())
             {
-                if let Err(err) = Self::load_edit_instance_inner(edit_instance, selected_instance) {
+                if let Err(err) = Self::load_edit_instance_inner(
+                    edit_instance,
+                    selected_instance,
+                    self.instance_meta.get(selected_instance),
+                ) {
                     err!("Could not open edit instance menu: {err}");
                     *edit_instance = None;
                 }
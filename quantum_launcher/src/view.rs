@@ -97,7 +97,12 @@ impl Launcher {
             .padding(10)
             .into(),
             State::GenericMessage(msg) => widget::column![widget::text(msg)].padding(10).into(),
-            State::AccountLogin => view_account_login(),
+            State::AccountLogin => view_account_login(
+                self.config
+                    .custom_auth_servers
+                    .as_deref()
+                    .unwrap_or_default(),
+            ),
             State::EditMods(menu) => menu.view(
                 self.selected_instance.as_ref().unwrap(),
                 self.tick_timer,
@@ -162,6 +167,8 @@ impl Launcher {
             }
 
             State::LoginAlternate(menu) => menu.view(self.tick_timer),
+            State::AddAuthServer(menu) => menu.view(self.tick_timer),
+            State::Skins(menu) => menu.view(self.tick_timer),
             State::ExportInstance(menu) => menu.view(self.tick_timer),
 
             State::LoginMS(menu) => menu.view(),